@@ -2,9 +2,13 @@
 
 use ewebsock::{WsEvent, WsMessage, WsReceiver, WsSender};
 use futures::channel::mpsc::{UnboundedReceiver, UnboundedSender};
+use protocol::FULL_UPDATE_REQUEST_INTERVAL_MS;
+use web_time::{Duration, Instant};
 
 use crate::platform::sleep_ms;
-use crate::protocol::{parse_client_update, send_disconnect, send_rpc};
+use crate::protocol::{
+    parse_client_update, send_chat, send_disconnect, send_full_update_request, send_rpc,
+};
 use crate::session::{BackendMsg, SessionEvent};
 use crate::traits::SerializationCap;
 
@@ -18,6 +22,7 @@ pub(crate) async fn client_loop<A, D, VS>(
     D: SerializationCap,
     VS: SerializationCap,
 {
+    let mut last_full_update_request: Option<Instant> = None;
     loop {
         // 1. Drain outbound actions.
         loop {
@@ -25,6 +30,18 @@ pub(crate) async fn client_loop<A, D, VS>(
                 Ok(Some(BackendMsg::Action(action))) => {
                     send_rpc(&mut ws_sender, &action);
                 }
+                Ok(Some(BackendMsg::Chat(text))) => {
+                    send_chat(&mut ws_sender, &text);
+                }
+                Ok(Some(BackendMsg::RequestFullUpdate)) => {
+                    // The relay drops requests coming sooner anyway.
+                    let interval = Duration::from_millis(FULL_UPDATE_REQUEST_INTERVAL_MS);
+                    let now = Instant::now();
+                    if last_full_update_request.is_none_or(|t| now - t >= interval) {
+                        last_full_update_request = Some(now);
+                        send_full_update_request(&mut ws_sender);
+                    }
+                }
                 Ok(Some(BackendMsg::Disconnect)) => {
                     send_disconnect(&mut ws_sender, false);
                     event_tx
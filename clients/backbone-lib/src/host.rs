@@ -14,6 +14,10 @@ use crate::protocol::{
 use crate::session::{BackendMsg, SessionEvent};
 use crate::traits::{BackEndArchitecture, BackendCommand, SerializationCap, ViewStateUpdate};
 
+/// How often an idle host saves its snapshot, as it can change without a
+/// command (a running clock, for one).
+const HOST_STATE_INTERVAL: Duration = Duration::from_secs(1);
+
 struct Timer {
     id: u16,
     fire_at: Instant,
@@ -47,8 +51,12 @@ pub(crate) async fn host_loop<A, D, VS, Backend>(
     let mut timers: Vec<Timer> = Vec::new();
     let mut cancelled_timers: HashSet<u16> = HashSet::new();
     let mut remote_player_count: u16 = 0;
+    // Last snapshot handed to the app, to only send changed ones.
+    let mut host_state: Option<Vec<u8>> = None;
+    let mut host_state_at = Instant::now();
 
     loop {
+        // Set when a client joined or asked for the full state this iteration.
         let mut client_joined = false;
 
         // 1. Drain local actions / detect session drop or disconnect request.
@@ -57,6 +65,16 @@ pub(crate) async fn host_loop<A, D, VS, Backend>(
                 Ok(Some(BackendMsg::Action(action))) => {
                     backend.inform_rpc(0, action);
                 }
+                Ok(Some(BackendMsg::Chat(text))) => {
+                    backend.inform_chat(0, text);
+                }
+                Ok(Some(BackendMsg::RequestFullUpdate)) => {
+                    // The local UI gets every update directly, resend it the state anyway.
+                    let state = backend.get_view_state().clone();
+                    event_tx
+                        .unbounded_send(SessionEvent::Update(ViewStateUpdate::Full(state)))
+                        .ok();
+                }
                 Ok(Some(BackendMsg::Disconnect)) => {
                     send_disconnect(&mut ws_sender, true);
                     event_tx
@@ -87,9 +105,24 @@ pub(crate) async fn host_loop<A, D, VS, Backend>(
                             backend.player_departure(id);
                             remote_player_count = remote_player_count.saturating_sub(1);
                         }
+                        ToServerCommand::SpectatorJoin(id) => {
+                            backend.spectator_arrival(id);
+                            remote_player_count += 1;
+                            client_joined = true;
+                        }
+                        ToServerCommand::SpectatorLeft(id) => {
+                            backend.spectator_departure(id);
+                            remote_player_count = remote_player_count.saturating_sub(1);
+                        }
                         ToServerCommand::Rpc(id, payload) => {
                             backend.inform_rpc(id, payload);
                         }
+                        ToServerCommand::Chat(id, text) => {
+                            backend.inform_chat(id, text);
+                        }
+                        ToServerCommand::FullUpdateRequest => {
+                            client_joined = true;
+                        }
                         ToServerCommand::Error(e) => {
                             event_tx
                                 .unbounded_send(SessionEvent::Disconnected(Some(e)))
@@ -138,6 +171,10 @@ pub(crate) async fn host_loop<A, D, VS, Backend>(
         let commands = backend.drain_commands();
 
         if commands.is_empty() && !client_joined {
+            if now.duration_since(host_state_at) >= HOST_STATE_INTERVAL {
+                send_host_state::<A, _, _, _>(&backend, &event_tx, &mut host_state);
+                host_state_at = now;
+            }
             sleep_ms(2).await;
             continue;
         }
@@ -201,11 +238,33 @@ pub(crate) async fn host_loop<A, D, VS, Backend>(
             }
         }
 
-        // Send full state to clients that joined this iteration.
+        // Send full state to clients that joined or lost track this iteration.
         if client_joined {
             send_full_state(&mut ws_sender, backend.get_view_state());
         }
 
+        send_host_state::<A, _, _, _>(&backend, &event_tx, &mut host_state);
+        host_state_at = now;
+
         sleep_ms(2).await;
     }
 }
+
+/// Hands the backend's snapshot to the app if it changed since the last one.
+fn send_host_state<A, D, VS, Backend>(
+    backend: &Backend,
+    event_tx: &UnboundedSender<SessionEvent<D, VS>>,
+    host_state: &mut Option<Vec<u8>>,
+) where
+    A: SerializationCap,
+    D: SerializationCap + Clone,
+    VS: SerializationCap + Clone,
+    Backend: BackEndArchitecture<A, D, VS>,
+{
+    if let Some(bytes) = backend.to_bytes().filter(|b| host_state.as_ref() != Some(b)) {
+        event_tx
+            .unbounded_send(SessionEvent::HostState(bytes.clone()))
+            .ok();
+        *host_state = Some(bytes);
+    }
+}
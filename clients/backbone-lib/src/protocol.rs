@@ -3,14 +3,16 @@
 //! Translates between raw WebSocket binary frames and typed Rust values using
 //! postcard serialization and the message-type constants from the `protocol` crate.
 
+use crate::session::ConnectError;
 use crate::traits::{SerializationCap, ViewStateUpdate};
 use bytes::{Buf, BufMut, Bytes, BytesMut};
 use ewebsock::{WsMessage, WsSender};
 use postcard::{from_bytes, take_from_bytes, to_stdvec};
 use protocol::{
-    CLIENT_DISCONNECTS, CLIENT_DISCONNECTS_SELF, CLIENT_GETS_KICKED, CLIENT_ID_SIZE, DELTA_UPDATE,
-    FULL_UPDATE, HAND_SHAKE_RESPONSE, JoinRequest, NEW_CLIENT, RESET, SERVER_DISCONNECTS,
-    SERVER_ERROR, SERVER_RPC,
+    CHAT_MESSAGE, CLIENT_DISCONNECTS, CLIENT_DISCONNECTS_SELF, CLIENT_GETS_KICKED, CLIENT_ID_SIZE, DELTA_UPDATE,
+    FULL_UPDATE, HAND_SHAKE_RESPONSE, HAND_SHAKE_RESPONSE_SIZE, JoinRequest, NEW_CLIENT, NEW_SPECTATOR,
+    PROTOCOL_VERSION, REQUEST_FULL_UPDATE, RESET, SERVER_DISCONNECTS, SERVER_ERROR, SERVER_RPC,
+    SPECTATOR_DISCONNECTS, VERSION_MISMATCH, VERSION_MISMATCH_HEADER_SIZE,
 };
 
 // ---------------------------------------------------------------------------
@@ -20,7 +22,12 @@ use protocol::{
 pub enum ToServerCommand<A> {
     ClientJoin(u16),
     ClientLeft(u16),
+    SpectatorJoin(u16),
+    SpectatorLeft(u16),
     Rpc(u16, A),
+    Chat(u16, String),
+    /// Answered with a full update to everybody, so the id of the client does not matter.
+    FullUpdateRequest,
     Error(String),
 }
 
@@ -46,6 +53,17 @@ pub fn send_rpc<A: SerializationCap>(sender: &mut WsSender, action: &A) {
     send_binary(sender, &buf);
 }
 
+pub fn send_chat(sender: &mut WsSender, text: &str) {
+    let mut buf = BytesMut::with_capacity(1 + text.len());
+    buf.put_u8(CHAT_MESSAGE);
+    buf.put_slice(text.as_bytes());
+    send_binary(sender, &buf);
+}
+
+pub fn send_full_update_request(sender: &mut WsSender) {
+    send_binary(sender, &[REQUEST_FULL_UPDATE]);
+}
+
 pub fn send_delta<D: SerializationCap>(sender: &mut WsSender, deltas: &[D]) {
     let serialized: Vec<u8> = deltas
         .iter()
@@ -96,18 +114,41 @@ pub fn send_disconnect(sender: &mut WsSender, as_host: bool) {
 /// Parses the relay's handshake response.
 ///
 /// Returns `(player_id, rule_variation, reconnect_token)`.
-pub fn parse_handshake_response(data: Vec<u8>) -> Result<(u16, u16, u64), String> {
+pub fn parse_handshake_response(data: Vec<u8>) -> Result<(u16, u16, u64), ConnectError> {
     let mut bytes = Bytes::from(data);
     let msg = bytes.get_u8();
     match msg {
-        SERVER_ERROR => Err(String::from_utf8_lossy(&bytes).to_string()),
+        SERVER_ERROR => Err(ConnectError::Handshake(
+            String::from_utf8_lossy(&bytes).to_string(),
+        )),
+        VERSION_MISMATCH if bytes.len() >= VERSION_MISMATCH_HEADER_SIZE - 1 => {
+            // The supported range precedes the reason, which says it all already.
+            bytes.advance(VERSION_MISMATCH_HEADER_SIZE - 1);
+            Err(ConnectError::VersionMismatch(
+                String::from_utf8_lossy(&bytes).to_string(),
+            ))
+        }
+        HAND_SHAKE_RESPONSE if bytes.len() < HAND_SHAKE_RESPONSE_SIZE - 1 => Err(
+            ConnectError::Handshake("Truncated handshake response.".into()),
+        ),
         HAND_SHAKE_RESPONSE => {
             let player_id = bytes.get_u16();
             let rule_variation = bytes.get_u16();
             let token = bytes.get_u64();
+            let protocol_version = bytes.get_u16();
+            let min_version = bytes.get_u16();
+            let max_version = bytes.get_u16();
+            if protocol_version != PROTOCOL_VERSION {
+                return Err(ConnectError::VersionMismatch(format!(
+                    "This version of the game speaks protocol {PROTOCOL_VERSION}, \
+                     the server supports {min_version} to {max_version}."
+                )));
+            }
             Ok((player_id, rule_variation, token))
         }
-        other => Err(format!("Unexpected handshake message id: {other}")),
+        other => Err(ConnectError::Handshake(format!(
+            "Unexpected handshake message id: {other}"
+        ))),
     }
 }
 
@@ -118,12 +159,19 @@ pub fn parse_server_command<A: SerializationCap>(data: Vec<u8>) -> ToServerComma
         SERVER_ERROR => ToServerCommand::Error(String::from_utf8_lossy(&bytes).to_string()),
         NEW_CLIENT => ToServerCommand::ClientJoin(bytes.get_u16()),
         CLIENT_DISCONNECTS => ToServerCommand::ClientLeft(bytes.get_u16()),
+        NEW_SPECTATOR => ToServerCommand::SpectatorJoin(bytes.get_u16()),
+        SPECTATOR_DISCONNECTS => ToServerCommand::SpectatorLeft(bytes.get_u16()),
+        REQUEST_FULL_UPDATE => ToServerCommand::FullUpdateRequest,
         SERVER_RPC => {
             let client_id = bytes.get_u16();
             let payload: A =
                 from_bytes(bytes.chunk()).expect("Failed to deserialize server RPC payload");
             ToServerCommand::Rpc(client_id, payload)
         }
+        CHAT_MESSAGE => {
+            let client_id = bytes.get_u16();
+            ToServerCommand::Chat(client_id, String::from_utf8_lossy(bytes.chunk()).to_string())
+        }
         other => ToServerCommand::Error(format!("Unknown server message id: {other}")),
     }
 }
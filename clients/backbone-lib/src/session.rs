@@ -12,6 +12,8 @@
 //!         rule_variation: 0,
 //!         role: RoomRole::Create,
 //!         reconnect_token: None,
+//!         host_state: None,
+//!         schema_version: 1,
 //!     })
 //!     .await?;
 //!
@@ -21,6 +23,7 @@
 //!         cmd = ui_rx.next().fuse() => session.send_action(cmd),
 //!         event = session.next_event().fuse() => match event {
 //!             Some(SessionEvent::Update(u)) => view_state.apply(u),
+//!             Some(SessionEvent::HostState(bytes)) => storage.save(bytes),
 //!             Some(SessionEvent::Disconnected(reason)) | None => break,
 //!         }
 //!     }
@@ -30,7 +33,7 @@
 use ewebsock::{WsEvent, WsMessage};
 use futures::StreamExt;
 use futures::channel::mpsc::{self, UnboundedReceiver, UnboundedSender};
-use protocol::JoinRequest;
+use protocol::{JoinRequest, PROTOCOL_VERSION};
 
 use crate::client::client_loop;
 use crate::host::host_loop;
@@ -42,10 +45,12 @@ use crate::traits::{BackEndArchitecture, SerializationCap, ViewStateUpdate};
 // Public configuration types
 // ---------------------------------------------------------------------------
 
-/// Whether to create a new room (host) or join an existing one (client).
+/// Whether to create a new room (host), join an existing one (client) or
+/// only watch an existing one (spectator).
 pub enum RoomRole {
     Create,
     Join,
+    Spectate,
 }
 
 /// Configuration required to connect to a game session.
@@ -64,11 +69,15 @@ pub struct RoomConfig {
     pub reconnect_token: Option<u64>,
     /// Serialized backend state for host reconnect.
     ///
-    /// Produced by the app layer (e.g. `serde_json::to_vec(&view_state)`) and stored in
+    /// The last [`SessionEvent::HostState`] snapshot, stored by the app layer in
     /// localStorage. Passed to [`BackEndArchitecture::from_bytes`] when the host
     /// reconnects so the game can resume from the last known state.
     /// Ignored for non-host reconnects and normal connections.
     pub host_state: Option<Vec<u8>>,
+    /// Version of the game's actions, deltas and view state. Bump it whenever their
+    /// serialized form changes: the relay only lets endpoints with the host's version
+    /// into a room.
+    pub schema_version: u16,
 }
 
 /// Error returned by [`GameSession::connect`].
@@ -76,6 +85,9 @@ pub struct RoomConfig {
 pub enum ConnectError {
     WebSocket(String),
     Handshake(String),
+    /// The relay or the room runs another version of the protocol or the game.
+    /// Reloading the page usually fetches the matching one.
+    VersionMismatch(String),
 }
 
 impl std::fmt::Display for ConnectError {
@@ -83,6 +95,7 @@ impl std::fmt::Display for ConnectError {
         match self {
             ConnectError::WebSocket(e) => write!(f, "WebSocket error: {e}"),
             ConnectError::Handshake(e) => write!(f, "Handshake error: {e}"),
+            ConnectError::VersionMismatch(e) => write!(f, "Version mismatch: {e}"),
         }
     }
 }
@@ -93,6 +106,8 @@ impl std::fmt::Display for ConnectError {
 
 pub(crate) enum BackendMsg<A> {
     Action(A),
+    Chat(String),
+    RequestFullUpdate,
     Disconnect,
 }
 
@@ -104,6 +119,9 @@ pub(crate) enum BackendMsg<A> {
 pub enum SessionEvent<Delta, ViewState> {
     /// A state update arrived from the host backend.
     Update(ViewStateUpdate<ViewState, Delta>),
+    /// Host only: the backend's [`BackEndArchitecture::to_bytes`] snapshot,
+    /// sent whenever it changed. Store it to pass back as [`RoomConfig::host_state`].
+    HostState(Vec<u8>),
     /// The session ended. `None` = clean disconnect, `Some(reason)` = error.
     Disconnected(Option<String>),
 }
@@ -126,6 +144,8 @@ pub struct GameSession<Action, Delta, ViewState> {
     /// Token to persist in localStorage for reconnect on page refresh.
     /// Only meaningful for non-host players (player_id > 0).
     pub reconnect_token: u64,
+    /// `true` if this client only watches. Actions are not sent.
+    pub is_spectator: bool,
     action_tx: UnboundedSender<BackendMsg<Action>>,
     event_rx: UnboundedReceiver<SessionEvent<Delta, ViewState>>,
 }
@@ -149,6 +169,7 @@ where
         Backend: BackEndArchitecture<A, D, VS> + TaskBound,
     {
         let create_room = matches!(config.role, RoomRole::Create);
+        let spectate = matches!(config.role, RoomRole::Spectate);
 
         // 1. Open WebSocket.
         let (mut ws_sender, ws_receiver) =
@@ -175,6 +196,9 @@ where
             rule_variation: config.rule_variation,
             create_room,
             reconnect_token: config.reconnect_token,
+            spectate,
+            protocol_version: PROTOCOL_VERSION,
+            schema_version: config.schema_version,
         };
         send_join_request(&mut ws_sender, &req).map_err(ConnectError::Handshake)?;
 
@@ -182,7 +206,7 @@ where
         let (player_id, rule_variation, reconnect_token) = loop {
             match ws_receiver.try_recv() {
                 Some(WsEvent::Message(WsMessage::Binary(data))) => {
-                    break parse_handshake_response(data).map_err(ConnectError::Handshake)?;
+                    break parse_handshake_response(data)?;
                 }
                 Some(WsEvent::Error(e)) => return Err(ConnectError::Handshake(e)),
                 Some(WsEvent::Closed) => {
@@ -192,8 +216,7 @@ where
                     if let Some(WsEvent::Message(WsMessage::Binary(data))) =
                         ws_receiver.try_recv()
                     {
-                        break parse_handshake_response(data)
-                            .map_err(ConnectError::Handshake)?;
+                        break parse_handshake_response(data)?;
                     }
                     return Err(ConnectError::Handshake(
                         "Connection closed during handshake".to_string(),
@@ -235,18 +258,41 @@ where
             rule_variation,
             is_host,
             reconnect_token,
+            is_spectator: spectate,
             action_tx,
             event_rx,
         })
     }
 
     /// Sends a game action to the backend (fire-and-forget).
+    /// Does nothing for spectators, the relay would reject it anyway.
     pub fn send_action(&self, action: A) {
+        if self.is_spectator {
+            return;
+        }
         self.action_tx
             .unbounded_send(BackendMsg::Action(action))
             .ok();
     }
 
+    /// Sends a chat message to the room (fire-and-forget).
+    /// Does nothing for spectators, the relay would reject it anyway.
+    pub fn send_chat(&self, text: String) {
+        if self.is_spectator {
+            return;
+        }
+        self.action_tx.unbounded_send(BackendMsg::Chat(text)).ok();
+    }
+
+    /// Asks the host for a full state, after a delta that could not be applied.
+    /// The next [`ViewStateUpdate::Full`] brings the session back in sync.
+    /// Requests are throttled, so calling this for every such delta is fine.
+    pub fn request_full_update(&self) {
+        self.action_tx
+            .unbounded_send(BackendMsg::RequestFullUpdate)
+            .ok();
+    }
+
     /// Awaits the next session event.
     ///
     /// Returns `None` if the background task has exited (i.e. the session is
@@ -61,9 +61,9 @@ where
 
     /// Attempt to restore a previously running game from serialized bytes.
     ///
-    /// Called when the host reconnects after a page refresh. The bytes are the
-    /// game-specific snapshot produced by the app layer (via `serde_json` or
-    /// similar) and stored in localStorage.
+    /// Called when the host reconnects after a page refresh. The bytes are a
+    /// snapshot produced by [`to_bytes`](Self::to_bytes), which the app layer
+    /// stored in localStorage.
     ///
     /// Return `None` if restoration is not supported or the bytes are invalid —
     /// the caller falls back to `new(rule_variation)`.
@@ -74,6 +74,15 @@ where
         None
     }
 
+    /// Serialize the complete game state, for [`from_bytes`](Self::from_bytes).
+    ///
+    /// The host calls it after each batch of commands, and every second while
+    /// idle, and hands changed snapshots to the app as [`SessionEvent::HostState`](crate::session::SessionEvent::HostState).
+    /// Return `None` (the default) if restoration is not supported.
+    fn to_bytes(&self) -> Option<Vec<u8>> {
+        None
+    }
+
     /// Called when a player connects. Player will receive a full state snapshot
     /// automatically after this returns.
     fn player_arrival(&mut self, player: u16);
@@ -81,9 +90,21 @@ where
     /// Called when a player disconnects.
     fn player_departure(&mut self, player: u16);
 
+    /// Called when a spectator connects. Spectators receive the same state
+    /// snapshots as players but never send actions. Ignored by default.
+    fn spectator_arrival(&mut self, _spectator: u16) {}
+
+    /// Called when a spectator disconnects. Ignored by default.
+    fn spectator_departure(&mut self, _spectator: u16) {}
+
     /// Called when a player sends a game action.
     fn inform_rpc(&mut self, player: u16, payload: ServerRpcPayload);
 
+    /// Called when a player sends a chat message. Messages from remote players
+    /// were already checked against the relay's length and rate limits.
+    /// Ignored by default.
+    fn inform_chat(&mut self, _player: u16, _text: String) {}
+
     /// Called when a previously scheduled timer fires.
     fn timer_triggered(&mut self, timer_id: u16);
 
@@ -0,0 +1,331 @@
+//! Headless relay client that seats a `BotStrategy` in a multiplayer room.
+//!
+//! Uses the same `GameSession` and game types as the web client, so the bot
+//! plays over the real protocol: it can join a room as a guest, or create one
+//! and run the host backend itself.
+mod player;
+mod strategy;
+
+use anyhow::{anyhow, Result};
+use backbone_lib::{GameSession, RoomConfig, RoomRole, SessionEvent, ViewStateUpdate};
+use log::{info, warn};
+use trictrac_game::backend::TrictracBackend;
+use trictrac_game::types::{GameDelta, PlayerAction, SerStage, ViewState, SCHEMA_VERSION};
+
+use player::RemoteBot;
+use strategy::parse_strategy;
+
+const GAME_ID: &str = "trictrac";
+/// How long a hosting bot keeps the room open once the game has ended, so that
+/// the final position reaches the opponent before the room closes.
+const HOST_LINGER_SECS: u64 = 5;
+
+const HELP: &str = "\
+Trictrac bot client
+
+USAGE:
+  bot_client --room ROOM [OPTIONS]
+
+FLAGS:
+  -h, --help            Prints help information
+  --host                Create the room and run the game backend instead of joining it
+  --forever             Start over in the same room after each game (permanent bot rooms)
+
+OPTIONS:
+  --relay URL           Relay WebSocket URL [default: ws://localhost:8080/ws]
+  --room ROOM           Room to join (or to create with --host)
+  --bot STRATEGY        Strategy of the bot [default: dummy], same tokens as client_cli:
+                        dummy, random, erroneous, ai[:PATH], dqnburn[:PATH],
+                        az[:PATH], az-resnet[:PATH], az-dqn[:PATH]
+  --name NAME           Name shown to the opponent [default: Bot]
+  --rules RULES         Rule variation (time control) when creating the room [default: 0]
+";
+
+struct Args {
+    relay: String,
+    room: String,
+    bot: String,
+    name: String,
+    rules: u16,
+    host: bool,
+    forever: bool,
+}
+
+fn main() -> Result<()> {
+    env_logger::init();
+    let args = match parse_args() {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("Error: {e}.");
+            std::process::exit(1);
+        }
+    };
+
+    futures::executor::block_on(async {
+        loop {
+            let strategy = parse_strategy(&args.bot)
+                .ok_or_else(|| anyhow!("unknown strategy {}", args.bot))?;
+            match play_game(&args, strategy).await {
+                Ok(()) => info!("game over in room {}", args.room),
+                Err(e) => warn!("room {}: {e}", args.room),
+            }
+            if !args.forever {
+                return Ok(());
+            }
+            // Leave the relay time to close the previous room.
+            std::thread::sleep(std::time::Duration::from_secs(2));
+        }
+    })
+}
+
+/// Plays one game in the configured room, until it ends or the session drops.
+async fn play_game(args: &Args, strategy: Box<dyn trictrac_bot::BotStrategy>) -> Result<()> {
+    let config = RoomConfig {
+        relay_url: args.relay.clone(),
+        game_id: GAME_ID.to_string(),
+        room_id: args.room.clone(),
+        rule_variation: args.rules,
+        role: if args.host {
+            RoomRole::Create
+        } else {
+            RoomRole::Join
+        },
+        reconnect_token: None,
+        host_state: None,
+        schema_version: SCHEMA_VERSION,
+    };
+    let session: GameSession<PlayerAction, GameDelta, ViewState> =
+        GameSession::connect::<TrictracBackend>(config)
+            .await
+            .map_err(|e| anyhow!("{e}"))?;
+    info!(
+        "seated in room {} as player {} with {}",
+        args.room, session.player_id, args.bot
+    );
+    let bot = RemoteBot::new(strategy, session.player_id);
+    play(session, &args.name, bot).await
+}
+
+/// The room session as seen by the game loop; tests stand a stub in for it.
+trait Seat {
+    fn is_host(&self) -> bool;
+    fn send_action(&mut self, action: PlayerAction);
+    fn request_full_update(&mut self);
+    async fn next_event(&mut self) -> Option<SessionEvent<GameDelta, ViewState>>;
+    fn disconnect(self);
+}
+
+impl Seat for GameSession<PlayerAction, GameDelta, ViewState> {
+    fn is_host(&self) -> bool {
+        self.is_host
+    }
+
+    fn send_action(&mut self, action: PlayerAction) {
+        GameSession::send_action(self, action);
+    }
+
+    fn request_full_update(&mut self) {
+        GameSession::request_full_update(self);
+    }
+
+    async fn next_event(&mut self) -> Option<SessionEvent<GameDelta, ViewState>> {
+        GameSession::next_event(self).await
+    }
+
+    fn disconnect(self) {
+        GameSession::disconnect(self);
+    }
+}
+
+/// Plays the game of `session` with `bot`, until it ends or the session drops.
+async fn play(mut session: impl Seat, name: &str, mut bot: RemoteBot) -> Result<()> {
+    session.send_action(PlayerAction::SetName(name.to_string()));
+
+    let mut view_state: Option<ViewState> = None;
+    while let Some(event) = session.next_event().await {
+        match event {
+            SessionEvent::Update(ViewStateUpdate::Full(vs)) => view_state = Some(vs),
+            SessionEvent::Update(ViewStateUpdate::Incremental(delta)) => {
+                let applied = view_state.as_mut().is_some_and(|vs| vs.apply_delta(&delta));
+                if !applied {
+                    session.request_full_update();
+                    continue;
+                }
+            }
+            // A hosting bot does not resume its rooms after a restart.
+            SessionEvent::HostState(_) => continue,
+            SessionEvent::Disconnected(None) => return Ok(()),
+            SessionEvent::Disconnected(Some(reason)) => return Err(anyhow!(reason)),
+        }
+        let Some(vs) = &view_state else { continue };
+        if vs.stage == SerStage::Ended {
+            let [host, guest] = &vs.scores;
+            info!(
+                "{} {} holes - {} {} holes",
+                host.name, host.holes, guest.name, guest.holes
+            );
+            if session.is_host() {
+                std::thread::sleep(std::time::Duration::from_secs(HOST_LINGER_SECS));
+            }
+            session.disconnect();
+            return Ok(());
+        }
+        if let Some(action) = bot.decide(vs) {
+            session.send_action(action);
+        }
+    }
+    Ok(())
+}
+
+fn parse_args() -> Result<Args, pico_args::Error> {
+    let mut pargs = pico_args::Arguments::from_env();
+
+    if pargs.contains(["-h", "--help"]) {
+        print!("{HELP}");
+        std::process::exit(0);
+    }
+
+    let args = Args {
+        host: pargs.contains("--host"),
+        forever: pargs.contains("--forever"),
+        relay: pargs
+            .opt_value_from_str("--relay")?
+            .unwrap_or_else(|| "ws://localhost:8080/ws".to_string()),
+        room: pargs.value_from_str("--room")?,
+        bot: pargs
+            .opt_value_from_str("--bot")?
+            .unwrap_or_else(|| "dummy".to_string()),
+        name: pargs
+            .opt_value_from_str("--name")?
+            .unwrap_or_else(|| "Bot".to_string()),
+        rules: pargs.opt_value_from_str("--rules")?.unwrap_or(0),
+    };
+
+    let remaining = pargs.finish();
+    if !remaining.is_empty() {
+        eprintln!("Warning: unused arguments left: {remaining:?}.");
+    }
+
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+    use std::rc::Rc;
+
+    use backbone_lib::traits::{BackEndArchitecture, BackendCommand};
+    use trictrac_bot::DefaultStrategy;
+
+    use super::*;
+
+    /// What the bot sent through the stub session.
+    #[derive(Default)]
+    struct Sent {
+        actions: Vec<PlayerAction>,
+        full_updates: usize,
+        disconnected: bool,
+    }
+
+    /// A room hosted in process, with another bot in the host's seat. The
+    /// guest gets the updates the relay would bring it.
+    struct StubSession {
+        backend: TrictracBackend,
+        host: RemoteBot,
+        events: VecDeque<SessionEvent<GameDelta, ViewState>>,
+        sent: Rc<RefCell<Sent>>,
+    }
+
+    impl StubSession {
+        fn join(sent: Rc<RefCell<Sent>>) -> Self {
+            let mut backend = TrictracBackend::new(0);
+            backend.player_arrival(0);
+            backend.player_arrival(1);
+            let mut stub = StubSession {
+                backend,
+                host: RemoteBot::new(Box::new(DefaultStrategy::default()), 0),
+                events: VecDeque::new(),
+                sent,
+            };
+            stub.host_plays();
+            stub
+        }
+
+        /// Lets the host bot act, then queues the updates like the host loop.
+        fn host_plays(&mut self) {
+            while let Some(action) = self.host.decide(self.backend.get_view_state()) {
+                self.backend.inform_rpc(0, action);
+            }
+            let mut deltas = Vec::new();
+            let mut reset = false;
+            for command in self.backend.drain_commands() {
+                match command {
+                    BackendCommand::Delta(d) => deltas.push(d),
+                    BackendCommand::ResetViewState => reset = true,
+                    _ => {}
+                }
+            }
+            if reset {
+                let state = self.backend.get_view_state().clone();
+                self.events
+                    .push_back(SessionEvent::Update(ViewStateUpdate::Full(state)));
+            } else {
+                for d in deltas {
+                    self.events
+                        .push_back(SessionEvent::Update(ViewStateUpdate::Incremental(d)));
+                }
+            }
+        }
+    }
+
+    impl Seat for StubSession {
+        fn is_host(&self) -> bool {
+            false
+        }
+
+        fn send_action(&mut self, action: PlayerAction) {
+            self.sent.borrow_mut().actions.push(action.clone());
+            self.backend.inform_rpc(1, action);
+            self.host_plays();
+        }
+
+        fn request_full_update(&mut self) {
+            self.sent.borrow_mut().full_updates += 1;
+            let state = self.backend.get_view_state().clone();
+            self.events
+                .push_back(SessionEvent::Update(ViewStateUpdate::Full(state)));
+        }
+
+        async fn next_event(&mut self) -> Option<SessionEvent<GameDelta, ViewState>> {
+            self.events.pop_front()
+        }
+
+        fn disconnect(self) {
+            self.sent.borrow_mut().disconnected = true;
+        }
+    }
+
+    #[test]
+    fn guest_bot_joins_plays_and_leaves_at_the_end() {
+        let sent = Rc::new(RefCell::new(Sent::default()));
+        let session = StubSession::join(Rc::clone(&sent));
+        let bot = RemoteBot::new(Box::new(DefaultStrategy::default()), 1);
+        futures::executor::block_on(play(session, "Bot", bot)).unwrap();
+
+        let sent = sent.borrow();
+        assert!(matches!(&sent.actions[0], PlayerAction::SetName(name) if name == "Bot"));
+        assert!(sent
+            .actions
+            .iter()
+            .any(|a| matches!(a, PlayerAction::PreGameRoll)));
+        assert!(sent
+            .actions
+            .iter()
+            .any(|a| matches!(a, PlayerAction::Move(..))));
+        // Every delta applied: the bot never lost track of the game.
+        assert_eq!(sent.full_updates, 0);
+        // Only the end of the game makes the bot leave.
+        assert!(sent.disconnected);
+    }
+}
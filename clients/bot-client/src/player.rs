@@ -0,0 +1,131 @@
+use log::debug;
+use trictrac_bot::BotStrategy;
+use trictrac_store::{Color, Dice, GameState, Player, Stage, TurnStage};
+
+use trictrac_game::types::{PlayerAction, SerStage, SerTurnStage, ViewState};
+
+// Strategies always play White as store player 1; the guest's board is mirrored.
+const BOT_STORE_ID: u64 = 1;
+const OPPONENT_STORE_ID: u64 = 2;
+
+/// A `BotStrategy` seated in a relay room.
+///
+/// The bot only sees the `ViewState` snapshots broadcast by the host, so the
+/// strategy's game is rebuilt from the snapshot each time it has to decide.
+pub struct RemoteBot {
+    strategy: Box<dyn BotStrategy>,
+    /// Seat in the room: 0 = host (White), 1 = guest (Black).
+    player_id: u16,
+    /// Position the last action was sent for. The host rebroadcasts the same
+    /// position on chat messages or spectator arrivals, which must not trigger
+    /// a second action.
+    acted_on: Option<ViewState>,
+}
+
+impl RemoteBot {
+    pub fn new(strategy: Box<dyn BotStrategy>, player_id: u16) -> Self {
+        Self {
+            strategy,
+            player_id,
+            acted_on: None,
+        }
+    }
+
+    /// Returns the action to send for `vs`, or `None` when it is not the bot's turn.
+    pub fn decide(&mut self, vs: &ViewState) -> Option<PlayerAction> {
+        let position = ViewState {
+            spectators: 0,
+            chat: Vec::new(),
+            clock: None,
+            seq: 0,
+            ..vs.clone()
+        };
+        if self.acted_on.as_ref() == Some(&position) {
+            return None;
+        }
+        let action = self.action_for(vs)?;
+        self.acted_on = Some(position);
+        Some(action)
+    }
+
+    fn action_for(&mut self, vs: &ViewState) -> Option<PlayerAction> {
+        match vs.stage {
+            SerStage::PreGameRoll => {
+                let pgr = vs.pre_game_roll.as_ref()?;
+                let my_die = if self.player_id == 0 {
+                    pgr.host_die
+                } else {
+                    pgr.guest_die
+                };
+                my_die.is_none().then_some(PlayerAction::PreGameRoll)
+            }
+            SerStage::InGame if vs.active_mp_player == Some(self.player_id) => {
+                match vs.turn_stage {
+                    SerTurnStage::RollDice => Some(PlayerAction::Roll),
+                    SerTurnStage::Move => {
+                        self.load_game(vs);
+                        Some(self.choose_move())
+                    }
+                    SerTurnStage::HoldOrGoChoice => {
+                        self.load_game(vs);
+                        if self.strategy.choose_go() {
+                            Some(PlayerAction::Go)
+                        } else {
+                            Some(self.choose_move())
+                        }
+                    }
+                    // Points are marked by the host; RollWaiting waits for the dice.
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn choose_move(&self) -> PlayerAction {
+        let (m1, m2) = self.strategy.choose_move();
+        debug!("bot {} moves {m1:?} {m2:?}", self.player_id);
+        if self.player_id == 0 {
+            PlayerAction::Move(m1, m2)
+        } else {
+            PlayerAction::Move(m1.mirror(), m2.mirror())
+        }
+    }
+
+    /// Rebuild the strategy's game from the snapshot, seen from the bot's side.
+    fn load_game(&mut self, vs: &ViewState) {
+        let (color, mine, theirs) = if self.player_id == 0 {
+            (Color::White, &vs.scores[0], &vs.scores[1])
+        } else {
+            (Color::Black, &vs.scores[1], &vs.scores[0])
+        };
+
+        let mut game = GameState::new(false);
+        // Positions are in White's frame; setting them for Black mirrors them.
+        game.board.set_positions(&color, vs.board);
+        game.stage = Stage::InGame;
+        game.turn_stage = match vs.turn_stage {
+            SerTurnStage::RollDice => TurnStage::RollDice,
+            SerTurnStage::RollWaiting => TurnStage::RollWaiting,
+            SerTurnStage::MarkPoints => TurnStage::MarkPoints,
+            SerTurnStage::HoldOrGoChoice => TurnStage::HoldOrGoChoice,
+            SerTurnStage::Move => TurnStage::Move,
+            SerTurnStage::MarkAdvPoints => TurnStage::MarkAdvPoints,
+        };
+        game.dice = Dice { values: vs.dice };
+        game.active_player_id = BOT_STORE_ID;
+
+        let mut me = Player::new(mine.name.clone(), Color::White);
+        me.points = mine.points;
+        me.holes = mine.holes;
+        me.can_bredouille = mine.can_bredouille;
+        let mut opponent = Player::new(theirs.name.clone(), Color::Black);
+        opponent.points = theirs.points;
+        opponent.holes = theirs.holes;
+        opponent.can_bredouille = theirs.can_bredouille;
+        game.players.insert(BOT_STORE_ID, me);
+        game.players.insert(OPPONENT_STORE_ID, opponent);
+
+        *self.strategy.get_mut_game() = game;
+    }
+}
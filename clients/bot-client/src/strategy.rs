@@ -0,0 +1,36 @@
+use spiel_bot::strategy::{AzBotStrategy, DqnSpielBotStrategy};
+use trictrac_bot::{
+    BotStrategy, DefaultStrategy, DqnBurnStrategy, ErroneousStrategy, RandomStrategy,
+    StableBaselines3Strategy,
+};
+
+/// Builds a strategy from its command-line token (same tokens as `client_cli --bot`).
+pub fn parse_strategy(token: &str) -> Option<Box<dyn BotStrategy>> {
+    let strategy: Box<dyn BotStrategy> = match token.trim() {
+        "dummy" => Box::new(DefaultStrategy::default()),
+        "random" => Box::new(RandomStrategy::default()),
+        "erroneous" => Box::new(ErroneousStrategy::default()),
+        "ai" => Box::new(StableBaselines3Strategy::default()),
+        "dqnburn" => Box::new(DqnBurnStrategy::default()),
+        s if s.starts_with("ai:") => {
+            Box::new(StableBaselines3Strategy::new(s.trim_start_matches("ai:")))
+        }
+        s if s.starts_with("dqnburn:") => Box::new(DqnBurnStrategy::new_with_model(
+            &s.trim_start_matches("dqnburn:").to_string(),
+        )),
+        "az" => Box::new(AzBotStrategy::new_mlp(None)),
+        s if s.starts_with("az:") => {
+            Box::new(AzBotStrategy::new_mlp(Some(s.trim_start_matches("az:"))))
+        }
+        "az-resnet" => Box::new(AzBotStrategy::new_resnet(None)),
+        s if s.starts_with("az-resnet:") => Box::new(AzBotStrategy::new_resnet(Some(
+            s.trim_start_matches("az-resnet:"),
+        ))),
+        "az-dqn" => Box::new(DqnSpielBotStrategy::new(None)),
+        s if s.starts_with("az-dqn:") => Box::new(DqnSpielBotStrategy::new(Some(
+            s.trim_start_matches("az-dqn:"),
+        ))),
+        _ => return None,
+    };
+    Some(strategy)
+}
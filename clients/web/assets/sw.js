@@ -0,0 +1,106 @@
+// Service worker: keeps the app (wasm bundle, styles, sounds, bot network)
+// in a cache so that it starts, and plays against the bot, without network.
+//
+// Page loads go to the network first and fall back to the cached index.html.
+// Trunk names the bundle files after their content, so those are served from
+// the cache once fetched; each new index.html brings its own set, and the
+// files of older releases are dropped. The relay's API and websockets are
+// never cached.
+//
+// The EXTRA files keep their name from one release to the next: they are
+// fetched again when this file changes, so bump CACHE along with them.
+
+const CACHE = "trictrac-app-v1";
+const INDEX = "/index.html";
+const ASSETS = /\.(?:js|wasm|css|mp3|ttnw|png|svg|webmanifest)$/;
+// Fetched along with each release, but not named in index.html.
+const EXTRA = [
+  "/diceroll.mp3",
+  "/manifest.webmanifest",
+  "/icons/icon-192.png",
+  "/icons/icon-512.png",
+  "/bot/trictrac.ttnw",
+];
+
+/// The bundle files index.html refers to.
+function releaseFiles(html) {
+  const files = new Set();
+  for (const [, path] of html.matchAll(/["'](\/[^"'?#]+)["']/g)) {
+    if (ASSETS.test(path) && path !== "/sw.js") files.add(path);
+  }
+  return files;
+}
+
+/// Caches `index` and the files it refers to, then drops the older ones.
+/// With `refresh`, files already cached are fetched again.
+async function cacheRelease(index, refresh) {
+  const cache = await caches.open(CACHE);
+  const files = releaseFiles(await index.clone().text());
+  await Promise.all(
+    [...files, ...EXTRA].map(async (path) => {
+      if (!refresh && (await cache.match(path))) return;
+      try {
+        const response = await fetch(path, { cache: "no-cache" });
+        if (response.ok) await cache.put(path, response);
+      } catch (_) {
+        // The bot network is optional; a missing file is fetched again later.
+      }
+    }),
+  );
+  await cache.put(INDEX, index);
+  for (const request of await cache.keys()) {
+    const path = new URL(request.url).pathname;
+    if (path !== INDEX && !files.has(path) && !EXTRA.includes(path)) {
+      await cache.delete(request);
+    }
+  }
+}
+
+self.addEventListener("install", (event) => {
+  event.waitUntil(
+    fetch(INDEX, { cache: "no-cache" })
+      .then((index) => cacheRelease(index, true))
+      .then(() => self.skipWaiting()),
+  );
+});
+
+self.addEventListener("activate", (event) => {
+  event.waitUntil(
+    caches
+      .keys()
+      .then((names) => Promise.all(names.filter((n) => n !== CACHE).map((n) => caches.delete(n))))
+      .then(() => self.clients.claim()),
+  );
+});
+
+self.addEventListener("fetch", (event) => {
+  const request = event.request;
+  const url = new URL(request.url);
+  if (request.method !== "GET" || url.origin !== self.location.origin) return;
+
+  if (request.mode === "navigate") {
+    event.respondWith(
+      fetch(request)
+        .then((response) => {
+          const html = (response.headers.get("content-type") || "").startsWith("text/html");
+          if (response.ok && html) event.waitUntil(cacheRelease(response.clone(), false));
+          return response;
+        })
+        .catch(() => caches.match(INDEX)),
+    );
+  } else if (ASSETS.test(url.pathname)) {
+    event.respondWith(
+      caches.match(url.pathname).then(
+        (cached) =>
+          cached ||
+          fetch(request).then(async (response) => {
+            if (response.ok) {
+              const cache = await caches.open(CACHE);
+              await cache.put(url.pathname, response.clone());
+            }
+            return response;
+          }),
+      ),
+    );
+  }
+});
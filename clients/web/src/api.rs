@@ -17,6 +17,14 @@ pub struct MeResponse {
     pub username: String,
     #[serde(default)]
     pub email_verified: bool,
+    #[serde(default)]
+    pub email_notifications: bool,
+}
+
+/// The OpenID Connect provider users may sign in with.
+#[derive(Clone, Debug, Deserialize)]
+pub struct OidcProvider {
+    pub name: String,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -61,6 +69,9 @@ pub struct GameDetail {
     pub started_at: i64,
     pub ended_at: Option<i64>,
     pub result: Option<String>,
+    /// Whether the moves were recorded, so that the game can be replayed.
+    #[serde(default)]
+    pub has_record: bool,
     pub participants: Vec<Participant>,
 }
 
@@ -70,6 +81,102 @@ pub struct PageContent {
     pub content: String,
 }
 
+#[derive(Clone, Debug, Deserialize)]
+pub struct TournamentSummary {
+    pub id: i64,
+    pub name: String,
+    pub format: String,
+    pub rule_variation: u16,
+    pub status: String,
+    pub current_round: i32,
+    pub total_rounds: i32,
+    pub organizer: Option<String>,
+    pub player_count: i64,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct TournamentsResponse {
+    pub tournaments: Vec<TournamentSummary>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct TournamentStanding {
+    pub rank: usize,
+    pub username: String,
+    pub score: f32,
+    pub buchholz: f32,
+    pub sonneborn_berger: f32,
+    pub wins: u32,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct TournamentGame {
+    pub id: i64,
+    pub round: i32,
+    pub board: i32,
+    pub host: Option<String>,
+    pub guest: Option<String>,
+    pub is_bye: bool,
+    /// Only sent to the two players of the game.
+    pub room_code: Option<String>,
+    pub game_record_id: Option<i64>,
+    /// "host", "guest" or "draw"; `None` while unplayed.
+    pub outcome: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct TournamentDetail {
+    #[serde(flatten)]
+    pub summary: TournamentSummary,
+    pub standings: Vec<TournamentStanding>,
+    pub games: Vec<TournamentGame>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Friend {
+    pub username: String,
+    pub online: bool,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct FriendsResponse {
+    pub friends: Vec<Friend>,
+    pub incoming: Vec<String>,
+    pub outgoing: Vec<String>,
+    pub blocked: Vec<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Challenge {
+    pub id: i64,
+    pub challenger: String,
+    pub invitee: String,
+    pub rule_variation: u16,
+    pub room_code: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ChallengesResponse {
+    pub incoming: Vec<Challenge>,
+    pub outgoing: Vec<Challenge>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PuzzleSummary {
+    pub id: i64,
+    pub title: String,
+    /// The game-specific position and solutions.
+    pub puzzle: serde_json::Value,
+    /// The current user's attempts; 0 when signed out.
+    pub attempts: i64,
+    pub solved: bool,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PuzzlesResponse {
+    pub puzzles: Vec<PuzzleSummary>,
+}
+
 // ── Request bodies ────────────────────────────────────────────────────────────
 
 #[derive(Serialize)]
@@ -100,6 +207,24 @@ pub async fn get_me() -> Result<MeResponse, String> {
     }
 }
 
+/// Fails when the relay has no OpenID Connect provider configured.
+pub async fn get_oidc_provider() -> Result<OidcProvider, String> {
+    let resp = gloo_net::http::Request::get(&url("/auth/oidc"))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if resp.status() == 200 {
+        resp.json::<OidcProvider>().await.map_err(|e| e.to_string())
+    } else {
+        Err(format!("status {}", resp.status()))
+    }
+}
+
+/// Where to send the browser to sign in with, or link, the provider account.
+pub fn oidc_login_url() -> String {
+    url("/auth/oidc/login")
+}
+
 pub async fn post_login(username: &str, password: &str) -> Result<MeResponse, String> {
     let body = LoginBody { username, password };
     let resp = gloo_net::http::Request::post(&url("/auth/login"))
@@ -201,6 +326,33 @@ pub async fn get_game_detail(id: i64) -> Result<GameDetail, String> {
     }
 }
 
+/// The game record uploaded with the result, as JSON text.
+pub async fn get_game_record(id: i64) -> Result<String, String> {
+    let resp = gloo_net::http::Request::get(&url(&format!("/games/{id}/record")))
+        .credentials(web_sys::RequestCredentials::Include)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if resp.status() == 200 {
+        resp.text().await.map_err(|e| e.to_string())
+    } else {
+        Err(format!("status {}", resp.status()))
+    }
+}
+
+/// The bot's trained network, served with the page (see `assets/bot`).
+pub async fn get_bot_network() -> Result<Vec<u8>, String> {
+    let resp = gloo_net::http::Request::get("/bot/trictrac.ttnw")
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if resp.status() == 200 {
+        resp.binary().await.map_err(|e| e.to_string())
+    } else {
+        Err(format!("status {}", resp.status()))
+    }
+}
+
 pub async fn get_verify_email(token: &str) -> Result<(), String> {
     let resp = gloo_net::http::Request::get(&url(&format!("/auth/verify-email?token={token}")))
         .credentials(web_sys::RequestCredentials::Include)
@@ -273,6 +425,330 @@ pub async fn get_page(slug: &str, lang: &str) -> Result<PageContent, String> {
     }
 }
 
+pub async fn get_tournaments() -> Result<TournamentsResponse, String> {
+    let resp = gloo_net::http::Request::get(&url("/tournaments"))
+        .credentials(web_sys::RequestCredentials::Include)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if resp.status() == 200 {
+        resp.json::<TournamentsResponse>().await.map_err(|e| e.to_string())
+    } else {
+        Err(format!("status {}", resp.status()))
+    }
+}
+
+pub async fn get_tournament(id: i64) -> Result<TournamentDetail, String> {
+    let resp = gloo_net::http::Request::get(&url(&format!("/tournaments/{id}")))
+        .credentials(web_sys::RequestCredentials::Include)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if resp.status() == 200 {
+        resp.json::<TournamentDetail>().await.map_err(|e| e.to_string())
+    } else {
+        Err(format!("status {}", resp.status()))
+    }
+}
+
+pub async fn post_create_tournament(
+    name: &str,
+    game_id: &str,
+    format: &str,
+    rule_variation: u16,
+    rounds: i32,
+) -> Result<TournamentSummary, String> {
+    let body = serde_json::json!({
+        "name": name,
+        "game_id": game_id,
+        "format": format,
+        "rule_variation": rule_variation,
+        "rounds": rounds,
+    });
+    let resp = gloo_net::http::Request::post(&url("/tournaments"))
+        .credentials(web_sys::RequestCredentials::Include)
+        .json(&body)
+        .map_err(|e| e.to_string())?
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if resp.status() == 201 {
+        resp.json::<TournamentSummary>().await.map_err(|e| e.to_string())
+    } else {
+        let text = resp.text().await.unwrap_or_default();
+        Err(text)
+    }
+}
+
+/// Registers (`register = true`) or withdraws the current user.
+pub async fn set_tournament_registration(id: i64, register: bool) -> Result<(), String> {
+    let path = url(&format!("/tournaments/{id}/register"));
+    let request = if register {
+        gloo_net::http::Request::post(&path)
+    } else {
+        gloo_net::http::Request::delete(&path)
+    };
+    let resp = request
+        .credentials(web_sys::RequestCredentials::Include)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if resp.ok() {
+        Ok(())
+    } else {
+        let text = resp.text().await.unwrap_or_default();
+        Err(text)
+    }
+}
+
+/// Organizer only: pairs the next round, or finishes the tournament after the last one.
+pub async fn post_tournament_next_round(id: i64) -> Result<(), String> {
+    let resp = gloo_net::http::Request::post(&url(&format!("/tournaments/{id}/rounds")))
+        .credentials(web_sys::RequestCredentials::Include)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if resp.ok() {
+        Ok(())
+    } else {
+        let text = resp.text().await.unwrap_or_default();
+        Err(text)
+    }
+}
+
+/// Organizer only: sets a game's outcome by hand ("host", "guest" or "draw").
+pub async fn post_tournament_result(id: i64, game_id: i64, outcome: &str) -> Result<(), String> {
+    let body = serde_json::json!({ "outcome": outcome });
+    let resp = gloo_net::http::Request::post(&url(&format!(
+        "/tournaments/{id}/games/{game_id}/result"
+    )))
+    .credentials(web_sys::RequestCredentials::Include)
+    .json(&body)
+    .map_err(|e| e.to_string())?
+    .send()
+    .await
+    .map_err(|e| e.to_string())?;
+    if resp.ok() {
+        Ok(())
+    } else {
+        let text = resp.text().await.unwrap_or_default();
+        Err(text)
+    }
+}
+
+pub async fn put_preferences(email_notifications: bool) -> Result<(), String> {
+    let body = serde_json::json!({ "email_notifications": email_notifications });
+    let resp = gloo_net::http::Request::put(&url("/auth/preferences"))
+        .credentials(web_sys::RequestCredentials::Include)
+        .json(&body)
+        .map_err(|e| e.to_string())?
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if resp.ok() {
+        Ok(())
+    } else {
+        let text = resp.text().await.unwrap_or_default();
+        Err(text)
+    }
+}
+
+pub async fn get_friends() -> Result<FriendsResponse, String> {
+    let resp = gloo_net::http::Request::get(&url("/friends"))
+        .credentials(web_sys::RequestCredentials::Include)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if resp.status() == 200 {
+        resp.json::<FriendsResponse>().await.map_err(|e| e.to_string())
+    } else {
+        Err(format!("status {}", resp.status()))
+    }
+}
+
+/// Sends a friend request (`add = true`), or removes a friend, a sent request
+/// or a received one.
+pub async fn set_friend(username: &str, add: bool) -> Result<(), String> {
+    let path = url(&format!("/friends/{username}"));
+    let request = if add {
+        gloo_net::http::Request::post(&path)
+    } else {
+        gloo_net::http::Request::delete(&path)
+    };
+    let resp = request
+        .credentials(web_sys::RequestCredentials::Include)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if resp.ok() {
+        Ok(())
+    } else {
+        let text = resp.text().await.unwrap_or_default();
+        Err(text)
+    }
+}
+
+pub async fn post_accept_friend(username: &str) -> Result<(), String> {
+    let resp = gloo_net::http::Request::post(&url(&format!("/friends/{username}/accept")))
+        .credentials(web_sys::RequestCredentials::Include)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if resp.ok() {
+        Ok(())
+    } else {
+        let text = resp.text().await.unwrap_or_default();
+        Err(text)
+    }
+}
+
+/// Blocks (`block = true`) or unblocks a user.
+pub async fn set_blocked(username: &str, block: bool) -> Result<(), String> {
+    let path = url(&format!("/friends/{username}/block"));
+    let request = if block {
+        gloo_net::http::Request::post(&path)
+    } else {
+        gloo_net::http::Request::delete(&path)
+    };
+    let resp = request
+        .credentials(web_sys::RequestCredentials::Include)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if resp.ok() {
+        Ok(())
+    } else {
+        let text = resp.text().await.unwrap_or_default();
+        Err(text)
+    }
+}
+
+pub async fn get_challenges() -> Result<ChallengesResponse, String> {
+    let resp = gloo_net::http::Request::get(&url("/challenges"))
+        .credentials(web_sys::RequestCredentials::Include)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if resp.status() == 200 {
+        resp.json::<ChallengesResponse>().await.map_err(|e| e.to_string())
+    } else {
+        Err(format!("status {}", resp.status()))
+    }
+}
+
+pub async fn post_challenge(
+    username: &str,
+    game_id: &str,
+    rule_variation: u16,
+) -> Result<Challenge, String> {
+    let body = serde_json::json!({
+        "username": username,
+        "game_id": game_id,
+        "rule_variation": rule_variation,
+    });
+    let resp = gloo_net::http::Request::post(&url("/challenges"))
+        .credentials(web_sys::RequestCredentials::Include)
+        .json(&body)
+        .map_err(|e| e.to_string())?
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if resp.status() == 201 {
+        resp.json::<Challenge>().await.map_err(|e| e.to_string())
+    } else {
+        let text = resp.text().await.unwrap_or_default();
+        Err(text)
+    }
+}
+
+/// Invitee only: accepts (`accept = true`) or declines a challenge.
+pub async fn answer_challenge(id: i64, accept: bool) -> Result<(), String> {
+    let action = if accept { "accept" } else { "decline" };
+    let resp = gloo_net::http::Request::post(&url(&format!("/challenges/{id}/{action}")))
+        .credentials(web_sys::RequestCredentials::Include)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if resp.ok() {
+        Ok(())
+    } else {
+        let text = resp.text().await.unwrap_or_default();
+        Err(text)
+    }
+}
+
+/// Challenger only: withdraws a challenge.
+pub async fn delete_challenge(id: i64) -> Result<(), String> {
+    let resp = gloo_net::http::Request::delete(&url(&format!("/challenges/{id}")))
+        .credentials(web_sys::RequestCredentials::Include)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if resp.ok() {
+        Ok(())
+    } else {
+        let text = resp.text().await.unwrap_or_default();
+        Err(text)
+    }
+}
+
+pub async fn get_puzzles(game_id: &str) -> Result<PuzzlesResponse, String> {
+    let resp = gloo_net::http::Request::get(&url(&format!("/puzzles?game_id={game_id}")))
+        .credentials(web_sys::RequestCredentials::Include)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if resp.status() == 200 {
+        resp.json::<PuzzlesResponse>().await.map_err(|e| e.to_string())
+    } else {
+        Err(format!("status {}", resp.status()))
+    }
+}
+
+/// The day's puzzle; `None` when there are no puzzles.
+pub async fn get_daily_puzzle(game_id: &str) -> Result<Option<PuzzleSummary>, String> {
+    let resp = gloo_net::http::Request::get(&url(&format!("/puzzles/daily?game_id={game_id}")))
+        .credentials(web_sys::RequestCredentials::Include)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    match resp.status() {
+        200 => resp.json::<PuzzleSummary>().await.map(Some).map_err(|e| e.to_string()),
+        404 => Ok(None),
+        status => Err(format!("status {status}")),
+    }
+}
+
+pub async fn get_puzzle(id: i64) -> Result<PuzzleSummary, String> {
+    let resp = gloo_net::http::Request::get(&url(&format!("/puzzles/{id}")))
+        .credentials(web_sys::RequestCredentials::Include)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if resp.status() == 200 {
+        resp.json::<PuzzleSummary>().await.map_err(|e| e.to_string())
+    } else {
+        Err(format!("status {}", resp.status()))
+    }
+}
+
+/// Signed-in users only: records an answer to a puzzle.
+pub async fn post_puzzle_attempt(id: i64, solved: bool) -> Result<(), String> {
+    let resp = gloo_net::http::Request::post(&url(&format!("/puzzles/{id}/attempts")))
+        .credentials(web_sys::RequestCredentials::Include)
+        .json(&serde_json::json!({ "solved": solved }))
+        .map_err(|e| e.to_string())?
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if resp.ok() {
+        Ok(())
+    } else {
+        let text = resp.text().await.unwrap_or_default();
+        Err(text)
+    }
+}
+
 // ── Utilities ─────────────────────────────────────────────────────────────────
 
 /// Maps to the `Intl.DateTimeFormat` options object accepted by `Date.toLocaleString`.
@@ -12,22 +12,30 @@ use backbone_lib::session::{ConnectError, GameSession, RoomConfig, RoomRole, Ses
 use backbone_lib::traits::ViewStateUpdate;
 
 use crate::api;
-use crate::game::components::{ConnectingScreen, GameScreen};
+use crate::game::components::{Announcer, ConnectingScreen, GameScreen};
 use crate::game::session::{
-    compute_last_moves, patch_player_name, push_or_show, run_local_bot_game,
-    run_local_bot_game_with_backend,
+    compute_last_moves, load_bot, patch_player_name, push_or_show, run_local_bot_game,
+    run_local_bot_game_with_backend, stored_bot_game,
+};
+use trictrac_game::backend::TrictracBackend;
+use trictrac_game::replay::GameRecord;
+use trictrac_game::types::{
+    ChatEntry, ClockView, GameDelta, PlayerAction, ScoredEvent, SerStage, ViewState,
+    SCHEMA_VERSION,
 };
-use crate::game::trictrac::backend::TrictracBackend;
-use crate::game::trictrac::types::{GameDelta, PlayerAction, ScoredEvent, SerStage, ViewState};
 use crate::i18n::*;
+use crate::notifications::{spawn_listener, NotificationTray, Notifications};
 use crate::portal::{
-    account::AccountPage, content_page::ContentPage, forgot_password::ForgotPasswordPage,
-    game_detail::GameDetailPage, lobby::LobbyPage, profile::ProfilePage,
-    reset_password::ResetPasswordPage, verify_email::VerifyEmailPage,
+    account::AccountPage, analysis::AnalysisPage, blunders::BlundersPage, content_page::ContentPage, forgot_password::ForgotPasswordPage,
+    friends::FriendsPage, game_detail::GameDetailPage, lobby::LobbyPage, profile::ProfilePage,
+    puzzles::{PuzzlePage, PuzzlesPage},
+    replay::ReplayPage, reset_password::ResetPasswordPage, tournament_detail::TournamentDetailPage,
+    tournaments::TournamentsPage, tutorial::TutorialPage, verify_email::VerifyEmailPage,
 };
 use trictrac_store::CheckerMove;
 
 use std::collections::VecDeque;
+use web_time::Instant;
 
 /// Newtype wrappers so context lookup can distinguish signals of the same inner type.
 #[derive(Clone, Copy)]
@@ -37,11 +45,27 @@ pub(crate) struct AuthEmailVerified(pub RwSignal<bool>);
 /// One-shot message shown as a top banner and auto-dismissed after a few seconds.
 #[derive(Clone, Copy)]
 pub(crate) struct FlashMessage(pub RwSignal<Option<String>>);
+/// Chat history of the current room. Kept outside `GameUiState` so that a new
+/// message does not re-mount the game screen.
+#[derive(Clone, Copy)]
+pub(crate) struct ChatLog(pub RwSignal<Vec<ChatEntry>>);
+/// Unsent chat input, kept across game screen re-mounts.
+#[derive(Clone, Copy)]
+pub(crate) struct ChatDraft(pub RwSignal<String>);
+/// Latest clock state of the room and when it was received; the clock display
+/// counts down from there.
+#[derive(Clone, Copy)]
+pub(crate) struct ClockSync(pub RwSignal<Option<(ClockView, Instant)>>);
 
 fn relay_url() -> String {
+    ws_url("/ws")
+}
+
+/// WebSocket URL of a relay endpoint, e.g. `/ws` or `/notifications`.
+pub(crate) fn ws_url(path: &str) -> String {
     #[cfg(debug_assertions)]
     {
-        "ws://localhost:8080/ws".to_string()
+        format!("ws://localhost:8080{path}")
     }
     #[cfg(not(debug_assertions))]
     {
@@ -49,10 +73,10 @@ fn relay_url() -> String {
         let protocol = location.protocol().unwrap_or_default();
         let host = location.host().unwrap_or_default();
         let ws_protocol = if protocol == "https:" { "wss" } else { "ws" };
-        format!("{ws_protocol}://{host}/ws")
+        format!("{ws_protocol}://{host}{path}")
     }
 }
-const GAME_ID: &str = "trictrac";
+pub(crate) const GAME_ID: &str = "trictrac";
 const STORAGE_KEY: &str = "trictrac_session";
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -72,6 +96,9 @@ pub struct GameUiState {
     /// True on the echo screen state set alongside a pending item — suppresses dice
     /// roll animation and sound since they already played on the pending screen.
     pub suppress_dice_anim: bool,
+    /// True when watching a room as a spectator: the board is read-only and
+    /// shown from the host's side.
+    pub is_spectator: bool,
 }
 
 /// Reason the UI is paused waiting for the player to click Continue.
@@ -95,10 +122,16 @@ pub enum Screen {
 pub enum NetCommand {
     CreateRoom {
         room: String,
+        /// Time control of the room, see `TimeControl`; 0 = untimed.
+        rule_variation: u16,
     },
     JoinRoom {
         room: String,
     },
+    /// Watch a room as a spectator.
+    WatchRoom {
+        room: String,
+    },
     Reconnect {
         relay_url: String,
         game_id: String,
@@ -109,7 +142,11 @@ pub enum NetCommand {
     PlayVsBot,
     /// Start a bot game with the board/score position from a previously taken snapshot.
     ReplaySnapshot(ViewState),
+    /// Go on with the bot game left unfinished when the app was last closed.
+    ResumeBotGame(ViewState),
     Action(PlayerAction),
+    /// Send a chat message (free text or a quick phrase shortcode).
+    Chat(String),
     Disconnect,
 }
 
@@ -121,8 +158,10 @@ struct StoredSession {
     token: u64,
     #[serde(default)]
     is_host: bool,
+    /// The host backend's last snapshot, to restore the game from. The
+    /// backend saves it as JSON, kept as text to stay compact.
     #[serde(default)]
-    view_state: Option<ViewState>,
+    host_state: Option<String>,
 }
 
 fn save_session(session: &StoredSession) {
@@ -138,9 +177,15 @@ fn clear_session() {
 }
 
 async fn submit_game_result(room_code: String, game_state: ViewState) {
+    let record = GameRecord::from_view_state(&game_state);
     let [score_pl1, score_pl2] = game_state.scores;
     let result_str = format!("{:?} - {:?}", score_pl1.holes, score_pl2.holes);
-    let outcomes = if score_pl1.holes < score_pl2.holes {
+    let flagged = game_state.clock.and_then(|c| c.flagged);
+    let outcomes = if flagged == Some(0) {
+        [("0", "loss"), ("1", "win")]
+    } else if flagged == Some(1) {
+        [("0", "win"), ("1", "loss")]
+    } else if score_pl1.holes < score_pl2.holes {
         [("0", "loss"), ("1", "win")]
     } else if score_pl2.holes < score_pl1.holes {
         [("0", "win"), ("1", "loss")]
@@ -152,6 +197,7 @@ async fn submit_game_result(room_code: String, game_state: ViewState) {
         "game_id":   GAME_ID,
         "result":    result_str,
         "outcomes":  std::collections::HashMap::from(outcomes),
+        "record":    record,
     });
     let _ = gloo_net::http::Request::post(&format!("{}/games/result", api::HTTP_BASE))
         .credentials(web_sys::RequestCredentials::Include)
@@ -187,6 +233,14 @@ pub fn App() -> impl IntoView {
     provide_context(AnonNickname(anon_nickname));
     let flash: RwSignal<Option<String>> = RwSignal::new(None);
     provide_context(FlashMessage(flash));
+    let chat_log: RwSignal<Vec<ChatEntry>> = RwSignal::new(Vec::new());
+    provide_context(ChatLog(chat_log));
+    provide_context(ChatDraft(RwSignal::new(String::new())));
+    let clock_sync: RwSignal<Option<(ClockView, Instant)>> = RwSignal::new(None);
+    provide_context(ClockSync(clock_sync));
+    let notifications = RwSignal::new(Vec::new());
+    provide_context(Notifications(notifications));
+    spawn_listener(auth_username, notifications);
     spawn_local(async move {
         if let Ok(me) = api::get_me().await {
             auth_username.set(Some(me.username));
@@ -201,10 +255,7 @@ pub fn App() -> impl IntoView {
     provide_context(cmd_tx.clone());
 
     if let Some(s) = stored {
-        let host_state = s
-            .view_state
-            .as_ref()
-            .and_then(|vs| serde_json::to_vec(vs).ok());
+        let host_state = s.host_state.map(String::into_bytes);
         cmd_tx
             .unbounded_send(NetCommand::Reconnect {
                 relay_url: s.relay_url,
@@ -214,11 +265,16 @@ pub fn App() -> impl IntoView {
                 host_state,
             })
             .ok();
+    } else if let Some(vs) = stored_bot_game() {
+        cmd_tx.unbounded_send(NetCommand::ResumeBotGame(vs)).ok();
     }
 
     spawn_local(async move {
+        // The bot's network, once downloaded.
+        let mut bot_net = None;
         loop {
             let mut snapshot_init: Option<ViewState> = None;
+            let mut resumed_name: Option<String> = None;
             let remote_config: Option<(RoomConfig, bool)> = loop {
                 match cmd_rx.next().await {
                     Some(NetCommand::PlayVsBot) => break None,
@@ -226,16 +282,25 @@ pub fn App() -> impl IntoView {
                         snapshot_init = Some(vs);
                         break None;
                     }
-                    Some(NetCommand::CreateRoom { room }) => {
+                    Some(NetCommand::ResumeBotGame(vs)) => {
+                        resumed_name = Some(vs.scores[0].name.clone());
+                        snapshot_init = Some(vs);
+                        break None;
+                    }
+                    Some(NetCommand::CreateRoom {
+                        room,
+                        rule_variation,
+                    }) => {
                         break Some((
                             RoomConfig {
                                 relay_url: relay_url(),
                                 game_id: GAME_ID.to_string(),
                                 room_id: room,
-                                rule_variation: 0,
+                                rule_variation,
                                 role: RoomRole::Create,
                                 reconnect_token: None,
                                 host_state: None,
+                                schema_version: SCHEMA_VERSION,
                             },
                             false,
                         ));
@@ -250,6 +315,22 @@ pub fn App() -> impl IntoView {
                                 role: RoomRole::Join,
                                 reconnect_token: None,
                                 host_state: None,
+                                schema_version: SCHEMA_VERSION,
+                            },
+                            false,
+                        ));
+                    }
+                    Some(NetCommand::WatchRoom { room }) => {
+                        break Some((
+                            RoomConfig {
+                                relay_url: relay_url(),
+                                game_id: GAME_ID.to_string(),
+                                room_id: room,
+                                rule_variation: 0,
+                                role: RoomRole::Spectate,
+                                reconnect_token: None,
+                                host_state: None,
+                                schema_version: SCHEMA_VERSION,
                             },
                             false,
                         ));
@@ -270,6 +351,7 @@ pub fn App() -> impl IntoView {
                                 role: RoomRole::Join,
                                 reconnect_token: Some(token),
                                 host_state,
+                                schema_version: SCHEMA_VERSION,
                             },
                             true,
                         ));
@@ -279,11 +361,12 @@ pub fn App() -> impl IntoView {
             };
 
             if remote_config.is_none() {
-                let player_name = auth_username
-                    .get_untracked()
+                let player_name = resumed_name
+                    .or_else(|| auth_username.get_untracked())
                     .or_else(|| anon_nickname.get_untracked())
                     .unwrap_or_else(|| untrack(|| t_string!(i18n, anonymous_name).to_string()));
                 loop {
+                    let bot = load_bot(&mut bot_net).await;
                     let restart = match snapshot_init.take() {
                         Some(vs) => {
                             let backend = TrictracBackend::from_view_state(vs, &player_name);
@@ -293,12 +376,19 @@ pub fn App() -> impl IntoView {
                                 pending,
                                 player_name.clone(),
                                 backend,
+                                bot,
                             )
                             .await
                         }
                         None => {
-                            run_local_bot_game(screen, &mut cmd_rx, pending, player_name.clone())
-                                .await
+                            run_local_bot_game(
+                                screen,
+                                &mut cmd_rx,
+                                pending,
+                                player_name.clone(),
+                                bot,
+                            )
+                            .await
                         }
                     };
                     if !restart {
@@ -324,16 +414,26 @@ pub fn App() -> impl IntoView {
                         screen.set(Screen::Login { error: Some(e) });
                         continue;
                     }
+                    Err(ConnectError::VersionMismatch(_)) => {
+                        if is_reconnect {
+                            clear_session();
+                        }
+                        let error = untrack(|| t_string!(i18n, version_mismatch).to_string());
+                        screen.set(Screen::Login { error: Some(error) });
+                        continue;
+                    }
                 };
 
-            if !session.is_host {
+            let is_spectator = session.is_spectator;
+            // Spectators have no seat to reconnect to.
+            if !session.is_host && !is_spectator {
                 save_session(&StoredSession {
                     relay_url: relay_url(),
                     game_id: GAME_ID.to_string(),
                     room_id: room_id_for_storage.clone(),
                     token: session.reconnect_token,
                     is_host: false,
-                    view_state: None,
+                    host_state: None,
                 });
             }
 
@@ -346,9 +446,13 @@ pub fn App() -> impl IntoView {
                 .unwrap_or_else(|| t_string!(i18n, anonymous_name).to_string());
             // Announce our name to the host backend so it can broadcast it to
             // the opponent. Done once immediately after connecting.
-            session.send_action(PlayerAction::SetName(my_name.clone()));
+            if !is_spectator {
+                session.send_action(PlayerAction::SetName(my_name.clone()));
+            }
             let mut vs = ViewState::default_with_names("", "");
             let mut result_submitted = false;
+            chat_log.set(Vec::new());
+            clock_sync.set(None);
 
             loop {
                 futures::select! {
@@ -356,6 +460,9 @@ pub fn App() -> impl IntoView {
                         Some(NetCommand::Action(action)) => {
                             session.send_action(action);
                         }
+                        Some(NetCommand::Chat(text)) => {
+                            session.send_chat(text);
+                        }
                         _ => {
                             clear_session();
                             session.disconnect();
@@ -369,9 +476,43 @@ pub fn App() -> impl IntoView {
                             let prev_vs = vs.clone();
                             match u {
                                 ViewStateUpdate::Full(state) => vs = state,
-                                ViewStateUpdate::Incremental(delta) => vs.apply_delta(&delta),
+                                ViewStateUpdate::Incremental(delta) => {
+                                    if !vs.apply_delta(&delta) {
+                                        // Out of sync: wait for the full state.
+                                        session.request_full_update();
+                                        continue;
+                                    }
+                                }
+                            }
+                            if vs.chat != prev_vs.chat {
+                                chat_log.set(vs.chat.clone());
+                            }
+                            if vs.clock != prev_vs.clock {
+                                clock_sync.set(vs.clock.clone().map(|c| (c, Instant::now())));
+                            }
+
+                            if is_spectator {
+                                if prev_vs.differs_only_in_chat_or_hints(&vs) {
+                                    continue;
+                                }
+                                // Watch from the host's side, without confirmation pauses.
+                                screen.set(Screen::Playing(GameUiState {
+                                    view_state: vs.clone(),
+                                    player_id: 0,
+                                    room_id: room_id_for_storage.clone(),
+                                    is_bot_game: false,
+                                    waiting_for_confirm: false,
+                                    pause_reason: None,
+                                    my_scored_event: None,
+                                    opp_scored_event: None,
+                                    last_moves: compute_last_moves(&prev_vs, &vs, false),
+                                    suppress_dice_anim: false,
+                                    is_spectator: true,
+                                }));
+                                continue;
                             }
                             patch_player_name(&mut vs, player_id, &my_name);
+                            let chat_or_hints_only = prev_vs.differs_only_in_chat_or_hints(&vs);
 
                             if is_host && !result_submitted && vs.stage == SerStage::Ended {
                                 result_submitted = true;
@@ -380,15 +521,8 @@ pub fn App() -> impl IntoView {
                                 spawn_local(submit_game_result(room, gs));
                             }
 
-                            if is_host {
-                                save_session(&StoredSession {
-                                    relay_url: relay_url(),
-                                    game_id: GAME_ID.to_string(),
-                                    room_id: room_id_for_storage.clone(),
-                                    token: reconnect_token,
-                                    is_host: true,
-                                    view_state: Some(vs.clone()),
-                                });
+                            if chat_or_hints_only {
+                                continue;
                             }
                             let is_own_move = prev_vs.active_mp_player == Some(player_id);
                             push_or_show(
@@ -404,11 +538,22 @@ pub fn App() -> impl IntoView {
                                     opp_scored_event: None,
                                     last_moves: compute_last_moves(&prev_vs, &vs, is_own_move),
                                     suppress_dice_anim: false,
+                                    is_spectator: false,
                                 },
                                 pending,
                                 screen,
                             );
                         }
+                        Some(SessionEvent::HostState(bytes)) => {
+                            save_session(&StoredSession {
+                                relay_url: relay_url(),
+                                game_id: GAME_ID.to_string(),
+                                room_id: room_id_for_storage.clone(),
+                                token: reconnect_token,
+                                is_host: true,
+                                host_state: String::from_utf8(bytes).ok(),
+                            });
+                        }
                         Some(SessionEvent::Disconnected(reason)) => {
                             pending.update(|q| q.clear());
                             screen.set(Screen::Login { error: reason });
@@ -429,12 +574,23 @@ pub fn App() -> impl IntoView {
         <Router>
             <SiteHamburger />
             <FlashBanner />
+            <NotificationTray />
             <main>
                 <Routes fallback=|| view! { <p class="portal-empty" style="padding:3rem;text-align:center">"Page not found."</p> }>
                     <Route path=path!("/") view=LobbyPage />
                     <Route path=path!("/account") view=AccountPage />
                     <Route path=path!("/profile/:username") view=ProfilePage />
                     <Route path=path!("/games/:id") view=GameDetailPage />
+                    <Route path=path!("/games/:id/replay") view=ReplayPage />
+                    <Route path=path!("/games/:id/blunders") view=BlundersPage />
+                    <Route path=path!("/replay") view=ReplayPage />
+                    <Route path=path!("/analysis") view=AnalysisPage />
+                    <Route path=path!("/tutorial") view=TutorialPage />
+                    <Route path=path!("/puzzles") view=PuzzlesPage />
+                    <Route path=path!("/puzzles/:id") view=PuzzlePage />
+                    <Route path=path!("/tournaments") view=TournamentsPage />
+                    <Route path=path!("/tournaments/:id") view=TournamentDetailPage />
+                    <Route path=path!("/friends") view=FriendsPage />
                     <Route path=path!("/verify-email") view=VerifyEmailPage />
                     <Route path=path!("/forgot-password") view=ForgotPasswordPage />
                     <Route path=path!("/reset-password") view=ResetPasswordPage />
@@ -489,7 +645,16 @@ fn GameOverlay(
     // state (the front) hasn't changed.
     let pending_front = Memo::new(move |_| pending.with(|q| q.front().cloned()));
 
-    move || {
+    // The position on screen, read out by the announcer as it changes. The
+    // announcer outlives each GameScreen, which is mounted anew per position.
+    let displayed = Memo::new(move |_| {
+        pending_front.get().map(|state| state.view_state).or_else(|| match screen.get() {
+            Screen::Playing(state) => Some(state.view_state),
+            _ => None,
+        })
+    });
+
+    let overlay = move || {
         if location.pathname.get() != "/" {
             return view! {}.into_any();
         }
@@ -510,6 +675,11 @@ fn GameOverlay(
             .into_any(),
             _ => view! {}.into_any(),
         }
+    };
+
+    view! {
+        <Announcer view_state=displayed />
+        {overlay}
     }
 }
 
@@ -597,11 +767,70 @@ fn SiteHamburger() -> impl IntoView {
             }}
             </div>
 
+            <div class="game-sidebar-section">
+                <svg class="icon" xmlns="http://www.w3.org/2000/svg" viewBox="0 0 640 640">
+                    <path fill="currentColor" d="M208 64C190.3 64 176 78.3 176 96L176 128L96 128C78.3 128 64 142.3 64 160L64 224C64 294.7 115 353.6 182.3 365.5C201.6 407.8 240.6 439.1 288 447.3L288 512L224 512C206.3 512 192 526.3 192 544C192 561.7 206.3 576 224 576L416 576C433.7 576 448 561.7 448 544C448 526.3 433.7 512 416 512L352 512L352 447.3C399.4 439.1 438.4 407.8 457.7 365.5C525 353.6 576 294.7 576 224L576 160C576 142.3 561.7 128 544 128L464 128L464 96C464 78.3 449.7 64 432 64L208 64zM176 192L176 296.6C143.4 283.9 120 252.7 120 216L120 192L176 192zM464 296.6L464 192L520 192L520 216C520 252.7 496.6 283.9 464 296.6z"/>
+                </svg>
+                <A href="/tournaments" attr:class="game-sidebar-link"
+                   on:click=move |_| sidebar_open.set(false)>
+                    {t!(i18n, tournaments_title)}
+                </A>
+            </div>
+
+            <div class="game-sidebar-section">
+                <svg class="icon" xmlns="http://www.w3.org/2000/svg" viewBox="0 0 640 640">
+                    <path fill="currentColor" d="M320 112C434.9 112 528 205.1 528 320C528 434.9 434.9 528 320 528C205.1 528 112 434.9 112 320C112 205.1 205.1 112 320 112zM320 576C461.4 576 576 461.4 576 320C576 178.6 461.4 64 320 64C178.6 64 64 178.6 64 320C64 461.4 178.6 576 320 576zM276.5 211.5C269.1 207 259.8 206.8 252.2 211C244.6 215.2 240 223.3 240 232L240 408C240 416.7 244.7 424.7 252.3 428.9C259.9 433.1 269.1 433 276.6 428.4L420.6 340.4C427.7 336 432.1 328.3 432.1 319.9C432.1 311.5 427.7 303.8 420.6 299.4L276.6 211.4z"/>
+                </svg>
+                <A href="/replay" attr:class="game-sidebar-link"
+                   on:click=move |_| sidebar_open.set(false)>
+                    {t!(i18n, replay_title)}
+                </A>
+            </div>
+
+            <div class="game-sidebar-section">
+                <svg class="icon" xmlns="http://www.w3.org/2000/svg" viewBox="0 0 640 640">
+                    <path fill="currentColor" d="M96 96C113.7 96 128 110.3 128 128L128 464C128 472.8 135.2 480 144 480L544 480C561.7 480 576 494.3 576 512C576 529.7 561.7 544 544 544L144 544C99.8 544 64 508.2 64 464L64 128C64 110.3 78.3 96 96 96zM208 288C225.7 288 240 302.3 240 320L240 384C240 401.7 225.7 416 208 416C190.3 416 176 401.7 176 384L176 320C176 302.3 190.3 288 208 288zM352 224L352 384C352 401.7 337.7 416 320 416C302.3 416 288 401.7 288 384L288 224C288 206.3 302.3 192 320 192C337.7 192 352 206.3 352 224zM432 256C449.7 256 464 270.3 464 288L464 384C464 401.7 449.7 416 432 416C414.3 416 400 401.7 400 384L400 288C400 270.3 414.3 256 432 256zM576 160L576 384C576 401.7 561.7 416 544 416C526.3 416 512 401.7 512 384L512 160C512 142.3 526.3 128 544 128C561.7 128 576 142.3 576 160z"/>
+                </svg>
+                <A href="/analysis" attr:class="game-sidebar-link"
+                   on:click=move |_| sidebar_open.set(false)>
+                    {t!(i18n, analysis_title)}
+                </A>
+            </div>
+
+            <div class="game-sidebar-section">
+                <svg class="icon" xmlns="http://www.w3.org/2000/svg" viewBox="0 0 640 640">
+                    <path fill="currentColor" d="M320 96L32 224L320 352L544 252.4L544 416C544 433.7 558.3 448 576 448C593.7 448 608 433.7 608 416L608 224L320 96zM128 326.4L128 448C128 501 215.9 544 320 544C424.1 544 512 501 512 448L512 326.4L320 411.7L128 326.4z"/>
+                </svg>
+                <A href="/tutorial" attr:class="game-sidebar-link"
+                   on:click=move |_| sidebar_open.set(false)>
+                    {t!(i18n, tutorial_title)}
+                </A>
+            </div>
+
+            <div class="game-sidebar-section">
+                <svg class="icon" xmlns="http://www.w3.org/2000/svg" viewBox="0 0 640 640">
+                    <path fill="currentColor" d="M288 96C288 78.3 302.3 64 320 64L352 64C369.7 64 384 78.3 384 96L384 128L480 128C497.7 128 512 142.3 512 160L512 256L544 256C561.7 256 576 270.3 576 288L576 320C576 337.7 561.7 352 544 352L512 352L512 512C512 529.7 497.7 544 480 544L352 544L352 512C352 494.3 337.7 480 320 480C302.3 480 288 494.3 288 512L288 544L160 544C142.3 544 128 529.7 128 512L128 384L160 384C177.7 384 192 369.7 192 352C192 334.3 177.7 320 160 320L128 320L128 160C128 142.3 142.3 128 160 128L288 128L288 96z"/>
+                </svg>
+                <A href="/puzzles" attr:class="game-sidebar-link"
+                   on:click=move |_| sidebar_open.set(false)>
+                    {t!(i18n, puzzles_title)}
+                </A>
+            </div>
+
             // Auth
                 {move || match auth_username.get() {
                     Some(u) => {
                         let href = format!("/profile/{u}");
                         view! {
+                        <div class="game-sidebar-section">
+                            <svg class="icon" xmlns="http://www.w3.org/2000/svg" viewBox="0 0 640 512">
+                                <path fill="currentColor" d="M96 128a128 128 0 1 1 256 0A128 128 0 1 1 96 128zM0 482.3C0 383.8 79.8 304 178.3 304l91.4 0C368.2 304 448 383.8 448 482.3c0 16.4-13.3 29.7-29.7 29.7L29.7 512C13.3 512 0 498.7 0 482.3zM609.3 512l-137.8 0c5.4-9.4 8.6-20.3 8.6-32l0-8c0-60.7-27.1-115.2-69.8-151.8c2.4-.1 4.7-.2 7.1-.2l61.4 0C567.8 320 640 392.2 640 481.3c0 17-13.8 30.7-30.7 30.7zM432 256c-31 0-59-12.6-79.3-32.9C372.4 196.5 384 163.6 384 128c0-26.8-6.6-52.1-18.3-74.3C384.3 40.1 407.2 32 432 32c61.9 0 112 50.1 112 112s-50.1 112-112 112z"/>
+                            </svg>
+                            <A href="/friends" attr:class="game-sidebar-link"
+                               on:click=move |_| sidebar_open.set(false)>
+                                {t!(i18n, friends_title)}
+                            </A>
+                        </div>
                         <div class="game-sidebar-section">
                             <svg class="icon" xmlns="http://www.w3.org/2000/svg" viewBox="0 0 640 640">
                                 <path fill="currentColor" d="M240 192C240 147.8 275.8 112 320 112C364.2 112 400 147.8 400 192C400 236.2 364.2 272 320 272C275.8 272 240 236.2 240 192zM448 192C448 121.3 390.7 64 320 64C249.3 64 192 121.3 192 192C192 262.7 249.3 320 320 320C390.7 320 448 262.7 448 192zM144 544C144 473.3 201.3 416 272 416L368 416C438.7 416 496 473.3 496 544L496 552C496 565.3 506.7 576 520 576C533.3 576 544 565.3 544 552L544 544C544 446.8 465.2 368 368 368L272 368C174.8 368 96 446.8 96 544L96 552C96 565.3 106.7 576 120 576C133.3 576 144 565.3 144 552L144 544z"/>
@@ -758,7 +987,7 @@ fn SiteHamburger() -> impl IntoView {
 mod tests {
     use super::*;
     use crate::game::session::infer_pause_reason;
-    use crate::game::trictrac::types::{PlayerScore, SerStage, SerTurnStage};
+    use trictrac_game::types::{PlayerScore, SerStage, SerTurnStage};
 
     fn score() -> PlayerScore {
         PlayerScore {
@@ -780,6 +1009,12 @@ mod tests {
             dice_jans: Vec::new(),
             dice_moves: (CheckerMove::default(), CheckerMove::default()),
             pre_game_roll: None,
+            spectators: 0,
+            chat: Vec::new(),
+            clock: None,
+            seq: 0,
+            history: Vec::new(),
+            hints: Default::default(),
         }
     }
 
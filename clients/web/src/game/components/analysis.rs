@@ -0,0 +1,342 @@
+use futures::channel::mpsc::UnboundedSender;
+use leptos::prelude::*;
+use trictrac_store::{CheckerMove, Color};
+
+use super::board::{Board, PositionSetup};
+use super::die::Die;
+use super::score_panel::jan_label;
+use crate::app::NetCommand;
+use trictrac_game::analysis::{
+    analyse, move_setup_checker, reserve, Analysis, SetupSlot, CHECKERS_PER_PLAYER,
+};
+use trictrac_game::types::{JanEntry, SerStage, SerTurnStage, ViewState};
+use crate::i18n::*;
+
+fn start_position() -> [i8; 24] {
+    let mut board = [0i8; 24];
+    board[0] = CHECKERS_PER_PLAYER as i8;
+    board[23] = -(CHECKERS_PER_PLAYER as i8);
+    board
+}
+
+/// "6→11", "24→⊗" for a checker taken off, or "—" for a move that could not be played.
+pub(super) fn move_text(m: CheckerMove) -> String {
+    match (m.get_from(), m.get_to()) {
+        (0, _) => "—".to_string(),
+        (from, 0) => format!("{from}→⊗"),
+        (from, to) => format!("{from}→{to}"),
+    }
+}
+
+/// Analysis board: set up any position, choose the dice and the player to
+/// move, and see the legal sequences with what they score and what the bot
+/// would play. Nothing is sent to a game; "Play from here" starts a bot game
+/// from the position, like the debug snapshot replay.
+#[component]
+pub fn AnalysisBoard() -> impl IntoView {
+    let i18n = use_i18n();
+    let cmd_tx = use_context::<UnboundedSender<NetCommand>>()
+        .expect("UnboundedSender<NetCommand> not found in context");
+
+    let board = RwSignal::new(start_position());
+    let dice = RwSignal::new((3u8, 5u8));
+    let color = RwSignal::new(Color::White);
+    let third_roll = RwSignal::new(false);
+    let show_bot = RwSignal::new(false);
+    let picked = RwSignal::new(None::<SetupSlot>);
+    let snapshot_open = RwSignal::new(false);
+    let snapshot_text = RwSignal::new(String::new());
+    let snapshot_error = RwSignal::new(false);
+
+    let hovered_jan_moves: RwSignal<Vec<(CheckerMove, CheckerMove)>> = RwSignal::new(vec![]);
+    provide_context(hovered_jan_moves);
+
+    let analysis: Memo<Analysis> =
+        Memo::new(move |_| analyse(&board.get(), dice.get(), color.get(), third_roll.get()));
+
+    let move_checker = Callback::new(move |(from, to): (SetupSlot, SetupSlot)| {
+        let mut b = board.get_untracked();
+        if move_setup_checker(&mut b, from, to) {
+            board.set(b);
+            hovered_jan_moves.set(vec![]);
+            show_bot.set(false);
+        }
+    });
+    let setup = PositionSetup {
+        picked,
+        on_move: move_checker,
+    };
+
+    let position_vs = move || {
+        let mut vs = ViewState::default_with_names("", "");
+        vs.board = board.get();
+        vs.stage = SerStage::InGame;
+        vs
+    };
+
+    let load_snapshot = move |_| {
+        match serde_json::from_str::<ViewState>(&snapshot_text.get_untracked()) {
+            Ok(vs) => {
+                board.set(vs.board);
+                if vs.dice.0 != 0 && vs.dice.1 != 0 {
+                    dice.set(vs.dice);
+                }
+                if let Some(p) = vs.active_mp_player {
+                    color.set(if p == 0 { Color::White } else { Color::Black });
+                }
+                hovered_jan_moves.set(vec![]);
+                show_bot.set(false);
+                snapshot_open.set(false);
+            }
+            Err(_) => snapshot_error.set(true),
+        }
+    };
+
+    let play_from_here = move |_| {
+        let mut vs = ViewState::default_with_names("", "");
+        vs.board = board.get_untracked();
+        vs.stage = SerStage::InGame;
+        vs.turn_stage = SerTurnStage::RollDice;
+        vs.active_mp_player = Some(if color.get_untracked() == Color::White { 0 } else { 1 });
+        cmd_tx.unbounded_send(NetCommand::ReplaySnapshot(vs)).ok();
+    };
+
+    let die_button = move |first: bool| {
+        view! {
+            <button class="analysis-die" title=move || t_string!(i18n, analysis_change_die)
+                on:click=move |_| dice.update(|d| {
+                    let v = if first { &mut d.0 } else { &mut d.1 };
+                    *v = *v % 6 + 1;
+                })
+            >
+                {move || {
+                    let (d0, d1) = dice.get();
+                    let v = if first { d0 } else { d1 };
+                    view! { <Die value=v used=false is_double=d0 == d1 /> }
+                }}
+            </button>
+        }
+    };
+
+    let reserve_tray = move |c: Color| {
+        let slot = SetupSlot::Reserve(c);
+        let class_name = if c == Color::White { "checker white" } else { "checker black" };
+        view! {
+            <div
+                class="analysis-reserve"
+                class:selected=move || picked.get() == Some(slot)
+                on:dragover=move |e| e.prevent_default()
+                on:drop=move |e| {
+                    e.prevent_default();
+                    if let Some(from) = picked.get_untracked() {
+                        move_checker.run((from, slot));
+                        picked.set(None);
+                    }
+                }
+                on:click=move |_| match picked.get_untracked() {
+                    Some(from) if from == slot => picked.set(None),
+                    Some(from) => {
+                        move_checker.run((from, slot));
+                        picked.set(None);
+                    }
+                    None if reserve(&board.get_untracked(), c) > 0 => picked.set(Some(slot)),
+                    None => {}
+                }
+            >
+                <div class=class_name draggable="true"
+                    on:dragstart=move |e| {
+                        #[cfg(target_arch = "wasm32")]
+                        if let Some(dt) = e.data_transfer() {
+                            let _ = dt.set_data("text/plain", "reserve");
+                        }
+                        let _ = e;
+                        picked.set(Some(slot));
+                    }
+                >{move || reserve(&board.get(), c).to_string()}</div>
+                <span class="analysis-reserve-label">{move || if c == Color::White {
+                    t_string!(i18n, analysis_reserve_white).to_owned()
+                } else {
+                    t_string!(i18n, analysis_reserve_black).to_owned()
+                }}</span>
+            </div>
+        }
+    };
+
+    view! {
+        <div class="game-container analysis-board">
+            <div class="analysis-reserves">
+                {reserve_tray(Color::White)}
+                {reserve_tray(Color::Black)}
+            </div>
+            <div class="main-body">
+                {move || view! {
+                    <Board
+                        view_state=position_vs()
+                        player_id=if color.get() == Color::White { 0 } else { 1 }
+                        selected_origin=RwSignal::new(None)
+                        staged_moves=RwSignal::new(Vec::new())
+                        valid_sequences=vec![]
+                        bar_dice=Some(dice.get())
+                        bar_is_double=dice.get().0 == dice.get().1
+                        setup=setup
+                    />
+                }}
+                <div class="controls">
+                    <div class="ctrl-dice">
+                        <div class="ctrl-dice-row">{die_button(true)}{die_button(false)}</div>
+                    </div>
+                    <div class="analysis-options">
+                        <label>
+                            {t!(i18n, analysis_to_move)} " "
+                            <select on:change=move |e| {
+                                color.set(if event_target_value(&e) == "black" { Color::Black } else { Color::White });
+                                hovered_jan_moves.set(vec![]);
+                                show_bot.set(false);
+                            }>
+                                <option value="white" selected=move || color.get() == Color::White>
+                                    {t!(i18n, analysis_white)}
+                                </option>
+                                <option value="black" selected=move || color.get() == Color::Black>
+                                    {t!(i18n, analysis_black)}
+                                </option>
+                            </select>
+                        </label>
+                        <label>
+                            <input type="checkbox"
+                                prop:checked=move || third_roll.get()
+                                on:change=move |e| third_roll.set(event_target_checked(&e))
+                            />
+                            " " {t!(i18n, analysis_third_roll)}
+                        </label>
+                        <button class="btn btn-secondary" on:click=move |_| {
+                            board.set(start_position());
+                            picked.set(None);
+                        }>{t!(i18n, analysis_start_position)}</button>
+                        <button class="btn btn-secondary" on:click=move |_| {
+                            board.set([0; 24]);
+                            picked.set(None);
+                        }>{t!(i18n, analysis_clear)}</button>
+                        <button class="btn btn-secondary" on:click=move |_| {
+                            snapshot_text.set(String::new());
+                            snapshot_error.set(false);
+                            snapshot_open.update(|o| *o = !*o);
+                        }>{t!(i18n, analysis_load_snapshot)}</button>
+                        <button class="btn btn-secondary" on:click=play_from_here>
+                            {t!(i18n, analysis_play_from_here)}
+                        </button>
+                    </div>
+                </div>
+            </div>
+
+            {move || snapshot_open.get().then(|| view! {
+                <div class="analysis-snapshot">
+                    <p class="game-sub-prompt">{t!(i18n, analysis_snapshot_hint)}</p>
+                    <textarea class="replay-record-input"
+                        placeholder="{ \"board\": [...], ... }"
+                        prop:value=move || snapshot_text.get()
+                        on:input=move |e| {
+                            snapshot_text.set(event_target_value(&e));
+                            snapshot_error.set(false);
+                        }
+                    />
+                    {move || snapshot_error.get().then(|| view! {
+                        <p class="portal-error">{t!(i18n, replay_invalid_state)}</p>
+                    })}
+                    <button class="btn btn-primary" on:click=load_snapshot>
+                        {t!(i18n, analysis_load)}
+                    </button>
+                </div>
+            })}
+
+            <div class="analysis-results" on:mouseleave=move |_| hovered_jan_moves.set(vec![])>
+                <div class="analysis-column">
+                    <h3>{t!(i18n, analysis_roll_jans)}</h3>
+                    {move || {
+                        let jans = analysis.get().jans;
+                        if jans.is_empty() {
+                            view! { <p class="portal-meta">{t!(i18n, analysis_no_jans)}</p> }.into_any()
+                        } else {
+                            jans.into_iter().map(jan_row).collect_view().into_any()
+                        }
+                    }}
+                </div>
+                <div class="analysis-column">
+                    <h3>
+                        {t!(i18n, analysis_sequences)}
+                        <button class="btn btn-secondary analysis-ask-bot"
+                            on:click=move |_| show_bot.update(|s| *s = !*s)
+                        >{move || if show_bot.get() {
+                            t_string!(i18n, analysis_hide_bot).to_owned()
+                        } else {
+                            t_string!(i18n, analysis_ask_bot).to_owned()
+                        }}</button>
+                    </h3>
+                    {move || {
+                        let a = analysis.get();
+                        if a.sequences.is_empty() {
+                            return view! {
+                                <p class="portal-meta">{t!(i18n, analysis_no_sequences)}</p>
+                            }.into_any();
+                        }
+                        let preferred = if show_bot.get() { a.preferred } else { None };
+                        a.sequences.into_iter().enumerate().map(|(i, s)| {
+                            let moves = s.moves;
+                            let is_preferred = preferred == Some(i);
+                            let points: i32 = s.jans.iter().map(|j| j.total as i32).sum();
+                            let jans = s.jans.iter().map(|j| jan_label(&j.jan)).collect::<Vec<_>>().join(", ");
+                            view! {
+                                <div class="analysis-sequence"
+                                    class:analysis-preferred=is_preferred
+                                    on:mouseenter=move |_| hovered_jan_moves.set(vec![moves])
+                                >
+                                    <span class="analysis-moves">
+                                        {move_text(moves.0)} " " {move_text(moves.1)}
+                                    </span>
+                                    <span class="analysis-seq-jans">{jans}</span>
+                                    {(points != 0).then(|| view! {
+                                        <span class="jan-pts">{format!("{points:+}")}</span>
+                                    })}
+                                    {is_preferred.then(|| view! {
+                                        <span class="jan-tag">{t!(i18n, analysis_bot_choice)}</span>
+                                    })}
+                                </div>
+                            }
+                        }).collect_view().into_any()
+                    }}
+                </div>
+            </div>
+        </div>
+    }
+}
+
+/// A jan the roll scores; hovering it shows its moves on the board.
+pub(super) fn jan_row(entry: JanEntry) -> impl IntoView {
+    let i18n = use_i18n();
+    let hovered = use_context::<RwSignal<Vec<(CheckerMove, CheckerMove)>>>();
+    let jan = entry.jan;
+    let is_double = entry.is_double;
+    let moves = entry.moves;
+    let class_name = if entry.total < 0 {
+        "scoring-jan-row jan-negative"
+    } else {
+        "scoring-jan-row jan-positive"
+    };
+    view! {
+        <div class=class_name
+            on:mouseenter=move |_| {
+                if let Some(h) = hovered {
+                    h.set(moves.clone());
+                }
+            }
+        >
+            <span class="jan-label">{move || jan_label(&jan)}</span>
+            <span class="jan-tag">{move || if is_double {
+                t_string!(i18n, jan_double).to_owned()
+            } else {
+                t_string!(i18n, jan_simple).to_owned()
+            }}</span>
+            <span class="jan-tag">{format!("×{}", entry.ways)}</span>
+            <span class="jan-pts">{format!("{:+}", entry.total)}</span>
+        </div>
+    }
+}
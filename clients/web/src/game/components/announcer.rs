@@ -0,0 +1,74 @@
+use leptos::prelude::*;
+
+use super::score_panel::jan_label;
+use trictrac_game::announce::{announcements, Announcement};
+use trictrac_game::types::ViewState;
+use crate::i18n::*;
+
+fn announcement_text(announcement: &Announcement, vs: &ViewState) -> String {
+    let i18n = use_i18n();
+    let name = |player: u16| vs.scores[player as usize].name.as_str();
+    match announcement {
+        Announcement::PreGameDie { player, die } => {
+            t_string!(i18n, a11y_pre_game_die, name = name(*player), die = *die).to_owned()
+        }
+        Announcement::Began { player } => t_string!(i18n, a11y_began, name = name(*player)).to_owned(),
+        Announcement::Rolled { player, dice } => {
+            t_string!(i18n, a11y_rolled, name = name(*player), d1 = dice.0, d2 = dice.1).to_owned()
+        }
+        Announcement::Scored { player, points, holes, jans } => {
+            let mut text = if jans.is_empty() {
+                t_string!(i18n, a11y_scored, name = name(*player), points = *points).to_owned()
+            } else {
+                let jans: Vec<String> = jans.iter().map(jan_label).collect();
+                let jans = jans.join(", ");
+                t_string!(i18n, a11y_scored_jans, name = name(*player), points = *points, jans = jans.as_str())
+                    .to_owned()
+            };
+            if *holes > 0 {
+                let total = vs.scores[*player as usize].holes;
+                text.push(' ');
+                text.push_str(&t_string!(i18n, a11y_holes, name = name(*player), holes = *holes, total = total));
+            }
+            text
+        }
+        Announcement::Went { player } => t_string!(i18n, a11y_went, name = name(*player)).to_owned(),
+        Announcement::Moved { player, moves } if moves.is_empty() => {
+            t_string!(i18n, a11y_cannot_move, name = name(*player)).to_owned()
+        }
+        Announcement::Moved { player, moves } => {
+            let moves: Vec<String> = moves
+                .iter()
+                .map(|&(from, to)| match to {
+                    0 => t_string!(i18n, a11y_move_off, from = from).to_owned(),
+                    _ => t_string!(i18n, a11y_move, from = from, to = to).to_owned(),
+                })
+                .collect();
+            let moves = moves.join(", ");
+            t_string!(i18n, a11y_moved, name = name(*player), moves = moves.as_str()).to_owned()
+        }
+    }
+}
+
+/// Live region reading out, for screen readers, the dice, jans and moves
+/// between each displayed position and the next.
+#[component]
+pub fn Announcer(view_state: Memo<Option<ViewState>>) -> impl IntoView {
+    let text = RwSignal::new(String::new());
+
+    Effect::new(move |prev: Option<Option<ViewState>>| {
+        let next = view_state.get();
+        if let (Some(Some(prev)), Some(next)) = (&prev, &next) {
+            let lines: Vec<String> = announcements(prev, next)
+                .iter()
+                .map(|a| announcement_text(a, next))
+                .collect();
+            if !lines.is_empty() {
+                text.set(lines.join(" "));
+            }
+        }
+        next
+    });
+
+    view! { <div class="sr-only" role="status" aria-live="polite">{move || text.get()}</div> }
+}
@@ -0,0 +1,82 @@
+use leptos::prelude::*;
+
+use super::analysis::move_text;
+use trictrac_game::blunders::{check_game, Blunder, BlunderKind, Choice, BLUNDER_THRESHOLD};
+use trictrac_game::replay::GameRecord;
+use crate::i18n::*;
+
+/// Report of a recorded game's blunders, one table per player.
+#[component]
+pub fn BlunderReport(record: GameRecord) -> impl IntoView {
+    let i18n = use_i18n();
+    let blunders = match check_game(&record.events, BLUNDER_THRESHOLD) {
+        Ok(blunders) => blunders,
+        Err(e) => {
+            let index = e.index + 1;
+            return view! {
+                <p class="portal-error">{t!(i18n, replay_invalid_event, index = index)}</p>
+            }
+            .into_any();
+        }
+    };
+
+    view! {
+        <p class="portal-meta">{t!(i18n, blunders_explanation)}</p>
+        {[0u16, 1].into_iter().map(|player| {
+            let name = record.players[player as usize].clone();
+            let rows: Vec<Blunder> = blunders.iter().filter(|b| b.player == player).cloned().collect();
+            view! {
+                <h2>{ name }</h2>
+                {if rows.is_empty() {
+                    view! { <p class="portal-meta">{t!(i18n, blunders_none)}</p> }.into_any()
+                } else {
+                    view! {
+                        <table class="blunder-table">
+                            <thead>
+                                <tr>
+                                    <th>{t!(i18n, replay_jump_label)}</th>
+                                    <th>{t!(i18n, blunders_col_dice)}</th>
+                                    <th>{t!(i18n, blunders_col_kind)}</th>
+                                    <th>{t!(i18n, blunders_col_played)}</th>
+                                    <th>{t!(i18n, blunders_col_best)}</th>
+                                    <th>{t!(i18n, blunders_col_loss)}</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                {rows.into_iter().map(|b| view! { <BlunderRow blunder=b /> }).collect_view()}
+                            </tbody>
+                        </table>
+                    }.into_any()
+                }}
+            }
+        }).collect_view()}
+    }
+    .into_any()
+}
+
+#[component]
+fn BlunderRow(blunder: Blunder) -> impl IntoView {
+    let i18n = use_i18n();
+    let kind = move || match blunder.kind {
+        BlunderKind::HoldOrGo => t_string!(i18n, blunders_kind_hold_or_go),
+        BlunderKind::MissedQuarterFilling => t_string!(i18n, blunders_kind_quarter),
+        BlunderKind::ExposedHalfField => t_string!(i18n, blunders_kind_exposed),
+        BlunderKind::WeakerMove => t_string!(i18n, blunders_kind_weaker),
+    };
+    let choice = move |c: Choice| match c {
+        Choice::Moves(m1, m2) => format!("{} {}", move_text(m1), move_text(m2)),
+        Choice::Go => t_string!(i18n, blunders_go).to_string(),
+    };
+    let (d1, d2) = blunder.dice;
+
+    view! {
+        <tr>
+            <td>{ blunder.turn }</td>
+            <td>{ format!("{d1}–{d2}") }</td>
+            <td>{ kind }</td>
+            <td class="blunder-choice">{ move || choice(blunder.played) }</td>
+            <td class="blunder-choice">{ move || choice(blunder.best) }</td>
+            <td>{ format!("{:.1}", blunder.loss) }</td>
+        </tr>
+    }
+}
@@ -1,8 +1,12 @@
 use leptos::prelude::*;
-use trictrac_store::CheckerMove;
+use trictrac_store::{CheckerMove, Color};
+use wasm_bindgen::JsCast;
 
 use super::die::Die;
-use crate::game::trictrac::types::{SerTurnStage, ViewState};
+use trictrac_game::analysis::{reserve, SetupSlot};
+use trictrac_game::announce::occupied_fields;
+use trictrac_game::types::{SerTurnStage, ViewState};
+use crate::i18n::*;
 
 /// Field numbers in visual display order (left-to-right for each quarter), white's perspective.
 const TOP_LEFT_W: [u8; 6] = [13, 14, 15, 16, 17, 18];
@@ -16,6 +20,55 @@ const TOP_RIGHT_B: [u8; 6] = [7, 8, 9, 10, 11, 12];
 const BOT_LEFT_B: [u8; 6] = [24, 23, 22, 21, 20, 19];
 const BOT_RIGHT_B: [u8; 6] = [18, 17, 16, 15, 14, 13];
 
+/// Both rows of fields as shown on screen, left to right: top, then bottom.
+fn screen_rows(is_white: bool) -> [[u8; 12]; 2] {
+    let (tl, tr, bl, br) = if is_white {
+        (TOP_LEFT_W, TOP_RIGHT_W, BOT_LEFT_W, BOT_RIGHT_W)
+    } else {
+        (TOP_LEFT_B, TOP_RIGHT_B, BOT_LEFT_B, BOT_RIGHT_B)
+    };
+    let mut rows = [[0; 12]; 2];
+    rows[0][..6].copy_from_slice(&tl);
+    rows[0][6..].copy_from_slice(&tr);
+    rows[1][..6].copy_from_slice(&bl);
+    rows[1][6..].copy_from_slice(&br);
+    rows
+}
+
+/// The field next to `field_num` on screen in the direction of the arrow `key`.
+fn neighbor_field(rows: &[[u8; 12]; 2], field_num: u8, key: &str) -> Option<u8> {
+    let (row, col) = rows.iter().enumerate().find_map(|(r, fields)| {
+        fields.iter().position(|&f| f == field_num).map(|c| (r, c))
+    })?;
+    let (row, col) = match key {
+        "ArrowLeft" => (row, col.checked_sub(1)?),
+        "ArrowRight" => (row, col + 1),
+        "ArrowUp" => (0, col),
+        "ArrowDown" => (1, col),
+        _ => return None,
+    };
+    rows[row].get(col).copied().filter(|&f| f != field_num)
+}
+
+fn focus_field(field_num: u8) {
+    if let Some(el) = document()
+        .get_element_by_id(&format!("field-{field_num}"))
+        .and_then(|el| el.dyn_into::<web_sys::HtmlElement>().ok())
+    {
+        let _ = el.focus();
+    }
+}
+
+/// What pressing a field (or the exit, field 0) does with the current selection.
+#[derive(Clone, Copy, PartialEq)]
+enum Press {
+    Nothing,
+    Select,
+    Deselect,
+    /// Stage the move from this origin.
+    Stage(u8),
+}
+
 /// The rest corner is field 12 (White) or field 13 (Black) in the store's coordinate system.
 /// Returns true when `field_num` is the rest corner for this perspective.
 #[allow(dead_code)]
@@ -150,8 +203,13 @@ fn field_center(f: usize, is_white: bool) -> Option<(f32, f32)> {
     Some((x, y))
 }
 
-/// SVG `<g>` element drawing one arrow (shadow + gold) from `fp` to `tp`.
-fn arrow_svg(fp: (f32, f32), tp: (f32, f32)) -> AnyView {
+/// Color of the arrows of hovered jans.
+const JAN_ARROW_RGB: &str = "255,215,0";
+/// Color of the arrows of a hint.
+const HINT_ARROW_RGB: &str = "110,200,255";
+
+/// SVG `<g>` element drawing one arrow (shadow + `rgb`) from `fp` to `tp`.
+fn arrow_svg(fp: (f32, f32), tp: (f32, f32), rgb: &str) -> AnyView {
     let (x1, y1) = fp;
     let (x2, y2) = tp;
     let dx = x2 - x1;
@@ -204,13 +262,12 @@ fn arrow_svg(fp: (f32, f32), tp: (f32, f32)) -> AnyView {
                 style="stroke:rgba(0,0,0,0.45);stroke-width:5;stroke-linecap:round"
             />
             <polygon points=shadow_pts style="fill:rgba(0,0,0,0.45)" />
-            // Gold arrow
             <line
                 x1=format!("{lx1:.1}") y1=format!("{ly1:.1}")
                 x2=format!("{lx2:.1}") y2=format!("{ly2:.1}")
-                style="stroke:rgba(255,215,0,0.9);stroke-width:3;stroke-linecap:round"
+                style=format!("stroke:rgba({rgb},0.9);stroke-width:3;stroke-linecap:round")
             />
-            <polygon points=pts style="fill:rgba(255,215,0,0.9)" />
+            <polygon points=pts style=format!("fill:rgba({rgb},0.9)") />
         </g>
     }
     .into_any()
@@ -348,6 +405,37 @@ fn free_mode_dests_for(
     dests
 }
 
+/// Free position setup on the analysis board: checkers are dragged (or
+/// clicked, then clicked again on their destination) from field to field.
+#[derive(Clone, Copy)]
+pub struct PositionSetup {
+    /// Where the checker being moved comes from.
+    pub picked: RwSignal<Option<SetupSlot>>,
+    /// Called with the origin and destination of each dropped checker.
+    pub on_move: Callback<(SetupSlot, SetupSlot)>,
+}
+
+impl PositionSetup {
+    fn click(&self, field_num: u8, val: i8) {
+        match self.picked.get_untracked() {
+            Some(SetupSlot::Field(f)) if f == field_num => self.picked.set(None),
+            Some(from) => {
+                self.on_move.run((from, SetupSlot::Field(field_num)));
+                self.picked.set(None);
+            }
+            None if val != 0 => self.picked.set(Some(SetupSlot::Field(field_num))),
+            None => {}
+        }
+    }
+
+    fn drop_on(&self, field_num: u8) {
+        if let Some(from) = self.picked.get_untracked() {
+            self.on_move.run((from, SetupSlot::Field(field_num)));
+            self.picked.set(None);
+        }
+    }
+}
+
 #[component]
 pub fn Board(
     view_state: ViewState,
@@ -381,6 +469,15 @@ pub fn Board(
     /// are computed from dice arithmetic rather than from pre-validated sequences.
     #[prop(default = RwSignal::new(false))]
     free_mode: RwSignal<bool>,
+    /// Spectator view: no field is ever selectable.
+    #[prop(default = false)]
+    read_only: bool,
+    /// Analysis board: checkers of both colors can be moved anywhere.
+    #[prop(optional)]
+    setup: Option<PositionSetup>,
+    /// Moves suggested by the bot, shown while set.
+    #[prop(optional)]
+    hint: Option<RwSignal<Option<(CheckerMove, CheckerMove)>>>,
 ) -> impl IntoView {
     let board = view_state.board;
     let vs_dice = view_state.dice;
@@ -388,7 +485,8 @@ pub fn Board(
     let white_can_bredouille = view_state.scores[0].can_bredouille;
     let black_points = view_state.scores[1].points;
     let black_can_bredouille = view_state.scores[1].can_bredouille;
-    let is_move_stage = view_state.active_mp_player == Some(player_id)
+    let is_move_stage = !read_only
+        && view_state.active_mp_player == Some(player_id)
         && matches!(
             view_state.turn_stage,
             SerTurnStage::Move | SerTurnStage::HoldOrGoChoice
@@ -419,9 +517,74 @@ pub fn Board(
         exit_field_test = |f| matches!(f, 1..=6);
     }
 
-    // Sequences clone for the reactive exit button (show/hide + class + click).
+    // Sequences clone for the reactive exit button (show/hide).
     let seqs_exit = valid_sequences.clone();
 
+    // Clicks and keys go through the same checks: free mode follows the dice,
+    // otherwise the valid sequences (when there are any).
+    let seqs = StoredValue::new(valid_sequences.clone());
+    let press_outcome = move |field_num: u8, staged: &[(u8, u8)], sel: Option<u8>, free: bool| {
+        if !is_move_stage || staged.len() >= 2 {
+            return Press::Nothing;
+        }
+        let valid = match sel {
+            Some(origin) if origin == field_num => return Press::Deselect,
+            Some(origin) => {
+                if free {
+                    free_mode_dests_for(board, staged, origin, vs_dice, is_white, all_in_exit)
+                        .contains(&field_num)
+                } else {
+                    seqs.with_value(|s| {
+                        s.is_empty() || valid_dests_for(s, staged, origin).contains(&field_num)
+                    })
+                }
+            }
+            // Only checkers leave from a field; the exit is a destination.
+            None if field_num == 0 => false,
+            None if free => free_mode_origins_for(board, staged, is_white).contains(&field_num),
+            None => seqs.with_value(|s| {
+                if s.is_empty() {
+                    let val = displayed_value(board, staged, is_white, field_num);
+                    is_white && val > 0 || !is_white && val < 0
+                } else {
+                    valid_origins_for(s, staged).contains(&field_num)
+                }
+            }),
+        };
+        match (valid, sel) {
+            (false, _) => Press::Nothing,
+            (true, Some(origin)) => Press::Stage(origin),
+            (true, None) => Press::Select,
+        }
+    };
+    let press = move |field_num: u8| {
+        let staged = staged_moves.get_untracked();
+        let outcome = press_outcome(
+            field_num,
+            &staged,
+            selected_origin.get_untracked(),
+            free_mode.get_untracked(),
+        );
+        match outcome {
+            Press::Nothing => {}
+            Press::Select => selected_origin.set(Some(field_num)),
+            Press::Deselect => selected_origin.set(None),
+            Press::Stage(origin) => {
+                staged_moves.update(|v| v.push((origin, field_num)));
+                selected_origin.set(None);
+            }
+        }
+    };
+    let click_field = move |field_num: u8| match setup {
+        Some(setup) => setup.click(field_num, board[(field_num - 1) as usize]),
+        None => press(field_num),
+    };
+
+    // Roving tab stop: one field is in the tab order, arrows move between them.
+    let rows = screen_rows(is_white);
+    let tab_stop = RwSignal::new(if is_white { 1u8 } else { 24 });
+    let i18n = use_i18n();
+
     // `valid_sequences` is cloned per field (the Vec is small; Send-safe unlike Rc).
     let fields_from = |nums: &[u8], is_top_row: bool| -> Vec<AnyView> {
         nums.iter()
@@ -429,7 +592,6 @@ pub fn Board(
                 // Each reactive closure gets its own owned clone — Vec<(CheckerMove,CheckerMove)>
                 // is Send, which Leptos requires for reactive attribute functions.
                 let seqs_c = valid_sequences.clone();
-                let seqs_k = valid_sequences.clone();
                 let corner_title = if is_rest_corner(field_num, is_white) {
                     Some("Coin de repos — must enter and leave with 2 checkers")
                 } else {
@@ -455,6 +617,45 @@ pub fn Board(
                     <div
                         id={format!("field-{field_num}")}
                         title=corner_title
+                        role="button"
+                        tabindex=move || if tab_stop.get() == field_num { "0" } else { "-1" }
+                        aria-pressed=move || {
+                            let picked = setup.map(|s| s.picked.get() == Some(SetupSlot::Field(field_num)));
+                            if picked.unwrap_or(selected_origin.get() == Some(field_num)) { "true" } else { "false" }
+                        }
+                        aria-label=move || {
+                            let staged = staged_moves.get();
+                            let val = displayed_value(board, &staged, is_white, field_num);
+                            let count = val.unsigned_abs();
+                            let mut label = match val.signum() {
+                                1 => t_string!(i18n, a11y_field_white, n = field_num, count = count).to_owned(),
+                                -1 => t_string!(i18n, a11y_field_black, n = field_num, count = count).to_owned(),
+                                _ => t_string!(i18n, a11y_field_empty, n = field_num).to_owned(),
+                            };
+                            let outcome = press_outcome(field_num, &staged, selected_origin.get(), free_mode.get());
+                            if let Press::Stage(_) = outcome {
+                                label.push_str(", ");
+                                label.push_str(t_string!(i18n, a11y_field_dest));
+                            }
+                            label
+                        }
+                        on:focus=move |_| tab_stop.set(field_num)
+                        on:keydown=move |e| match e.key().as_str() {
+                            "Enter" | " " => {
+                                e.prevent_default();
+                                click_field(field_num);
+                            }
+                            "x" | "X" => press(0),
+                            key => if let Some(next) = neighbor_field(&rows, field_num, key) {
+                                e.prevent_default();
+                                focus_field(next);
+                            }
+                        }
+                        on:dragover=move |e| if setup.is_some() { e.prevent_default() }
+                        on:drop=move |e| if let Some(setup) = setup {
+                            e.prevent_default();
+                            setup.drop_on(field_num);
+                        }
                         class=move || {
                             let staged = staged_moves.get();
                             let val = displayed_value(board, &staged, is_white, field_num);
@@ -532,6 +733,20 @@ pub fn Board(
                                 }
                             }
 
+                            if let Some(setup) = setup {
+                                cls.push_str(" clickable");
+                                if setup.picked.get() == Some(SetupSlot::Field(field_num)) {
+                                    cls.push_str(" selected");
+                                }
+                            }
+
+                            if let Some((m1, m2)) = hint.and_then(|h| h.get()) {
+                                let f = field_num as usize;
+                                if [m1.get_from(), m1.get_to(), m2.get_from(), m2.get_to()].contains(&f) {
+                                    cls.push_str(" hint-field");
+                                }
+                            }
+
                             // §6c: highlight fields touched by the hovered jan
                             if let Some(hm) = hovered_moves {
                                 let pairs = hm.get();
@@ -549,64 +764,7 @@ pub fn Board(
 
                             cls
                         }
-                        on:click=move |_| {
-                            if !is_move_stage { return; }
-                            let staged = staged_moves.get_untracked();
-                            if staged.len() >= 2 { return; }
-
-                            if free_mode.get_untracked() {
-                                match selected_origin.get_untracked() {
-                                    Some(origin) if origin == field_num => {
-                                        selected_origin.set(None);
-                                    }
-                                    Some(origin) => {
-                                        let dests = free_mode_dests_for(board, &staged, origin, vs_dice, is_white, all_in_exit);
-                                        if dests.iter().any(|&d| d == field_num) {
-                                            staged_moves.update(|v| v.push((origin, field_num)));
-                                            selected_origin.set(None);
-                                        }
-                                    }
-                                    None => {
-                                        let origins = free_mode_origins_for(board, &staged, is_white);
-                                        if origins.iter().any(|&o| o == field_num) {
-                                            selected_origin.set(Some(field_num));
-                                        }
-                                    }
-                                }
-                            } else {
-                                match selected_origin.get_untracked() {
-                                    Some(origin) if origin == field_num => {
-                                        selected_origin.set(None);
-                                    }
-                                    Some(origin) => {
-                                        let valid = if seqs_k.is_empty() {
-                                            true
-                                        } else {
-                                            valid_dests_for(&seqs_k, &staged, origin)
-                                                .iter()
-                                                .any(|&d| d == field_num)
-                                        };
-                                        if valid {
-                                            staged_moves.update(|v| v.push((origin, field_num)));
-                                            selected_origin.set(None);
-                                        }
-                                    }
-                                    None => {
-                                        if seqs_k.is_empty() {
-                                            let val = displayed_value(board, &staged, is_white, field_num);
-                                            if is_white && val > 0 || !is_white && val < 0 {
-                                                selected_origin.set(Some(field_num));
-                                            }
-                                        } else {
-                                            let origins = valid_origins_for(&seqs_k, &staged);
-                                            if origins.iter().any(|&o| o == field_num) {
-                                                selected_origin.set(Some(field_num));
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
+                        on:click=move |_| click_field(field_num)
                     >
                         <span class="field-num">{field_num}</span>
                         {move || {
@@ -645,7 +803,22 @@ pub fn Board(
                                         <div class=format!("checker {color}")>{label}</div>
                                     }.into_any()
                                 }).collect();
-                                view! { <div class="checker-stack">{chips}</div> }.into_any()
+                                match setup {
+                                    // The whole stack is the drag handle: one checker moves per drop.
+                                    Some(setup) => view! {
+                                        <div class="checker-stack" draggable="true"
+                                            on:dragstart=move |e| {
+                                                #[cfg(target_arch = "wasm32")]
+                                                if let Some(dt) = e.data_transfer() {
+                                                    let _ = dt.set_data("text/plain", &field_num.to_string());
+                                                }
+                                                let _ = e;
+                                                setup.picked.set(Some(SetupSlot::Field(field_num)));
+                                            }
+                                        >{chips}</div>
+                                    }.into_any(),
+                                    None => view! { <div class="checker-stack">{chips}</div> }.into_any(),
+                                }
                             });
                             (ripple, stack)
                         }}
@@ -703,7 +876,13 @@ pub fn Board(
         // inside .board stays correctly positioned (position:absolute top:0 left:0
         // is relative to .board, not the wrapper).
         <div class="board-wrapper">
-            <div class="board">
+            <p id="board-keys" class="sr-only">{t!(i18n, a11y_board_keys)}</p>
+            <div
+                class="board"
+                role="group"
+                aria-label=move || t_string!(i18n, a11y_board)
+                aria-describedby="board-keys"
+            >
                 <div class="board-row top-row">
                     <div class="board-quarter">{fields_from(tl, true)}</div>
                     <div class="board-bar">{bar_content(0)}</div>
@@ -738,10 +917,22 @@ pub fn Board(
                             .filter_map(|(from, to)| {
                                 let p1 = field_center(from, is_white)?;
                                 let p2 = field_center(to, is_white)?;
-                                Some(arrow_svg(p1, p2))
+                                Some(arrow_svg(p1, p2, JAN_ARROW_RGB))
                             })
                             .collect()
                     }}
+                    {move || {
+                        let (m1, m2) = hint.and_then(|h| h.get())?;
+                        let arrows: Vec<AnyView> = [m1, m2]
+                            .into_iter()
+                            .filter_map(|m| {
+                                let p1 = field_center(m.get_from(), is_white)?;
+                                let p2 = field_center(m.get_to(), is_white)?;
+                                Some(arrow_svg(p1, p2, HINT_ARROW_RGB))
+                            })
+                            .collect();
+                        Some(arrows)
+                    }}
                 </svg>
                 // Exit sign: circle+arrow outside the board, next to the last exit field.
                 // White exits to the right (top-right quarter); Black exits to the left (top-left).
@@ -766,8 +957,6 @@ pub fn Board(
                         }
                     };
                     show.then(|| {
-                        let seqs_exit_cls = seqs_exit.clone();
-                        let seqs_exit_click = seqs_exit.clone();
                         let (pos_style, line_x1, line_x2, head_pts): (&str, &str, &str, &str) =
                             if is_white {
                                 (
@@ -784,43 +973,18 @@ pub fn Board(
                             <div
                                 title="Exit"
                                 style=pos_style
+                                role="button"
+                                tabindex="0"
+                                aria-label=move || t_string!(i18n, a11y_exit)
                                 class=move || {
                                     let staged = staged_moves.get();
-                                    let sel = selected_origin.get();
-                                    let active = match sel {
-                                        Some(origin) => if free_mode.get() {
-                                            free_mode_dests_for(board, &staged, origin, vs_dice, is_white, all_in_exit)
-                                                .iter().any(|&d| d == 0)
-                                        } else {
-                                            seqs_exit_cls.is_empty()
-                                                || valid_dests_for(&seqs_exit_cls, &staged, origin)
-                                                    .iter()
-                                                    .any(|&d| d == 0)
-                                        },
-                                        None => false,
-                                    };
-                                    if active { "exit-btn exit-active" } else { "exit-btn" }
+                                    let outcome = press_outcome(0, &staged, selected_origin.get(), free_mode.get());
+                                    if let Press::Stage(_) = outcome { "exit-btn exit-active" } else { "exit-btn" }
                                 }
-                                on:click=move |_| {
-                                    if !is_move_stage { return; }
-                                    let staged = staged_moves.get_untracked();
-                                    if staged.len() >= 2 { return; }
-                                    let Some(origin) = selected_origin.get_untracked() else {
-                                        return;
-                                    };
-                                    let valid = if free_mode.get_untracked() {
-                                        free_mode_dests_for(board, &staged, origin, vs_dice, is_white, all_in_exit)
-                                            .iter().any(|&d| d == 0)
-                                    } else {
-                                        seqs_exit_click.is_empty()
-                                            || valid_dests_for(&seqs_exit_click, &staged, origin)
-                                                .iter()
-                                                .any(|&d| d == 0)
-                                    };
-                                    if valid {
-                                        staged_moves.update(|v| v.push((origin, 0)));
-                                        selected_origin.set(None);
-                                    }
+                                on:click=move |_| press(0)
+                                on:keydown=move |e| if matches!(e.key().as_str(), "Enter" | " ") {
+                                    e.prevent_default();
+                                    press(0);
                                 }
                             >
                                 <svg width="50" height="50" viewBox="0 0 50 50">
@@ -843,6 +1007,33 @@ pub fn Board(
                     })
                 }}
             </div>
+            <details class="board-description">
+                <summary>{t!(i18n, a11y_board_description)}</summary>
+                {move || {
+                    let staged = staged_moves.get();
+                    let mut shown = [0i8; 24];
+                    for field_num in 1..=24u8 {
+                        shown[field_num as usize - 1] = displayed_value(board, &staged, is_white, field_num);
+                    }
+                    let [white, black] = occupied_fields(&shown);
+                    let describe = |fields: Vec<(u8, u8)>, color: Color| {
+                        let mut items: Vec<String> = fields
+                            .into_iter()
+                            .map(|(n, count)| t_string!(i18n, a11y_fields_item, count = count, n = n).to_owned())
+                            .collect();
+                        let off = reserve(&shown, color);
+                        if off > 0 {
+                            items.push(t_string!(i18n, a11y_off_board, count = off).to_owned());
+                        }
+                        items.join(", ")
+                    };
+                    let (white, black) = (describe(white, Color::White), describe(black, Color::Black));
+                    view! {
+                        <p>{t_string!(i18n, a11y_white_checkers, fields = white.as_str()).to_owned()}</p>
+                        <p>{t_string!(i18n, a11y_black_checkers, fields = black.as_str()).to_owned()}</p>
+                    }
+                }}
+            </details>
         </div>
     }
 }
@@ -852,6 +1043,20 @@ mod tests {
     use super::*;
     use wasm_bindgen_test::wasm_bindgen_test;
 
+    #[wasm_bindgen_test]
+    fn arrows_follow_the_screen() {
+        let rows = screen_rows(true);
+        assert_eq!(neighbor_field(&rows, 1, "ArrowLeft"), Some(2));
+        assert_eq!(neighbor_field(&rows, 1, "ArrowRight"), None);
+        assert_eq!(neighbor_field(&rows, 1, "ArrowUp"), Some(24));
+        assert_eq!(neighbor_field(&rows, 7, "ArrowRight"), Some(6));
+        assert_eq!(neighbor_field(&rows, 13, "ArrowDown"), Some(12));
+        assert_eq!(neighbor_field(&rows, 13, "ArrowUp"), None);
+        let rows = screen_rows(false);
+        assert_eq!(neighbor_field(&rows, 24, "ArrowUp"), Some(1));
+        assert_eq!(neighbor_field(&rows, 12, "Enter"), None);
+    }
+
     #[wasm_bindgen_test]
     fn test_bar_matched_dice_used() {
         assert_eq!((true, false), bar_matched_dice_used(&[(22, 24)], (2, 3)));
@@ -0,0 +1,139 @@
+use futures::channel::mpsc::UnboundedSender;
+use gloo_storage::Storage as _;
+use leptos::prelude::*;
+
+use crate::app::{ChatDraft, ChatLog, NetCommand};
+use trictrac_game::types::{ChatBody, QuickPhrase, MAX_CHAT_CHARS};
+use crate::i18n::*;
+
+const MUTE_STORAGE_KEY: &str = "trictrac_chat_muted";
+
+pub fn quick_phrase_label(phrase: QuickPhrase) -> String {
+    let i18n = use_i18n();
+    match phrase {
+        QuickPhrase::Hello => t_string!(i18n, quick_hello).to_owned(),
+        QuickPhrase::WellPlayed => t_string!(i18n, quick_well_played).to_owned(),
+        QuickPhrase::JAdoube => t_string!(i18n, quick_j_adoube).to_owned(),
+        QuickPhrase::Ecole => t_string!(i18n, quick_ecole).to_owned(),
+        QuickPhrase::Thanks => t_string!(i18n, quick_thanks).to_owned(),
+    }
+}
+
+/// Chat box below the board: message history, quick phrases and a text input.
+///
+/// Messages come from the `ChatLog` context rather than a prop, so a new
+/// message updates the list without re-mounting the game screen. Muting hides
+/// the opponent's messages; the setting is kept in localStorage.
+#[component]
+pub fn ChatPanel(
+    /// Local player (0 = host, 1 = guest).
+    player_id: u16,
+    /// Display names indexed by player id.
+    names: [String; 2],
+    /// Spectators can read the chat but not write.
+    #[prop(default = false)]
+    read_only: bool,
+) -> impl IntoView {
+    let i18n = use_i18n();
+    let chat_log = use_context::<ChatLog>().expect("ChatLog context not found").0;
+    let draft = use_context::<ChatDraft>().expect("ChatDraft context not found").0;
+    let cmd_tx = use_context::<UnboundedSender<NetCommand>>()
+        .expect("UnboundedSender<NetCommand> not found in context");
+
+    let muted: RwSignal<bool> = RwSignal::new(
+        gloo_storage::LocalStorage::get::<bool>(MUTE_STORAGE_KEY).unwrap_or(false),
+    );
+
+    let cmd_tx_send = cmd_tx.clone();
+    let send_draft = move || {
+        let text = draft.get_untracked().trim().to_string();
+        if text.is_empty() {
+            return;
+        }
+        cmd_tx_send.unbounded_send(NetCommand::Chat(text)).ok();
+        draft.set(String::new());
+    };
+    let send_on_enter = send_draft.clone();
+
+    view! {
+        <div class="chat-panel">
+            <div class="chat-header">
+                <span class="chat-title">{t!(i18n, chat_title)}</span>
+                <label class="chat-mute">
+                    <input
+                        type="checkbox"
+                        prop:checked=move || muted.get()
+                        on:change=move |ev| {
+                            let v = event_target_checked(&ev);
+                            gloo_storage::LocalStorage::set(MUTE_STORAGE_KEY, v).ok();
+                            muted.set(v);
+                        }
+                    />
+                    {t!(i18n, chat_mute)}
+                </label>
+            </div>
+            <ul class="chat-messages" aria-live="polite">
+                {move || {
+                    let is_muted = muted.get();
+                    chat_log
+                        .get()
+                        .into_iter()
+                        .filter(|e| !is_muted || e.from == player_id)
+                        .map(|e| {
+                            let name = names
+                                .get(e.from as usize)
+                                .cloned()
+                                .unwrap_or_default();
+                            let (text, quick) = match e.body {
+                                ChatBody::Text(t) => (t, false),
+                                ChatBody::Quick(q) => (quick_phrase_label(q), true),
+                            };
+                            view! {
+                                <li class="chat-message" class:chat-own=e.from == player_id>
+                                    <span class="chat-author">{name}": "</span>
+                                    <span class:chat-quick=quick>{text}</span>
+                                </li>
+                            }
+                        })
+                        .collect_view()
+                }}
+            </ul>
+            {(!read_only).then(|| {
+                let cmd_tx_quick = cmd_tx.clone();
+                view! {
+                    <div class="chat-quick-row">
+                        {QuickPhrase::ALL
+                            .into_iter()
+                            .map(|q| {
+                                let tx = cmd_tx_quick.clone();
+                                view! {
+                                    <button class="chat-quick-btn" on:click=move |_| {
+                                        tx.unbounded_send(NetCommand::Chat(q.code().to_string())).ok();
+                                    }>{quick_phrase_label(q)}</button>
+                                }
+                            })
+                            .collect_view()}
+                    </div>
+                    <div class="chat-input-row">
+                        <input
+                            class="chat-input"
+                            type="text"
+                            maxlength=MAX_CHAT_CHARS
+                            placeholder=move || t_string!(i18n, chat_placeholder).to_owned()
+                            prop:value=move || draft.get()
+                            on:input=move |ev| draft.set(event_target_value(&ev))
+                            on:keydown=move |ev| {
+                                if ev.key() == "Enter" {
+                                    send_on_enter();
+                                }
+                            }
+                        />
+                        <button class="btn btn-secondary" on:click=move |_| send_draft()>
+                            {t!(i18n, chat_send)}
+                        </button>
+                    </div>
+                }
+            })}
+        </div>
+    }
+}
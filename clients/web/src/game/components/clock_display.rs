@@ -0,0 +1,89 @@
+#[cfg(target_arch = "wasm32")]
+use gloo_timers::future::TimeoutFuture;
+use leptos::prelude::*;
+#[cfg(target_arch = "wasm32")]
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen_futures::spawn_local;
+use web_time::Instant;
+
+use crate::app::ClockSync;
+use trictrac_game::types::ClockView;
+
+/// Below this many milliseconds a clock is shown as running low.
+const LOW_TIME_MS: u64 = 10_000;
+
+/// Time left for `player`, counted down from the moment `clock` was received.
+fn remaining_ms(clock: &ClockView, received: Instant, now: Instant, player: u16) -> u64 {
+    let left = clock.remaining_ms[player as usize];
+    if clock.running != Some(player) {
+        return left;
+    }
+    let elapsed = now.saturating_duration_since(received).as_millis() as u64;
+    left.saturating_sub(elapsed.saturating_sub(clock.delay_ms))
+}
+
+fn format_clock(ms: u64) -> String {
+    // Round up so that "0:00" only shows once the flag has fallen.
+    let secs = ms.div_ceil(1000);
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+/// The two players' clocks, mine on the left as in the player strip.
+///
+/// Reads the `ClockSync` context so the countdown is not restarted when the
+/// game screen re-mounts.
+#[component]
+pub fn GameClocks(
+    /// Local player (0 = host, 1 = guest).
+    player_id: u16,
+) -> impl IntoView {
+    let clock_sync = use_context::<ClockSync>()
+        .expect("ClockSync context not found")
+        .0;
+    let now: RwSignal<Instant> = RwSignal::new(Instant::now());
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        let is_alive = Arc::new(AtomicBool::new(true));
+        let alive_c = is_alive.clone();
+        on_cleanup(move || alive_c.store(false, Ordering::Relaxed));
+        spawn_local(async move {
+            loop {
+                TimeoutFuture::new(200).await;
+                if !is_alive.load(Ordering::Relaxed) {
+                    return;
+                }
+                now.set(Instant::now());
+            }
+        });
+    }
+
+    let face = move |player: u16| {
+        move || {
+            clock_sync.get().map(|(clock, received)| {
+                let ms = remaining_ms(&clock, received, now.get(), player);
+                view! {
+                    <span
+                        class="clock-face"
+                        class:clock-active=clock.running == Some(player)
+                        class:clock-low=ms < LOW_TIME_MS
+                        class:clock-flagged=clock.flagged == Some(player)
+                    >
+                        {format_clock(ms)}
+                    </span>
+                }
+            })
+        }
+    };
+
+    view! {
+        <div class="clock-row">
+            {face(player_id)}
+            {face(1 - player_id)}
+        </div>
+    }
+}
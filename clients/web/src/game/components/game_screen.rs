@@ -7,17 +7,39 @@ use leptos::prelude::*;
 use trictrac_store::{
     Board as StoreBoard, CheckerMove, Color, Dice as StoreDice, Jan, MoveError, MoveRules,
 };
+use wasm_bindgen::JsCast;
 
 use super::board::{bar_matched_dice_used, Board};
+use super::chat::ChatPanel;
+use super::clock_display::GameClocks;
 use super::die::Die;
 use crate::app::{GameUiState, NetCommand, PauseReason};
-use crate::game::trictrac::types::{PlayerAction, PreGameRollState, SerStage, SerTurnStage};
+use trictrac_game::analysis::hint;
+use trictrac_game::types::{PlayerAction, PreGameRollState, SerStage, SerTurnStage};
 use crate::i18n::*;
-use crate::portal::lobby::{qr_svg, room_url};
+use crate::portal::lobby::{qr_svg, room_url, watch_url};
 
 use super::score_panel::MergedScorePanel;
 use super::scoring::ScoringPanel;
 
+/// Why a move was refused; `None` when it does not follow the dice.
+pub(super) fn move_error_label(err: Option<MoveError>) -> String {
+    let i18n = use_i18n();
+    match err {
+        None => t_string!(i18n, err_invalid_move).to_owned(),
+        Some(MoveError::OpponentCorner) => t_string!(i18n, err_opponent_corner).to_owned(),
+        Some(MoveError::CornerNeedsTwoCheckers) => t_string!(i18n, err_corner_needs_two).to_owned(),
+        Some(MoveError::CornerByEffectPossible) => t_string!(i18n, err_corner_by_effect).to_owned(),
+        Some(MoveError::ExitNeedsAllCheckersOnLastQuarter) => t_string!(i18n, err_exit_needs_all_in_last_jan).to_owned(),
+        Some(MoveError::ExitByEffectPossible) => t_string!(i18n, err_exit_by_effect).to_owned(),
+        Some(MoveError::ExitNotFarthest) => t_string!(i18n, err_exit_not_farthest).to_owned(),
+        Some(MoveError::OpponentCanFillQuarter) => t_string!(i18n, err_opponent_can_fill_quarter).to_owned(),
+        Some(MoveError::MustFillQuarter) => t_string!(i18n, err_must_fill_quarter).to_owned(),
+        Some(MoveError::MustPlayAllDice) => t_string!(i18n, err_must_play_all_dice).to_owned(),
+        Some(MoveError::MustPlayStrongerDie) => t_string!(i18n, err_must_play_stronger_die).to_owned(),
+    }
+}
+
 #[component]
 pub fn GameScreen(state: GameUiState) -> impl IntoView {
     let i18n = use_i18n();
@@ -26,7 +48,9 @@ pub fn GameScreen(state: GameUiState) -> impl IntoView {
     let vs_board = vs.board;
     let vs_dice = vs.dice;
     let player_id = state.player_id;
-    let is_my_turn = vs.active_mp_player == Some(player_id);
+    // Spectators watch from the host's side and never act.
+    let is_spectator = state.is_spectator;
+    let is_my_turn = !is_spectator && vs.active_mp_player == Some(player_id);
     let is_move_stage = is_my_turn
         && matches!(
             vs.turn_stage,
@@ -158,18 +182,104 @@ pub fn GameScreen(state: GameUiState) -> impl IntoView {
     } else {
         vec![]
     };
-    let valid_seqs_empty = valid_sequences.clone();
+    // Whether the empty move is among the valid ones after `staged`.
+    let valid_seqs_empty = StoredValue::new(valid_sequences.clone());
+    let can_play_empty = move |staged: &[(u8, u8)]| {
+        is_move_stage && staged.len() < 2 && valid_seqs_empty.with_value(|seqs| {
+            seqs.is_empty() || match staged.len() {
+                0 => seqs.iter().any(|(m1, _)| m1.get_from() == 0),
+                1 => {
+                    let (f0, t0) = staged[0];
+                    seqs.iter()
+                        .filter(|(m1, _)| m1.get_from() as u8 == f0 && m1.get_to() as u8 == t0)
+                        .any(|(_, m2)| m2.get_from() == 0)
+                }
+                _ => false,
+            }
+        })
+    };
+
+    // ── Hint (casual games only) ───────────────────────────────────────────────
+    let hint_moves: RwSignal<Option<(CheckerMove, CheckerMove)>> = RwSignal::new(None);
+    let show_hint = is_move_stage && vs.hints.allowed && !waiting_for_confirm;
+    let hint_vs = vs.clone();
+    let cmd_tx_hint = cmd_tx.clone();
+    let hint_is_hold = vs.turn_stage == SerTurnStage::HoldOrGoChoice;
+    let ask_hint = move || {
+        if hint_moves.get_untracked().is_some() {
+            return;
+        }
+        let Some(moves) = hint(&hint_vs, player_id) else { return };
+        hint_moves.set(Some(moves));
+        cmd_tx_hint
+            .unbounded_send(NetCommand::Action(PlayerAction::Hint))
+            .ok();
+    };
 
     // ── Scores ─────────────────────────────────────────────────────────────────
     let my_score = vs.scores[player_id as usize].clone();
     let opp_score = vs.scores[1 - player_id as usize].clone();
+    let spectators = vs.spectators;
+    let chat_names = [vs.scores[0].name.clone(), vs.scores[1].name.clone()];
+    let active_name = vs
+        .active_mp_player
+        .map(|p| vs.scores[p as usize].name.clone())
+        .unwrap_or_default();
 
     // ── Ceremony state ──────────────────────────────────────────────────────────
     let is_ceremony = vs.stage == SerStage::PreGameRoll;
     let pre_game_roll_data: Option<PreGameRollState> = vs.pre_game_roll.clone();
     let my_name_ceremony = my_score.name.clone();
     let opp_name_ceremony = opp_score.name.clone();
+    let my_pre_game_die = pre_game_roll_data
+        .as_ref()
+        .and_then(|p| if player_id == 0 { p.host_die } else { p.guest_die });
+    let can_pre_game_roll = is_ceremony && !is_spectator && my_pre_game_die.is_none() && !waiting_for_confirm;
     let cmd_tx_ceremony = cmd_tx.clone();
+    let pre_game_roll = move || {
+        cmd_tx_ceremony
+            .unbounded_send(NetCommand::Action(PlayerAction::PreGameRoll))
+            .ok();
+    };
+    let go = move || {
+        cmd_tx_go.unbounded_send(NetCommand::Action(PlayerAction::Go)).ok();
+    };
+
+    // ── Keyboard shortcuts for the buttons (fields are played on the board) ─────
+    let (go_key, ask_hint_key, pre_game_roll_key) = (go.clone(), ask_hint.clone(), pre_game_roll.clone());
+    let shortcuts = window_event_listener(leptos::ev::keydown, move |e| {
+        if e.ctrl_key() || e.meta_key() || e.alt_key() || e.repeat() {
+            return;
+        }
+        // Keys typed in the chat are not shortcuts.
+        let typing = e
+            .target()
+            .and_then(|t| t.dyn_into::<web_sys::HtmlElement>().ok())
+            .is_some_and(|el| matches!(el.tag_name().as_str(), "INPUT" | "TEXTAREA" | "SELECT"));
+        if typing {
+            return;
+        }
+        let staged = staged_moves.get_untracked();
+        match e.key().as_str() {
+            "c" | "C" if waiting_for_confirm => pending.update(|q| { q.pop_front(); }),
+            "r" | "R" if can_pre_game_roll => pre_game_roll_key(),
+            "g" | "G" if show_hold_go => go_key(),
+            "h" | "H" if show_hint => ask_hint_key(),
+            "e" | "E" if can_play_empty(&staged) => {
+                selected_origin.set(None);
+                staged_moves.update(|v| v.push((0, 0)));
+            }
+            "Escape" if is_move_stage => {
+                if staged.len() == 1 || move_error.get_untracked().is_some() {
+                    staged_moves.set(vec![]);
+                    move_error.set(None);
+                }
+                selected_origin.set(None);
+            }
+            _ => {}
+        }
+    });
+    on_cleanup(move || shortcuts.remove());
 
     // ── Scoring notifications ──────────────────────────────────────────────────
     let my_scored_event = state.my_scored_event.clone();
@@ -269,7 +379,20 @@ pub fn GameScreen(state: GameUiState) -> impl IntoView {
 
     // ── Game-over info ─────────────────────────────────────────────────────────
     let stage_is_ended = stage == SerStage::Ended;
-    let winner_is_me = my_score.holes >= 12;
+    let has_clock = vs.clock.is_some();
+    let flagged = vs.clock.as_ref().and_then(|c| c.flagged);
+    // On flag fall the other player wins whatever the score.
+    let i_won = match flagged {
+        Some(loser) => loser != player_id,
+        None => my_score.holes >= 12,
+    };
+    let winner_is_me = !is_spectator && i_won;
+    let winner_name_end = if i_won {
+        my_score.name.clone()
+    } else {
+        opp_score.name.clone()
+    };
+    let flagged_name = flagged.map(|loser| vs.scores[loser as usize].name.clone());
     let my_name_end = my_score.name.clone();
     let my_holes_end = my_score.holes;
     let opp_name_end = opp_score.name.clone();
@@ -286,12 +409,18 @@ pub fn GameScreen(state: GameUiState) -> impl IntoView {
     } else {
         String::new()
     };
+    let share_watch_url = if !is_bot_game {
+        watch_url(&room_id)
+    } else {
+        String::new()
+    };
 
     view! {
         <div class="game-container">
+            <p class="sr-only">{t!(i18n, a11y_game_keys)}</p>
 
             // ── Share popover (while waiting for opponent) ───────────────────
-            {(!is_bot_game && stage == SerStage::PreGame).then(|| {
+            {(!is_bot_game && !is_spectator && stage == SerStage::PreGame).then(|| {
                 let url_label = share_url.clone();
                 let url_copy  = share_url.clone();
                 let svg       = share_svg.clone();
@@ -327,6 +456,10 @@ pub fn GameScreen(state: GameUiState) -> impl IntoView {
                         </div>
                         <p class="share-popover-label">{t!(i18n, scan_qr)}</p>
                         <div class="qr-container" inner_html=svg />
+                        <p class="share-popover-label">{t!(i18n, share_watch_link)}</p>
+                        <div class="share-url-row">
+                            <span class="share-url-text">{share_watch_url.clone()}</span>
+                        </div>
                     </div>
                 }
             })}
@@ -342,6 +475,7 @@ pub fn GameScreen(state: GameUiState) -> impl IntoView {
                 my_bredouille=my_bredouille_flash
                 active_player_is_me=active_player_is_me
             />
+            {has_clock.then(|| view! { <GameClocks player_id=player_id /> })}
 
             // ── Board + controls (sidebar on wide, footer on narrow) ─────────
             <div class="main-body">
@@ -359,6 +493,8 @@ pub fn GameScreen(state: GameUiState) -> impl IntoView {
                     hit_fields=hit_fields
                     suppress_dice_anim=suppress_dice_anim
                     free_mode=free_mode
+                    read_only=is_spectator
+                    hint=hint_moves
                 />
 
                 // ── Controls: dice card + status/actions card ────────────────
@@ -381,6 +517,7 @@ pub fn GameScreen(state: GameUiState) -> impl IntoView {
                                     }
                                 }}
                             </div>
+                            {(!is_spectator).then(|| view! {
                             <label class="free-mode-toggle">
                                 <input
                                     type="checkbox"
@@ -398,6 +535,7 @@ pub fn GameScreen(state: GameUiState) -> impl IntoView {
                                     "?"
                                 </span>
                             </label>
+                            })}
                         </div>
                     })}
 
@@ -412,6 +550,13 @@ pub fn GameScreen(state: GameUiState) -> impl IntoView {
                                         PauseReason::AfterOpponentPreGameRoll => t_string!(i18n, after_opponent_pre_game_roll),
                                     });
                                 }
+                                if is_spectator {
+                                    return match stage {
+                                        SerStage::Ended => t_string!(i18n, game_over).to_owned(),
+                                        SerStage::InGame => t_string!(i18n, spectator_turn, name = active_name.as_str()).to_owned(),
+                                        _ => t_string!(i18n, spectating).to_owned(),
+                                    };
+                                }
                                 let n = staged_moves.get().len();
                                 if is_move_stage {
                                     t_string!(i18n, select_move, n = n + 1)
@@ -439,22 +584,24 @@ pub fn GameScreen(state: GameUiState) -> impl IntoView {
                             };
                             (!hint.is_empty()).then(|| view! { <p class="game-sub-prompt">{hint}</p> })
                         }}
+                        {move || hint_moves.get().map(|_| view! {
+                            <p class="game-sub-prompt hint-prompt">
+                                {if hint_is_hold {
+                                    t_string!(i18n, bot_hint_holds)
+                                } else {
+                                    t_string!(i18n, bot_hint_moves)
+                                }}
+                            </p>
+                        })}
+                        {(spectators > 0).then(|| view! {
+                            <p class="game-sub-prompt spectator-count">
+                                {t!(i18n, spectators_watching, n = spectators)}
+                            </p>
+                        })}
                         // ── Free-mode error banner ─────────────────────────────
                         {move || {
                             move_error.get().map(|opt_err| {
-                                let msg: String = match opt_err {
-                                    None => t_string!(i18n, err_invalid_move).to_owned(),
-                                    Some(MoveError::OpponentCorner) => t_string!(i18n, err_opponent_corner).to_owned(),
-                                    Some(MoveError::CornerNeedsTwoCheckers) => t_string!(i18n, err_corner_needs_two).to_owned(),
-                                    Some(MoveError::CornerByEffectPossible) => t_string!(i18n, err_corner_by_effect).to_owned(),
-                                    Some(MoveError::ExitNeedsAllCheckersOnLastQuarter) => t_string!(i18n, err_exit_needs_all_in_last_jan).to_owned(),
-                                    Some(MoveError::ExitByEffectPossible) => t_string!(i18n, err_exit_by_effect).to_owned(),
-                                    Some(MoveError::ExitNotFarthest) => t_string!(i18n, err_exit_not_farthest).to_owned(),
-                                    Some(MoveError::OpponentCanFillQuarter) => t_string!(i18n, err_opponent_can_fill_quarter).to_owned(),
-                                    Some(MoveError::MustFillQuarter) => t_string!(i18n, err_must_fill_quarter).to_owned(),
-                                    Some(MoveError::MustPlayAllDice) => t_string!(i18n, err_must_play_all_dice).to_owned(),
-                                    Some(MoveError::MustPlayStrongerDie) => t_string!(i18n, err_must_play_stronger_die).to_owned(),
-                                };
+                                let msg = move_error_label(opt_err);
                                 view! {
                                     <div class="free-mode-error">
                                         <span class="free-mode-error-msg">{msg}</span>
@@ -472,35 +619,28 @@ pub fn GameScreen(state: GameUiState) -> impl IntoView {
                         }}
                         <div class="board-actions">
                             {waiting_for_confirm.then(|| view! {
-                                <button class="btn btn-primary" on:click=move |_| {
+                                <button class="btn btn-primary" aria-keyshortcuts="C" on:click=move |_| {
                                     pending.update(|q| { q.pop_front(); });
                                 }>{t!(i18n, continue_btn)}</button>
                             })}
+                            {show_hint.then(|| view! {
+                                <button
+                                    class="btn btn-secondary"
+                                    aria-keyshortcuts="H"
+                                    prop:disabled=move || hint_moves.get().is_some()
+                                    on:click=move |_| ask_hint()
+                                >{t!(i18n, bot_hint_btn)}</button>
+                            })}
                             {show_hold_go.then(|| view! {
-                                <button class="btn btn-primary" on:click=move |_| {
-                                    cmd_tx_go.unbounded_send(NetCommand::Action(PlayerAction::Go)).ok();
-                                }>{t!(i18n, go)}</button>
+                                <button class="btn btn-primary" aria-keyshortcuts="G" on:click=move |_| go()>
+                                    {t!(i18n, go)}
+                                </button>
                             })}
                             {move || {
-                                let staged = staged_moves.get();
-                                let show = is_move_stage && staged.len() < 2 && (
-                                    valid_seqs_empty.is_empty() || match staged.len() {
-                                        0 => valid_seqs_empty.iter().any(|(m1, _)| m1.get_from() == 0),
-                                        1 => {
-                                            let (f0, t0) = staged[0];
-                                            valid_seqs_empty.iter()
-                                                .filter(|(m1, _)| {
-                                                    m1.get_from() as u8 == f0
-                                                        && m1.get_to() as u8 == t0
-                                                })
-                                                .any(|(_, m2)| m2.get_from() == 0)
-                                        }
-                                        _ => false,
-                                    }
-                                );
-                                show.then(|| view! {
+                                can_play_empty(&staged_moves.get()).then(|| view! {
                                     <button
                                         class="btn btn-secondary"
+                                        aria-keyshortcuts="E"
                                         on:click=move |_| {
                                             selected_origin.set(None);
                                             staged_moves.update(|v| v.push((0, 0)));
@@ -512,6 +652,7 @@ pub fn GameScreen(state: GameUiState) -> impl IntoView {
                                 (is_move_stage && staged_moves.get().len() == 1).then(|| view! {
                                     <button
                                         class="btn btn-secondary"
+                                        aria-keyshortcuts="Escape"
                                         on:click=move |_| {
                                             staged_moves.set(vec![]);
                                             selected_origin.set(None);
@@ -536,6 +677,11 @@ pub fn GameScreen(state: GameUiState) -> impl IntoView {
                 </div>
             </div>
 
+            // ── Chat (multiplayer only) ───────────────────────────────────────
+            {(!is_bot_game).then(|| view! {
+                <ChatPanel player_id=player_id names=chat_names read_only=is_spectator />
+            })}
+
             // ── Pre-game ceremony overlay ─────────────────────────────────────
             {is_ceremony.then(|| {
                 let pgr = pre_game_roll_data.unwrap_or(PreGameRollState {
@@ -549,13 +695,14 @@ pub fn GameScreen(state: GameUiState) -> impl IntoView {
 
                 let my_die = if player_id == 0 { pgr.host_die } else { pgr.guest_die };
                 let opp_die = if player_id == 0 { pgr.guest_die } else { pgr.host_die };
-                let can_roll = my_die.is_none() && !waiting_for_confirm;
+                let can_roll = can_pre_game_roll;
                 let show_tie = pgr.tie_count > 0;
                 let toss_result: Option<bool> = match (my_die, opp_die) {
                     (Some(m), Some(o)) if m != o => Some(m > o),
                     _ => None,
                 };
                 let opp_name_toss = opp_name_ceremony.clone();
+                let my_name_toss = my_name_ceremony.clone();
                 view! {
                     <div class="ceremony-overlay">
                         <div class="ceremony-box">
@@ -565,7 +712,10 @@ pub fn GameScreen(state: GameUiState) -> impl IntoView {
                             })}
                             <div class="ceremony-dice">
                                 <div class="ceremony-die-slot">
-                                    <span class="ceremony-die-label">{my_name_ceremony}{t!(i18n, you_suffix)}</span>
+                                    <span class="ceremony-die-label">
+                                        {my_name_ceremony}
+                                        {(!is_spectator).then_some(t!(i18n, you_suffix))}
+                                    </span>
                                     <Die value=my_die.unwrap_or(0) used=false />
                                 </div>
                                 <div class="ceremony-die-slot">
@@ -574,7 +724,10 @@ pub fn GameScreen(state: GameUiState) -> impl IntoView {
                                 </div>
                             </div>
                             {toss_result.map(|i_win| {
-                                let text = move || if i_win {
+                                let text = move || if is_spectator {
+                                    let first = if i_win { &my_name_toss } else { &opp_name_toss };
+                                    t_string!(i18n, toss_opp_first, name = first.as_str()).to_owned()
+                                } else if i_win {
                                     t_string!(i18n, toss_you_first).to_owned()
                                 } else {
                                     t_string!(i18n, toss_opp_first, name = opp_name_toss.as_str()).to_owned()
@@ -584,18 +737,15 @@ pub fn GameScreen(state: GameUiState) -> impl IntoView {
                             {waiting_for_confirm.then(|| {
                                 let pending_c = pending;
                                 view! {
-                                    <button class="btn btn-primary" on:click=move |_| {
+                                    <button class="btn btn-primary" aria-keyshortcuts="C" on:click=move |_| {
                                         pending_c.update(|q| { q.pop_front(); });
                                     }>{t!(i18n, continue_btn)}</button>
                                 }
                             })}
-                            {can_roll.then(|| {
-                                let cmd_tx_c = cmd_tx_ceremony.clone();
-                                view! {
-                                    <button class="btn btn-primary" on:click=move |_| {
-                                        cmd_tx_c.unbounded_send(NetCommand::Action(PlayerAction::PreGameRoll)).ok();
-                                    }>{t!(i18n, pre_game_roll_btn)}</button>
-                                }
+                            {can_roll.then(|| view! {
+                                <button class="btn btn-primary" aria-keyshortcuts="R" on:click=move |_| pre_game_roll()>
+                                    {t!(i18n, pre_game_roll_btn)}
+                                </button>
                             })}
                         </div>
                     </div>
@@ -606,20 +756,24 @@ pub fn GameScreen(state: GameUiState) -> impl IntoView {
             {stage_is_ended.then(|| {
                 if winner_is_me {
                     crate::game::sound::play_victory();
-                } else {
+                } else if !is_spectator {
                     crate::game::sound::play_defeat();
                 }
-                let opp_name_end_clone = opp_name_end.clone();
                 let winner_text = move || if winner_is_me {
                     t_string!(i18n, you_win).to_owned()
                 } else {
-                    t_string!(i18n, opp_wins, name = opp_name_end_clone.as_str())
+                    t_string!(i18n, opp_wins, name = winner_name_end.as_str())
                 };
                 view! {
                     <div class="game-over-overlay">
                         <div class="game-over-box">
                             <h2>{t!(i18n, game_over)}</h2>
                             <p class="game-over-winner">{winner_text}</p>
+                            {flagged_name.clone().map(|name| view! {
+                                <p class="game-over-reason">
+                                    {t!(i18n, lost_on_time, name = name.clone())}
+                                </p>
+                            })}
                             <div class="game-over-score">
                                 <span class="game-over-score-name">{my_name_end}</span>
                                 <span class="game-over-score-nums">
@@ -1,9 +1,23 @@
+mod analysis;
+mod announcer;
+mod blunder_report;
 mod board;
+mod chat;
+mod clock_display;
 mod connecting_screen;
 mod die;
+mod replay;
 mod game_screen;
+mod puzzle_board;
 mod score_panel;
 mod scoring;
+mod tutorial;
 
+pub use analysis::AnalysisBoard;
+pub use announcer::Announcer;
+pub use blunder_report::BlunderReport;
 pub use connecting_screen::ConnectingScreen;
 pub use game_screen::GameScreen;
+pub use puzzle_board::PuzzleBoard;
+pub use replay::ReplayViewer;
+pub use tutorial::Tutorial;
@@ -0,0 +1,98 @@
+use leptos::prelude::*;
+use trictrac_store::CheckerMove;
+
+use super::analysis::jan_row;
+use super::board::Board;
+use super::game_screen::move_error_label;
+use trictrac_game::puzzle::Puzzle;
+use trictrac_game::tutorial::Verdict;
+use trictrac_game::types::{SerStage, SerTurnStage, ViewState};
+use crate::i18n::*;
+
+/// A puzzle to solve as White. Each answer is judged once two moves are
+/// staged and set in `verdict`; a solved puzzle shows what the roll scores.
+#[component]
+pub fn PuzzleBoard(puzzle: Puzzle, verdict: RwSignal<Option<Verdict>>) -> impl IntoView {
+    let i18n = use_i18n();
+    let puzzle = StoredValue::new(puzzle);
+    let selected_origin = RwSignal::new(None::<u8>);
+    let staged_moves: RwSignal<Vec<(u8, u8)>> = RwSignal::new(Vec::new());
+
+    let hovered_jan_moves: RwSignal<Vec<(CheckerMove, CheckerMove)>> = RwSignal::new(vec![]);
+    provide_context(hovered_jan_moves);
+
+    let retry = move || {
+        staged_moves.set(vec![]);
+        selected_origin.set(None);
+        verdict.set(None);
+        hovered_jan_moves.set(vec![]);
+    };
+
+    Effect::new(move |_| {
+        let moves = staged_moves.get();
+        if moves.len() == 2 && verdict.get_untracked().is_none() {
+            let to_cm = |&(from, to): &(u8, u8)| {
+                CheckerMove::new(from as usize, to as usize).unwrap_or_default()
+            };
+            let answer = (to_cm(&moves[0]), to_cm(&moves[1]));
+            verdict.set(Some(puzzle.with_value(|p| p.check_moves(answer))));
+        }
+    });
+
+    let (d1, d2) = puzzle.with_value(|p| p.dice);
+    let mut vs = ViewState::default_with_names("", "");
+    vs.board = puzzle.with_value(|p| p.board());
+    vs.dice = (d1, d2);
+    vs.stage = SerStage::InGame;
+    vs.turn_stage = SerTurnStage::Move;
+    vs.active_mp_player = Some(0);
+
+    view! {
+        <p class="tutorial-task">{t!(i18n, puzzle_task, d1 = d1, d2 = d2)}</p>
+        <div class="game-container tutorial-board">
+            <Board
+                view_state=vs
+                player_id=0
+                selected_origin=selected_origin
+                staged_moves=staged_moves
+                valid_sequences=vec![]
+                bar_dice=Some((d1, d2))
+                bar_is_move=true
+                is_my_turn=true
+                bar_is_double=d1 == d2
+                free_mode=RwSignal::new(true)
+            />
+        </div>
+
+        {move || verdict.get().map(|v| {
+            let (class_name, msg, solved) = match v {
+                Verdict::Correct => ("tutorial-feedback correct", t_string!(i18n, puzzle_solved).to_owned(), true),
+                Verdict::Illegal(err) => ("tutorial-feedback wrong", move_error_label(err), false),
+                _ => ("tutorial-feedback wrong", t_string!(i18n, puzzle_missed).to_owned(), false),
+            };
+            let (white, black) = puzzle.with_value(|p| p.points());
+            let jans = puzzle.with_value(|p| p.jans());
+            view! {
+                <div class=class_name>
+                    <p>{msg}</p>
+                    {solved.then(|| view! {
+                        <p>{t!(i18n, puzzle_points, white = white, black = black)}</p>
+                        {(!jans.is_empty()).then(|| view! {
+                            <div class="tutorial-jans" on:mouseleave=move |_| hovered_jan_moves.set(vec![])>
+                                <h3>{t!(i18n, analysis_roll_jans)}</h3>
+                                {jans.into_iter().map(jan_row).collect_view()}
+                            </div>
+                        })}
+                    })}
+                    {(!solved).then(|| view! {
+                        <div class="board-actions">
+                            <button class="btn btn-secondary" on:click=move |_| retry()>
+                                {t!(i18n, reset_move)}
+                            </button>
+                        </div>
+                    })}
+                </div>
+            }
+        })}
+    }
+}
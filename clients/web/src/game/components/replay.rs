@@ -0,0 +1,245 @@
+use std::sync::Arc;
+
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use trictrac_store::CheckerMove;
+
+use super::board::Board;
+use super::die::Die;
+use super::score_panel::MergedScorePanel;
+use super::scoring::ScoringPanel;
+use crate::game::session::{compute_last_moves, compute_scored_event};
+use trictrac_game::replay::{replay, GameRecord, ReplayStep};
+use trictrac_game::types::{SerStage, SerTurnStage};
+use crate::i18n::*;
+
+/// Time each position stays on screen during autoplay.
+const AUTOPLAY_STEP_MS: u32 = 1_200;
+
+/// Step-by-step viewer of a recorded game, seen from White's side.
+#[component]
+pub fn ReplayViewer(
+    record: GameRecord,
+    /// Name offered when downloading the record.
+    #[prop(default = "trictrac-game.json".to_string())]
+    file_name: String,
+) -> impl IntoView {
+    let i18n = use_i18n();
+    let steps = match replay(&record) {
+        Ok(steps) => Arc::new(steps),
+        Err(e) => {
+            let index = e.index + 1;
+            return view! {
+                <p class="portal-error">{t!(i18n, replay_invalid_event, index = index)}</p>
+            }
+            .into_any();
+        }
+    };
+    let last = steps.len() - 1;
+    let turns = steps[last].turn;
+
+    let current = RwSignal::new(0usize);
+    let playing = RwSignal::new(false);
+    let jump_turn = RwSignal::new(String::new());
+
+    let hovered_jan_moves: RwSignal<Vec<(CheckerMove, CheckerMove)>> = RwSignal::new(vec![]);
+    provide_context(hovered_jan_moves);
+
+    // Autoplay: each position schedules the next one. Stepping by hand or
+    // pausing in between makes the pending step a no-op.
+    Effect::new(move |_| {
+        if !playing.get() {
+            return;
+        }
+        let at = current.get();
+        spawn_local(async move {
+            gloo_timers::future::TimeoutFuture::new(AUTOPLAY_STEP_MS).await;
+            // The viewer may have been unmounted while waiting.
+            if playing.try_get_untracked() != Some(true)
+                || current.try_get_untracked() != Some(at)
+            {
+                return;
+            }
+            if at < last {
+                current.set(at + 1);
+            } else {
+                playing.set(false);
+            }
+        });
+    });
+
+    let steps_jump = steps.clone();
+    let go_to_turn = move || {
+        let Ok(turn) = jump_turn.get_untracked().trim().parse::<u16>() else {
+            return;
+        };
+        let step = steps_jump
+            .iter()
+            .position(|s| s.turn >= turn)
+            .unwrap_or(last);
+        playing.set(false);
+        current.set(step);
+    };
+    let go_to_turn_key = go_to_turn.clone();
+
+    let [white, black] = record.players.clone();
+    let [white_hints, black_hints] = record.hints_used;
+    let hints_used = (white_hints + black_hints > 0).then(|| {
+        view! {
+            <p class="replay-hints">
+                {t!(i18n, replay_hints_used,
+                    white = white.clone(), n_white = white_hints,
+                    black = black.clone(), n_black = black_hints)}
+            </p>
+        }
+    });
+
+    let href = format!(
+        "data:application/json;charset=utf-8,{}",
+        String::from(js_sys::encode_uri_component(&record.to_json()))
+    );
+
+    view! {
+        <div class="game-container replay-viewer">
+            {move || {
+                let at = current.get();
+                let prev = steps[at.saturating_sub(1)].clone();
+                view! { <ReplayPosition step=steps[at].clone() prev=prev turns=turns /> }
+            }}
+
+            <div class="replay-controls">
+                <button class="btn btn-secondary" title=move || t_string!(i18n, replay_first)
+                    prop:disabled=move || current.get() == 0
+                    on:click=move |_| { playing.set(false); current.set(0); }
+                >"⏮"</button>
+                <button class="btn btn-secondary" title=move || t_string!(i18n, replay_back)
+                    prop:disabled=move || current.get() == 0
+                    on:click=move |_| { playing.set(false); current.update(|c| *c = c.saturating_sub(1)); }
+                >"◀"</button>
+                <button class="btn btn-primary"
+                    on:click=move |_| {
+                        if !playing.get_untracked() && current.get_untracked() == last {
+                            current.set(0);
+                        }
+                        playing.update(|p| *p = !*p);
+                    }
+                >{move || if playing.get() {
+                    t_string!(i18n, replay_pause)
+                } else {
+                    t_string!(i18n, replay_play)
+                }}</button>
+                <button class="btn btn-secondary" title=move || t_string!(i18n, replay_forward)
+                    prop:disabled=move || current.get() == last
+                    on:click=move |_| { playing.set(false); current.update(|c| *c = (*c + 1).min(last)); }
+                >"▶"</button>
+                <button class="btn btn-secondary" title=move || t_string!(i18n, replay_last)
+                    prop:disabled=move || current.get() == last
+                    on:click=move |_| { playing.set(false); current.set(last); }
+                >"⏭"</button>
+                <input type="range" class="replay-slider" min="0" max=last.to_string()
+                    prop:value=move || current.get().to_string()
+                    on:input=move |e| {
+                        if let Ok(step) = event_target_value(&e).parse::<usize>() {
+                            playing.set(false);
+                            current.set(step.min(last));
+                        }
+                    }
+                />
+                <label class="replay-jump">
+                    {t!(i18n, replay_jump_label)}
+                    <input type="number" min="0" max=turns.to_string()
+                        prop:value=move || jump_turn.get()
+                        on:input=move |e| jump_turn.set(event_target_value(&e))
+                        on:keydown=move |e| if e.key() == "Enter" { go_to_turn_key() }
+                    />
+                    <button class="btn btn-secondary" on:click=move |_| go_to_turn()>
+                        {t!(i18n, replay_jump)}
+                    </button>
+                </label>
+                <a class="btn btn-secondary" href=href download=file_name>
+                    {t!(i18n, replay_download)}
+                </a>
+            </div>
+            {hints_used}
+        </div>
+    }
+    .into_any()
+}
+
+/// One position of the replay: board, scores, dice and what the last roll scored.
+#[component]
+fn ReplayPosition(step: ReplayStep, prev: ReplayStep, turns: u16) -> impl IntoView {
+    let i18n = use_i18n();
+    let vs = step.view_state;
+    let prev_vs = prev.view_state;
+    let turn = step.turn;
+
+    let white_scored = compute_scored_event(&prev_vs, &vs, 0);
+    let black_scored = compute_scored_event(&prev_vs, &vs, 1);
+    let last_moves = compute_last_moves(&prev_vs, &vs, false);
+    let dice = vs.dice;
+    let show_dice = dice != (0, 0);
+    let is_double_dice = dice.0 == dice.1 && dice.0 != 0;
+
+    let status = match (&vs.stage, vs.active_mp_player) {
+        (SerStage::Ended, _) => t_string!(i18n, game_over).to_owned(),
+        (_, Some(p)) if turn > 0 => {
+            let name = vs.scores[p as usize].name.clone();
+            t_string!(i18n, replay_to_play, name = name.as_str()).to_owned()
+        }
+        _ => String::new(),
+    };
+
+    view! {
+        <MergedScorePanel
+            my_score=vs.scores[0].clone()
+            opp_score=vs.scores[1].clone()
+            my_points_earned=white_scored.as_ref().map_or(0, |e| e.points_earned)
+            opp_points_earned=black_scored.as_ref().map_or(0, |e| e.points_earned)
+            my_holes_gained=white_scored.as_ref().map_or(0, |e| e.holes_gained)
+            opp_holes_gained=black_scored.as_ref().map_or(0, |e| e.holes_gained)
+            my_bredouille=white_scored.as_ref().is_some_and(|e| e.bredouille)
+            active_player_is_me=vs.active_mp_player.map(|p| p == 0)
+        />
+        <div class="main-body">
+            <Board
+                view_state=vs
+                player_id=0
+                selected_origin=RwSignal::new(None)
+                staged_moves=RwSignal::new(Vec::new())
+                valid_sequences=vec![]
+                bar_dice=show_dice.then_some(dice)
+                bar_is_double=is_double_dice
+                last_moves=last_moves
+                read_only=true
+            />
+            <div class="controls">
+                {show_dice.then(|| view! {
+                    <div class="ctrl-dice">
+                        <div class="ctrl-dice-row">
+                            <Die value=dice.0 used=false is_double=is_double_dice />
+                            <Die value=dice.1 used=false is_double=is_double_dice />
+                        </div>
+                    </div>
+                })}
+                <div class="ctrl-status">
+                    <div class="game-status">
+                        {t!(i18n, replay_turn, n = turn, total = turns)}
+                    </div>
+                    <p class="game-sub-prompt">{status}</p>
+                </div>
+            </div>
+        </div>
+        // The viewer never sends anything: no Hold/Go buttons in the panels.
+        <div class="scoring-row">
+            <div class="scoring-panels-container">
+                {white_scored.map(|event| view! {
+                    <ScoringPanel event=event turn_stage=SerTurnStage::RollDice />
+                })}
+                {black_scored.map(|event| view! {
+                    <ScoringPanel event=event turn_stage=SerTurnStage::RollDice is_opponent=true />
+                })}
+            </div>
+        </div>
+    }
+}
@@ -10,7 +10,7 @@ use trictrac_store::Jan;
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen_futures::spawn_local;
 
-use crate::game::trictrac::types::PlayerScore;
+use trictrac_game::types::PlayerScore;
 use crate::i18n::*;
 
 pub fn jan_label(jan: &Jan) -> String {
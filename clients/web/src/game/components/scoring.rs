@@ -12,7 +12,7 @@ use trictrac_store::CheckerMove;
 use wasm_bindgen_futures::spawn_local;
 
 use crate::app::NetCommand;
-use crate::game::trictrac::types::{JanEntry, PlayerAction, ScoredEvent, SerTurnStage};
+use trictrac_game::types::{JanEntry, PlayerAction, ScoredEvent, SerTurnStage};
 use crate::i18n::*;
 
 use super::score_panel::jan_label;
@@ -0,0 +1,238 @@
+use leptos::prelude::*;
+use trictrac_store::CheckerMove;
+
+use super::analysis::jan_row;
+use super::board::Board;
+use super::game_screen::move_error_label;
+use trictrac_game::tutorial::{lessons, ExerciseKey, Goal, LessonKey, Verdict};
+use trictrac_game::types::{SerStage, SerTurnStage, ViewState};
+use crate::i18n::*;
+
+fn lesson_title(key: LessonKey) -> String {
+    let i18n = use_i18n();
+    match key {
+        LessonKey::Moving => t_string!(i18n, tutorial_lesson_moving).to_owned(),
+        LessonKey::RestCorner => t_string!(i18n, tutorial_lesson_rest_corner).to_owned(),
+        LessonKey::Exits => t_string!(i18n, tutorial_lesson_exits).to_owned(),
+        LessonKey::Filling => t_string!(i18n, tutorial_lesson_filling).to_owned(),
+        LessonKey::Jans => t_string!(i18n, tutorial_lesson_jans).to_owned(),
+    }
+}
+
+fn lesson_intro(key: LessonKey) -> String {
+    let i18n = use_i18n();
+    match key {
+        LessonKey::Moving => t_string!(i18n, tutorial_intro_moving).to_owned(),
+        LessonKey::RestCorner => t_string!(i18n, tutorial_intro_rest_corner).to_owned(),
+        LessonKey::Exits => t_string!(i18n, tutorial_intro_exits).to_owned(),
+        LessonKey::Filling => t_string!(i18n, tutorial_intro_filling).to_owned(),
+        LessonKey::Jans => t_string!(i18n, tutorial_intro_jans).to_owned(),
+    }
+}
+
+fn exercise_text(key: ExerciseKey) -> String {
+    let i18n = use_i18n();
+    match key {
+        ExerciseKey::FirstMove => t_string!(i18n, tutorial_ex_first_move).to_owned(),
+        ExerciseKey::CornerByEffect => t_string!(i18n, tutorial_ex_corner_by_effect).to_owned(),
+        ExerciseKey::CornerByPuissance => t_string!(i18n, tutorial_ex_corner_by_puissance).to_owned(),
+        ExerciseKey::CornerLeaving => t_string!(i18n, tutorial_ex_corner_leaving).to_owned(),
+        ExerciseKey::ExitExact => t_string!(i18n, tutorial_ex_exit_exact).to_owned(),
+        ExerciseKey::ExitAllHome => t_string!(i18n, tutorial_ex_exit_all_home).to_owned(),
+        ExerciseKey::FillSmallJan => t_string!(i18n, tutorial_ex_fill_small_jan).to_owned(),
+        ExerciseKey::KeepSmallJan => t_string!(i18n, tutorial_ex_keep_small_jan).to_owned(),
+        ExerciseKey::OpponentQuarter => t_string!(i18n, tutorial_ex_opponent_quarter).to_owned(),
+        ExerciseKey::TrueHitSmallJan => t_string!(i18n, tutorial_ex_true_hit_small_jan).to_owned(),
+        ExerciseKey::TrueHitBigJan => t_string!(i18n, tutorial_ex_true_hit_big_jan).to_owned(),
+        ExerciseKey::FalseHit => t_string!(i18n, tutorial_ex_false_hit).to_owned(),
+        ExerciseKey::TrueHitCorner => t_string!(i18n, tutorial_ex_true_hit_corner).to_owned(),
+        ExerciseKey::TwoTables => t_string!(i18n, tutorial_ex_two_tables).to_owned(),
+        ExerciseKey::ContreTwoTables => t_string!(i18n, tutorial_ex_contre_two_tables).to_owned(),
+        ExerciseKey::Mezeas => t_string!(i18n, tutorial_ex_mezeas).to_owned(),
+        ExerciseKey::SixTables => t_string!(i18n, tutorial_ex_six_tables).to_owned(),
+        ExerciseKey::FirstToExit => t_string!(i18n, tutorial_ex_first_to_exit).to_owned(),
+        ExerciseKey::HelplessMan => t_string!(i18n, tutorial_ex_helpless_man).to_owned(),
+    }
+}
+
+/// The tutorial: lessons of scripted positions where the learner plays the
+/// expected moves, or marks the points of the roll, as White. Refused moves
+/// are explained with the rule they break.
+#[component]
+pub fn Tutorial() -> impl IntoView {
+    let i18n = use_i18n();
+    let lessons = StoredValue::new(lessons());
+    let lesson_idx = RwSignal::new(0usize);
+    let exercise_idx = RwSignal::new(0usize);
+    let verdict = RwSignal::new(None::<Verdict>);
+
+    let selected_origin = RwSignal::new(None::<u8>);
+    let staged_moves: RwSignal<Vec<(u8, u8)>> = RwSignal::new(Vec::new());
+    let marked_white = RwSignal::new(String::new());
+    let marked_black = RwSignal::new(String::new());
+
+    let hovered_jan_moves: RwSignal<Vec<(CheckerMove, CheckerMove)>> = RwSignal::new(vec![]);
+    provide_context(hovered_jan_moves);
+
+    let exercise = move || {
+        lessons.with_value(|l| l[lesson_idx.get()].exercises[exercise_idx.get()].clone())
+    };
+    let exercise_count = move || lessons.with_value(|l| l[lesson_idx.get()].exercises.len());
+
+    let retry = move || {
+        staged_moves.set(vec![]);
+        selected_origin.set(None);
+        verdict.set(None);
+        hovered_jan_moves.set(vec![]);
+    };
+    let open = move |lesson: usize, ex: usize| {
+        lesson_idx.set(lesson);
+        exercise_idx.set(ex);
+        marked_white.set(String::new());
+        marked_black.set(String::new());
+        retry();
+    };
+    let next = move || {
+        let (l, e) = (lesson_idx.get_untracked(), exercise_idx.get_untracked());
+        let lesson_count = lessons.with_value(|l| l.len());
+        if e + 1 < exercise_count() {
+            open(l, e + 1);
+        } else if l + 1 < lesson_count {
+            open(l + 1, 0);
+        }
+    };
+    let is_last = move || {
+        lesson_idx.get() + 1 == lessons.with_value(|l| l.len()) && exercise_idx.get() + 1 == exercise_count()
+    };
+
+    Effect::new(move |_| {
+        let moves = staged_moves.get();
+        if moves.len() == 2 && verdict.get_untracked().is_none() {
+            let to_cm = |&(from, to): &(u8, u8)| {
+                CheckerMove::new(from as usize, to as usize).unwrap_or_default()
+            };
+            verdict.set(Some(exercise().check_moves((to_cm(&moves[0]), to_cm(&moves[1])))));
+        }
+    });
+
+    let mark = move |_| {
+        let parse = |s: String| s.trim().parse::<u8>().unwrap_or(0);
+        let points = (parse(marked_white.get_untracked()), parse(marked_black.get_untracked()));
+        verdict.set(Some(exercise().check_mark(points)));
+    };
+
+    view! {
+        <div class="tutorial">
+            <nav class="tutorial-lessons">
+                {move || lessons.with_value(|l| l.iter().enumerate().map(|(i, lesson)| {
+                    let key = lesson.key;
+                    view! {
+                        <button class="tutorial-lesson"
+                            class:active=move || lesson_idx.get() == i
+                            on:click=move |_| open(i, 0)
+                        >{move || lesson_title(key)}</button>
+                    }
+                }).collect_view())}
+            </nav>
+            <div class="tutorial-body">
+                <h2>{move || lessons.with_value(|l| lesson_title(l[lesson_idx.get()].key))}</h2>
+                <p class="tutorial-intro">
+                    {move || lessons.with_value(|l| lesson_intro(l[lesson_idx.get()].key))}
+                </p>
+                <p class="portal-meta">
+                    {move || t_string!(i18n, tutorial_progress,
+                        n = exercise_idx.get() + 1, total = exercise_count()).to_owned()}
+                </p>
+                <p class="tutorial-task">{move || exercise_text(exercise().key)}</p>
+
+                <div class="game-container tutorial-board">
+                    {move || {
+                        let ex = exercise();
+                        let marking = ex.goal == Goal::Mark;
+                        let mut vs = ViewState::default_with_names("", "");
+                        vs.board = ex.board;
+                        vs.dice = ex.dice;
+                        vs.stage = SerStage::InGame;
+                        vs.turn_stage = SerTurnStage::Move;
+                        vs.active_mp_player = Some(0);
+                        view! {
+                            <Board
+                                view_state=vs
+                                player_id=0
+                                selected_origin=selected_origin
+                                staged_moves=staged_moves
+                                valid_sequences=vec![]
+                                bar_dice=Some(ex.dice)
+                                bar_is_move=!marking
+                                is_my_turn=!marking
+                                bar_is_double=ex.dice.0 == ex.dice.1
+                                free_mode=RwSignal::new(true)
+                                read_only=marking
+                            />
+                        }
+                    }}
+                </div>
+
+                {move || (exercise().goal == Goal::Mark).then(|| view! {
+                    <div class="tutorial-mark">
+                        <label>
+                            {t!(i18n, tutorial_points_white)} " "
+                            <input type="number" min="0" max="99"
+                                prop:value=move || marked_white.get()
+                                on:input=move |e| marked_white.set(event_target_value(&e))
+                            />
+                        </label>
+                        <label>
+                            {t!(i18n, tutorial_points_black)} " "
+                            <input type="number" min="0" max="99"
+                                prop:value=move || marked_black.get()
+                                on:input=move |e| marked_black.set(event_target_value(&e))
+                            />
+                        </label>
+                        <button class="btn btn-primary" on:click=mark>{t!(i18n, tutorial_mark)}</button>
+                    </div>
+                })}
+
+                {move || verdict.get().map(|v| {
+                    let (class_name, msg, solved) = match v {
+                        Verdict::Correct => ("tutorial-feedback correct", t_string!(i18n, tutorial_correct).to_owned(), true),
+                        Verdict::Illegal(err) => ("tutorial-feedback wrong", move_error_label(err), false),
+                        Verdict::Missed => ("tutorial-feedback wrong", t_string!(i18n, tutorial_missed).to_owned(), false),
+                        Verdict::WrongMark((white, black)) => (
+                            "tutorial-feedback wrong",
+                            t_string!(i18n, tutorial_wrong_mark, white = white, black = black).to_owned(),
+                            true,
+                        ),
+                    };
+                    let jans = exercise().jans();
+                    view! {
+                        <div class=class_name>
+                            <p>{msg}</p>
+                            {(solved && !jans.is_empty()).then(|| view! {
+                                <div class="tutorial-jans" on:mouseleave=move |_| hovered_jan_moves.set(vec![])>
+                                    <h3>{t!(i18n, analysis_roll_jans)}</h3>
+                                    {jans.into_iter().map(jan_row).collect_view()}
+                                </div>
+                            })}
+                            <div class="board-actions">
+                                {(!solved).then(|| view! {
+                                    <button class="btn btn-secondary" on:click=move |_| retry()>
+                                        {t!(i18n, reset_move)}
+                                    </button>
+                                })}
+                                {move || (solved && !is_last()).then(|| view! {
+                                    <button class="btn btn-primary" on:click=move |_| next()>
+                                        {t!(i18n, tutorial_next)}
+                                    </button>
+                                })}
+                                {move || (solved && is_last()).then(|| view! {
+                                    <span class="tutorial-done">{t!(i18n, tutorial_finished)}</span>
+                                })}
+                            </div>
+                        </div>
+                    }
+                })}
+            </div>
+        </div>
+    }
+}
@@ -1,4 +1,3 @@
 pub mod components;
 pub mod session;
 pub mod sound;
-pub mod trictrac;
@@ -3,15 +3,84 @@ use leptos::prelude::*;
 
 use backbone_lib::traits::{BackEndArchitecture, BackendCommand};
 
+use crate::api;
 use crate::app::{GameUiState, NetCommand, PauseReason, Screen};
-use crate::game::trictrac::backend::TrictracBackend;
-use crate::game::trictrac::bot_local::bot_decide;
-use crate::game::trictrac::types::{
-    JanEntry, ScoredEvent, SerStage, SerTurnStage, ViewState,
+use trictrac_game::backend::TrictracBackend;
+use trictrac_game::bot_local::{BotLevel, BotPlayer};
+use trictrac_game::network::PolicyValueNet;
+use trictrac_game::types::{
+    JanEntry, ScoredEvent, SerStage, SerTurnStage, ViewState, CASUAL_GAME,
 };
+use trictrac_store::training_common::ACTION_SPACE_SIZE;
 use trictrac_store::CheckerMove;
 
+use gloo_storage::{LocalStorage, Storage};
 use std::collections::VecDeque;
+use std::sync::Arc;
+
+/// Bot level chosen in the lobby.
+pub const BOT_LEVEL_STORAGE_KEY: &str = "trictrac_bot_level";
+
+/// Unfinished game against the bot, resumed when the app is opened again.
+const BOT_GAME_STORAGE_KEY: &str = "trictrac_bot_game";
+
+/// Length of `GameState::to_tensor`, the network's input.
+const OBS_SIZE: usize = 217;
+
+pub fn stored_bot_level() -> BotLevel {
+    LocalStorage::get::<String>(BOT_LEVEL_STORAGE_KEY)
+        .ok()
+        .and_then(|code| BotLevel::from_code(&code))
+        .unwrap_or_default()
+}
+
+/// The bot for the stored level. The network is downloaded for the first
+/// game that needs it and kept in `net` for the next ones; without it the
+/// classic bot plays.
+pub async fn load_bot(net: &mut Option<Arc<PolicyValueNet>>) -> BotPlayer {
+    let level = stored_bot_level();
+    if level.mcts_config().is_some() && net.is_none() {
+        match api::get_bot_network().await {
+            Ok(bytes) => match PolicyValueNet::from_bytes(&bytes) {
+                Ok(n) if n.action_size() != ACTION_SPACE_SIZE || n.obs_size() != OBS_SIZE => {
+                    log_bot_error(&format!(
+                        "network plays {} actions on {} inputs, expected {ACTION_SPACE_SIZE} on {OBS_SIZE}",
+                        n.action_size(),
+                        n.obs_size()
+                    ))
+                }
+                Ok(n) => *net = Some(Arc::new(n)),
+                Err(e) => log_bot_error(&e),
+            },
+            Err(e) => log_bot_error(&e),
+        }
+    }
+    BotPlayer::new(level, net.clone())
+}
+
+/// The bot game left unfinished, if any.
+pub fn stored_bot_game() -> Option<ViewState> {
+    LocalStorage::get(BOT_GAME_STORAGE_KEY).ok()
+}
+
+/// Keeps the position of a bot game under way, and forgets it once over.
+fn store_bot_game(vs: &ViewState) {
+    match vs.stage {
+        SerStage::InGame => {
+            let _ = LocalStorage::set(BOT_GAME_STORAGE_KEY, vs);
+        }
+        SerStage::Ended => LocalStorage::delete(BOT_GAME_STORAGE_KEY),
+        // The first player is not drawn yet: nothing worth resuming.
+        SerStage::PreGame | SerStage::PreGameRoll => {}
+    }
+}
+
+fn log_bot_error(e: &str) {
+    #[cfg(target_arch = "wasm32")]
+    web_sys::console::log_1(&format!("bot network unavailable: {e}").into());
+    #[cfg(not(target_arch = "wasm32"))]
+    let _ = e;
+}
 
 /// Runs one local bot game. Returns `true` if the player wants to play again.
 pub async fn run_local_bot_game(
@@ -19,8 +88,10 @@ pub async fn run_local_bot_game(
     cmd_rx: &mut mpsc::UnboundedReceiver<NetCommand>,
     pending: RwSignal<VecDeque<GameUiState>>,
     player_name: String,
+    bot: BotPlayer,
 ) -> bool {
-    let mut backend = TrictracBackend::new(0);
+    // Games against the bot are never rated.
+    let mut backend = TrictracBackend::new(CASUAL_GAME);
     backend.player_arrival(0);
     backend.player_arrival(1);
 
@@ -48,9 +119,10 @@ pub async fn run_local_bot_game(
         opp_scored_event: None,
         last_moves: None,
         suppress_dice_anim: false,
+        is_spectator: false,
     }));
 
-    run_local_bot_game_loop(screen, cmd_rx, pending, player_name, backend, vs).await
+    run_local_bot_game_loop(screen, cmd_rx, pending, player_name, backend, vs, bot).await
 }
 
 /// Runs a bot game from a pre-built backend and initial ViewState (used for snapshot replay).
@@ -61,6 +133,7 @@ pub async fn run_local_bot_game_with_backend(
     pending: RwSignal<VecDeque<GameUiState>>,
     player_name: String,
     backend: TrictracBackend,
+    bot: BotPlayer,
 ) -> bool {
     let mut vs = backend.get_view_state().clone();
     patch_bot_names(&mut vs, &player_name);
@@ -75,9 +148,10 @@ pub async fn run_local_bot_game_with_backend(
         opp_scored_event: None,
         last_moves: None,
         suppress_dice_anim: false,
+        is_spectator: false,
     }));
 
-    run_local_bot_game_loop(screen, cmd_rx, pending, player_name, backend, vs).await
+    run_local_bot_game_loop(screen, cmd_rx, pending, player_name, backend, vs, bot).await
 }
 
 async fn run_local_bot_game_loop(
@@ -87,9 +161,11 @@ async fn run_local_bot_game_loop(
     player_name: String,
     mut backend: TrictracBackend,
     mut vs: ViewState,
+    bot: BotPlayer,
 ) -> bool {
     use futures::StreamExt;
     loop {
+        store_bot_game(&vs);
         match cmd_rx.next().await {
             Some(NetCommand::Action(action)) => {
                 let prev_vs = vs.clone();
@@ -100,6 +176,9 @@ async fn run_local_bot_game_loop(
                     }
                 }
                 patch_bot_names(&mut vs, &player_name);
+                if prev_vs.differs_only_in_chat_or_hints(&vs) {
+                    continue;
+                }
                 let scored = compute_scored_event(&prev_vs, &vs, 0);
                 let opp_scored = compute_scored_event(&prev_vs, &vs, 1);
                 screen.set(Screen::Playing(GameUiState {
@@ -113,15 +192,25 @@ async fn run_local_bot_game_loop(
                     opp_scored_event: opp_scored,
                     last_moves: compute_last_moves(&prev_vs, &vs, true),
                     suppress_dice_anim: false,
+                    is_spectator: false,
                 }));
             }
-            Some(NetCommand::PlayVsBot) => return true,
-            _ => return false,
+            // No one to chat with in a bot game.
+            Some(NetCommand::Chat(_)) => continue,
+            // The game is left: it is not resumed on the next visit.
+            Some(NetCommand::PlayVsBot) => {
+                LocalStorage::delete(BOT_GAME_STORAGE_KEY);
+                return true;
+            }
+            _ => {
+                LocalStorage::delete(BOT_GAME_STORAGE_KEY);
+                return false;
+            }
         }
 
         loop {
             let pgr = backend.get_view_state().pre_game_roll.clone();
-            match bot_decide(backend.get_game(), pgr.as_ref()) {
+            match bot.decide(backend.get_game(), pgr.as_ref()) {
                 None => break,
                 Some(action) => {
                     backend.inform_rpc(1, action);
@@ -143,6 +232,7 @@ async fn run_local_bot_game_loop(
                                     opp_scored_event: None,
                                     last_moves: compute_last_moves(&delta_prev_vs, &vs, false),
                                     suppress_dice_anim: false,
+                                    is_spectator: false,
                                 },
                                 pending,
                                 screen,
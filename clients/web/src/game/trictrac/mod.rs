@@ -1,3 +0,0 @@
-pub mod backend;
-pub mod bot_local;
-pub mod types;
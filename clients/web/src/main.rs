@@ -3,6 +3,7 @@ leptos_i18n::load_locales!();
 mod api;
 mod app;
 mod game;
+mod notifications;
 mod portal;
 
 use app::App;
@@ -0,0 +1,250 @@
+//! The logged-in user's notification socket, and the tray showing what it
+//! receives: friend requests, challenges and their answers.
+
+use std::ops::ControlFlow;
+
+use ewebsock::{WsEvent, WsMessage};
+use futures::channel::mpsc::{self, UnboundedSender};
+use futures::{FutureExt, StreamExt};
+use gloo_timers::future::TimeoutFuture;
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use leptos_router::hooks::use_navigate;
+use serde::Deserialize;
+
+use crate::api;
+use crate::app::{ws_url, FlashMessage, NetCommand};
+use crate::i18n::*;
+use crate::portal::tournaments::rules_label;
+
+/// How long to wait before reopening a dropped socket, or checking again
+/// whether someone logged in.
+const RECONNECT_DELAY_MS: u32 = 5_000;
+/// How often a listening socket checks that its user is still logged in.
+const LOGOUT_CHECK_MS: u32 = 1_000;
+
+/// An event pushed by the relay, as sent on `/notifications`.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Notification {
+    FriendRequest {
+        from: String,
+    },
+    FriendAccepted {
+        by: String,
+    },
+    Challenge {
+        id: i64,
+        from: String,
+        rule_variation: u16,
+        room_code: String,
+    },
+    ChallengeAccepted {
+        id: i64,
+        by: String,
+        room_code: String,
+    },
+    ChallengeDeclined {
+        id: i64,
+        by: String,
+    },
+    ChallengeCancelled {
+        id: i64,
+        by: String,
+    },
+}
+
+/// Notifications received since the page was loaded and not dismissed yet,
+/// oldest first.
+#[derive(Clone, Copy)]
+pub(crate) struct Notifications(pub RwSignal<Vec<Notification>>);
+
+/// Keeps a notification socket open for as long as a user is logged in.
+pub(crate) fn spawn_listener(
+    auth_username: RwSignal<Option<String>>,
+    notifications: RwSignal<Vec<Notification>>,
+) {
+    spawn_local(async move {
+        loop {
+            if auth_username.get_untracked().is_some() {
+                listen(auth_username, notifications).await;
+            }
+            TimeoutFuture::new(RECONNECT_DELAY_MS).await;
+        }
+    });
+}
+
+/// Reads the socket until it closes or the user logs out.
+async fn listen(
+    auth_username: RwSignal<Option<String>>,
+    notifications: RwSignal<Vec<Notification>>,
+) {
+    let (event_tx, mut event_rx) = mpsc::unbounded::<WsEvent>();
+    let on_event = Box::new(move |event| {
+        if event_tx.unbounded_send(event).is_ok() {
+            ControlFlow::Continue(())
+        } else {
+            ControlFlow::Break(())
+        }
+    });
+    // Dropping the sender closes the socket.
+    let Ok(_ws_sender) = ewebsock::ws_connect(
+        ws_url("/notifications"),
+        ewebsock::Options::default(),
+        on_event,
+    ) else {
+        return;
+    };
+
+    loop {
+        let mut logout_check = TimeoutFuture::new(LOGOUT_CHECK_MS).fuse();
+        futures::select! {
+            event = event_rx.next() => match event {
+                Some(WsEvent::Message(WsMessage::Text(text))) => {
+                    if let Ok(n) = serde_json::from_str::<Notification>(&text) {
+                        receive(notifications, n);
+                    }
+                }
+                Some(WsEvent::Closed) | Some(WsEvent::Error(_)) | None => return,
+                Some(_) => {}
+            },
+            _ = logout_check => {}
+        }
+        if auth_username.get_untracked().is_none() {
+            return;
+        }
+    }
+}
+
+fn receive(notifications: RwSignal<Vec<Notification>>, notification: Notification) {
+    notifications.update(|list| {
+        // A withdrawn challenge can no longer be accepted.
+        if let Notification::ChallengeCancelled { id, .. } = &notification {
+            list.retain(|n| !matches!(n, Notification::Challenge { id: c, .. } if c == id));
+        }
+        list.push(notification);
+    });
+}
+
+/// Cards in the corner of the screen, one per pending notification.
+#[component]
+pub(crate) fn NotificationTray() -> impl IntoView {
+    let notifications = use_context::<Notifications>()
+        .expect("Notifications context not found")
+        .0;
+
+    move || {
+        let list = notifications.get();
+        (!list.is_empty()).then(|| {
+            view! {
+                <div class="notification-tray">
+                    {list.into_iter().enumerate().map(|(index, notification)| view! {
+                        <NotificationCard index=index notification=notification />
+                    }).collect_view()}
+                </div>
+            }
+        })
+    }
+}
+
+#[component]
+fn NotificationCard(index: usize, notification: Notification) -> impl IntoView {
+    let i18n = use_i18n();
+    let notifications = use_context::<Notifications>()
+        .expect("Notifications context not found")
+        .0;
+    let flash = use_context::<FlashMessage>()
+        .expect("FlashMessage context not found")
+        .0;
+    let cmd_tx = use_context::<UnboundedSender<NetCommand>>().expect("NetCommand sender");
+    let navigate = use_navigate();
+
+    let dismiss = move || {
+        notifications.update(|list| {
+            if index < list.len() {
+                list.remove(index);
+            }
+        })
+    };
+
+    let (text, actions) = match notification {
+        Notification::FriendRequest { from } => {
+            let name = from.clone();
+            let accept = move |_| {
+                let name = name.clone();
+                spawn_local(async move {
+                    if let Err(e) = api::post_accept_friend(&name).await {
+                        flash.set(Some(e));
+                    }
+                });
+                dismiss();
+            };
+            (
+                t_string!(i18n, notif_friend_request, name = from).to_string(),
+                Some(view! {
+                    <button class="portal-page-btn" on:click=accept>{t!(i18n, friend_accept)}</button>
+                }.into_any()),
+            )
+        }
+        Notification::FriendAccepted { by } => (
+            t_string!(i18n, notif_friend_accepted, name = by).to_string(),
+            None,
+        ),
+        Notification::Challenge { id, from, rule_variation, room_code } => {
+            let accept = move |_| {
+                let room = room_code.clone();
+                let tx = cmd_tx.clone();
+                let nav = navigate.clone();
+                spawn_local(async move {
+                    match api::answer_challenge(id, true).await {
+                        Ok(()) => {
+                            tx.unbounded_send(NetCommand::JoinRoom { room }).ok();
+                            nav("/", Default::default());
+                        }
+                        Err(e) => flash.set(Some(e)),
+                    }
+                });
+                dismiss();
+            };
+            let decline = move |_| {
+                spawn_local(async move {
+                    let _ = api::answer_challenge(id, false).await;
+                });
+                dismiss();
+            };
+            (
+                format!(
+                    "{} · {}",
+                    t_string!(i18n, notif_challenge, name = from),
+                    rules_label(rule_variation)
+                ),
+                Some(view! {
+                    <button class="portal-submit-btn" on:click=accept>{t!(i18n, friend_accept)}</button>
+                    <button class="portal-page-btn" on:click=decline>{t!(i18n, friend_decline)}</button>
+                }.into_any()),
+            )
+        }
+        Notification::ChallengeAccepted { by, .. } => (
+            t_string!(i18n, notif_challenge_accepted, name = by).to_string(),
+            None,
+        ),
+        Notification::ChallengeDeclined { by, .. } => (
+            t_string!(i18n, notif_challenge_declined, name = by).to_string(),
+            None,
+        ),
+        Notification::ChallengeCancelled { by, .. } => (
+            t_string!(i18n, notif_challenge_cancelled, name = by).to_string(),
+            None,
+        ),
+    };
+
+    view! {
+        <div class="notification-card">
+            <span class="notification-text">{ text }</span>
+            <div class="notification-actions">
+                {actions}
+                <button class="flash-dismiss" on:click=move |_| dismiss()>"✕"</button>
+            </div>
+        </div>
+    }
+}
@@ -1,5 +1,5 @@
 use leptos::prelude::*;
-use leptos_router::hooks::use_navigate;
+use leptos_router::hooks::{use_navigate, use_query_map};
 
 use crate::api;
 use crate::app::AuthEmailVerified;
@@ -170,6 +170,50 @@ fn LoginForm() -> impl IntoView {
                 view! { <span /> }.into_any()
             }}
         </form>
+        <OidcSignIn />
+    }
+}
+
+/// "Sign in with …" link to the configured OpenID Connect provider, if any.
+#[component]
+fn OidcSignIn() -> impl IntoView {
+    let i18n = use_i18n();
+    let provider = LocalResource::new(api::get_oidc_provider);
+
+    move || {
+        provider.get().map(|sw| sw.take()).and_then(Result::ok).map(|p| {
+            view! {
+                <div class="portal-oidc">
+                    <div class="portal-oidc-divider">{t!(i18n, oidc_or)}</div>
+                    <a class="portal-oidc-btn" href=api::oidc_login_url()>
+                        { t_string!(i18n, oidc_sign_in_with, name = p.name).to_string() }
+                    </a>
+                    <OidcError />
+                </div>
+            }
+        })
+    }
+}
+
+/// Explains the `oidc_error` the relay adds to the URL when a provider login
+/// or account link fails.
+#[component]
+pub(crate) fn OidcError() -> impl IntoView {
+    let i18n = use_i18n();
+    let query = use_query_map();
+
+    move || {
+        query.read().get("oidc_error").map(|reason| {
+            let message = match reason.as_str() {
+                "cancelled" => t_string!(i18n, oidc_error_cancelled),
+                "email_required" => t_string!(i18n, oidc_error_email_required),
+                "email_in_use" => t_string!(i18n, oidc_error_email_in_use),
+                "identity_in_use" => t_string!(i18n, oidc_error_identity_in_use),
+                "banned" => t_string!(i18n, oidc_error_banned),
+                _ => t_string!(i18n, oidc_error_failed),
+            };
+            view! { <p class="portal-error">{ message.to_string() }</p> }
+        })
     }
 }
 
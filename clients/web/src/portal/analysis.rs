@@ -0,0 +1,17 @@
+use leptos::prelude::*;
+
+use crate::game::components::AnalysisBoard;
+use crate::i18n::*;
+
+/// `/analysis`: the analysis board, outside of any game.
+#[component]
+pub fn AnalysisPage() -> impl IntoView {
+    let i18n = use_i18n();
+    view! {
+        <div class="portal-main analysis-page">
+            <h1>{t!(i18n, analysis_title)}</h1>
+            <p class="portal-meta">{t!(i18n, analysis_hint)}</p>
+            <AnalysisBoard />
+        </div>
+    }
+}
@@ -0,0 +1,42 @@
+use leptos::prelude::*;
+use leptos_router::{components::A, hooks::use_params_map};
+
+use crate::api;
+use crate::game::components::BlunderReport;
+use trictrac_game::replay::GameRecord;
+use crate::i18n::*;
+
+/// Blunder check of a recorded game, `/games/:id/blunders`.
+#[component]
+pub fn BlundersPage() -> impl IntoView {
+    let i18n = use_i18n();
+    let params = use_params_map();
+    let id = params.read_untracked().get("id").unwrap_or_default();
+    let back = format!("/games/{id}");
+    let replay_link = format!("/games/{id}/replay");
+    let record = LocalResource::new(move || {
+        let id = id.clone();
+        async move {
+            let id: i64 = id.parse().map_err(|_| "invalid game id".to_string())?;
+            GameRecord::from_json(&api::get_game_record(id).await?)
+        }
+    });
+
+    view! {
+        <div class="portal-main">
+            <p class="portal-meta">
+                <A href=back>{t!(i18n, replay_back_to_game)}</A>
+                " · "
+                <A href=replay_link>{t!(i18n, replay_watch)}</A>
+            </p>
+            <div class="portal-card">
+                <h1>{t!(i18n, blunders_title)}</h1>
+                {move || match record.get().map(|sw| sw.take()) {
+                    None => view! { <p class="portal-loading">{t!(i18n, loading)}</p> }.into_any(),
+                    Some(Err(e)) => view! { <p class="portal-error">{ e }</p> }.into_any(),
+                    Some(Ok(r)) => view! { <BlunderReport record=r /> }.into_any(),
+                }}
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,406 @@
+use futures::channel::mpsc::UnboundedSender;
+use leptos::prelude::*;
+use leptos_router::{components::A, hooks::use_navigate};
+
+use crate::api::{self, Challenge, ChallengesResponse, FriendsResponse};
+use crate::app::{NetCommand, GAME_ID};
+use trictrac_game::types::TimeControl;
+use crate::i18n::*;
+use crate::notifications::Notifications;
+use crate::portal::lobby::time_control_label;
+use crate::portal::tournaments::rules_label;
+
+type Action = std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), String>>>>;
+
+#[component]
+pub fn FriendsPage() -> impl IntoView {
+    let i18n = use_i18n();
+    let auth_username =
+        use_context::<RwSignal<Option<String>>>().expect("auth_username context not found");
+    let notifications = use_context::<Notifications>()
+        .expect("Notifications context not found")
+        .0;
+    // Bumped after every action so the lists are fetched again.
+    let version = RwSignal::new(0u32);
+
+    let lists = LocalResource::new(move || {
+        version.track();
+        // Incoming notifications usually mean the lists changed.
+        notifications.with(|n| n.len());
+        let logged_in = auth_username.get().is_some();
+        async move {
+            if !logged_in {
+                return Err(String::new());
+            }
+            let friends = api::get_friends().await?;
+            let challenges = api::get_challenges().await?;
+            Ok((friends, challenges))
+        }
+    });
+
+    view! {
+        <div class="portal-main">
+            {move || {
+                if auth_username.get().is_none() {
+                    return view! {
+                        <div class="portal-card">
+                            <h1>{t!(i18n, friends_title)}</h1>
+                            <p class="portal-empty">{t!(i18n, friends_sign_in)}</p>
+                        </div>
+                    }.into_any();
+                }
+                match lists.get().map(|sw| sw.take()) {
+                    None => view! { <p class="portal-loading">{t!(i18n, loading)}</p> }.into_any(),
+                    Some(Err(e)) => view! { <p class="portal-error">{ e }</p> }.into_any(),
+                    Some(Ok((friends, challenges))) => view! {
+                        <FriendsView friends=friends challenges=challenges version=version />
+                    }.into_any(),
+                }
+            }}
+        </div>
+    }
+}
+
+#[component]
+fn FriendsView(
+    friends: FriendsResponse,
+    challenges: ChallengesResponse,
+    version: RwSignal<u32>,
+) -> impl IntoView {
+    let i18n = use_i18n();
+    let error = RwSignal::new(String::new());
+    let pending = RwSignal::new(false);
+
+    let run = move |fut: Action| {
+        if pending.get_untracked() { return; }
+        pending.set(true);
+        error.set(String::new());
+        wasm_bindgen_futures::spawn_local(async move {
+            match fut.await {
+                Ok(()) => version.update(|v| *v += 1),
+                Err(e) => error.set(e),
+            }
+            pending.set(false);
+        });
+    };
+
+    let has_requests = !friends.incoming.is_empty() || !friends.outgoing.is_empty();
+    let has_challenges = !challenges.incoming.is_empty() || !challenges.outgoing.is_empty();
+    let blocked = friends.blocked.clone();
+
+    view! {
+        <div class="portal-card">
+            <h1>{t!(i18n, friends_title)}</h1>
+            <AddFriendForm run=run />
+            {move || (!error.get().is_empty()).then(|| view! { <p class="portal-error">{ error.get() }</p> })}
+            <FriendList friends=friends.friends.clone() run=run error=error />
+        </div>
+
+        {has_challenges.then(|| view! {
+            <div class="portal-card">
+                <h2>{t!(i18n, challenges_title)}</h2>
+                <ChallengeList challenges=challenges run=run />
+            </div>
+        })}
+
+        {has_requests.then(|| view! {
+            <div class="portal-card">
+                <h2>{t!(i18n, friend_requests_title)}</h2>
+                <ul class="friend-list">
+                    {friends.incoming.into_iter().map(|name| {
+                        let accept_name = name.clone();
+                        let decline_name = name.clone();
+                        view! {
+                            <li class="friend-row">
+                                <A href=format!("/profile/{name}")>{ name.clone() }</A>
+                                <span class="friend-actions">
+                                    <button class="portal-submit-btn"
+                                        on:click=move |_| {
+                                            let n = accept_name.clone();
+                                            run(Box::pin(async move { api::post_accept_friend(&n).await }))
+                                        }
+                                    >{t!(i18n, friend_accept)}</button>
+                                    <button class="portal-page-btn"
+                                        on:click=move |_| {
+                                            let n = decline_name.clone();
+                                            run(Box::pin(async move { api::set_friend(&n, false).await }))
+                                        }
+                                    >{t!(i18n, friend_decline)}</button>
+                                </span>
+                            </li>
+                        }
+                    }).collect_view()}
+                    {friends.outgoing.into_iter().map(|name| {
+                        let cancel_name = name.clone();
+                        view! {
+                            <li class="friend-row">
+                                <A href=format!("/profile/{name}")>{ name.clone() }</A>
+                                <span class="friend-status">{t!(i18n, friend_request_sent)}</span>
+                                <span class="friend-actions">
+                                    <button class="portal-page-btn"
+                                        on:click=move |_| {
+                                            let n = cancel_name.clone();
+                                            run(Box::pin(async move { api::set_friend(&n, false).await }))
+                                        }
+                                    >{t!(i18n, cancel)}</button>
+                                </span>
+                            </li>
+                        }
+                    }).collect_view()}
+                </ul>
+            </div>
+        })}
+
+        {(!blocked.is_empty()).then(|| view! {
+            <div class="portal-card">
+                <h2>{t!(i18n, blocked_users_title)}</h2>
+                <ul class="friend-list">
+                    {blocked.into_iter().map(|name| {
+                        let unblock_name = name.clone();
+                        view! {
+                            <li class="friend-row">
+                                <span>{ name }</span>
+                                <span class="friend-actions">
+                                    <button class="portal-page-btn"
+                                        on:click=move |_| {
+                                            let n = unblock_name.clone();
+                                            run(Box::pin(async move { api::set_blocked(&n, false).await }))
+                                        }
+                                    >{t!(i18n, friend_unblock)}</button>
+                                </span>
+                            </li>
+                        }
+                    }).collect_view()}
+                </ul>
+            </div>
+        })}
+
+        <EmailPreference />
+    }
+}
+
+#[component]
+fn AddFriendForm(run: impl Fn(Action) + Copy + 'static) -> impl IntoView {
+    let i18n = use_i18n();
+    let username = RwSignal::new(String::new());
+
+    let submit = move |ev: leptos::ev::SubmitEvent| {
+        ev.prevent_default();
+        let name = username.get().trim().to_string();
+        if name.is_empty() { return; }
+        username.set(String::new());
+        run(Box::pin(async move { api::set_friend(&name, true).await }));
+    };
+
+    view! {
+        <form class="friend-add" on:submit=submit>
+            <input class="portal-input" type="text" required maxlength="30"
+                placeholder=move || t_string!(i18n, label_username)
+                prop:value=move || username.get()
+                on:input=move |ev| username.set(event_target_value(&ev)) />
+            <button class="portal-submit-btn" type="submit">{t!(i18n, friend_add)}</button>
+        </form>
+    }
+}
+
+/// Accepted friends, each with a button to challenge them to a game.
+#[component]
+fn FriendList(
+    friends: Vec<api::Friend>,
+    run: impl Fn(Action) + Copy + 'static,
+    error: RwSignal<String>,
+) -> impl IntoView {
+    let i18n = use_i18n();
+    let cmd_tx = use_context::<UnboundedSender<NetCommand>>().expect("NetCommand sender");
+    let navigate = use_navigate();
+    // Time control of the challenges sent from this list.
+    let rule_variation = RwSignal::new(0u16);
+
+    if friends.is_empty() {
+        return view! { <p class="portal-empty">{t!(i18n, friends_none)}</p> }.into_any();
+    }
+
+    let challenge = move |name: String| {
+        let tx = cmd_tx.clone();
+        let nav = navigate.clone();
+        let rv = rule_variation.get_untracked();
+        wasm_bindgen_futures::spawn_local(async move {
+            match api::post_challenge(&name, GAME_ID, rv).await {
+                // The challenger opens the room right away and waits there.
+                Ok(c) => {
+                    tx.unbounded_send(NetCommand::CreateRoom {
+                        room: c.room_code,
+                        rule_variation: c.rule_variation,
+                    })
+                    .ok();
+                    nav("/", Default::default());
+                }
+                Err(e) => error.set(e),
+            }
+        });
+    };
+
+    let time_control_options = TimeControl::PRESETS
+        .into_iter()
+        .map(|tc| {
+            let value = tc.to_rule_variation().to_string();
+            view! { <option value=value>{move || time_control_label(tc)}</option> }
+        })
+        .collect_view();
+
+    view! {
+        <label class="time-control-field">
+            <span>{t!(i18n, time_control_label)}</span>
+            <select class="time-control-select"
+                on:change=move |ev| rule_variation.set(event_target_value(&ev).parse().unwrap_or(0))>
+                <option value="0">{t!(i18n, time_control_none)}</option>
+                {time_control_options}
+            </select>
+        </label>
+        <ul class="friend-list">
+            {friends.into_iter().map(|f| {
+                let name = f.username.clone();
+                let challenge_name = name.clone();
+                let remove_name = name.clone();
+                let block_name = name.clone();
+                let challenge = challenge.clone();
+                view! {
+                    <li class="friend-row">
+                        <span class="friend-online" class:friend-online-on=f.online
+                            title=move || if f.online { t_string!(i18n, friend_online) } else { "" }></span>
+                        <A href=format!("/profile/{name}")>{ name.clone() }</A>
+                        <span class="friend-actions">
+                            <button class="portal-submit-btn"
+                                on:click=move |_| challenge(challenge_name.clone())
+                            >{t!(i18n, challenge_send)}</button>
+                            <button class="portal-page-btn"
+                                on:click=move |_| {
+                                    let n = remove_name.clone();
+                                    run(Box::pin(async move { api::set_friend(&n, false).await }))
+                                }
+                            >{t!(i18n, friend_remove)}</button>
+                            <button class="portal-page-btn"
+                                on:click=move |_| {
+                                    let n = block_name.clone();
+                                    run(Box::pin(async move { api::set_blocked(&n, true).await }))
+                                }
+                            >{t!(i18n, friend_block)}</button>
+                        </span>
+                    </li>
+                }
+            }).collect_view()}
+        </ul>
+    }
+    .into_any()
+}
+
+/// Pending challenges: received ones can be accepted or declined, sent ones
+/// reopened (the room closes when the challenger leaves it) or withdrawn.
+#[component]
+fn ChallengeList(
+    challenges: ChallengesResponse,
+    run: impl Fn(Action) + Copy + 'static,
+) -> impl IntoView {
+    let i18n = use_i18n();
+    let cmd_tx = use_context::<UnboundedSender<NetCommand>>().expect("NetCommand sender");
+    let navigate = use_navigate();
+
+    let open_room = {
+        let tx = cmd_tx.clone();
+        let nav = navigate.clone();
+        move |c: &Challenge, accept: bool| {
+            let cmd = if accept {
+                NetCommand::JoinRoom { room: c.room_code.clone() }
+            } else {
+                NetCommand::CreateRoom {
+                    room: c.room_code.clone(),
+                    rule_variation: c.rule_variation,
+                }
+            };
+            tx.unbounded_send(cmd).ok();
+            nav("/", Default::default());
+        }
+    };
+
+    view! {
+        <ul class="friend-list">
+            {challenges.incoming.into_iter().map(|c| {
+                let id = c.id;
+                let challenger = c.challenger.clone();
+                let rules = c.rule_variation;
+                let open_room = open_room.clone();
+                let accept = move |_| {
+                    let c = c.clone();
+                    let open_room = open_room.clone();
+                    wasm_bindgen_futures::spawn_local(async move {
+                        if api::answer_challenge(c.id, true).await.is_ok() {
+                            open_room(&c, true);
+                        }
+                    });
+                };
+                view! {
+                    <li class="friend-row">
+                        <span>{ t_string!(i18n, notif_challenge, name = challenger).to_string() }</span>
+                        <span class="friend-status">{ rules_label(rules) }</span>
+                        <span class="friend-actions">
+                            <button class="portal-submit-btn" on:click=accept>{t!(i18n, friend_accept)}</button>
+                            <button class="portal-page-btn"
+                                on:click=move |_| run(Box::pin(api::answer_challenge(id, false)))
+                            >{t!(i18n, friend_decline)}</button>
+                        </span>
+                    </li>
+                }
+            }).collect_view()}
+            {challenges.outgoing.into_iter().map(|c| {
+                let id = c.id;
+                let open_room = open_room.clone();
+                let invitee = c.invitee.clone();
+                let rules = c.rule_variation;
+                view! {
+                    <li class="friend-row">
+                        <span>{ t_string!(i18n, challenge_waiting, name = invitee).to_string() }</span>
+                        <span class="friend-status">{ rules_label(rules) }</span>
+                        <span class="friend-actions">
+                            <button class="portal-submit-btn"
+                                on:click=move |_| open_room(&c, false)
+                            >{t!(i18n, tournament_open_game)}</button>
+                            <button class="portal-page-btn"
+                                on:click=move |_| run(Box::pin(api::delete_challenge(id)))
+                            >{t!(i18n, cancel)}</button>
+                        </span>
+                    </li>
+                }
+            }).collect_view()}
+        </ul>
+    }
+}
+
+/// Whether challenges received while offline are also sent by email.
+#[component]
+fn EmailPreference() -> impl IntoView {
+    let i18n = use_i18n();
+    let enabled = RwSignal::new(None::<bool>);
+    wasm_bindgen_futures::spawn_local(async move {
+        if let Ok(me) = api::get_me().await {
+            enabled.set(Some(me.email_notifications));
+        }
+    });
+
+    move || {
+        enabled.get().map(|on| view! {
+            <div class="portal-card">
+                <label class="friend-email-pref">
+                    <input type="checkbox" prop:checked=on
+                        on:change=move |ev| {
+                            let checked = event_target_checked(&ev);
+                            enabled.set(Some(checked));
+                            wasm_bindgen_futures::spawn_local(async move {
+                                let _ = api::put_preferences(checked).await;
+                            });
+                        } />
+                    <span>{t!(i18n, email_notifications_label)}</span>
+                </label>
+            </div>
+        })
+    }
+}
@@ -73,6 +73,18 @@ fn GameDetailView(game: GameDetail) -> impl IntoView {
                     </p>
                 </div>
             })}
+
+            {game.has_record.then(|| view! {
+                <p style="margin-top:1.5rem">
+                    <A href=format!("/games/{}/replay", game.id) attr:class="btn btn-primary">
+                        {t!(i18n, replay_watch)}
+                    </A>
+                    " "
+                    <A href=format!("/games/{}/blunders", game.id) attr:class="btn btn-secondary">
+                        {t!(i18n, blunders_check)}
+                    </A>
+                </p>
+            })}
         </div>
     }
 }
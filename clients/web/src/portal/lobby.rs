@@ -1,9 +1,13 @@
 use futures::channel::mpsc::UnboundedSender;
+use gloo_storage::{LocalStorage, Storage};
 use leptos::prelude::*;
 use leptos_router::components::A;
 use leptos_router::hooks::use_query_map;
 
 use crate::app::{AnonNickname, NetCommand, Screen};
+use crate::game::session::{stored_bot_level, BOT_LEVEL_STORAGE_KEY};
+use trictrac_game::bot_local::BotLevel;
+use trictrac_game::types::{ClockMode, TimeControl, CASUAL_GAME};
 use crate::i18n::*;
 
 // ── Room/nickname generation ──────────────────────────────────────────────────
@@ -80,12 +84,25 @@ pub(crate) fn room_url(code: &str) -> String {
     format!("http://localhost:9091/?room={}", code)
 }
 
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn watch_url(code: &str) -> String {
+    let origin = web_sys::window()
+        .and_then(|w| w.location().origin().ok())
+        .unwrap_or_default();
+    format!("{}/?watch={}", origin, code)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn watch_url(code: &str) -> String {
+    format!("http://localhost:9091/?watch={}", code)
+}
+
 // ── Lobby state ───────────────────────────────────────────────────────────────
 
 /// Action to execute once the anonymous player has chosen their nickname.
 #[derive(Clone)]
 enum PendingLobbyAction {
-    Create { code: String },
+    Create { code: String, rule_variation: u16 },
     Join { code: String },
 }
 
@@ -110,6 +127,23 @@ pub fn LobbyPage() -> impl IntoView {
     // Non-None while the nickname-chooser modal is open.
     let pending_action: RwSignal<Option<PendingLobbyAction>> = RwSignal::new(None);
 
+    // ── Auto-watch when URL has ?watch=CODE ────────────────────────────────
+    // Spectators never need a nickname, so no need to wait for auth.
+    let watch_processed = StoredValue::new(false);
+    let cmd_tx_w = cmd_tx.clone();
+    Effect::new(move |_| {
+        if watch_processed.get_value() {
+            return;
+        }
+        let Some(code) = query.read().get("watch").filter(|s| !s.is_empty()) else {
+            return;
+        };
+        watch_processed.set_value(true);
+        cmd_tx_w
+            .unbounded_send(NetCommand::WatchRoom { room: code })
+            .ok();
+    });
+
     // ── Auto-join when URL has ?room=CODE ──────────────────────────────────
     // Wait for auth to resolve so we join directly when already logged in,
     // or show the nickname modal when anonymous.
@@ -198,20 +232,86 @@ fn IdleCard(
 
     let cmd_bot = cmd_tx.clone();
     let cmd_create = cmd_tx.clone();
+    // Rule variation of the room to create: 0 = untimed, else a `TimeControl`.
+    let time_control: RwSignal<u16> = RwSignal::new(0);
+    // Casual games are not rated and allow hints.
+    let casual = RwSignal::new(false);
 
     let on_create = move |_: leptos::ev::MouseEvent| {
         let code = generate_room_code();
+        let rule_variation = if casual.get_untracked() {
+            time_control.get_untracked() | CASUAL_GAME
+        } else {
+            time_control.get_untracked()
+        };
         if auth_username.get_untracked().is_some() {
             cmd_create
-                .unbounded_send(NetCommand::CreateRoom { room: code.clone() })
+                .unbounded_send(NetCommand::CreateRoom {
+                    room: code.clone(),
+                    rule_variation,
+                })
                 .ok();
             view_state.set(LobbyView::Waiting { code });
         } else {
-            pending_action.set(Some(PendingLobbyAction::Create { code }));
+            pending_action.set(Some(PendingLobbyAction::Create {
+                code,
+                rule_variation,
+            }));
         }
     };
 
+    let time_control_options = TimeControl::PRESETS
+        .into_iter()
+        .map(|tc| {
+            let value = tc.to_rule_variation().to_string();
+            let label = move || time_control_label(tc);
+            view! { <option value=value>{label}</option> }
+        })
+        .collect_view();
+
+    let bot_level = stored_bot_level();
+    let bot_level_options = BotLevel::ALL
+        .into_iter()
+        .map(|level| {
+            let label = move || bot_level_label(level);
+            view! {
+                <option value=level.code() selected=level == bot_level>{label}</option>
+            }
+        })
+        .collect_view();
+
     view! {
+        <label class="time-control-field">
+            <span>{t!(i18n, time_control_label)}</span>
+            <select
+                class="time-control-select"
+                on:change=move |ev| {
+                    time_control.set(event_target_value(&ev).parse().unwrap_or(0));
+                }
+            >
+                <option value="0">{t!(i18n, time_control_none)}</option>
+                {time_control_options}
+            </select>
+        </label>
+        <label class="casual-game-toggle">
+            <input
+                type="checkbox"
+                prop:checked=move || casual.get()
+                on:change=move |ev| casual.set(event_target_checked(&ev))
+            />
+            {t!(i18n, casual_game_label)}
+        </label>
+        <label class="time-control-field">
+            <span>{t!(i18n, bot_level_label)}</span>
+            <select
+                class="time-control-select"
+                on:change=move |ev| {
+                    LocalStorage::set(BOT_LEVEL_STORAGE_KEY, event_target_value(&ev)).ok();
+                }
+            >
+                {bot_level_options}
+            </select>
+        </label>
         <div class="login-actions">
             <button
                 class="login-btn login-btn-secondary"
@@ -232,6 +332,31 @@ fn IdleCard(
     }
 }
 
+fn bot_level_label(level: BotLevel) -> String {
+    let i18n = use_i18n();
+    match level {
+        BotLevel::Classic => t_string!(i18n, bot_level_classic),
+        BotLevel::Easy => t_string!(i18n, bot_level_easy),
+        BotLevel::Medium => t_string!(i18n, bot_level_medium),
+        BotLevel::Hard => t_string!(i18n, bot_level_hard),
+    }
+    .to_string()
+}
+
+pub(crate) fn time_control_label(tc: TimeControl) -> String {
+    let i18n = use_i18n();
+    match tc.mode {
+        ClockMode::Fischer => {
+            t_string!(i18n, time_control_fischer, min = tc.minutes, sec = tc.bonus_secs)
+                .to_string()
+        }
+        ClockMode::Delay => {
+            t_string!(i18n, time_control_delay, min = tc.minutes, sec = tc.bonus_secs)
+                .to_string()
+        }
+    }
+}
+
 // ── NicknameModal ─────────────────────────────────────────────────────────────
 
 #[component]
@@ -255,9 +380,15 @@ fn NicknameModal(
         };
         anon_nickname.set(Some(chosen));
         match &pending {
-            PendingLobbyAction::Create { code } => {
+            PendingLobbyAction::Create {
+                code,
+                rule_variation,
+            } => {
                 cmd_tx
-                    .unbounded_send(NetCommand::CreateRoom { room: code.clone() })
+                    .unbounded_send(NetCommand::CreateRoom {
+                        room: code.clone(),
+                        rule_variation: *rule_variation,
+                    })
                     .ok();
                 view_state.set(LobbyView::Waiting { code: code.clone() });
             }
@@ -1,8 +1,16 @@
 pub mod account;
+pub mod analysis;
+pub mod blunders;
 pub mod content_page;
 pub mod forgot_password;
+pub mod friends;
 pub mod game_detail;
 pub mod lobby;
 pub mod profile;
+pub mod puzzles;
+pub mod replay;
 pub mod reset_password;
+pub mod tournament_detail;
+pub mod tournaments;
+pub mod tutorial;
 pub mod verify_email;
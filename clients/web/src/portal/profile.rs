@@ -4,6 +4,7 @@ use leptos_router::{components::A, hooks::use_navigate, hooks::use_params_map};
 use crate::api::{self, GameSummary, UserProfile};
 use crate::app::{AuthEmailVerified, FlashMessage};
 use crate::i18n::*;
+use crate::portal::account::OidcError;
 
 #[component]
 pub fn ProfilePage() -> impl IntoView {
@@ -91,13 +92,41 @@ fn ProfileContent(profile: UserProfile, username: String) -> impl IntoView {
 
         {move || if is_own_profile() {
             let uname = profile.username.clone();
-            view! { <DeleteAccountSection username=uname /> }.into_any()
+            view! {
+                <LinkedAccountSection />
+                <DeleteAccountSection username=uname />
+            }.into_any()
         } else {
             view! { <span /> }.into_any()
         }}
     }
 }
 
+/// Lets a user also sign in through the OpenID Connect provider, if one is
+/// configured.
+#[component]
+fn LinkedAccountSection() -> impl IntoView {
+    let i18n = use_i18n();
+    let provider = LocalResource::new(api::get_oidc_provider);
+
+    move || {
+        provider.get().map(|sw| sw.take()).and_then(Result::ok).map(|p| {
+            view! {
+                <div class="portal-card">
+                    <h2>{t!(i18n, oidc_link_title)}</h2>
+                    <p class="portal-meta" style="margin-bottom:1rem">
+                        { t_string!(i18n, oidc_link_desc, name = p.name.clone()).to_string() }
+                    </p>
+                    <a class="portal-oidc-btn" href=api::oidc_login_url()>
+                        { t_string!(i18n, oidc_link_btn, name = p.name).to_string() }
+                    </a>
+                    <OidcError />
+                </div>
+            }
+        })
+    }
+}
+
 #[component]
 fn DeleteAccountSection(username: String) -> impl IntoView {
     let i18n = use_i18n();
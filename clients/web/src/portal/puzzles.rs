@@ -0,0 +1,156 @@
+use leptos::prelude::*;
+use leptos_router::{components::A, hooks::use_params_map};
+
+use crate::api::{self, PuzzleSummary};
+use crate::app::GAME_ID;
+use crate::game::components::PuzzleBoard;
+use trictrac_game::puzzle::Puzzle;
+use trictrac_game::tutorial::Verdict;
+use crate::i18n::*;
+
+/// `/puzzles`: the day's puzzle and the list of all of them.
+#[component]
+pub fn PuzzlesPage() -> impl IntoView {
+    let i18n = use_i18n();
+    let daily = LocalResource::new(|| api::get_daily_puzzle(GAME_ID));
+    let puzzles = LocalResource::new(|| api::get_puzzles(GAME_ID));
+
+    view! {
+        <div class="portal-main">
+            <div class="portal-card">
+                <h1>{t!(i18n, puzzles_daily)}</h1>
+                {move || match daily.get().map(|sw| sw.take()) {
+                    None => view! { <p class="portal-loading">{t!(i18n, loading)}</p> }.into_any(),
+                    Some(Err(e)) => view! { <p class="portal-error">{ e }</p> }.into_any(),
+                    Some(Ok(None)) => view! { <p class="portal-empty">{t!(i18n, puzzles_empty)}</p> }.into_any(),
+                    Some(Ok(Some(p))) => view! { <PuzzleSolver summary=p /> }.into_any(),
+                }}
+            </div>
+            <div class="portal-card">
+                <h2>{t!(i18n, puzzles_title)}</h2>
+                {move || match puzzles.get().map(|sw| sw.take()) {
+                    None => view! { <p class="portal-loading">{t!(i18n, loading)}</p> }.into_any(),
+                    Some(Err(e)) => view! { <p class="portal-error">{ e }</p> }.into_any(),
+                    Some(Ok(r)) if r.puzzles.is_empty() => {
+                        view! { <p class="portal-empty">{t!(i18n, puzzles_empty)}</p> }.into_any()
+                    }
+                    Some(Ok(r)) => view! { <PuzzlesTable puzzles=r.puzzles /> }.into_any(),
+                }}
+            </div>
+        </div>
+    }
+}
+
+#[component]
+fn PuzzlesTable(puzzles: Vec<PuzzleSummary>) -> impl IntoView {
+    let i18n = use_i18n();
+
+    view! {
+        <table>
+            <thead>
+                <tr>
+                    <th>{t!(i18n, col_puzzle)}</th>
+                    <th>{t!(i18n, col_attempts)}</th>
+                    <th>{t!(i18n, col_status)}</th>
+                </tr>
+            </thead>
+            <tbody>
+                {puzzles.into_iter().map(|p| {
+                    let solved = p.solved;
+                    view! {
+                        <tr>
+                            <td><A href=format!("/puzzles/{}", p.id)>{ p.title.clone() }</A></td>
+                            <td>{ p.attempts }</td>
+                            <td class:puzzle-solved=solved>
+                                {move || if solved {
+                                    t_string!(i18n, puzzle_status_solved)
+                                } else {
+                                    t_string!(i18n, puzzle_status_unsolved)
+                                }}
+                            </td>
+                        </tr>
+                    }
+                }).collect_view()}
+            </tbody>
+        </table>
+    }
+}
+
+/// `/puzzles/:id`: a single puzzle.
+#[component]
+pub fn PuzzlePage() -> impl IntoView {
+    let i18n = use_i18n();
+    let params = use_params_map();
+    let id = params.read_untracked().get("id").unwrap_or_default();
+    let puzzle = LocalResource::new(move || {
+        let id = id.clone();
+        async move {
+            let id: i64 = id.parse().map_err(|_| "invalid puzzle id".to_string())?;
+            api::get_puzzle(id).await
+        }
+    });
+
+    view! {
+        <div class="portal-main">
+            <p class="portal-meta"><A href="/puzzles">{t!(i18n, puzzles_back)}</A></p>
+            <div class="portal-card">
+                {move || match puzzle.get().map(|sw| sw.take()) {
+                    None => view! { <p class="portal-loading">{t!(i18n, loading)}</p> }.into_any(),
+                    Some(Err(e)) => view! { <p class="portal-error">{ e }</p> }.into_any(),
+                    Some(Ok(p)) => view! { <PuzzleSolver summary=p /> }.into_any(),
+                }}
+            </div>
+        </div>
+    }
+}
+
+/// Title, board and progress of a puzzle. Answers of a signed-in user are
+/// recorded as attempts.
+#[component]
+fn PuzzleSolver(summary: PuzzleSummary) -> impl IntoView {
+    let i18n = use_i18n();
+    let auth_username =
+        use_context::<RwSignal<Option<String>>>().expect("auth_username context not found");
+    let puzzle = match Puzzle::from_json(&summary.puzzle.to_string()) {
+        Ok(puzzle) => puzzle,
+        Err(e) => return view! { <p class="portal-error">{ e }</p> }.into_any(),
+    };
+    let id = summary.id;
+    let verdict = RwSignal::new(None::<Verdict>);
+    let attempts = RwSignal::new(summary.attempts);
+    let solved = RwSignal::new(summary.solved);
+    let error = RwSignal::new(String::new());
+
+    Effect::new(move |_| {
+        let Some(v) = verdict.get() else { return };
+        if auth_username.get_untracked().is_none() {
+            return;
+        }
+        let correct = v == Verdict::Correct;
+        attempts.update(|n| *n += 1);
+        if correct {
+            solved.set(true);
+        }
+        wasm_bindgen_futures::spawn_local(async move {
+            if let Err(e) = api::post_puzzle_attempt(id, correct).await {
+                error.set(e);
+            }
+        });
+    });
+
+    view! {
+        <h2>{ summary.title }</h2>
+        <p class="portal-meta">
+            {move || if auth_username.get().is_none() {
+                t_string!(i18n, puzzle_sign_in).to_owned()
+            } else if solved.get() {
+                t_string!(i18n, puzzle_progress_solved, attempts = attempts.get()).to_owned()
+            } else {
+                t_string!(i18n, puzzle_progress, attempts = attempts.get()).to_owned()
+            }}
+        </p>
+        {move || (!error.get().is_empty()).then(|| view! { <p class="portal-error">{ error.get() }</p> })}
+        <PuzzleBoard puzzle=puzzle verdict=verdict />
+    }
+    .into_any()
+}
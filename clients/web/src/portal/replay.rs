@@ -0,0 +1,91 @@
+use leptos::prelude::*;
+use leptos_router::{components::A, hooks::use_params_map};
+
+use crate::api;
+use crate::game::components::ReplayViewer;
+use trictrac_game::replay::GameRecord;
+use crate::i18n::*;
+
+/// Replay of a game: the recorded game `/games/:id/replay`, or on `/replay`
+/// a game record pasted by the player.
+#[component]
+pub fn ReplayPage() -> impl IntoView {
+    let params = use_params_map();
+    let game_id = move || params.read().get("id");
+
+    view! {
+        <div class="portal-main replay-page">
+            {move || match game_id() {
+                Some(id) => view! { <RecordedGameReplay id=id /> }.into_any(),
+                None => view! { <PastedRecordReplay /> }.into_any(),
+            }}
+        </div>
+    }
+}
+
+#[component]
+fn RecordedGameReplay(id: String) -> impl IntoView {
+    let i18n = use_i18n();
+    let file_name = format!("trictrac-game-{id}.json");
+    let back = format!("/games/{id}");
+    let record = LocalResource::new(move || {
+        let id = id.clone();
+        async move {
+            let id: i64 = id.parse().map_err(|_| "invalid game id".to_string())?;
+            GameRecord::from_json(&api::get_game_record(id).await?)
+        }
+    });
+
+    view! {
+        <p class="portal-meta"><A href=back>{t!(i18n, replay_back_to_game)}</A></p>
+        {move || match record.get().map(|sw| sw.take()) {
+            None => view! { <p class="portal-loading">{t!(i18n, loading)}</p> }.into_any(),
+            Some(Err(e)) => view! { <p class="portal-error">{ e }</p> }.into_any(),
+            Some(Ok(r)) => view! { <ReplayViewer record=r file_name=file_name.clone() /> }.into_any(),
+        }}
+    }
+}
+
+#[component]
+fn PastedRecordReplay() -> impl IntoView {
+    let i18n = use_i18n();
+    let text = RwSignal::new(String::new());
+    let error = RwSignal::new(None::<String>);
+    let record = RwSignal::new(None::<GameRecord>);
+
+    let load = move |_| match GameRecord::from_json(&text.get_untracked()) {
+        Ok(r) => record.set(Some(r)),
+        Err(e) => error.set(Some(e)),
+    };
+
+    view! {
+        {move || match record.get() {
+            Some(r) => view! {
+                <p class="portal-meta">
+                    <a href="#" on:click=move |e| { e.prevent_default(); record.set(None); }>
+                        {t!(i18n, replay_load_another)}
+                    </a>
+                </p>
+                <ReplayViewer record=r />
+            }.into_any(),
+            None => view! {
+                <div class="portal-card">
+                    <h1>{t!(i18n, replay_title)}</h1>
+                    <p class="portal-meta">{t!(i18n, replay_record_hint)}</p>
+                    <textarea class="replay-record-input"
+                        placeholder="{ \"schema_version\": 3, \"players\": [...], \"events\": [...] }"
+                        prop:value=move || text.get()
+                        on:input=move |e| {
+                            text.set(event_target_value(&e));
+                            error.set(None);
+                        }
+                    />
+                    {move || error.get().map(|e| view! {
+                        <p class="portal-error">{t!(i18n, replay_invalid_record)} " " {e}</p>
+                    })}
+                    <button class="btn btn-primary" on:click=load>{t!(i18n, replay_open)}</button>
+                </div>
+            }.into_any(),
+        }}
+    }
+}
@@ -0,0 +1,371 @@
+use futures::channel::mpsc::UnboundedSender;
+use leptos::prelude::*;
+use leptos_router::{components::A, hooks::use_navigate, hooks::use_params_map};
+
+use crate::api::{self, TournamentDetail, TournamentGame, TournamentStanding};
+use crate::app::NetCommand;
+use crate::i18n::*;
+use crate::portal::tournaments::{format_label, rules_label, status_label};
+
+/// Score as shown in tables: "1½" rather than "1.5".
+fn format_points(points: f32) -> String {
+    let whole = points.trunc() as i64;
+    match (whole, points.fract() > 0.0) {
+        (0, true) => "½".to_string(),
+        (n, true) => format!("{n}½"),
+        (n, false) => n.to_string(),
+    }
+}
+
+fn result_text(game: &TournamentGame) -> &'static str {
+    if game.is_bye {
+        return "+";
+    }
+    match game.outcome.as_deref() {
+        Some("host") => "1 – 0",
+        Some("guest") => "0 – 1",
+        Some("draw") => "½ – ½",
+        _ => "–",
+    }
+}
+
+#[component]
+pub fn TournamentDetailPage() -> impl IntoView {
+    let i18n = use_i18n();
+    let params = use_params_map();
+    let id_str = move || params.read().get("id").unwrap_or_default();
+    // Bumped after every action so the tournament is fetched again.
+    let version = RwSignal::new(0u32);
+
+    let detail = LocalResource::new(move || {
+        let s = id_str();
+        version.track();
+        async move {
+            let id: i64 = s.parse().map_err(|_| "invalid tournament id".to_string())?;
+            api::get_tournament(id).await
+        }
+    });
+
+    view! {
+        <div class="portal-main">
+            {move || match detail.get().map(|sw| sw.take()) {
+                None => view! { <p class="portal-loading">{t!(i18n, loading)}</p> }.into_any(),
+                Some(Err(e)) => view! { <p class="portal-error">{ e }</p> }.into_any(),
+                Some(Ok(t)) => view! { <TournamentView tournament=t version=version /> }.into_any(),
+            }}
+        </div>
+    }
+}
+
+#[component]
+fn TournamentView(tournament: TournamentDetail, version: RwSignal<u32>) -> impl IntoView {
+    let i18n = use_i18n();
+    let auth_username =
+        use_context::<RwSignal<Option<String>>>().expect("auth_username context not found");
+    let summary = tournament.summary.clone();
+    let format = summary.format.clone();
+    let status = summary.status.clone();
+    let rules = summary.rule_variation;
+    let is_knockout = summary.format == "knockout";
+
+    let rounds: Vec<(i32, Vec<TournamentGame>)> = (1..=summary.current_round)
+        .map(|round| {
+            let games = tournament
+                .games
+                .iter()
+                .filter(|g| g.round == round)
+                .cloned()
+                .collect();
+            (round, games)
+        })
+        .collect();
+    let organizer_tools = summary.status == "running"
+        && summary.organizer.is_some()
+        && auth_username.get_untracked() == summary.organizer;
+
+    view! {
+        <div class="portal-card">
+            <h1>{ summary.name.clone() }</h1>
+            <p class="portal-meta">
+                { move || format_label(&format) }
+                " · " { move || status_label(&status) }
+                " · " {t!(i18n, col_round)} " " { summary.current_round } " / " { summary.total_rounds }
+                " · " { move || rules_label(rules) }
+                {summary.organizer.clone().map(|o| view! {
+                    " · " {t!(i18n, tournament_organizer)} " "
+                    <A href=format!("/profile/{o}")>{ o.clone() }</A>
+                })}
+            </p>
+            <TournamentActions tournament=tournament.clone() version=version />
+        </div>
+
+        <div class="portal-card">
+            <h2>{t!(i18n, standings_title)}</h2>
+            {if tournament.standings.is_empty() {
+                view! { <p class="portal-empty">{t!(i18n, no_tournament_players)}</p> }.into_any()
+            } else {
+                view! { <StandingsTable standings=tournament.standings.clone() /> }.into_any()
+            }}
+        </div>
+
+        {(!rounds.is_empty()).then(|| {
+            let id = tournament.summary.id;
+            if is_knockout {
+                view! {
+                    <div class="portal-card">
+                        <h2>{t!(i18n, bracket_title)}</h2>
+                        <div class="tournament-bracket">
+                            {rounds.into_iter().map(|(round, games)| view! {
+                                <div class="bracket-round">
+                                    <h3>{t!(i18n, col_round)} " " { round }</h3>
+                                    {games.into_iter().map(|g| view! {
+                                        <div class="bracket-match">
+                                            <BracketSide game=g.clone() host=true />
+                                            <BracketSide game=g.clone() host=false />
+                                            {(organizer_tools && g.outcome.is_none()).then(|| view! {
+                                                <ResultOverride tournament_id=id game_id=g.id version=version />
+                                            })}
+                                        </div>
+                                    }).collect_view()}
+                                </div>
+                            }).collect_view()}
+                        </div>
+                    </div>
+                }.into_any()
+            } else {
+                rounds.into_iter().rev().map(|(round, games)| view! {
+                    <div class="portal-card">
+                        <h2>{t!(i18n, col_round)} " " { round }</h2>
+                        <RoundTable games=games organizer_tools=organizer_tools tournament_id=id version=version />
+                    </div>
+                }).collect_view().into_any()
+            }
+        })}
+    }
+}
+
+/// Register / withdraw for players, round control for the organizer, and a
+/// button to open or join the player's own game of the current round.
+#[component]
+fn TournamentActions(tournament: TournamentDetail, version: RwSignal<u32>) -> impl IntoView {
+    let i18n = use_i18n();
+    let auth_username =
+        use_context::<RwSignal<Option<String>>>().expect("auth_username context not found");
+    let cmd_tx = use_context::<UnboundedSender<NetCommand>>().expect("NetCommand sender");
+    let navigate = use_navigate();
+    let error = RwSignal::new(String::new());
+    let pending = RwSignal::new(false);
+
+    let id = tournament.summary.id;
+    let status = tournament.summary.status.clone();
+    let organizer = tournament.summary.organizer.clone();
+    let last_round = tournament.summary.current_round >= tournament.summary.total_rounds;
+    let rule_variation = tournament.summary.rule_variation;
+    let current_round = tournament.summary.current_round;
+
+    let run = move |fut: std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), String>>>>| {
+        if pending.get_untracked() { return; }
+        pending.set(true);
+        error.set(String::new());
+        wasm_bindgen_futures::spawn_local(async move {
+            match fut.await {
+                Ok(()) => version.update(|v| *v += 1),
+                Err(e) => error.set(e),
+            }
+            pending.set(false);
+        });
+    };
+
+    move || {
+        let me = auth_username.get();
+        let registered = me
+            .as_ref()
+            .is_some_and(|u| tournament.standings.iter().any(|s| &s.username == u));
+        let is_organizer = me.is_some() && me == organizer;
+
+        let registration = (me.is_some() && status == "registration").then(|| {
+            let label = if registered {
+                t_string!(i18n, tournament_withdraw)
+            } else {
+                t_string!(i18n, tournament_register)
+            };
+            view! {
+                <button class="portal-page-btn" disabled=move || pending.get()
+                    on:click=move |_| run(Box::pin(api::set_tournament_registration(id, !registered)))
+                >{label}</button>
+            }
+        });
+
+        let round_control = (is_organizer && status != "finished").then(|| {
+            let label = if status == "registration" {
+                t_string!(i18n, tournament_start)
+            } else if last_round {
+                t_string!(i18n, tournament_finish)
+            } else {
+                t_string!(i18n, tournament_next_round)
+            };
+            view! {
+                <button class="portal-submit-btn" disabled=move || pending.get()
+                    on:click=move |_| run(Box::pin(api::post_tournament_next_round(id)))
+                >{label}</button>
+            }
+        });
+
+        let my_game = me.as_ref().and_then(|u| {
+            tournament.games.iter().find(|g| {
+                g.round == current_round
+                    && g.outcome.is_none()
+                    && (g.host.as_ref() == Some(u) || g.guest.as_ref() == Some(u))
+            })
+        });
+        let play = my_game.and_then(|g| {
+            let is_host = g.host == me;
+            let room = g.room_code.clone()?;
+            let tx = cmd_tx.clone();
+            let nav = navigate.clone();
+            Some(view! {
+                <button class="portal-submit-btn" on:click=move |_| {
+                    let cmd = if is_host {
+                        NetCommand::CreateRoom { room: room.clone(), rule_variation }
+                    } else {
+                        NetCommand::JoinRoom { room: room.clone() }
+                    };
+                    tx.unbounded_send(cmd).ok();
+                    nav("/", Default::default());
+                }>
+                    {if is_host { t!(i18n, tournament_open_game).into_any() } else { t!(i18n, tournament_join_game).into_any() }}
+                </button>
+            })
+        });
+
+        view! {
+            <div class="tournament-actions">
+                {play}
+                {registration}
+                {round_control}
+            </div>
+            {move || (!error.get().is_empty()).then(|| view! { <p class="portal-error">{ error.get() }</p> })}
+        }
+    }
+}
+
+#[component]
+fn StandingsTable(standings: Vec<TournamentStanding>) -> impl IntoView {
+    let i18n = use_i18n();
+
+    view! {
+        <table>
+            <thead>
+                <tr>
+                    <th>"#"</th>
+                    <th>{t!(i18n, col_player)}</th>
+                    <th>{t!(i18n, col_points)}</th>
+                    <th title="Buchholz">"BH"</th>
+                    <th title="Sonneborn-Berger">"SB"</th>
+                    <th>{t!(i18n, stat_wins)}</th>
+                </tr>
+            </thead>
+            <tbody>
+                {standings.into_iter().map(|s| view! {
+                    <tr>
+                        <td>{ s.rank }</td>
+                        <td><A href=format!("/profile/{}", s.username)>{ s.username.clone() }</A></td>
+                        <td>{ format_points(s.score) }</td>
+                        <td>{ format_points(s.buchholz) }</td>
+                        <td>{ format!("{:.2}", s.sonneborn_berger) }</td>
+                        <td>{ s.wins }</td>
+                    </tr>
+                }).collect_view()}
+            </tbody>
+        </table>
+    }
+}
+
+#[component]
+fn PlayerName(name: Option<String>) -> impl IntoView {
+    let i18n = use_i18n();
+    match name {
+        Some(u) => view! { <A href=format!("/profile/{u}")>{ u.clone() }</A> }.into_any(),
+        None => view! { <span class="tournament-bye">{t!(i18n, tournament_bye)}</span> }.into_any(),
+    }
+}
+
+#[component]
+fn RoundTable(
+    games: Vec<TournamentGame>,
+    organizer_tools: bool,
+    tournament_id: i64,
+    version: RwSignal<u32>,
+) -> impl IntoView {
+    let i18n = use_i18n();
+
+    view! {
+        <table>
+            <thead>
+                <tr>
+                    <th>{t!(i18n, col_board)}</th>
+                    <th>{t!(i18n, col_player)}</th>
+                    <th>{t!(i18n, col_result)}</th>
+                    <th>{t!(i18n, col_player)}</th>
+                    <th>{t!(i18n, col_detail)}</th>
+                </tr>
+            </thead>
+            <tbody>
+                {games.into_iter().map(|g| {
+                    let result = result_text(&g);
+                    view! {
+                        <tr>
+                            <td>{ g.board }</td>
+                            <td><PlayerName name=g.host.clone() /></td>
+                            <td>{ result }</td>
+                            <td><PlayerName name=g.guest.clone() /></td>
+                            <td>
+                                {g.game_record_id.map(|rid| view! {
+                                    <A href=format!("/games/{rid}")>{t!(i18n, view_link)}</A>
+                                })}
+                                {(organizer_tools && g.outcome.is_none()).then(|| view! {
+                                    <ResultOverride tournament_id=tournament_id game_id=g.id version=version />
+                                })}
+                            </td>
+                        </tr>
+                    }
+                }).collect_view()}
+            </tbody>
+        </table>
+    }
+}
+
+/// One player's line in a knockout bracket box.
+#[component]
+fn BracketSide(game: TournamentGame, host: bool) -> impl IntoView {
+    let won = matches!(
+        (game.outcome.as_deref(), host),
+        (Some("host"), true) | (Some("guest"), false)
+    );
+    let name = if host { game.host.clone() } else { game.guest.clone() };
+    view! {
+        <div class="bracket-player" class:bracket-winner=won>
+            <PlayerName name=name />
+        </div>
+    }
+}
+
+/// Organizer buttons to record a result by hand, e.g. after a forfeit.
+#[component]
+fn ResultOverride(tournament_id: i64, game_id: i64, version: RwSignal<u32>) -> impl IntoView {
+    let i18n = use_i18n();
+    let set = move |outcome: &'static str| {
+        wasm_bindgen_futures::spawn_local(async move {
+            if api::post_tournament_result(tournament_id, game_id, outcome).await.is_ok() {
+                version.update(|v| *v += 1);
+            }
+        });
+    };
+    view! {
+        <span class="tournament-override" title=move || t_string!(i18n, tournament_set_result)>
+            <button class="portal-page-btn" on:click=move |_| set("host")>"1–0"</button>
+            <button class="portal-page-btn" on:click=move |_| set("draw")>"½"</button>
+            <button class="portal-page-btn" on:click=move |_| set("guest")>"0–1"</button>
+        </span>
+    }
+}
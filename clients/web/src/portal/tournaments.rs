@@ -0,0 +1,190 @@
+use leptos::prelude::*;
+use leptos_router::{components::A, hooks::use_navigate};
+
+use crate::api::{self, TournamentSummary};
+use crate::app::{AuthEmailVerified, GAME_ID};
+use trictrac_game::types::{is_casual, TimeControl};
+use crate::i18n::*;
+use crate::portal::lobby::time_control_label;
+
+pub(crate) fn format_label(format: &str) -> String {
+    let i18n = use_i18n();
+    match format {
+        "knockout" => t_string!(i18n, tournament_format_knockout).to_string(),
+        _ => t_string!(i18n, tournament_format_swiss).to_string(),
+    }
+}
+
+pub(crate) fn status_label(status: &str) -> String {
+    let i18n = use_i18n();
+    match status {
+        "running" => t_string!(i18n, tournament_status_running).to_string(),
+        "finished" => t_string!(i18n, tournament_status_finished).to_string(),
+        _ => t_string!(i18n, tournament_status_registration).to_string(),
+    }
+}
+
+pub(crate) fn rules_label(rule_variation: u16) -> String {
+    let i18n = use_i18n();
+    let clock = match TimeControl::from_rule_variation(rule_variation) {
+        Some(tc) => time_control_label(tc),
+        None => t_string!(i18n, time_control_none).to_string(),
+    };
+    if is_casual(rule_variation) {
+        t_string!(i18n, rules_casual, clock = clock).to_string()
+    } else {
+        clock
+    }
+}
+
+#[component]
+pub fn TournamentsPage() -> impl IntoView {
+    let i18n = use_i18n();
+    let auth_username =
+        use_context::<RwSignal<Option<String>>>().expect("auth_username context not found");
+    let auth_email_verified = use_context::<AuthEmailVerified>()
+        .expect("auth_email_verified context not found").0;
+
+    let tournaments = LocalResource::new(api::get_tournaments);
+
+    view! {
+        <div class="portal-main">
+            <div class="portal-card">
+                <h1>{t!(i18n, tournaments_title)}</h1>
+                {move || match tournaments.get().map(|sw| sw.take()) {
+                    None => view! { <p class="portal-loading">{t!(i18n, loading)}</p> }.into_any(),
+                    Some(Err(e)) => view! { <p class="portal-error">{ e }</p> }.into_any(),
+                    Some(Ok(r)) => {
+                        if r.tournaments.is_empty() {
+                            view! { <p class="portal-empty">{t!(i18n, no_tournaments)}</p> }.into_any()
+                        } else {
+                            view! { <TournamentsTable tournaments=r.tournaments /> }.into_any()
+                        }
+                    }
+                }}
+            </div>
+
+            {move || (auth_username.get().is_some() && auth_email_verified.get())
+                .then(|| view! { <CreateTournamentForm /> })}
+        </div>
+    }
+}
+
+#[component]
+fn TournamentsTable(tournaments: Vec<TournamentSummary>) -> impl IntoView {
+    let i18n = use_i18n();
+
+    view! {
+        <table>
+            <thead>
+                <tr>
+                    <th>{t!(i18n, col_tournament)}</th>
+                    <th>{t!(i18n, col_format)}</th>
+                    <th>{t!(i18n, col_status)}</th>
+                    <th>{t!(i18n, col_players)}</th>
+                    <th>{t!(i18n, col_round)}</th>
+                </tr>
+            </thead>
+            <tbody>
+                {tournaments.into_iter().map(|t| {
+                    let format = t.format.clone();
+                    let status = t.status.clone();
+                    view! {
+                        <tr>
+                            <td><A href=format!("/tournaments/{}", t.id)>{ t.name.clone() }</A></td>
+                            <td>{ move || format_label(&format) }</td>
+                            <td>{ move || status_label(&status) }</td>
+                            <td>{ t.player_count }</td>
+                            <td>{ t.current_round } " / " { t.total_rounds }</td>
+                        </tr>
+                    }
+                }).collect_view()}
+            </tbody>
+        </table>
+    }
+}
+
+#[component]
+fn CreateTournamentForm() -> impl IntoView {
+    let i18n = use_i18n();
+    let navigate = use_navigate();
+
+    let name = RwSignal::new(String::new());
+    let format = RwSignal::new("swiss".to_string());
+    // 0 lets the server pick enough rounds for the number of players.
+    let rounds = RwSignal::new(0i32);
+    let rule_variation = RwSignal::new(0u16);
+    let error = RwSignal::new(String::new());
+    let pending = RwSignal::new(false);
+
+    let submit = move |ev: leptos::ev::SubmitEvent| {
+        ev.prevent_default();
+        if pending.get() { return; }
+        pending.set(true);
+        error.set(String::new());
+        let n = name.get();
+        let f = format.get();
+        let r = rounds.get();
+        let rv = rule_variation.get();
+        let navigate = navigate.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            match api::post_create_tournament(&n, GAME_ID, &f, rv, r).await {
+                Ok(t) => navigate(&format!("/tournaments/{}", t.id), Default::default()),
+                Err(e) => {
+                    error.set(e);
+                    pending.set(false);
+                }
+            }
+        });
+    };
+
+    let time_control_options = TimeControl::PRESETS
+        .into_iter()
+        .map(|tc| {
+            let value = tc.to_rule_variation().to_string();
+            view! { <option value=value>{move || time_control_label(tc)}</option> }
+        })
+        .collect_view();
+
+    view! {
+        <div class="portal-card">
+            <h2>{t!(i18n, create_tournament_title)}</h2>
+            <form on:submit=submit>
+                <label class="portal-label">{t!(i18n, label_tournament_name)}</label>
+                <input class="portal-input" type="text" required maxlength="60"
+                    prop:value=move || name.get()
+                    on:input=move |ev| name.set(event_target_value(&ev)) />
+
+                <label class="portal-label">{t!(i18n, col_format)}</label>
+                <select class="portal-input"
+                    on:change=move |ev| format.set(event_target_value(&ev))>
+                    <option value="swiss">{t!(i18n, tournament_format_swiss)}</option>
+                    <option value="knockout">{t!(i18n, tournament_format_knockout)}</option>
+                </select>
+
+                {move || (format.get() == "swiss").then(|| view! {
+                    <label class="portal-label">{t!(i18n, label_tournament_rounds)}</label>
+                    <input class="portal-input" type="number" min="0" max="20"
+                        prop:value=move || rounds.get().to_string()
+                        on:input=move |ev| rounds.set(event_target_value(&ev).parse().unwrap_or(0)) />
+                })}
+
+                <label class="portal-label">{t!(i18n, time_control_label)}</label>
+                <select class="portal-input"
+                    on:change=move |ev| rule_variation.set(event_target_value(&ev).parse().unwrap_or(0))>
+                    <option value="0">{t!(i18n, time_control_none)}</option>
+                    {time_control_options}
+                </select>
+
+                <button class="portal-submit-btn" type="submit"
+                    disabled=move || pending.get()
+                >{t!(i18n, create_tournament_btn)}</button>
+                {move || if !error.get().is_empty() {
+                    view! { <p class="portal-error">{ error.get() }</p> }.into_any()
+                } else {
+                    view! { <span /> }.into_any()
+                }}
+            </form>
+        </div>
+    }
+}
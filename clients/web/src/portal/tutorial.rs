@@ -0,0 +1,17 @@
+use leptos::prelude::*;
+
+use crate::game::components::Tutorial;
+use crate::i18n::*;
+
+/// `/tutorial`: lessons on the rules, played on scripted positions.
+#[component]
+pub fn TutorialPage() -> impl IntoView {
+    let i18n = use_i18n();
+    view! {
+        <div class="portal-main tutorial-page">
+            <h1>{t!(i18n, tutorial_title)}</h1>
+            <p class="portal-meta">{t!(i18n, tutorial_hint)}</p>
+            <Tutorial />
+        </div>
+    }
+}
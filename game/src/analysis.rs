@@ -0,0 +1,276 @@
+//! Position analysis for the analysis board.
+//!
+//! The board is set up freely, without a game: checkers are moved between
+//! fields and the reserves of checkers off the board. [`analyse`] then lists
+//! the legal sequences for some dice, the jans the roll scores and the
+//! sequence the local bot would play. [`hint`] asks the same of a position
+//! in a game.
+
+use trictrac_store::{Board, CheckerMove, Color, Dice, MoveRules, PointsRules};
+
+use super::bot_local::score_sequence;
+use super::types::{jan_entries, JanEntry, SerTurnStage, ViewState};
+
+/// Checkers each player owns.
+pub const CHECKERS_PER_PLAYER: u8 = 15;
+
+/// Where a checker sits while setting up a position.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SetupSlot {
+    /// Field 1–24.
+    Field(u8),
+    /// Off the board.
+    Reserve(Color),
+}
+
+/// Checkers of `color` that are off the board.
+pub fn reserve(board: &[i8; 24], color: Color) -> u8 {
+    let on_board: u8 = board
+        .iter()
+        .map(|&n| match color {
+            Color::White => n.max(0) as u8,
+            Color::Black => (-n).max(0) as u8,
+        })
+        .sum();
+    CHECKERS_PER_PLAYER.saturating_sub(on_board)
+}
+
+/// Moves one checker from `from` to `to`. A field holds the checkers of one
+/// player only, and a checker can only go back to its own reserve. Returns
+/// `false`, leaving the board alone, when the move is not possible.
+pub fn move_setup_checker(board: &mut [i8; 24], from: SetupSlot, to: SetupSlot) -> bool {
+    if from == to {
+        return false;
+    }
+    let color = match from {
+        SetupSlot::Field(f) => match board.get(f as usize - 1) {
+            Some(&n) if n > 0 => Color::White,
+            Some(&n) if n < 0 => Color::Black,
+            _ => return false,
+        },
+        SetupSlot::Reserve(color) => {
+            if reserve(board, color) == 0 {
+                return false;
+            }
+            color
+        }
+    };
+    let step: i8 = if color == Color::White { 1 } else { -1 };
+    match to {
+        SetupSlot::Field(t) => match board.get(t as usize - 1) {
+            Some(&n) if n == 0 || n.signum() == step => {}
+            _ => return false,
+        },
+        SetupSlot::Reserve(c) if c == color => {}
+        SetupSlot::Reserve(_) => return false,
+    }
+    if let SetupSlot::Field(f) = from {
+        board[f as usize - 1] -= step;
+    }
+    if let SetupSlot::Field(t) = to {
+        board[t as usize - 1] += step;
+    }
+    true
+}
+
+/// One legal sequence of the analysed roll.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SequenceAnalysis {
+    /// The two moves, in board coordinates.
+    pub moves: (CheckerMove, CheckerMove),
+    /// Jans of the roll this sequence plays, one way each.
+    pub jans: Vec<JanEntry>,
+    /// The bot's evaluation of the resulting position; higher is better for the mover.
+    pub score: f32,
+}
+
+/// What a roll offers in a position.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Analysis {
+    /// Everything the roll scores, as shown after a roll in a game.
+    pub jans: Vec<JanEntry>,
+    pub sequences: Vec<SequenceAnalysis>,
+    /// Index in `sequences` of the one the bot prefers.
+    pub preferred: Option<usize>,
+}
+
+/// Analyses `dice` rolled by `color` in `board`. `third_roll` tells whether
+/// this is the player's third roll, when the jan de six tables can be made.
+pub fn analyse(board: &[i8; 24], dice: (u8, u8), color: Color, third_roll: bool) -> Analysis {
+    let mut store_board = Board::new();
+    store_board.set_positions(&Color::White, *board);
+    let dice = Dice { values: dice };
+
+    // Both rule sets work in the mover's (White) space: mirror back for Black.
+    let to_board = |m: CheckerMove| if color == Color::Black { m.mirror() } else { m };
+    let (mut jans, _) =
+        PointsRules::new(&color, &store_board, dice).get_result_jans(if third_roll { 3 } else { 0 });
+    for moves in jans.values_mut() {
+        for (m1, m2) in moves.iter_mut() {
+            (*m1, *m2) = (to_board(*m1), to_board(*m2));
+        }
+    }
+    let jans = jan_entries(&jans, dice.values);
+
+    let sequences: Vec<SequenceAnalysis> = MoveRules::new(&color, &store_board, dice)
+        .get_possible_moves_sequences(true, vec![])
+        .into_iter()
+        .map(|(m1, m2)| {
+            let moves = (to_board(m1), to_board(m2));
+            let seq_jans = jans
+                .iter()
+                .filter(|e| e.moves.contains(&moves))
+                .map(|e| JanEntry {
+                    ways: 1,
+                    total: e.points_per,
+                    moves: vec![moves],
+                    ..e.clone()
+                })
+                .collect();
+            SequenceAnalysis {
+                moves,
+                jans: seq_jans,
+                score: score_sequence(&store_board, color, moves.0, moves.1),
+            }
+        })
+        .collect();
+
+    let preferred = (0..sequences.len()).max_by(|&a, &b| {
+        sequences[a]
+            .score
+            .partial_cmp(&sequences[b].score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    Analysis {
+        jans,
+        sequences,
+        preferred,
+    }
+}
+
+/// The sequence the bot would play for `player_id` (0 = White) in a game,
+/// or `None` when that player has no move to choose. In the hold-or-go
+/// choice, it is the move to play when holding.
+pub fn hint(vs: &ViewState, player_id: u16) -> Option<(CheckerMove, CheckerMove)> {
+    let choosing = vs.active_mp_player == Some(player_id)
+        && matches!(vs.turn_stage, SerTurnStage::Move | SerTurnStage::HoldOrGoChoice);
+    if !choosing {
+        return None;
+    }
+    let color = if player_id == 0 { Color::White } else { Color::Black };
+    let analysis = analyse(&vs.board, vs.dice, color, false);
+    analysis.preferred.map(|i| analysis.sequences[i].moves)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::bot_local::bot_decide;
+    use super::super::types::PlayerAction;
+    use trictrac_store::{GameEvent, GameState, Jan};
+
+    fn start() -> [i8; 24] {
+        let mut board = [0i8; 24];
+        board[0] = 15;
+        board[23] = -15;
+        board
+    }
+
+    #[test]
+    fn setup_moves_respect_colors_and_reserves() {
+        let mut board = [0i8; 24];
+        assert!(!move_setup_checker(&mut board, SetupSlot::Field(3), SetupSlot::Field(4)));
+        assert!(move_setup_checker(&mut board, SetupSlot::Reserve(Color::White), SetupSlot::Field(3)));
+        assert!(move_setup_checker(&mut board, SetupSlot::Reserve(Color::Black), SetupSlot::Field(5)));
+        // Fields hold one color only, checkers go back to their own reserve.
+        assert!(!move_setup_checker(&mut board, SetupSlot::Field(3), SetupSlot::Field(5)));
+        assert!(!move_setup_checker(&mut board, SetupSlot::Field(3), SetupSlot::Reserve(Color::Black)));
+        assert!(move_setup_checker(&mut board, SetupSlot::Field(3), SetupSlot::Field(4)));
+        assert_eq!(board[2..5], [0, 1, -1]);
+        assert_eq!(reserve(&board, Color::White), 14);
+
+        let mut full = start();
+        assert!(!move_setup_checker(&mut full, SetupSlot::Reserve(Color::White), SetupSlot::Field(2)));
+        assert!(move_setup_checker(&mut full, SetupSlot::Field(24), SetupSlot::Reserve(Color::Black)));
+        assert_eq!(reserve(&full, Color::Black), 1);
+    }
+
+    #[test]
+    fn sequences_are_legal_for_both_colors() {
+        let white = analyse(&start(), (3, 5), Color::White, false);
+        assert!(!white.sequences.is_empty());
+        assert!(white
+            .sequences
+            .iter()
+            .all(|s| s.moves.0.get_from() == 1));
+        assert!(white.preferred.is_some());
+
+        let black = analyse(&start(), (3, 5), Color::Black, false);
+        assert!(black
+            .sequences
+            .iter()
+            .all(|s| s.moves.0.get_from() == 24));
+    }
+
+    #[test]
+    fn sequences_carry_the_jans_they_play() {
+        // Double fives bring two checkers from the talon to fill the first quarter.
+        let mut board = [0i8; 24];
+        board[0] = 5;
+        board[1..5].fill(2);
+        board[9] = 2;
+        board[23] = -15;
+        let white = analyse(&board, (5, 5), Color::White, false);
+        let filling = (CheckerMove::new(1, 6).unwrap(), CheckerMove::new(1, 6).unwrap());
+        let seq = white
+            .sequences
+            .iter()
+            .find(|s| s.moves == filling)
+            .expect("filling the quarter is legal");
+        assert!(seq.jans.iter().any(|j| j.jan == Jan::FilledQuarter && j.total > 0));
+        for s in &white.sequences {
+            for j in &s.jans {
+                let roll_jan = white.jans.iter().find(|e| e.jan == j.jan).unwrap();
+                assert!(roll_jan.moves.contains(&s.moves));
+            }
+        }
+
+        // The same position seen from Black scores the same, with mirrored moves.
+        let mut mirrored = board.map(|n| -n);
+        mirrored.reverse();
+        let black = analyse(&mirrored, (5, 5), Color::Black, false);
+        let totals = |a: &Analysis| {
+            let mut t: Vec<i8> = a.jans.iter().map(|e| e.total).collect();
+            t.sort_unstable();
+            t
+        };
+        assert_eq!(totals(&white), totals(&black));
+        assert_eq!(white.sequences.len(), black.sequences.len());
+        assert!(black.sequences.iter().all(|b| white
+            .sequences
+            .iter()
+            .any(|w| w.moves == (b.moves.0.mirror(), b.moves.1.mirror()))));
+    }
+
+    #[test]
+    fn hint_is_the_bot_move() {
+        let mut game = GameState::new(false);
+        game.init_player("White");
+        game.init_player("Black");
+        game.consume(&GameEvent::BeginGame { goes_first: 2 }).unwrap();
+        game.consume(&GameEvent::Roll { player_id: 2 }).unwrap();
+        game.consume(&GameEvent::RollResult {
+            player_id: 2,
+            dice: Dice { values: (6, 4) },
+        })
+        .unwrap();
+        let vs = ViewState::from_game_state(&game, 1, 2);
+
+        assert_eq!(hint(&vs, 0), None);
+        let Some(PlayerAction::Move(m1, m2)) = bot_decide(&game, None) else {
+            panic!("the bot moves");
+        };
+        assert_eq!(hint(&vs, 1), Some((m1, m2)));
+    }
+}
@@ -0,0 +1,319 @@
+//! What happened between two displayed positions, for screen readers.
+//!
+//! [`announcements`] compares the previous and the next [`ViewState`] and
+//! lists, in the order they happened, the dice rolled, the jans scored, the
+//! moves played and the choices to go. The game screen reads them out in an
+//! ARIA live region; [`occupied_fields`] backs its textual board description.
+
+use trictrac_store::{GameEvent, Jan};
+
+use super::types::{SerStage, ViewState};
+
+// Store PlayerId values of host and guest, as in the backend.
+const HOST_PLAYER_ID: u64 = 1;
+
+/// One thing to read out. `player` is the multiplayer id (0 = host, White).
+#[derive(Clone, Debug, PartialEq)]
+pub enum Announcement {
+    /// A die of the roll deciding who plays first.
+    PreGameDie { player: u16, die: u8 },
+    /// The player who plays first.
+    Began { player: u16 },
+    Rolled { player: u16, dice: (u8, u8) },
+    /// Points and holes won, with the jans that scored them (possibly none,
+    /// when the points came from the opponent's false hits).
+    Scored {
+        player: u16,
+        points: u8,
+        holes: u8,
+        jans: Vec<Jan>,
+    },
+    Went { player: u16 },
+    /// The checkers moved, as (from, to) fields in White's numbering, 0 being
+    /// off the board. Empty when the player could not move.
+    Moved { player: u16, moves: Vec<(u8, u8)> },
+}
+
+fn mp_player(store_id: u64) -> u16 {
+    if store_id == HOST_PLAYER_ID {
+        0
+    } else {
+        1
+    }
+}
+
+/// The announcements for the step from `prev` to `next`, oldest first.
+pub fn announcements(prev: &ViewState, next: &ViewState) -> Vec<Announcement> {
+    let mut out = Vec::new();
+
+    if next.stage == SerStage::PreGameRoll {
+        if let Some(next_pgr) = &next.pre_game_roll {
+            let prev_pgr = prev.pre_game_roll.as_ref();
+            let dice = [
+                (0, prev_pgr.and_then(|p| p.host_die), next_pgr.host_die),
+                (1, prev_pgr.and_then(|p| p.guest_die), next_pgr.guest_die),
+            ];
+            for (player, before, now) in dice {
+                if let (None, Some(die)) = (before, now) {
+                    out.push(Announcement::PreGameDie { player, die });
+                }
+            }
+        }
+    }
+
+    // The history is replaced, not extended, by a new game.
+    let Some(events) = next.history.strip_prefix(prev.history.as_slice()) else {
+        return out;
+    };
+    let scored = scored(prev, next);
+    let mut scored_told = false;
+    for event in events {
+        match event {
+            GameEvent::BeginGame { goes_first } => out.push(Announcement::Began {
+                player: mp_player(*goes_first),
+            }),
+            GameEvent::RollResult { player_id, dice } => {
+                out.push(Announcement::Rolled {
+                    player: mp_player(*player_id),
+                    dice: dice.values,
+                });
+            }
+            GameEvent::Mark { .. } if !scored_told => {
+                out.extend(scored.iter().cloned());
+                scored_told = true;
+            }
+            GameEvent::Go { player_id } => out.push(Announcement::Went {
+                player: mp_player(*player_id),
+            }),
+            GameEvent::Move { player_id, moves } => {
+                let moves = [moves.0, moves.1]
+                    .iter()
+                    .filter(|m| m.get_from() != 0)
+                    .map(|m| (m.get_from() as u8, m.get_to() as u8))
+                    .collect();
+                out.push(Announcement::Moved {
+                    player: mp_player(*player_id),
+                    moves,
+                });
+            }
+            _ => {}
+        }
+    }
+    if !scored_told {
+        out.extend(scored);
+    }
+    out
+}
+
+/// The points and holes each player won from `prev` to `next`.
+fn scored(prev: &ViewState, next: &ViewState) -> Vec<Announcement> {
+    (0..2u16)
+        .filter_map(|player| {
+            let (before, after) = (&prev.scores[player as usize], &next.scores[player as usize]);
+            let holes = after.holes.saturating_sub(before.holes);
+            if holes == 0 && after.points == before.points {
+                return None;
+            }
+            // Points go back to 0 past 12, with each hole won.
+            let points = if holes > 0 {
+                after.points + 12 - before.points
+            } else {
+                after.points.checked_sub(before.points)?
+            };
+            // The roll's jans count for the active player when positive, for
+            // the opponent when negative (false hits).
+            let mine = next.active_mp_player == Some(player);
+            let jans = next
+                .dice_jans
+                .iter()
+                .filter(|e| if mine { e.total > 0 } else { e.total < 0 })
+                .map(|e| e.jan.clone())
+                .collect();
+            Some(Announcement::Scored {
+                player,
+                points,
+                holes,
+                jans,
+            })
+        })
+        .collect()
+}
+
+/// The fields holding checkers, as (field, count), White's then Black's, in
+/// each player's direction of play.
+pub fn occupied_fields(board: &[i8; 24]) -> [Vec<(u8, u8)>; 2] {
+    let white = (1..=24u8)
+        .filter(|&f| board[f as usize - 1] > 0)
+        .map(|f| (f, board[f as usize - 1] as u8))
+        .collect();
+    let black = (1..=24u8)
+        .rev()
+        .filter(|&f| board[f as usize - 1] < 0)
+        .map(|f| (f, board[f as usize - 1].unsigned_abs()))
+        .collect();
+    [white, black]
+}
+
+#[cfg(test)]
+mod tests {
+    use trictrac_store::{CheckerMove, Dice};
+
+    use super::super::types::{JanEntry, PreGameRollState, SerTurnStage};
+    use super::*;
+
+    const GUEST_PLAYER_ID: u64 = 2;
+
+    fn in_game() -> ViewState {
+        let mut vs = ViewState::default_with_names("Alice", "Bob");
+        vs.stage = SerStage::InGame;
+        vs.turn_stage = SerTurnStage::RollDice;
+        vs.active_mp_player = Some(0);
+        vs
+    }
+
+    fn cm(from: usize, to: usize) -> CheckerMove {
+        CheckerMove::new(from, to).unwrap()
+    }
+
+    #[test]
+    fn nothing_new_nothing_said() {
+        let vs = in_game();
+        assert!(announcements(&vs, &vs).is_empty());
+    }
+
+    #[test]
+    fn pre_game_dice_are_read_once() {
+        let mut prev = ViewState::default_with_names("Alice", "Bob");
+        prev.stage = SerStage::PreGameRoll;
+        prev.pre_game_roll = Some(PreGameRollState {
+            host_die: Some(4),
+            guest_die: None,
+            tie_count: 0,
+        });
+        let mut next = prev.clone();
+        next.pre_game_roll.as_mut().unwrap().guest_die = Some(2);
+        assert_eq!(
+            announcements(&prev, &next),
+            vec![Announcement::PreGameDie { player: 1, die: 2 }]
+        );
+    }
+
+    #[test]
+    fn roll_jans_and_move_in_order() {
+        let prev = in_game();
+        let mut next = prev.clone();
+        next.history = vec![
+            GameEvent::Roll { player_id: HOST_PLAYER_ID },
+            GameEvent::RollResult {
+                player_id: HOST_PLAYER_ID,
+                dice: Dice { values: (6, 6) },
+            },
+            GameEvent::Mark {
+                player_id: HOST_PLAYER_ID,
+                points: 4,
+            },
+            GameEvent::Move {
+                player_id: HOST_PLAYER_ID,
+                moves: (cm(1, 7), cm(0, 0)),
+            },
+        ];
+        next.scores[0].points = 4;
+        next.dice_jans = vec![JanEntry {
+            jan: Jan::TwoTables,
+            is_double: true,
+            ways: 1,
+            points_per: 4,
+            total: 4,
+            moves: vec![],
+        }];
+        assert_eq!(
+            announcements(&prev, &next),
+            vec![
+                Announcement::Rolled {
+                    player: 0,
+                    dice: (6, 6)
+                },
+                Announcement::Scored {
+                    player: 0,
+                    points: 4,
+                    holes: 0,
+                    jans: vec![Jan::TwoTables],
+                },
+                Announcement::Moved {
+                    player: 0,
+                    moves: vec![(1, 7)]
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn false_hits_score_for_the_opponent_round_a_hole() {
+        let mut prev = in_game();
+        prev.scores[1].points = 10;
+        let mut next = prev.clone();
+        next.history = vec![GameEvent::Mark {
+            player_id: GUEST_PLAYER_ID,
+            points: 4,
+        }];
+        next.scores[1].points = 2;
+        next.scores[1].holes = 1;
+        next.dice_jans = vec![JanEntry {
+            jan: Jan::FalseHitBigJan,
+            is_double: false,
+            ways: 1,
+            points_per: -4,
+            total: -4,
+            moves: vec![],
+        }];
+        assert_eq!(
+            announcements(&prev, &next),
+            vec![Announcement::Scored {
+                player: 1,
+                points: 4,
+                holes: 1,
+                jans: vec![Jan::FalseHitBigJan],
+            }]
+        );
+    }
+
+    #[test]
+    fn a_new_game_is_not_replayed() {
+        let mut prev = in_game();
+        prev.history = vec![GameEvent::Go { player_id: GUEST_PLAYER_ID }];
+        let mut next = in_game();
+        next.history = vec![GameEvent::BeginGame { goes_first: GUEST_PLAYER_ID }];
+        assert!(announcements(&prev, &next).is_empty());
+
+        let next = ViewState {
+            history: vec![
+                GameEvent::Go { player_id: GUEST_PLAYER_ID },
+                GameEvent::Move {
+                    player_id: GUEST_PLAYER_ID,
+                    moves: (cm(0, 0), cm(0, 0)),
+                },
+            ],
+            ..prev.clone()
+        };
+        assert_eq!(
+            announcements(&prev, &next),
+            vec![Announcement::Moved {
+                player: 1,
+                moves: vec![]
+            }]
+        );
+    }
+
+    #[test]
+    fn fields_in_each_direction_of_play() {
+        let mut board = [0i8; 24];
+        board[0] = 13;
+        board[5] = 2;
+        board[23] = -14;
+        board[11] = -1;
+        assert_eq!(
+            occupied_fields(&board),
+            [vec![(1, 13), (6, 2)], vec![(24, 14), (12, 1)]]
+        );
+    }
+}
@@ -0,0 +1,1240 @@
+use backbone_lib::traits::{BackEndArchitecture, BackendCommand};
+use serde::{Deserialize, Serialize};
+use trictrac_store::{
+    Color, Dice, DiceRoller, EndGameReason, GameEvent, GameState, Player, Stage, TurnStage,
+};
+use web_time::Instant;
+
+use super::clock::GameClock;
+use super::types::{
+    is_casual, ChatBody, ChatEntry, GameDelta, HintUse, PlayerAction, PreGameRollState,
+    QuickPhrase, SerStage, SerTurnStage, TimeControl, ViewState, CHAT_HISTORY_LEN,
+    MAX_CHAT_CHARS,
+};
+
+// Store PlayerId (u64) values used for the two players.
+const HOST_PLAYER_ID: u64 = 1;
+const GUEST_PLAYER_ID: u64 = 2;
+
+// Timers 0 and 1 are the reconnect grace periods of host and guest.
+const CLOCK_TIMER_ID: u16 = 2;
+
+/// Everything [`TrictracBackend::to_bytes`] saves for the host to resume the
+/// game after a page refresh. Connections are not saved: players and
+/// spectators arrive again.
+#[derive(Serialize, Deserialize)]
+struct HostSnapshot {
+    game: GameState,
+    pre_game_dice: [Option<u8>; 2],
+    tie_count: u8,
+    ceremony_started: bool,
+    chat: Vec<ChatEntry>,
+    clock: Option<GameClock>,
+    hints: HintUse,
+    /// Sequence number of the last delta, for the deltas to go on from it.
+    seq: u32,
+}
+
+pub struct TrictracBackend {
+    game: GameState,
+    dice_roller: DiceRoller,
+    commands: Vec<BackendCommand<GameDelta>>,
+    view_state: ViewState,
+    /// Arrival flags: have host (index 0) and guest (index 1) joined?
+    arrived: [bool; 2],
+    /// Die rolled by each player during the ceremony ([host, guest]).
+    pre_game_dice: [Option<u8>; 2],
+    /// Number of tied rounds so far.
+    tie_count: u8,
+    /// True while the first-player ceremony is running.
+    ceremony_started: bool,
+    /// Number of spectators currently connected.
+    spectators: u16,
+    /// Chat history, oldest first.
+    chat: Vec<ChatEntry>,
+    /// Game clocks; `None` for untimed games.
+    clock: Option<GameClock>,
+    hints: HintUse,
+}
+
+impl TrictracBackend {
+    fn sync_view_state(&mut self) {
+        self.update_clock(Instant::now());
+        let mut vs = ViewState::from_game_state(&self.game, HOST_PLAYER_ID, GUEST_PLAYER_ID);
+        vs.seq = self.view_state.seq;
+        vs.spectators = self.spectators;
+        vs.chat = self.chat.clone();
+        vs.clock = self.clock.as_ref().map(GameClock::view);
+        vs.hints = self.hints.clone();
+        if self.ceremony_started {
+            vs.stage = SerStage::PreGameRoll;
+            vs.pre_game_roll = Some(PreGameRollState {
+                host_die: self.pre_game_dice[0],
+                guest_die: self.pre_game_dice[1],
+                tie_count: self.tie_count,
+            });
+            // Both players roll independently; no single "active" player.
+            vs.active_mp_player = None;
+        }
+        self.view_state = vs;
+    }
+
+    /// Sends what changed since the last broadcast, if anything did.
+    fn broadcast_state(&mut self) {
+        let previous = self.view_state.clone();
+        self.sync_view_state();
+        let changes = previous.diff(&self.view_state);
+        if changes.is_empty() {
+            return;
+        }
+        self.view_state.seq = previous.seq.wrapping_add(1);
+        let delta = GameDelta {
+            seq: self.view_state.seq,
+            changes,
+            checksum: self.view_state.checksum(),
+        };
+        self.commands.push(BackendCommand::Delta(delta));
+    }
+
+    /// Run the active player's clock while the game is on; pause it during
+    /// the ceremony, after the game and while a player is disconnected.
+    fn update_clock(&mut self, now: Instant) {
+        let Some(clock) = self.clock.as_mut() else {
+            return;
+        };
+        let playing = self.game.stage == Stage::InGame
+            && !self.ceremony_started
+            && self.arrived == [true, true];
+        if playing {
+            let active = if self.game.active_player_id == HOST_PLAYER_ID { 0 } else { 1 };
+            clock.run(active, now);
+        } else {
+            clock.pause(now);
+        }
+        let command = match clock.time_to_flag(now) {
+            Some(left) => BackendCommand::SetTimer {
+                timer_id: CLOCK_TIMER_ID,
+                duration: left.as_secs_f32(),
+            },
+            None => BackendCommand::CancelTimer {
+                timer_id: CLOCK_TIMER_ID,
+            },
+        };
+        self.commands.push(command);
+    }
+
+    /// End the game if the running clock is out of time, otherwise re-arm the
+    /// flag timer (it may fire slightly early).
+    fn check_flag(&mut self, now: Instant) {
+        let Some(clock) = self.clock.as_mut() else {
+            return;
+        };
+        match clock.check_flag(now) {
+            Some(mp_player) => {
+                let player_id = if mp_player == 0 { HOST_PLAYER_ID } else { GUEST_PLAYER_ID };
+                let _ = self.game.consume(&GameEvent::EndGame {
+                    reason: EndGameReason::Timeout { player_id },
+                });
+                self.broadcast_state();
+            }
+            None => {
+                if let Some(left) = clock.time_to_flag(now) {
+                    self.commands.push(BackendCommand::SetTimer {
+                        timer_id: CLOCK_TIMER_ID,
+                        duration: left.as_secs_f32(),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Process one ceremony die-roll for `mp_player` (0 = host, 1 = guest).
+    fn handle_pre_game_roll(&mut self, mp_player: u16) {
+        let idx = mp_player as usize;
+        // Ignore if this player already rolled.
+        if self.pre_game_dice[idx].is_some() {
+            return;
+        }
+        let single = self.dice_roller.roll().values.0;
+        self.pre_game_dice[idx] = Some(single);
+
+        if let [Some(h), Some(g)] = self.pre_game_dice {
+            // Both have rolled — broadcast both dice before resolving.
+            self.broadcast_state();
+            if h == g {
+                // Tie: reset for another round.
+                self.tie_count += 1;
+                self.pre_game_dice = [None; 2];
+                self.broadcast_state();
+            } else {
+                // Highest die goes first.
+                let goes_first = if h > g {
+                    HOST_PLAYER_ID
+                } else {
+                    GUEST_PLAYER_ID
+                };
+                self.ceremony_started = false;
+                let _ = self.game.consume(&GameEvent::BeginGame { goes_first });
+                // Use pre-game dice roll for the first move
+                let _ = self.game.consume(&GameEvent::Roll {
+                    player_id: goes_first,
+                });
+                let _ = self.game.consume(&GameEvent::RollResult {
+                    player_id: goes_first,
+                    dice: Dice { values: (g, h) },
+                });
+                self.broadcast_state();
+            }
+        } else {
+            // Only one die rolled so far — broadcast the partial result.
+            self.broadcast_state();
+        }
+    }
+
+    /// Roll dice using the store's DiceRoller and fire Roll + RollResult events.
+    fn do_roll(&mut self) {
+        let dice = self.dice_roller.roll();
+        let player_id = self.game.active_player_id;
+        let _ = self.game.consume(&GameEvent::Roll { player_id });
+        let _ = self
+            .game
+            .consume(&GameEvent::RollResult { player_id, dice });
+
+        // Drive automatic stages that require no player input.
+        self.drive_automatic_stages();
+    }
+
+    /// Advance through stages that can be resolved without player input
+    /// (MarkPoints, MarkAdvPoints).
+    fn drive_automatic_stages(&mut self) {
+        loop {
+            // Stop if the game has already ended (stage transitions to Ended but
+            // turn_stage may still be MarkPoints when schools_enabled=false, which
+            // makes consume(Mark) a no-op and would cause an infinite loop).
+            if self.game.stage == trictrac_store::Stage::Ended {
+                break;
+            }
+            let player_id = self.game.active_player_id;
+            match self.game.turn_stage {
+                TurnStage::MarkPoints | TurnStage::MarkAdvPoints => {
+                    let _ = self.game.consume(&GameEvent::Mark {
+                        player_id,
+                        points: self.game.dice_points.0.max(self.game.dice_points.1),
+                    });
+                }
+                _ => break,
+            }
+        }
+    }
+}
+
+impl TrictracBackend {
+    pub fn get_game(&self) -> &GameState {
+        &self.game
+    }
+
+    /// Build a backend pre-loaded with the given `ViewState` snapshot so a bot
+    /// game can resume from an arbitrary position (debug feature).
+    pub fn from_view_state(vs: ViewState, player_name: &str) -> Self {
+        let mut game = GameState::new(false);
+
+        game.board.set_positions(&Color::White, vs.board);
+
+        game.stage = match vs.stage {
+            SerStage::InGame => Stage::InGame,
+            SerStage::Ended => Stage::Ended,
+            _ => Stage::InGame,
+        };
+
+        game.turn_stage = match vs.turn_stage {
+            SerTurnStage::RollDice => TurnStage::RollDice,
+            SerTurnStage::RollWaiting => TurnStage::RollWaiting,
+            SerTurnStage::MarkPoints => TurnStage::MarkPoints,
+            SerTurnStage::HoldOrGoChoice => TurnStage::HoldOrGoChoice,
+            SerTurnStage::Move => TurnStage::Move,
+            SerTurnStage::MarkAdvPoints => TurnStage::MarkAdvPoints,
+        };
+
+        game.dice = Dice { values: vs.dice };
+
+        game.active_player_id = match vs.active_mp_player {
+            Some(0) => HOST_PLAYER_ID,
+            Some(1) => GUEST_PLAYER_ID,
+            _ => HOST_PLAYER_ID,
+        };
+
+        let build_player = |score: &super::types::PlayerScore,
+                             color: Color|
+         -> Player {
+            let mut p = Player::new(score.name.clone(), color);
+            p.points = score.points;
+            p.holes = score.holes;
+            p.can_bredouille = score.can_bredouille;
+            p
+        };
+
+        game.players.insert(HOST_PLAYER_ID, build_player(&vs.scores[0], Color::White));
+        game.players.insert(GUEST_PLAYER_ID, build_player(&vs.scores[1], Color::Black));
+
+        // Games against the bot are never rated.
+        let hints = HintUse {
+            allowed: true,
+            ..vs.hints
+        };
+        let mut view_state = ViewState::from_game_state(&game, HOST_PLAYER_ID, GUEST_PLAYER_ID);
+        view_state.scores[0].name = player_name.to_string();
+        view_state.scores[1].name = "Bot".to_string();
+        view_state.hints = hints.clone();
+
+        TrictracBackend {
+            game,
+            dice_roller: DiceRoller::default(),
+            commands: Vec::new(),
+            view_state,
+            arrived: [true, true],
+            pre_game_dice: [None; 2],
+            tie_count: 0,
+            ceremony_started: false,
+            spectators: 0,
+            chat: Vec::new(),
+            clock: None,
+            hints,
+        }
+    }
+}
+
+impl BackEndArchitecture<PlayerAction, GameDelta, ViewState> for TrictracBackend {
+    fn new(rule_variation: u16) -> Self {
+        let mut game = GameState::new(false);
+        game.init_player("Blancs");
+        game.init_player("Noirs");
+
+        let hints = HintUse {
+            allowed: is_casual(rule_variation),
+            used: [0; 2],
+        };
+        let mut view_state = ViewState::from_game_state(&game, HOST_PLAYER_ID, GUEST_PLAYER_ID);
+        view_state.hints = hints.clone();
+
+        TrictracBackend {
+            game,
+            dice_roller: DiceRoller::default(),
+            commands: Vec::new(),
+            view_state,
+            arrived: [false; 2],
+            pre_game_dice: [None; 2],
+            tie_count: 0,
+            ceremony_started: false,
+            spectators: 0,
+            chat: Vec::new(),
+            clock: TimeControl::from_rule_variation(rule_variation).map(GameClock::new),
+            hints,
+        }
+    }
+
+    fn from_bytes(rule_variation: u16, bytes: &[u8]) -> Option<Self> {
+        let snapshot: HostSnapshot = serde_json::from_slice(bytes).ok()?;
+        let mut backend = Self::new(rule_variation);
+        backend.game = snapshot.game;
+        backend.pre_game_dice = snapshot.pre_game_dice;
+        backend.tie_count = snapshot.tie_count;
+        backend.ceremony_started = snapshot.ceremony_started;
+        backend.chat = snapshot.chat;
+        backend.clock = snapshot.clock;
+        backend.hints = snapshot.hints;
+        backend.view_state.seq = snapshot.seq;
+        backend.sync_view_state();
+        Some(backend)
+    }
+
+    fn to_bytes(&self) -> Option<Vec<u8>> {
+        // Save the running clock's time so far: it is paused once restored. The
+        // host saves a snapshot every second while idle, so a refresh refunds
+        // at most that much thinking time.
+        let clock = self.clock.clone().map(|mut clock| {
+            clock.pause(Instant::now());
+            clock
+        });
+        let snapshot = HostSnapshot {
+            game: self.game.clone(),
+            pre_game_dice: self.pre_game_dice,
+            tie_count: self.tie_count,
+            ceremony_started: self.ceremony_started,
+            chat: self.chat.clone(),
+            clock,
+            hints: self.hints.clone(),
+            seq: self.view_state.seq,
+        };
+        serde_json::to_vec(&snapshot).ok()
+    }
+
+    fn player_arrival(&mut self, mp_player: u16) {
+        if mp_player > 1 {
+            self.commands
+                .push(BackendCommand::KickPlayer { player: mp_player });
+            return;
+        }
+        self.arrived[mp_player as usize] = true;
+
+        // Cancel any reconnect timer for this player.
+        self.commands.push(BackendCommand::CancelTimer {
+            timer_id: mp_player,
+        });
+
+        // Start the ceremony once both players have arrived.
+        if self.arrived[0]
+            && self.arrived[1]
+            && self.game.stage == trictrac_store::Stage::PreGame
+            && !self.ceremony_started
+        {
+            self.ceremony_started = true;
+            self.pre_game_dice = [None; 2];
+            self.tie_count = 0;
+            self.sync_view_state();
+            self.commands.push(BackendCommand::ResetViewState);
+        } else {
+            self.broadcast_state();
+        }
+    }
+
+    fn player_departure(&mut self, mp_player: u16) {
+        if mp_player > 1 {
+            return;
+        }
+        self.arrived[mp_player as usize] = false;
+        // Give 60 seconds to reconnect before terminating the room.
+        self.commands.push(BackendCommand::SetTimer {
+            timer_id: mp_player,
+            duration: 60.0,
+        });
+        if self.clock.is_some() {
+            // Pause the clocks and show it to the remaining player.
+            self.broadcast_state();
+        }
+    }
+
+    fn spectator_arrival(&mut self, _spectator: u16) {
+        self.spectators += 1;
+        self.broadcast_state();
+    }
+
+    fn spectator_departure(&mut self, _spectator: u16) {
+        self.spectators = self.spectators.saturating_sub(1);
+        self.broadcast_state();
+    }
+
+    fn inform_chat(&mut self, mp_player: u16, text: String) {
+        if mp_player > 1 {
+            return;
+        }
+        let text = text.trim();
+        if text.is_empty() {
+            return;
+        }
+        let body = match QuickPhrase::from_code(text) {
+            Some(phrase) => ChatBody::Quick(phrase),
+            None => ChatBody::Text(text.chars().take(MAX_CHAT_CHARS).collect()),
+        };
+        self.chat.push(ChatEntry {
+            from: mp_player,
+            body,
+        });
+        if self.chat.len() > CHAT_HISTORY_LEN {
+            self.chat.remove(0);
+        }
+        self.broadcast_state();
+    }
+
+    fn inform_rpc(&mut self, mp_player: u16, action: PlayerAction) {
+        // SetName is always accepted regardless of game stage or whose turn it is.
+        if let PlayerAction::SetName(name) = action {
+            let store_id = if mp_player == 0 { HOST_PLAYER_ID } else { GUEST_PLAYER_ID };
+            if let Some(p) = self.game.players.get_mut(&store_id) {
+                p.name = name;
+            }
+            self.broadcast_state();
+            return;
+        }
+
+        // During the first-player ceremony only PreGameRoll actions are accepted.
+        if self.ceremony_started {
+            if matches!(action, PlayerAction::PreGameRoll) {
+                self.handle_pre_game_roll(mp_player);
+            }
+            return;
+        }
+
+        if self.game.stage == trictrac_store::Stage::Ended {
+            return;
+        }
+
+        let store_id = if mp_player == 0 {
+            HOST_PLAYER_ID
+        } else {
+            GUEST_PLAYER_ID
+        };
+
+        // Only the active player may act (except during Chance-like waiting stages).
+        if self.game.active_player_id != store_id {
+            return;
+        }
+
+        match action {
+            PlayerAction::Roll => {
+                if self.game.turn_stage == TurnStage::RollDice {
+                    self.do_roll();
+                }
+            }
+            PlayerAction::Move(m1, m2) => {
+                if self.game.turn_stage != TurnStage::Move
+                    && self.game.turn_stage != TurnStage::HoldOrGoChoice
+                {
+                    return;
+                }
+                let event = GameEvent::Move {
+                    player_id: store_id,
+                    moves: (m1, m2),
+                };
+                if self.game.validate(&event) {
+                    let _ = self.game.consume(&event);
+                    self.drive_automatic_stages();
+                }
+            }
+            PlayerAction::Go => {
+                if self.game.turn_stage == TurnStage::HoldOrGoChoice {
+                    let _ = self.game.consume(&GameEvent::Go {
+                        player_id: store_id,
+                    });
+                }
+            }
+            PlayerAction::Mark => {
+                if matches!(
+                    self.game.turn_stage,
+                    TurnStage::MarkPoints | TurnStage::MarkAdvPoints
+                ) {
+                    self.drive_automatic_stages();
+                }
+            }
+            PlayerAction::Hint => {
+                if self.hints.allowed
+                    && matches!(
+                        self.game.turn_stage,
+                        TurnStage::Move | TurnStage::HoldOrGoChoice
+                    )
+                {
+                    self.hints.used[mp_player as usize] += 1;
+                }
+            }
+            PlayerAction::PreGameRoll => {} // ignored outside ceremony
+            PlayerAction::SetName(_) => {}  // handled at the top of inform_rpc
+        }
+
+        self.broadcast_state();
+    }
+
+    fn timer_triggered(&mut self, timer_id: u16) {
+        match timer_id {
+            0 | 1 => {
+                // Reconnect grace period expired for host (0) or guest (1).
+                self.commands.push(BackendCommand::TerminateRoom);
+            }
+            CLOCK_TIMER_ID => self.check_flag(Instant::now()),
+            _ => {}
+        }
+    }
+
+    fn get_view_state(&self) -> &ViewState {
+        &self.view_state
+    }
+
+    fn drain_commands(&mut self) -> Vec<BackendCommand<GameDelta>> {
+        std::mem::take(&mut self.commands)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::bot_local::{bot_decide, BotPlayer};
+    use super::super::mcts::MctsConfig;
+    use super::super::network::{tests::exported, PolicyValueNet};
+    use super::super::replay::{replay, GameRecord};
+    use super::super::types::{StateChange, CASUAL_GAME};
+    use super::{SerStage, SerTurnStage};
+    use backbone_lib::traits::BackEndArchitecture;
+    use trictrac_store::MoveRules;
+
+    fn make_backend() -> TrictracBackend {
+        TrictracBackend::new(0)
+    }
+
+    /// Helper: drain commands and return the backend's ViewState once per
+    /// Delta or ResetViewState, so that tests can tell whether anything was broadcast.
+    fn drain_deltas(b: &mut TrictracBackend) -> Vec<ViewState> {
+        b.drain_commands()
+            .into_iter()
+            .filter_map(|cmd| match cmd {
+                BackendCommand::Delta(_) | BackendCommand::ResetViewState => {
+                    Some(b.view_state.clone())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Drive the ceremony to completion (both players roll until one wins).
+    fn complete_ceremony(b: &mut TrictracBackend) {
+        loop {
+            if b.get_view_state().stage != SerStage::PreGameRoll {
+                break;
+            }
+            let pgr = b.get_view_state().pre_game_roll.clone().unwrap_or_default();
+            let host_needs = pgr.host_die.is_none();
+            let guest_needs = pgr.guest_die.is_none();
+            if !host_needs && !guest_needs {
+                break; // both rolled but stage not yet resolved — shouldn't happen
+            }
+            if host_needs {
+                b.inform_rpc(0, PlayerAction::PreGameRoll);
+            }
+            if guest_needs {
+                b.inform_rpc(1, PlayerAction::PreGameRoll);
+            }
+            b.drain_commands();
+        }
+    }
+
+    #[test]
+    fn both_players_arrive_starts_ceremony() {
+        let mut b = make_backend();
+        b.player_arrival(0); // host
+        b.drain_commands();
+        b.player_arrival(1); // guest
+        let cmds = b.drain_commands();
+
+        // ResetViewState should have been issued to start the ceremony.
+        let has_reset = cmds
+            .iter()
+            .any(|c| matches!(c, BackendCommand::ResetViewState));
+        assert!(
+            has_reset,
+            "expected ResetViewState after both players arrive"
+        );
+
+        // Stage should now be PreGameRoll, not InGame.
+        assert_eq!(b.get_view_state().stage, SerStage::PreGameRoll);
+    }
+
+    #[test]
+    fn ceremony_resolves_to_in_game() {
+        let mut b = make_backend();
+        b.player_arrival(0);
+        b.player_arrival(1);
+        b.drain_commands();
+
+        complete_ceremony(&mut b);
+
+        assert_eq!(b.get_view_state().stage, SerStage::InGame);
+    }
+
+    #[test]
+    fn ceremony_any_order_allowed() {
+        let mut b = make_backend();
+        b.player_arrival(0);
+        b.player_arrival(1);
+        b.drain_commands();
+
+        // Guest may roll before host.
+        b.inform_rpc(1, PlayerAction::PreGameRoll);
+        let states = drain_deltas(&mut b);
+        assert!(
+            !states.is_empty(),
+            "guest PreGameRoll should broadcast a state"
+        );
+        let pgr = states.last().unwrap().pre_game_roll.as_ref().unwrap();
+        assert!(
+            pgr.guest_die.is_some(),
+            "guest die should be set after guest rolls"
+        );
+        assert!(pgr.host_die.is_none(), "host die should still be blank");
+    }
+
+    #[test]
+    fn unknown_player_kicked() {
+        let mut b = make_backend();
+        b.player_arrival(99);
+        let cmds = b.drain_commands();
+        assert!(cmds
+            .iter()
+            .any(|c| matches!(c, BackendCommand::KickPlayer { player: 99 })));
+    }
+
+    #[test]
+    fn spectators_are_counted_not_kicked() {
+        let mut b = make_backend();
+        b.player_arrival(0);
+        b.drain_commands();
+
+        b.spectator_arrival(5);
+        b.spectator_arrival(6);
+        let cmds = b.drain_commands();
+        assert!(!cmds
+            .iter()
+            .any(|c| matches!(c, BackendCommand::KickPlayer { .. })));
+        assert_eq!(b.get_view_state().spectators, 2);
+
+        b.spectator_departure(5);
+        let states = drain_deltas(&mut b);
+        assert_eq!(states.last().map(|s| s.spectators), Some(1));
+    }
+
+    #[test]
+    fn spectator_count_survives_ceremony_start() {
+        let mut b = make_backend();
+        b.player_arrival(0);
+        b.spectator_arrival(5);
+        b.player_arrival(1);
+        b.drain_commands();
+        assert_eq!(b.get_view_state().stage, SerStage::PreGameRoll);
+        assert_eq!(b.get_view_state().spectators, 1);
+    }
+
+    #[test]
+    fn chat_message_is_broadcast() {
+        let mut b = make_backend();
+        b.player_arrival(0);
+        b.drain_commands();
+
+        b.inform_chat(0, "  bonjour  ".to_string());
+        let states = drain_deltas(&mut b);
+        let chat = &states.last().expect("chat should broadcast a state").chat;
+        assert_eq!(
+            chat,
+            &vec![ChatEntry {
+                from: 0,
+                body: ChatBody::Text("bonjour".to_string())
+            }]
+        );
+    }
+
+    #[test]
+    fn chat_quick_phrase_and_limits() {
+        let mut b = make_backend();
+        b.inform_chat(1, QuickPhrase::Ecole.code().to_string());
+        assert_eq!(
+            b.get_view_state().chat.last().map(|e| &e.body),
+            Some(&ChatBody::Quick(QuickPhrase::Ecole))
+        );
+
+        // Empty messages and unknown senders are ignored.
+        b.drain_commands();
+        b.inform_chat(0, "   ".to_string());
+        b.inform_chat(7, "hi".to_string());
+        assert!(b.drain_commands().is_empty());
+
+        // Long messages are truncated, history is capped.
+        b.inform_chat(0, "x".repeat(MAX_CHAT_CHARS + 50));
+        assert_eq!(
+            b.get_view_state().chat.last().map(|e| &e.body),
+            Some(&ChatBody::Text("x".repeat(MAX_CHAT_CHARS)))
+        );
+        for i in 0..CHAT_HISTORY_LEN {
+            b.inform_chat(1, format!("msg {i}"));
+        }
+        let chat = &b.get_view_state().chat;
+        assert_eq!(chat.len(), CHAT_HISTORY_LEN);
+        assert_eq!(chat[0].body, ChatBody::Text("msg 0".to_string()));
+    }
+
+    #[test]
+    fn roll_advances_to_move_or_hold() {
+        let mut b = make_backend();
+        b.player_arrival(0);
+        b.player_arrival(1);
+        b.drain_commands();
+
+        // Complete ceremony before rolling.
+        complete_ceremony(&mut b);
+
+        // The ceremony dice are the first roll: play on until somebody has to roll.
+        while b.get_view_state().turn_stage != SerTurnStage::RollDice {
+            let (player, action) = next_action(&b).expect("the game goes on");
+            b.inform_rpc(player, action);
+        }
+        b.drain_commands();
+        let roller = b
+            .get_view_state()
+            .active_mp_player
+            .expect("someone should be active");
+        b.inform_rpc(roller, PlayerAction::Roll);
+        let states = drain_deltas(&mut b);
+        assert!(!states.is_empty(), "expected a state broadcast after roll");
+
+        let last = states.last().unwrap();
+        assert!(
+            matches!(
+                last.turn_stage,
+                SerTurnStage::Move | SerTurnStage::HoldOrGoChoice
+            ),
+            "expected Move or HoldOrGoChoice after roll, got {:?}",
+            last.turn_stage
+        );
+        assert_eq!(last.dice, b.get_view_state().dice);
+        assert!(last.dice.0 >= 1 && last.dice.0 <= 6);
+        assert!(last.dice.1 >= 1 && last.dice.1 <= 6);
+    }
+
+    #[test]
+    fn wrong_player_roll_ignored() {
+        let mut b = make_backend();
+        b.player_arrival(0);
+        b.player_arrival(1);
+        b.drain_commands();
+        complete_ceremony(&mut b);
+
+        // Identify who goes first and have the OTHER player try to roll.
+        let active = b.get_view_state().active_mp_player;
+        let wrong_player = if active == Some(0) { 1u16 } else { 0u16 };
+        b.inform_rpc(wrong_player, PlayerAction::Roll);
+        let cmds = b.drain_commands();
+        assert!(cmds.is_empty(), "wrong player roll should be ignored");
+    }
+
+    #[test]
+    fn hints_are_counted_in_casual_games_only() {
+        for (rule_variation, counted) in [(0, 0), (CASUAL_GAME, 1)] {
+            let mut b = TrictracBackend::new(rule_variation);
+            b.player_arrival(0);
+            b.player_arrival(1);
+            complete_ceremony(&mut b);
+            while !matches!(
+                b.get_view_state().turn_stage,
+                SerTurnStage::Move | SerTurnStage::HoldOrGoChoice
+            ) {
+                let (player, action) = next_action(&b).expect("the game goes on");
+                b.inform_rpc(player, action);
+            }
+            let player = b.get_view_state().active_mp_player.unwrap();
+            b.inform_rpc(player, PlayerAction::Hint);
+            // Hints are only for the player to move.
+            b.inform_rpc(1 - player, PlayerAction::Hint);
+
+            let hints = &b.get_view_state().hints;
+            assert_eq!(hints.allowed, counted == 1);
+            assert_eq!(hints.used[player as usize], counted);
+            assert_eq!(hints.used[1 - player as usize], 0);
+            let record = GameRecord::from_view_state(b.get_view_state());
+            assert_eq!(record.hints_used, hints.used);
+        }
+    }
+
+    fn make_timed_backend() -> TrictracBackend {
+        let mut b = TrictracBackend::new(TimeControl::PRESETS[0].to_rule_variation());
+        b.player_arrival(0);
+        b.player_arrival(1);
+        complete_ceremony(&mut b);
+        b.drain_commands();
+        b
+    }
+
+    #[test]
+    fn untimed_game_has_no_clock() {
+        let mut b = make_backend();
+        b.player_arrival(0);
+        b.player_arrival(1);
+        complete_ceremony(&mut b);
+        assert!(b.get_view_state().clock.is_none());
+        assert!(!b
+            .drain_commands()
+            .iter()
+            .any(|c| matches!(c, BackendCommand::SetTimer { timer_id: CLOCK_TIMER_ID, .. })));
+    }
+
+    #[test]
+    fn clock_runs_for_active_player_after_ceremony() {
+        let b = make_timed_backend();
+        let vs = b.get_view_state();
+        let clock = vs.clock.as_ref().expect("timed game has a clock");
+        assert_eq!(clock.running, vs.active_mp_player);
+        assert_eq!(clock.remaining_ms, [300_000, 300_000]);
+    }
+
+    #[test]
+    fn clock_paused_during_ceremony_and_disconnection() {
+        let mut b = TrictracBackend::new(TimeControl::PRESETS[0].to_rule_variation());
+        b.player_arrival(0);
+        b.player_arrival(1);
+        assert_eq!(b.get_view_state().clock.as_ref().unwrap().running, None);
+        complete_ceremony(&mut b);
+        b.player_departure(1);
+        let vs = drain_deltas(&mut b).pop().expect("departure broadcasts the paused clock");
+        assert_eq!(vs.clock.unwrap().running, None);
+        b.player_arrival(1);
+        assert!(b.get_view_state().clock.as_ref().unwrap().running.is_some());
+    }
+
+    #[test]
+    fn flag_fall_ends_game() {
+        let mut b = make_timed_backend();
+        let loser = b.get_view_state().active_mp_player.unwrap();
+        b.check_flag(Instant::now() + web_time::Duration::from_secs(3600));
+        let vs = b.get_view_state();
+        assert_eq!(vs.stage, SerStage::Ended);
+        assert_eq!(vs.clock.as_ref().unwrap().flagged, Some(loser));
+        // No action is accepted once the game is over.
+        b.drain_commands();
+        b.inform_rpc(loser, PlayerAction::Roll);
+        assert!(drain_deltas(&mut b).is_empty());
+    }
+
+    #[test]
+    fn departure_sets_reconnect_timer() {
+        let mut b = make_backend();
+        b.player_arrival(0);
+        b.drain_commands();
+        b.player_departure(0);
+        let cmds = b.drain_commands();
+        assert!(
+            cmds.iter()
+                .any(|c| matches!(c, BackendCommand::SetTimer { timer_id: 0, .. })),
+            "expected reconnect timer after host departure"
+        );
+    }
+
+    fn drain_game_deltas(b: &mut TrictracBackend) -> Vec<GameDelta> {
+        b.drain_commands()
+            .into_iter()
+            .filter_map(|cmd| match cmd {
+                BackendCommand::Delta(d) => Some(d),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The next action of whoever has to act, `None` once the game is over.
+    fn next_action(b: &TrictracBackend) -> Option<(u16, PlayerAction)> {
+        let vs = b.get_view_state();
+        if let Some(pgr) = &vs.pre_game_roll {
+            let player = if pgr.host_die.is_none() { 0 } else { 1 };
+            return Some((player, PlayerAction::PreGameRoll));
+        }
+        let game = b.get_game();
+        if game.stage == Stage::Ended {
+            return None;
+        }
+        let player = vs.active_mp_player?;
+        if player == 1 {
+            return bot_decide(game, None).map(|action| (1, action));
+        }
+        let action = match game.turn_stage {
+            TurnStage::RollDice => PlayerAction::Roll,
+            TurnStage::Move | TurnStage::HoldOrGoChoice => {
+                let rules = MoveRules::new(&Color::White, &game.board, game.dice);
+                let (m1, m2) = rules
+                    .get_possible_moves_sequences(true, vec![])
+                    .first()
+                    .copied()
+                    .unwrap_or_default();
+                PlayerAction::Move(m1, m2)
+            }
+            _ => PlayerAction::Mark,
+        };
+        Some((0, action))
+    }
+
+    #[test]
+    fn deltas_replay_to_host_state() {
+        let mut b = make_backend();
+        b.player_arrival(0);
+        b.player_arrival(1);
+        // The ceremony starts with a reset: the client gets the full state.
+        b.drain_commands();
+        let mut client = b.get_view_state().clone();
+
+        b.inform_chat(1, "salut".to_string());
+        let mut deltas = 0;
+        for _ in 0..300 {
+            let Some((player, action)) = next_action(&b) else {
+                break;
+            };
+            b.inform_rpc(player, action);
+            for delta in drain_game_deltas(&mut b) {
+                assert!(client.apply_delta(&delta), "delta {} did not apply", delta.seq);
+                deltas += 1;
+            }
+        }
+        assert!(deltas > 20);
+        assert!(client == *b.get_view_state());
+    }
+
+    #[test]
+    fn full_updates_leave_the_history_out() {
+        let mut b = make_backend();
+        b.player_arrival(0);
+        b.player_arrival(1);
+        for _ in 0..40 {
+            let (player, action) = next_action(&b).expect("the game goes on");
+            b.inform_rpc(player, action);
+        }
+        b.drain_commands();
+        let played = b.get_view_state().history.len();
+        assert!(played > 10);
+
+        // A client joining now gets the state without the events played so far.
+        let json = serde_json::to_string(b.get_view_state()).unwrap();
+        let mut client: ViewState = serde_json::from_str(&json).unwrap();
+        assert!(client.history.is_empty());
+
+        // Deltas still apply, and bring the events played from then on.
+        let (player, action) = next_action(&b).expect("the game goes on");
+        b.inform_rpc(player, action);
+        for delta in drain_game_deltas(&mut b) {
+            assert!(client.apply_delta(&delta), "delta {} did not apply", delta.seq);
+        }
+        assert_eq!(client.history, b.get_view_state().history[played..]);
+    }
+
+    #[test]
+    fn out_of_sync_deltas_are_detected() {
+        let mut b = make_backend();
+        let mut client = b.get_view_state().clone();
+        b.spectator_arrival(5);
+        b.spectator_arrival(6);
+        let deltas = drain_game_deltas(&mut b);
+        assert_eq!(deltas.len(), 2);
+        assert_eq!(deltas[0].changes, vec![StateChange::Spectators(1)]);
+
+        // A missed delta leaves the state alone.
+        let mut skipped = client.clone();
+        assert!(!skipped.apply_delta(&deltas[1]));
+        assert!(skipped == client);
+
+        // A state that diverged fails the checksum.
+        let mut diverged = client.clone();
+        diverged.board[0] += 1;
+        assert!(!diverged.apply_delta(&deltas[0]));
+
+        assert!(client.apply_delta(&deltas[0]));
+        assert!(client.apply_delta(&deltas[1]));
+        assert_eq!(client.spectators, 2);
+    }
+
+    #[test]
+    fn chat_deltas_carry_new_messages_only() {
+        let mut b = make_backend();
+        for i in 0..CHAT_HISTORY_LEN {
+            b.inform_chat(0, format!("msg {i}"));
+        }
+        b.drain_commands();
+        let mut client = b.get_view_state().clone();
+
+        b.inform_chat(1, "one more".to_string());
+        let deltas = drain_game_deltas(&mut b);
+        let entry = ChatEntry {
+            from: 1,
+            body: ChatBody::Text("one more".to_string()),
+        };
+        assert_eq!(deltas[0].changes, vec![StateChange::ChatAppended(vec![entry])]);
+        assert!(client.apply_delta(&deltas[0]));
+        assert!(client.chat == b.get_view_state().chat);
+        assert_eq!(client.chat.len(), CHAT_HISTORY_LEN);
+    }
+
+    #[test]
+    fn recorded_game_replays_to_final_position() {
+        let mut b = make_backend();
+        b.player_arrival(0);
+        b.player_arrival(1);
+        for _ in 0..2000 {
+            let Some((player, action)) = next_action(&b) else {
+                break;
+            };
+            b.inform_rpc(player, action);
+        }
+        let vs = b.get_view_state();
+        assert_eq!(vs.stage, SerStage::Ended);
+
+        let steps = replay(&GameRecord::from_view_state(vs)).expect("every event replays");
+        let last = &steps.last().unwrap().view_state;
+        assert_eq!(last.board, vs.board);
+        assert_eq!(last.scores, vs.scores);
+        assert_eq!(last.stage, SerStage::Ended);
+        assert!(steps.windows(2).all(|w| w[0].turn <= w[1].turn));
+    }
+
+    #[test]
+    fn network_bot_plays_a_whole_game() {
+        let net = PolicyValueNet::from_bytes(&exported(0, 217, 514, 4, 0.01)).unwrap();
+        let bot = BotPlayer::Network {
+            net: std::sync::Arc::new(net),
+            config: MctsConfig {
+                n_simulations: 4,
+                c_puct: 1.5,
+                temperature: 1.0,
+            },
+        };
+        let mut b = make_backend();
+        b.player_arrival(0);
+        b.player_arrival(1);
+        for _ in 0..2000 {
+            let Some((player, action)) = next_action(&b) else {
+                break;
+            };
+            let action = if player == 1 {
+                let pgr = b.get_view_state().pre_game_roll.clone();
+                bot.decide(b.get_game(), pgr.as_ref()).expect("the bot has to act")
+            } else {
+                action
+            };
+            b.inform_rpc(player, action);
+        }
+        assert_eq!(b.get_view_state().stage, SerStage::Ended);
+    }
+
+    #[test]
+    fn timer_triggers_terminate_room() {
+        let mut b = make_backend();
+        b.timer_triggered(0);
+        let cmds = b.drain_commands();
+        assert!(cmds
+            .iter()
+            .any(|c| matches!(c, BackendCommand::TerminateRoom)));
+    }
+
+    /// The backend of a host who refreshed the page: restored from its last
+    /// snapshot, with both players back.
+    fn refreshed(b: &TrictracBackend, rule_variation: u16) -> TrictracBackend {
+        let bytes = b.to_bytes().expect("the backend saves its state");
+        let mut restored =
+            TrictracBackend::from_bytes(rule_variation, &bytes).expect("the snapshot restores");
+        restored.player_arrival(0);
+        restored.player_arrival(1);
+        restored
+    }
+
+    fn assert_same_game(restored: &TrictracBackend, b: &TrictracBackend) {
+        assert!(restored.game == b.game, "game state differs at {:?}", b.game.turn_stage);
+        assert_eq!(restored.pre_game_dice, b.pre_game_dice);
+        assert_eq!(restored.tie_count, b.tie_count);
+        assert_eq!(restored.ceremony_started, b.ceremony_started);
+        assert!(restored.view_state == b.view_state);
+    }
+
+    #[test]
+    fn refresh_at_every_step_finishes_the_game() {
+        let mut b = make_backend();
+        b.player_arrival(0);
+        b.player_arrival(1);
+        b.inform_chat(1, "salut".to_string());
+        let mut stages = Vec::new();
+        for _ in 0..2000 {
+            let restored = refreshed(&b, 0);
+            assert_same_game(&restored, &b);
+            if !stages.contains(&b.game.turn_stage) {
+                stages.push(b.game.turn_stage);
+            }
+            // Go on with the restored backend, as the host would.
+            b = restored;
+            let Some((player, action)) = next_action(&b) else {
+                break;
+            };
+            b.inform_rpc(player, action);
+        }
+        assert_eq!(b.get_view_state().stage, SerStage::Ended);
+        assert!(b.game.history.len() > 20);
+        assert!(stages.contains(&TurnStage::RollDice));
+        assert!(stages.contains(&TurnStage::HoldOrGoChoice));
+        assert!(stages.contains(&TurnStage::Move));
+    }
+
+    #[test]
+    fn refresh_restores_every_turn_stage() {
+        let mut b = make_backend();
+        b.player_arrival(0);
+        b.player_arrival(1);
+        complete_ceremony(&mut b);
+        b.inform_rpc(0, PlayerAction::SetName("Alice".to_string()));
+        // Player state the view does not show.
+        let host = b.game.players.get_mut(&HOST_PLAYER_ID).unwrap();
+        host.dice_roll_count = 3;
+        host.can_big_bredouille = false;
+        // The stages the game rests in, and those it only goes through while
+        // the backend acts (rolling, marking points).
+        for stage in [
+            TurnStage::RollDice,
+            TurnStage::RollWaiting,
+            TurnStage::MarkPoints,
+            TurnStage::HoldOrGoChoice,
+            TurnStage::Move,
+            TurnStage::MarkAdvPoints,
+        ] {
+            b.game.turn_stage = stage;
+            b.sync_view_state();
+            let restored = refreshed(&b, 0);
+            assert_same_game(&restored, &b);
+            assert_eq!(restored.game.turn_stage, stage);
+            assert_eq!(restored.get_view_state().scores[0].name, "Alice");
+        }
+    }
+
+    #[test]
+    fn refresh_during_ceremony_keeps_dice_and_ties() {
+        let mut b = make_backend();
+        b.player_arrival(0);
+        b.player_arrival(1);
+        b.tie_count = 2;
+        b.inform_rpc(0, PlayerAction::PreGameRoll);
+        let restored = refreshed(&b, 0);
+        assert_same_game(&restored, &b);
+        let pgr = restored.get_view_state().pre_game_roll.clone().unwrap();
+        assert!(pgr.host_die.is_some() && pgr.guest_die.is_none());
+        assert_eq!(pgr.tie_count, 2);
+
+        // The host does not roll again; the guest's die ends the ceremony.
+        let mut b = restored;
+        b.inform_rpc(0, PlayerAction::PreGameRoll);
+        assert_eq!(b.pre_game_dice[0], pgr.host_die);
+        complete_ceremony(&mut b);
+        assert_eq!(b.get_view_state().stage, SerStage::InGame);
+    }
+
+    #[test]
+    fn refresh_keeps_clock_chat_and_hints() {
+        let rule_variation = TimeControl::PRESETS[0].to_rule_variation() | CASUAL_GAME;
+        let mut b = TrictracBackend::new(rule_variation);
+        b.player_arrival(0);
+        b.player_arrival(1);
+        complete_ceremony(&mut b);
+        b.inform_chat(0, "bonne chance".to_string());
+        let player = b.get_view_state().active_mp_player.unwrap();
+        if b.game.turn_stage == TurnStage::RollDice {
+            b.inform_rpc(player, PlayerAction::Roll);
+        }
+        b.inform_rpc(b.get_view_state().active_mp_player.unwrap(), PlayerAction::Hint);
+        let before = b.get_view_state().clock.clone().unwrap();
+
+        let bytes = b.to_bytes().unwrap();
+        let mut restored = TrictracBackend::from_bytes(rule_variation, &bytes).unwrap();
+        let clock = restored.get_view_state().clock.clone().unwrap();
+        // Paused until the players are back, with the time they had.
+        assert_eq!(clock.running, None);
+        for (now, then) in clock.remaining_ms.iter().zip(before.remaining_ms) {
+            assert!(*now <= then && then - now < 1000);
+        }
+        restored.player_arrival(0);
+        restored.player_arrival(1);
+        let vs = restored.get_view_state();
+        assert_eq!(vs.clock.as_ref().unwrap().running, vs.active_mp_player);
+        assert!(vs.chat == b.get_view_state().chat);
+        assert!(vs.hints == b.get_view_state().hints);
+        assert_eq!(vs.hints.used.iter().sum::<u16>(), 1);
+    }
+
+    #[test]
+    fn view_state_snapshot_is_not_a_host_state() {
+        let b = make_backend();
+        let bytes = serde_json::to_vec(b.get_view_state()).unwrap();
+        assert!(TrictracBackend::from_bytes(0, &bytes).is_none());
+    }
+}
+
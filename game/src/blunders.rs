@@ -0,0 +1,300 @@
+//! Post-game error analysis ("blunder check").
+//!
+//! [`check_game`] replays an event history and, at each decision a player
+//! made — a move, or the choice to hold or go — values the choice and its
+//! alternatives with the bot's evaluation, less the points the opponent can
+//! expect from true hits on the next roll. Choices that lose at least
+//! `min_loss` against the best alternative are reported.
+
+use trictrac_store::{Board, CheckerMove, Color, Dice, GameEvent, GameState, Jan, MoveRules, PointsRules, TurnStage};
+
+use super::bot_local::evaluate;
+use super::replay::ReplayError;
+
+// Store PlayerId values of host and guest, as in the backend.
+const HOST_PLAYER_ID: u64 = 1;
+const GUEST_PLAYER_ID: u64 = 2;
+
+/// Loss from which the report page lists a choice, in the evaluator's units
+/// (a filled quarter is worth 8).
+pub const BLUNDER_THRESHOLD: f32 = 1.0;
+
+/// Sequences valued in full at each move, the best ones by plain evaluation.
+/// Counting the threats of every sequence would make long games slow to check.
+const CANDIDATES: usize = 4;
+
+/// What a player chose, in board coordinates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Choice {
+    /// Play these moves (holding, in the hold-or-go choice).
+    Moves(CheckerMove, CheckerMove),
+    Go,
+}
+
+/// Why the played choice was worse than the best one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BlunderKind {
+    /// Held when going was better, or the reverse.
+    HoldOrGo,
+    /// The best choice filled (or kept) a quarter the played one did not.
+    MissedQuarterFilling,
+    /// The played choice left half-fields the opponent can hit more often.
+    ExposedHalfField,
+    /// Any other weaker choice.
+    WeakerMove,
+}
+
+/// A choice that lost value against the best alternative.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Blunder {
+    /// Index of the decision in the event history.
+    pub index: usize,
+    /// Multiplayer player_id of the player who chose (0 = host, White).
+    pub player: u16,
+    /// Number of rolls made so far, as in the replay viewer.
+    pub turn: u16,
+    pub dice: (u8, u8),
+    pub kind: BlunderKind,
+    pub played: Choice,
+    pub best: Choice,
+    /// Value lost against `best`.
+    pub loss: f32,
+}
+
+/// One valued choice; positions are in the mover's (White) space.
+struct Valued {
+    choice: Choice,
+    value: f32,
+    quarters: usize,
+    threat: f32,
+}
+
+/// Checks every decision of `events`, played from the start of a game, and
+/// returns those losing at least `min_loss`, in game order.
+pub fn check_game(events: &[GameEvent], min_loss: f32) -> Result<Vec<Blunder>, ReplayError> {
+    let mut game = GameState::new(false);
+    game.init_player("White");
+    game.init_player("Black");
+
+    let mut turn = 0;
+    let mut blunders = Vec::new();
+    for (index, event) in events.iter().enumerate() {
+        let played = match event {
+            GameEvent::Move { moves, .. } => Some(Choice::Moves(moves.0, moves.1)),
+            GameEvent::Go { .. } => Some(Choice::Go),
+            _ => None,
+        };
+        let deciding = matches!(game.turn_stage, TurnStage::Move | TurnStage::HoldOrGoChoice);
+        if let (Some(played), true) = (played, deciding) {
+            if let Some(blunder) = check_decision(&game, played, index, turn) {
+                if blunder.loss >= min_loss {
+                    blunders.push(blunder);
+                }
+            }
+        }
+        if !game.validate(event) || game.consume(event).is_err() {
+            return Err(ReplayError { index });
+        }
+        // Cloned at each decision; the history is not needed for that.
+        game.history.clear();
+        if matches!(event, GameEvent::RollResult { .. }) {
+            turn += 1;
+        }
+    }
+    Ok(blunders)
+}
+
+fn check_decision(game: &GameState, played: Choice, index: usize, turn: u16) -> Option<Blunder> {
+    let (player, color) = match game.active_player_id {
+        HOST_PLAYER_ID => (0, Color::White),
+        GUEST_PLAYER_ID => (1, Color::Black),
+        _ => return None,
+    };
+    // Work in the mover's space, where the mover is White.
+    let own = if color == Color::Black { game.board.mirror() } else { game.board.clone() };
+    let to_own = |m: CheckerMove| if color == Color::Black { m.mirror() } else { m };
+    let to_board = to_own;
+
+    let mut sequences: Vec<(CheckerMove, CheckerMove)> = MoveRules::new(&Color::White, &own, game.dice)
+        .get_possible_moves_sequences(true, vec![]);
+    let played_own = match played {
+        Choice::Moves(m1, m2) => Some((to_own(m1), to_own(m2))),
+        Choice::Go => None,
+    };
+    sequences.sort_by(|a, b| {
+        after(&own, *b)
+            .map_or(f32::MIN, |b| evaluate(&b))
+            .total_cmp(&after(&own, *a).map_or(f32::MIN, |a| evaluate(&a)))
+    });
+    sequences.truncate(CANDIDATES);
+    if let Some(p) = played_own {
+        if !sequences.contains(&p) {
+            sequences.push(p);
+        }
+    }
+
+    let mut valued: Vec<Valued> = sequences
+        .into_iter()
+        .filter_map(|(m1, m2)| {
+            let board = after(&own, (m1, m2))?;
+            Some(value(Choice::Moves(to_board(m1), to_board(m2)), &board))
+        })
+        .collect();
+    if game.turn_stage == TurnStage::HoldOrGoChoice {
+        // Going sets both players' checkers back to the start.
+        let mut start = Board::new();
+        start.set_positions(&Color::White, start_position());
+        valued.push(value(Choice::Go, &start));
+    }
+
+    let played = valued.iter().find(|v| v.choice == played)?;
+    let best = valued.iter().max_by(|a, b| a.value.total_cmp(&b.value))?;
+    let kind = if (played.choice == Choice::Go) != (best.choice == Choice::Go) {
+        BlunderKind::HoldOrGo
+    } else if best.quarters > played.quarters {
+        BlunderKind::MissedQuarterFilling
+    } else if played.threat > best.threat {
+        BlunderKind::ExposedHalfField
+    } else {
+        BlunderKind::WeakerMove
+    };
+    Some(Blunder {
+        index,
+        player,
+        turn,
+        dice: game.dice.values,
+        kind,
+        played: played.choice,
+        best: best.choice,
+        loss: best.value - played.value,
+    })
+}
+
+/// The mover's board after `moves`, both in the mover's space.
+fn after(own: &Board, (m1, m2): (CheckerMove, CheckerMove)) -> Option<Board> {
+    let mut board = own.clone();
+    board.move_checker(&Color::White, m1).ok()?;
+    board.move_checker(&Color::White, m2).ok()?;
+    Some(board)
+}
+
+fn value(choice: Choice, board: &Board) -> Valued {
+    let threat = hit_threat(board);
+    Valued {
+        choice,
+        value: evaluate(board) - threat,
+        quarters: [1, 7, 13, 19]
+            .into_iter()
+            .filter(|&q| board.is_quarter_filled(Color::White, q))
+            .count(),
+        threat,
+    }
+}
+
+/// Points Black (the opponent in the mover's space) can expect from true
+/// hits on its next roll.
+fn hit_threat(board: &Board) -> f32 {
+    let mut total = 0.0;
+    for d1 in 1..=6u8 {
+        for d2 in d1..=6u8 {
+            let weight = if d1 == d2 { 1.0 } else { 2.0 };
+            let dice = Dice { values: (d1, d2) };
+            let (jans, _) = PointsRules::new(&Color::Black, board, dice).get_result_jans(0);
+            let points: i32 = jans
+                .iter()
+                .filter(|(jan, _)| {
+                    matches!(
+                        jan,
+                        Jan::TrueHitSmallJan | Jan::TrueHitBigJan | Jan::TrueHitOpponentCorner
+                    )
+                })
+                .map(|(jan, moves)| jan.get_points(d1 == d2) as i32 * moves.len() as i32)
+                .sum();
+            total += weight * points as f32;
+        }
+    }
+    total / 36.0
+}
+
+fn start_position() -> [i8; 24] {
+    let mut board = [0i8; 24];
+    board[0] = 15;
+    board[23] = -15;
+    board
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rolled(dice: (u8, u8)) -> Vec<GameEvent> {
+        vec![
+            GameEvent::BeginGame { goes_first: HOST_PLAYER_ID },
+            GameEvent::Roll { player_id: HOST_PLAYER_ID },
+            GameEvent::RollResult {
+                player_id: HOST_PLAYER_ID,
+                dice: Dice { values: dice },
+            },
+        ]
+    }
+
+    fn moved(events: &mut Vec<GameEvent>, from: [(usize, usize); 2]) {
+        events.push(GameEvent::Move {
+            player_id: HOST_PLAYER_ID,
+            moves: (
+                CheckerMove::new(from[0].0, from[0].1).unwrap(),
+                CheckerMove::new(from[1].0, from[1].1).unwrap(),
+            ),
+        });
+    }
+
+    #[test]
+    fn every_decision_is_valued() {
+        let mut events = rolled((6, 5));
+        moved(&mut events, [(1, 7), (1, 6)]);
+        let all = check_game(&events, f32::MIN).unwrap();
+        assert_eq!(all.len(), 1);
+        let b = &all[0];
+        assert_eq!((b.index, b.player, b.turn, b.dice), (3, 0, 1, (6, 5)));
+        assert!(b.loss >= 0.0);
+        // No choice is better than the best one.
+        assert!(check_game(&events, 0.01)
+            .unwrap()
+            .iter()
+            .all(|b| b.played != b.best));
+    }
+
+    #[test]
+    fn exposing_checkers_to_hits_is_reported() {
+        // White's checkers within reach of Black's talon: 17→19 17→21 leaves
+        // a half-field on 21 that Black hits with a 3.
+        let mut game = GameState::new(false);
+        game.init_player("White");
+        game.init_player("Black");
+        let mut board = [0i8; 24];
+        board[0] = 9;
+        board[14] = 2;
+        board[16] = 2;
+        board[18] = 2;
+        board[23] = -15;
+        game.consume(&GameEvent::BeginGame { goes_first: HOST_PLAYER_ID }).unwrap();
+        game.board.set_positions(&Color::White, board);
+        game.consume(&GameEvent::Roll { player_id: HOST_PLAYER_ID }).unwrap();
+        game.consume(&GameEvent::RollResult {
+            player_id: HOST_PLAYER_ID,
+            dice: Dice { values: (2, 4) },
+        })
+        .unwrap();
+        let spread = Choice::Moves(CheckerMove::new(17, 19).unwrap(), CheckerMove::new(17, 21).unwrap());
+        let blunder = check_decision(&game, spread, 0, 1).expect("the spread is legal");
+        assert!(blunder.loss > 0.0);
+        assert_eq!(blunder.kind, BlunderKind::ExposedHalfField);
+    }
+
+    #[test]
+    fn impossible_event_is_reported() {
+        let mut events = rolled((6, 5));
+        moved(&mut events, [(1, 2), (1, 3)]);
+        assert_eq!(check_game(&events, 0.0), Err(ReplayError { index: 3 }));
+    }
+}
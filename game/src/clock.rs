@@ -0,0 +1,193 @@
+use serde::{Deserialize, Serialize};
+use web_time::{Duration, Instant};
+
+use super::types::{ClockMode, ClockView, TimeControl};
+
+/// Chess-style clocks for the two players, driven by the host backend.
+///
+/// The clock of the player whose turn it is runs unless paused (pre-game
+/// ceremony, disconnections). Elapsed time is only settled when the clock
+/// starts or stops, so `view` stays unchanged while it runs.
+///
+/// A deserialized clock is paused: the time since it last started is not saved.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GameClock {
+    control: TimeControl,
+    remaining: [Duration; 2],
+    /// Player whose turn is being timed, kept while paused.
+    turn: Option<u16>,
+    /// Unused part of the current turn's delay (delay mode only).
+    delay_left: Duration,
+    /// Set while the clock is counting down.
+    #[serde(skip)]
+    running_since: Option<Instant>,
+    flagged: Option<u16>,
+}
+
+impl GameClock {
+    pub fn new(control: TimeControl) -> Self {
+        let initial = Duration::from_secs(control.minutes as u64 * 60);
+        GameClock {
+            control,
+            remaining: [initial; 2],
+            turn: None,
+            delay_left: Duration::ZERO,
+            running_since: None,
+            flagged: None,
+        }
+    }
+
+    fn bonus(&self) -> Duration {
+        Duration::from_secs(self.control.bonus_secs as u64)
+    }
+
+    /// Move the time elapsed since the clock started into `remaining`.
+    fn settle(&mut self, now: Instant) {
+        let (Some(since), Some(player)) = (self.running_since, self.turn) else {
+            return;
+        };
+        let elapsed = now.saturating_duration_since(since);
+        let counted = elapsed.saturating_sub(self.delay_left);
+        self.delay_left = self.delay_left.saturating_sub(elapsed);
+        let left = &mut self.remaining[player as usize];
+        *left = left.saturating_sub(counted);
+        self.running_since = Some(now);
+    }
+
+    /// Run `player`'s clock. When the turn passes from the other player, they
+    /// receive their Fischer increment and `player` gets a fresh delay.
+    pub fn run(&mut self, player: u16, now: Instant) {
+        if self.flagged.is_some() {
+            return;
+        }
+        if self.turn == Some(player) {
+            if self.running_since.is_none() {
+                self.running_since = Some(now);
+            }
+            return;
+        }
+        self.settle(now);
+        if let Some(previous) = self.turn {
+            if self.control.mode == ClockMode::Fischer {
+                self.remaining[previous as usize] += self.bonus();
+            }
+        }
+        self.turn = Some(player);
+        self.delay_left = match self.control.mode {
+            ClockMode::Delay => self.bonus(),
+            ClockMode::Fischer => Duration::ZERO,
+        };
+        self.running_since = Some(now);
+    }
+
+    /// Stop the clock without ending the turn.
+    pub fn pause(&mut self, now: Instant) {
+        self.settle(now);
+        self.running_since = None;
+    }
+
+    /// Time until the running player's flag falls; `None` while paused.
+    pub fn time_to_flag(&self, now: Instant) -> Option<Duration> {
+        let since = self.running_since?;
+        let player = self.turn?;
+        let budget = self.delay_left + self.remaining[player as usize];
+        Some(budget.saturating_sub(now.saturating_duration_since(since)))
+    }
+
+    /// If the running player is out of time, stop the clock and return them.
+    pub fn check_flag(&mut self, now: Instant) -> Option<u16> {
+        if self.time_to_flag(now)? > Duration::ZERO {
+            return None;
+        }
+        self.pause(now);
+        self.flagged = self.turn;
+        self.flagged
+    }
+
+    pub fn view(&self) -> ClockView {
+        ClockView {
+            control: self.control,
+            remaining_ms: self.remaining.map(|d| d.as_millis() as u64),
+            delay_ms: self.delay_left.as_millis() as u64,
+            running: self.running_since.and(self.turn),
+            flagged: self.flagged,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::types::CASUAL_GAME;
+
+    fn control(mode: ClockMode) -> TimeControl {
+        TimeControl {
+            minutes: 1,
+            bonus_secs: 5,
+            mode,
+        }
+    }
+
+    fn secs(s: u64) -> Duration {
+        Duration::from_secs(s)
+    }
+
+    #[test]
+    fn rule_variation_round_trip() {
+        assert_eq!(TimeControl::from_rule_variation(0), None);
+        for tc in TimeControl::PRESETS {
+            assert_eq!(TimeControl::from_rule_variation(tc.to_rule_variation()), Some(tc));
+            let casual = tc.to_rule_variation() | CASUAL_GAME;
+            assert_eq!(TimeControl::from_rule_variation(casual), Some(tc));
+        }
+    }
+
+    #[test]
+    fn fischer_increment_after_turn() {
+        let t0 = Instant::now();
+        let mut clock = GameClock::new(control(ClockMode::Fischer));
+        clock.run(0, t0);
+        clock.run(1, t0 + secs(10));
+        let view = clock.view();
+        assert_eq!(view.remaining_ms, [55_000, 60_000]);
+        assert_eq!(view.running, Some(1));
+    }
+
+    #[test]
+    fn delay_is_not_counted() {
+        let t0 = Instant::now();
+        let mut clock = GameClock::new(control(ClockMode::Delay));
+        clock.run(0, t0);
+        clock.run(1, t0 + secs(3));
+        clock.run(0, t0 + secs(13));
+        assert_eq!(clock.view().remaining_ms, [60_000, 55_000]);
+    }
+
+    #[test]
+    fn pause_stops_time() {
+        let t0 = Instant::now();
+        let mut clock = GameClock::new(control(ClockMode::Fischer));
+        clock.run(0, t0);
+        clock.pause(t0 + secs(10));
+        assert_eq!(clock.time_to_flag(t0 + secs(30)), None);
+        // Resuming the same turn grants no increment.
+        clock.run(0, t0 + secs(30));
+        assert_eq!(clock.view().remaining_ms, [50_000, 60_000]);
+        assert_eq!(clock.time_to_flag(t0 + secs(40)), Some(secs(40)));
+    }
+
+    #[test]
+    fn flag_falls_when_time_is_up() {
+        let t0 = Instant::now();
+        let mut clock = GameClock::new(control(ClockMode::Delay));
+        clock.run(1, t0);
+        assert_eq!(clock.check_flag(t0 + secs(64)), None);
+        assert_eq!(clock.check_flag(t0 + secs(65)), Some(1));
+        let view = clock.view();
+        assert_eq!(view.remaining_ms[1], 0);
+        assert_eq!(view.running, None);
+        // A flagged clock never restarts.
+        clock.run(0, t0 + secs(70));
+        assert_eq!(clock.view().running, None);
+    }
+}
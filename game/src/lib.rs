@@ -0,0 +1,17 @@
+//! Online Trictrac: the state shared between the host and its clients, the
+//! host backend running the game, and the game logic of the web client's
+//! local bot, analysis, replays, puzzles and tutorial.
+//!
+//! Used by the web client and the headless bot client.
+pub mod analysis;
+pub mod announce;
+pub mod backend;
+pub mod blunders;
+pub mod bot_local;
+pub mod clock;
+pub mod mcts;
+pub mod network;
+pub mod puzzle;
+pub mod replay;
+pub mod tutorial;
+pub mod types;
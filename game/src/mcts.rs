@@ -0,0 +1,351 @@
+//! Monte Carlo Tree Search guided by a [`PolicyValueNet`], for the bot.
+//!
+//! This is `spiel_bot`'s AlphaZero search (PUCT selection, dice sampled at
+//! chance nodes and never stored in the tree), on the game state the host
+//! already holds. There is no exploration noise: the bot plays, it does not
+//! train. As in `spiel_bot`, White is player 0, Black is player 1, and the
+//! state is mirrored for Black so that the network always sees White's side.
+
+use rand::Rng;
+use trictrac_store::{
+    training_common::{get_valid_action_indices, TrictracAction},
+    Dice, GameEvent, GameState, Stage, TurnStage,
+};
+
+use super::network::PolicyValueNet;
+
+const WHITE_PLAYER_ID: u64 = 1;
+const BLACK_PLAYER_ID: u64 = 2;
+
+/// Search settings.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MctsConfig {
+    pub n_simulations: usize,
+    /// PUCT exploration constant.
+    pub c_puct: f32,
+    /// `0` plays the most visited action; higher values sample among the
+    /// visited ones, proportionally to `N^(1 / temperature)`.
+    pub temperature: f32,
+}
+
+enum NodeKind {
+    Decision(usize),
+    Chance,
+    Terminal,
+}
+
+fn node_kind(s: &GameState) -> NodeKind {
+    if s.stage == Stage::Ended {
+        return NodeKind::Terminal;
+    }
+    match s.turn_stage {
+        TurnStage::RollDice | TurnStage::RollWaiting => NodeKind::Chance,
+        _ if s.active_player_id == WHITE_PLAYER_ID => NodeKind::Decision(0),
+        _ => NodeKind::Decision(1),
+    }
+}
+
+fn legal_actions(s: &GameState) -> Vec<usize> {
+    let indices = if s.active_player_id == BLACK_PLAYER_ID {
+        get_valid_action_indices(&s.mirror())
+    } else {
+        get_valid_action_indices(s)
+    };
+    indices.unwrap_or_default()
+}
+
+/// The event `action` stands for in `s`, in board coordinates.
+pub fn action_event(s: &GameState, action: usize) -> Option<GameEvent> {
+    let action = TrictracAction::from_action_index(action)?;
+    if s.active_player_id == BLACK_PLAYER_ID {
+        action.to_event(&s.mirror()).map(|e| e.get_mirror(false))
+    } else {
+        action.to_event(s)
+    }
+}
+
+fn apply(s: &mut GameState, action: usize) -> bool {
+    action_event(s, action).is_some_and(|e| s.consume(&e).is_ok())
+}
+
+fn apply_chance(s: &mut GameState, rng: &mut impl Rng) {
+    if s.turn_stage == TurnStage::RollDice {
+        let _ = s.consume(&GameEvent::Roll {
+            player_id: s.active_player_id,
+        });
+    }
+    let dice = Dice {
+        values: (rng.random_range(1u8..=6), rng.random_range(1u8..=6)),
+    };
+    let _ = s.consume(&GameEvent::RollResult {
+        player_id: s.active_player_id,
+        dice,
+    });
+}
+
+fn observation(s: &GameState, player: usize) -> Vec<f32> {
+    if player == 0 {
+        s.to_tensor()
+    } else {
+        s.mirror().to_tensor()
+    }
+}
+
+/// +1 for the player ahead at the end (holes × 12 + points), -1 behind, 0 even.
+fn returns(s: &GameState, player: usize) -> f32 {
+    let score = |id: u64| {
+        s.players
+            .get(&id)
+            .map_or(0, |p| p.holes as i32 * 12 + p.points as i32)
+    };
+    let diff = score(WHITE_PLAYER_ID) - score(BLACK_PLAYER_ID);
+    let white = diff.signum() as f32;
+    if player == 0 {
+        white
+    } else {
+        -white
+    }
+}
+
+fn masked_softmax(logits: &[f32], legal: &[usize]) -> Vec<f32> {
+    let max = legal
+        .iter()
+        .map(|&a| logits.get(a).copied().unwrap_or(0.0))
+        .fold(f32::NEG_INFINITY, f32::max);
+    let exps: Vec<f32> = legal
+        .iter()
+        .map(|&a| (logits.get(a).copied().unwrap_or(0.0) - max).exp())
+        .collect();
+    let sum: f32 = exps.iter().sum();
+    if sum > 0.0 {
+        exps.into_iter().map(|e| e / sum).collect()
+    } else {
+        vec![1.0 / legal.len() as f32; legal.len()]
+    }
+}
+
+/// One decision position of the tree. `w` is summed from the point of view
+/// of the player choosing this node in its parent.
+struct Node {
+    n: u32,
+    w: f32,
+    p: f32,
+    children: Vec<(usize, Node)>,
+    expanded: bool,
+}
+
+impl Node {
+    fn new(p: f32) -> Self {
+        Node {
+            n: 0,
+            w: 0.0,
+            p,
+            children: Vec::new(),
+            expanded: false,
+        }
+    }
+
+    fn q(&self) -> f32 {
+        if self.n == 0 {
+            0.0
+        } else {
+            self.w / self.n as f32
+        }
+    }
+
+    fn puct(&self, parent_n: u32, c_puct: f32) -> f32 {
+        self.q() + c_puct * self.p * (parent_n as f32).sqrt() / (1.0 + self.n as f32)
+    }
+
+    /// Evaluates `s` and adds its legal actions as children. Returns the
+    /// value for `player`, who acts in `s`.
+    fn expand(&mut self, s: &GameState, net: &PolicyValueNet, player: usize) -> f32 {
+        let legal = legal_actions(s);
+        let (logits, value) = net.evaluate(&observation(s, player));
+        let priors = masked_softmax(&logits, &legal);
+        self.children = legal.into_iter().zip(priors).map(|(a, p)| (a, Node::new(p))).collect();
+        self.expanded = true;
+        self.n = 1;
+        self.w = value;
+        value
+    }
+}
+
+/// One simulation below the expanded `node`, where `player` acts. Returns the
+/// value for `player`.
+fn simulate(
+    node: &mut Node,
+    state: &GameState,
+    net: &PolicyValueNet,
+    config: &MctsConfig,
+    rng: &mut impl Rng,
+    player: usize,
+) -> f32 {
+    let parent_n = node.n;
+    let Some(best) = (0..node.children.len()).max_by(|&a, &b| {
+        let pa = node.children[a].1.puct(parent_n, config.c_puct);
+        let pb = node.children[b].1.puct(parent_n, config.c_puct);
+        pa.partial_cmp(&pb).unwrap_or(std::cmp::Ordering::Equal)
+    }) else {
+        return 0.0;
+    };
+    let (action, child) = &mut node.children[best];
+
+    let mut next = state.clone();
+    if !apply(&mut next, *action) {
+        // Cannot happen with actions from `legal_actions`; score it as a loss.
+        child.n += 1;
+        child.w -= 1.0;
+        node.n += 1;
+        node.w -= 1.0;
+        return -1.0;
+    }
+    let mut crossed_chance = false;
+    while matches!(node_kind(&next), NodeKind::Chance) {
+        apply_chance(&mut next, rng);
+        crossed_chance = true;
+    }
+
+    let value = match node_kind(&next) {
+        NodeKind::Terminal => {
+            let v = returns(&next, player);
+            child.n += 1;
+            child.w += v;
+            v
+        }
+        NodeKind::Chance => unreachable!("chance nodes are resolved above"),
+        NodeKind::Decision(next_player) => {
+            let v = if crossed_chance {
+                // Other dice, other legal actions: the position is only
+                // evaluated, the tree stops at chance nodes.
+                let (_, v) = net.evaluate(&observation(&next, next_player));
+                child.n += 1;
+                child.w += if next_player == player { v } else { -v };
+                v
+            } else if child.expanded {
+                simulate(child, &next, net, config, rng, next_player)
+            } else {
+                child.expand(&next, net, next_player)
+            };
+            if next_player == player {
+                v
+            } else {
+                -v
+            }
+        }
+    };
+    node.n += 1;
+    node.w += value;
+    value
+}
+
+/// Searches `game` and returns the chosen action index, or `None` when no
+/// player has to choose an action.
+pub fn search(
+    game: &GameState,
+    net: &PolicyValueNet,
+    config: &MctsConfig,
+    rng: &mut impl Rng,
+) -> Option<usize> {
+    let NodeKind::Decision(player) = node_kind(game) else {
+        return None;
+    };
+    let mut root = Node::new(1.0);
+    root.expand(game, net, player);
+    match root.children.len() {
+        0 => return None,
+        1 => return Some(root.children[0].0),
+        _ => {}
+    }
+    for _ in 0..config.n_simulations {
+        simulate(&mut root, game, net, config, rng, player);
+    }
+
+    if config.temperature <= 0.0 {
+        return root
+            .children
+            .iter()
+            .max_by(|(_, a), (_, b)| {
+                a.n.cmp(&b.n)
+                    .then(a.p.partial_cmp(&b.p).unwrap_or(std::cmp::Ordering::Equal))
+            })
+            .map(|(a, _)| *a);
+    }
+    let weights: Vec<f32> = root
+        .children
+        .iter()
+        .map(|(_, c)| (c.n as f32).powf(1.0 / config.temperature))
+        .collect();
+    let total: f32 = weights.iter().sum();
+    if total <= 0.0 {
+        return root.children.first().map(|(a, _)| *a);
+    }
+    let mut r = rng.random::<f32>() * total;
+    for ((a, _), w) in root.children.iter().zip(weights) {
+        r -= w;
+        if r <= 0.0 {
+            return Some(*a);
+        }
+    }
+    root.children.last().map(|(a, _)| *a)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::network::tests::exported;
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    fn rolled(dice: (u8, u8)) -> GameState {
+        let mut game = GameState::new_with_players("White", "Black");
+        game.consume(&GameEvent::Roll { player_id: WHITE_PLAYER_ID }).unwrap();
+        game.consume(&GameEvent::RollResult {
+            player_id: WHITE_PLAYER_ID,
+            dice: Dice { values: dice },
+        })
+        .unwrap();
+        game
+    }
+
+    fn uniform_net() -> PolicyValueNet {
+        PolicyValueNet::from_bytes(&exported(0, 217, 514, 4, 0.0)).unwrap()
+    }
+
+    #[test]
+    fn search_picks_a_legal_action_for_both_colors() {
+        let net = uniform_net();
+        let config = MctsConfig {
+            n_simulations: 16,
+            c_puct: 1.5,
+            temperature: 1.0,
+        };
+        let mut rng = SmallRng::seed_from_u64(7);
+
+        let white = rolled((3, 5));
+        let action = search(&white, &net, &config, &mut rng).unwrap();
+        assert!(legal_actions(&white).contains(&action));
+        assert!(white.validate(&action_event(&white, action).unwrap()));
+
+        // Play White's move and Black's roll, then search for Black.
+        let mut black = white.clone();
+        assert!(apply(&mut black, action));
+        while matches!(node_kind(&black), NodeKind::Chance) {
+            apply_chance(&mut black, &mut rng);
+        }
+        if let NodeKind::Decision(1) = node_kind(&black) {
+            let action = search(&black, &net, &config, &mut rng).unwrap();
+            assert!(black.validate(&action_event(&black, action).unwrap()));
+        }
+    }
+
+    #[test]
+    fn no_search_while_dice_are_to_roll() {
+        let game = GameState::new_with_players("White", "Black");
+        let config = MctsConfig {
+            n_simulations: 4,
+            c_puct: 1.5,
+            temperature: 0.0,
+        };
+        assert_eq!(search(&game, &uniform_net(), &config, &mut SmallRng::seed_from_u64(1)), None);
+    }
+}
@@ -0,0 +1,239 @@
+//! Policy-value networks trained by `spiel_bot`, evaluated without Burn.
+//!
+//! `spiel_bot`'s `az_export` writes the weights of an `MlpNet` or `ResNet`
+//! checkpoint in a flat little-endian file (layout documented in
+//! `spiel_bot/src/network/export.rs`). [`PolicyValueNet`] reads it back and
+//! runs the same forward pass with plain `f32` arithmetic, which is all the
+//! browser needs to play.
+
+/// Little-endian header of an exported network.
+const MAGIC: &[u8; 4] = b"TTNW";
+const VERSION: u32 = 1;
+const ARCH_MLP: u32 = 0;
+const ARCH_RESNET: u32 = 1;
+/// Residual blocks of a `ResNet`.
+const RESNET_BLOCKS: usize = 4;
+
+/// One fully connected layer: `y = x · W + b`.
+struct Linear {
+    d_input: usize,
+    d_output: usize,
+    /// Row-major `[d_input, d_output]`, as Burn stores it.
+    weight: Vec<f32>,
+    bias: Vec<f32>,
+}
+
+impl Linear {
+    fn forward(&self, x: &[f32]) -> Vec<f32> {
+        let mut y = self.bias.clone();
+        for (i, &xi) in x.iter().enumerate().take(self.d_input) {
+            // Observations and ReLU outputs are mostly zeros.
+            if xi == 0.0 {
+                continue;
+            }
+            let row = &self.weight[i * self.d_output..(i + 1) * self.d_output];
+            for (yj, wij) in y.iter_mut().zip(row) {
+                *yj += xi * wij;
+            }
+        }
+        y
+    }
+}
+
+fn relu(mut x: Vec<f32>) -> Vec<f32> {
+    for v in x.iter_mut() {
+        *v = v.max(0.0);
+    }
+    x
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Arch {
+    Mlp,
+    ResNet,
+}
+
+/// A trained policy-value network.
+pub struct PolicyValueNet {
+    arch: Arch,
+    obs_size: usize,
+    action_size: usize,
+    /// In forward order; see the export layout.
+    layers: Vec<Linear>,
+}
+
+/// Reads the exported file front to back.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn take(&mut self, n: usize) -> Result<&[u8], String> {
+        if self.bytes.len() < n {
+            return Err("truncated network file".to_string());
+        }
+        let (head, rest) = self.bytes.split_at(n);
+        self.bytes = rest;
+        Ok(head)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let b = self.take(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn f32s(&mut self, n: usize) -> Result<Vec<f32>, String> {
+        let len = n.checked_mul(4).ok_or("network too large")?;
+        Ok(self
+            .take(len)?
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect())
+    }
+
+    fn linear(&mut self, d_input: usize, d_output: usize) -> Result<Linear, String> {
+        let (i, o) = (self.u32()? as usize, self.u32()? as usize);
+        if (i, o) != (d_input, d_output) {
+            return Err(format!(
+                "layer is {i}×{o}, expected {d_input}×{d_output}"
+            ));
+        }
+        Ok(Linear {
+            d_input,
+            d_output,
+            weight: self.f32s(d_input * d_output)?,
+            bias: self.f32s(d_output)?,
+        })
+    }
+}
+
+impl PolicyValueNet {
+    /// Reads a network exported by `az_export`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let mut r = Reader { bytes };
+        if r.take(4)? != MAGIC {
+            return Err("not a network file".to_string());
+        }
+        let version = r.u32()?;
+        if version != VERSION {
+            return Err(format!("unsupported network file version {version}"));
+        }
+        let arch = match r.u32()? {
+            ARCH_MLP => Arch::Mlp,
+            ARCH_RESNET => Arch::ResNet,
+            other => return Err(format!("unknown network architecture {other}")),
+        };
+        let obs_size = r.u32()? as usize;
+        let action_size = r.u32()? as usize;
+        let hidden = r.u32()? as usize;
+
+        let mut layers = vec![r.linear(obs_size, hidden)?];
+        let trunk = match arch {
+            Arch::Mlp => 1,
+            Arch::ResNet => 2 * RESNET_BLOCKS,
+        };
+        for _ in 0..trunk {
+            layers.push(r.linear(hidden, hidden)?);
+        }
+        layers.push(r.linear(hidden, action_size)?);
+        layers.push(r.linear(hidden, 1)?);
+        if !r.bytes.is_empty() {
+            return Err("trailing bytes after the last layer".to_string());
+        }
+        Ok(PolicyValueNet {
+            arch,
+            obs_size,
+            action_size,
+            layers,
+        })
+    }
+
+    pub fn obs_size(&self) -> usize {
+        self.obs_size
+    }
+
+    pub fn action_size(&self) -> usize {
+        self.action_size
+    }
+
+    /// Raw policy logits, one per action, and the value in (-1, 1) for the
+    /// player the observation was taken for.
+    pub fn evaluate(&self, obs: &[f32]) -> (Vec<f32>, f32) {
+        let n = self.layers.len();
+        let mut x = relu(self.layers[0].forward(obs));
+        match self.arch {
+            Arch::Mlp => x = relu(self.layers[1].forward(&x)),
+            Arch::ResNet => {
+                for block in self.layers[1..n - 2].chunks_exact(2) {
+                    let out = relu(block[0].forward(&x));
+                    let mut out = block[1].forward(&out);
+                    for (o, r) in out.iter_mut().zip(&x) {
+                        *o += r;
+                    }
+                    x = relu(out);
+                }
+            }
+        }
+        let policy = self.layers[n - 2].forward(&x);
+        let value = self.layers[n - 1].forward(&x)[0].tanh();
+        (policy, value)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Bytes of a network whose layers are all `weight` and zero biases.
+    pub(crate) fn exported(arch: u32, obs: usize, actions: usize, hidden: usize, weight: f32) -> Vec<u8> {
+        let mut b = MAGIC.to_vec();
+        for v in [VERSION, arch, obs as u32, actions as u32, hidden as u32] {
+            b.extend_from_slice(&v.to_le_bytes());
+        }
+        let trunk = if arch == ARCH_MLP { 1 } else { 2 * RESNET_BLOCKS };
+        let dims = std::iter::once((obs, hidden))
+            .chain(std::iter::repeat_n((hidden, hidden), trunk))
+            .chain([(hidden, actions), (hidden, 1)]);
+        for (i, o) in dims {
+            b.extend_from_slice(&(i as u32).to_le_bytes());
+            b.extend_from_slice(&(o as u32).to_le_bytes());
+            for _ in 0..i * o {
+                b.extend_from_slice(&weight.to_le_bytes());
+            }
+            for _ in 0..o {
+                b.extend_from_slice(&0f32.to_le_bytes());
+            }
+        }
+        b
+    }
+
+    #[test]
+    fn mlp_forward_pass() {
+        let net = PolicyValueNet::from_bytes(&exported(ARCH_MLP, 3, 2, 2, 0.5)).unwrap();
+        // [1, 0, 1] → fc1: [1, 1] → fc2: [1, 1] → policy [1, 1], value tanh(1).
+        let (policy, value) = net.evaluate(&[1.0, 0.0, 1.0]);
+        assert_eq!(policy, vec![1.0, 1.0]);
+        assert!((value - 1f32.tanh()).abs() < 1e-6);
+        let (_, value) = net.evaluate(&[-1.0, 0.0, 0.0]);
+        assert_eq!(value, 0.0);
+    }
+
+    #[test]
+    fn resnet_adds_the_skip_connections() {
+        let net = PolicyValueNet::from_bytes(&exported(ARCH_RESNET, 1, 1, 1, 1.0)).unwrap();
+        // Each block doubles its input: relu(x·1·1 + x).
+        let (policy, _) = net.evaluate(&[1.0]);
+        assert_eq!(policy, vec![16.0]);
+    }
+
+    #[test]
+    fn malformed_files_are_rejected() {
+        let good = exported(ARCH_MLP, 3, 2, 2, 0.5);
+        assert!(PolicyValueNet::from_bytes(&good[..good.len() - 1]).is_err());
+        assert!(PolicyValueNet::from_bytes(&[good.as_slice(), &[0]].concat()).is_err());
+        assert!(PolicyValueNet::from_bytes(b"PK\x03\x04").is_err());
+        let mut other_arch = good.clone();
+        other_arch[8] = 7;
+        assert!(PolicyValueNet::from_bytes(&other_arch).is_err());
+    }
+}
@@ -0,0 +1,167 @@
+//! Puzzles: curated positions with the sequences that solve them.
+//!
+//! The relay stores a puzzle's data without looking into it; for trictrac it
+//! is a [`Puzzle`] in JSON. White is to play the dice, and any of the listed
+//! solutions, in either order, solves it. Answers are checked by the store's
+//! rules like the tutorial's exercises, so a refused move comes with the
+//! [`trictrac_store::MoveError`] that explains it.
+
+use serde::{Deserialize, Serialize};
+use trictrac_store::{CheckerMove, Color};
+
+use super::analysis::{analyse, CHECKERS_PER_PLAYER};
+use super::tutorial::{check_sequence, roll_points, Goal, Verdict};
+use super::types::JanEntry;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Puzzle {
+    /// White's `(field, checkers)`.
+    pub white: Vec<(usize, u8)>,
+    /// Black's `(field, checkers)`.
+    pub black: Vec<(usize, u8)>,
+    pub dice: (u8, u8),
+    /// Third roll of the game, when the jan de six tables can be made.
+    #[serde(default)]
+    pub third_roll: bool,
+    /// The sequences that solve the puzzle, as `(from, to)` moves; 0 bears off.
+    pub solutions: Vec<[(usize, usize); 2]>,
+}
+
+impl Puzzle {
+    /// Parses and checks a puzzle's data.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let puzzle: Puzzle = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        puzzle.validate()?;
+        Ok(puzzle)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for side in [&self.white, &self.black] {
+            if side.iter().any(|&(field, _)| !(1..=24).contains(&field)) {
+                return Err("fields go from 1 to 24".into());
+            }
+            if side.iter().map(|&(_, n)| n as u32).sum::<u32>() > CHECKERS_PER_PLAYER as u32 {
+                return Err(format!("a player has at most {CHECKERS_PER_PLAYER} checkers"));
+            }
+        }
+        if self.white.iter().any(|w| self.black.iter().any(|b| b.0 == w.0)) {
+            return Err("a field holds the checkers of one player only".into());
+        }
+        if ![self.dice.0, self.dice.1].iter().all(|d| (1..=6).contains(d)) {
+            return Err("dice go from 1 to 6".into());
+        }
+        if self.solutions.is_empty() {
+            return Err("a puzzle needs a solution".into());
+        }
+        Ok(())
+    }
+
+    /// White to play, in board coordinates.
+    pub fn board(&self) -> [i8; 24] {
+        let mut board = [0i8; 24];
+        for &(field, n) in &self.white {
+            board[field - 1] = n as i8;
+        }
+        for &(field, n) in &self.black {
+            board[field - 1] = -(n as i8);
+        }
+        board
+    }
+
+    fn goal(&self) -> Goal {
+        Goal::Play(
+            self.solutions
+                .iter()
+                .map(|&[(f1, t1), (f2, t2)]| {
+                    (
+                        CheckerMove::new(f1, t1).unwrap_or_default(),
+                        CheckerMove::new(f2, t2).unwrap_or_default(),
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    pub fn check_moves(&self, moves: (CheckerMove, CheckerMove)) -> Verdict {
+        check_sequence(&self.board(), self.dice, self.third_roll, &self.goal(), moves)
+    }
+
+    /// Everything the roll scores.
+    pub fn jans(&self) -> Vec<JanEntry> {
+        analyse(&self.board(), self.dice, Color::White, self.third_roll).jans
+    }
+
+    /// Points the roll scores for White and for Black.
+    pub fn points(&self) -> (u8, u8) {
+        roll_points(&self.board(), self.dice, self.third_roll)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trictrac_store::{Jan, MoveError};
+
+    /// The puzzles the relay ships, as `(slug, puzzle)`.
+    fn shipped() -> Vec<(String, Puzzle)> {
+        let file: serde_json::Value = serde_json::from_str(include_str!(
+            "../../server/relay-server/puzzles/trictrac.json"
+        ))
+        .unwrap();
+        file["puzzles"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| {
+                let slug = p["slug"].as_str().unwrap().to_string();
+                let puzzle = Puzzle::from_json(&p["puzzle"].to_string()).unwrap();
+                (slug, puzzle)
+            })
+            .collect()
+    }
+
+    fn puzzle(slug: &str) -> Puzzle {
+        shipped().into_iter().find(|(s, _)| s == slug).unwrap().1
+    }
+
+    fn seq(m1: (usize, usize), m2: (usize, usize)) -> (CheckerMove, CheckerMove) {
+        (CheckerMove::new(m1.0, m1.1).unwrap(), CheckerMove::new(m2.0, m2.1).unwrap())
+    }
+
+    #[test]
+    fn shipped_puzzles_are_solvable() {
+        for (slug, p) in shipped() {
+            for &[m1, m2] in &p.solutions {
+                assert_eq!(p.check_moves(seq(m1, m2)), Verdict::Correct, "{slug}");
+            }
+        }
+    }
+
+    #[test]
+    fn answers_are_judged_by_the_rules() {
+        let keep = puzzle("keep-the-small-jan");
+        assert_eq!(keep.check_moves(seq((1, 6), (1, 3))), Verdict::Illegal(Some(MoveError::MustFillQuarter)));
+        assert!(keep.jans().iter().any(|e| e.jan == Jan::FilledQuarter));
+
+        let corner = puzzle("rest-corner-by-effect");
+        assert_eq!(corner.check_moves(seq((1, 7), (1, 6))), Verdict::Missed);
+        assert_eq!(corner.check_moves(seq((7, 12), (6, 12))), Verdict::Correct);
+
+        let fill = puzzle("fill-the-small-jan");
+        assert_eq!(fill.check_moves(seq((1, 6), (6, 7))), Verdict::Illegal(Some(MoveError::MustFillQuarter)));
+
+        let six_tables = puzzle("six-tables");
+        assert!(six_tables.jans().iter().any(|e| e.jan == Jan::SixTables));
+        assert!(six_tables.points().0 > 0);
+    }
+
+    #[test]
+    fn malformed_puzzles_are_refused() {
+        let base = r#""black": [[24, 15]], "dice": [3, 2], "solutions": [[[1, 4], [1, 3]]]"#;
+        assert!(Puzzle::from_json(&format!(r#"{{"white": [[1, 15]], {base}}}"#)).is_ok());
+        assert!(Puzzle::from_json(&format!(r#"{{"white": [[1, 16]], {base}}}"#)).is_err());
+        assert!(Puzzle::from_json(&format!(r#"{{"white": [[25, 1]], {base}}}"#)).is_err());
+        assert!(Puzzle::from_json(&format!(r#"{{"white": [[24, 1]], {base}}}"#)).is_err());
+        assert!(Puzzle::from_json(r#"{"white": [[1, 15]], "black": [], "dice": [7, 2], "solutions": []}"#).is_err());
+    }
+}
@@ -0,0 +1,153 @@
+//! Rebuilding the positions of a recorded game, for the replay viewer.
+//!
+//! A [`GameRecord`] is the event list of a game together with the player
+//! names. The host uploads it with the result, and it can be saved to and
+//! loaded from a file. [`replay`] feeds the events one by one to a fresh
+//! `GameState` and keeps the position after each player action.
+
+use serde::{Deserialize, Serialize};
+use trictrac_store::{GameEvent, GameState};
+
+use super::types::{ViewState, SCHEMA_VERSION};
+
+// Store PlayerId values of host and guest, as in the backend.
+const HOST_PLAYER_ID: u64 = 1;
+const GUEST_PLAYER_ID: u64 = 2;
+
+/// The events of one game, as uploaded with its result or saved to a file.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GameRecord {
+    /// [`SCHEMA_VERSION`] of the page that recorded the game.
+    pub schema_version: u16,
+    /// Host (White) and guest (Black) names.
+    pub players: [String; 2],
+    pub events: Vec<GameEvent>,
+    /// Hints each player asked the bot for; none in rated games.
+    #[serde(default)]
+    pub hints_used: [u16; 2],
+}
+
+impl GameRecord {
+    pub fn from_view_state(vs: &ViewState) -> Self {
+        GameRecord {
+            schema_version: SCHEMA_VERSION,
+            players: [vs.scores[0].name.clone(), vs.scores[1].name.clone()],
+            events: vs.history.clone(),
+            hints_used: vs.hints.used,
+        }
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        serde_json::from_str(text.trim()).map_err(|e| e.to_string())
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("game record serializes")
+    }
+}
+
+/// One position of a replay.
+#[derive(Clone)]
+pub struct ReplayStep {
+    /// The position, without history.
+    pub view_state: ViewState,
+    /// Number of rolls made so far; 0 before the first one.
+    pub turn: u16,
+}
+
+/// The event at `index` could not be played in the position before it.
+#[derive(Debug, PartialEq)]
+pub struct ReplayError {
+    pub index: usize,
+}
+
+/// The positions of `record`: the starting one, then one per player action.
+pub fn replay(record: &GameRecord) -> Result<Vec<ReplayStep>, ReplayError> {
+    let mut game = GameState::new(false);
+    game.init_player(&record.players[0]);
+    game.init_player(&record.players[1]);
+
+    let mut turn = 0;
+    let mut steps = vec![ReplayStep {
+        view_state: ViewState::from_game_state(&game, HOST_PLAYER_ID, GUEST_PLAYER_ID),
+        turn,
+    }];
+    for (index, event) in record.events.iter().enumerate() {
+        if !game.validate(event) || game.consume(event).is_err() {
+            return Err(ReplayError { index });
+        }
+        // The steps do not need the history; keeping it would copy it into each one.
+        game.history.clear();
+        if matches!(event, GameEvent::RollResult { .. }) {
+            turn += 1;
+        }
+        if ends_step(event, record.events.get(index + 1)) {
+            steps.push(ReplayStep {
+                view_state: ViewState::from_game_state(&game, HOST_PLAYER_ID, GUEST_PLAYER_ID),
+                turn,
+            });
+        }
+    }
+    Ok(steps)
+}
+
+/// A roll is shown with its result and a mark with what it scored, so that
+/// each step is one thing a player did.
+fn ends_step(event: &GameEvent, next: Option<&GameEvent>) -> bool {
+    !matches!(event, GameEvent::BeginGame { .. } | GameEvent::Roll { .. })
+        && !matches!(next, Some(GameEvent::Mark { .. }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trictrac_store::{CheckerMove, Dice};
+
+    fn opening() -> Vec<GameEvent> {
+        vec![
+            GameEvent::BeginGame { goes_first: HOST_PLAYER_ID },
+            GameEvent::Roll { player_id: HOST_PLAYER_ID },
+            GameEvent::RollResult {
+                player_id: HOST_PLAYER_ID,
+                dice: Dice { values: (3, 5) },
+            },
+        ]
+    }
+
+    fn record(events: Vec<GameEvent>) -> GameRecord {
+        GameRecord {
+            schema_version: SCHEMA_VERSION,
+            players: ["Alice".to_string(), "Bob".to_string()],
+            events,
+            hints_used: [0; 2],
+        }
+    }
+
+    #[test]
+    fn roll_is_one_step() {
+        let steps = replay(&record(opening())).unwrap();
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0].turn, 0);
+        assert_eq!(steps[1].turn, 1);
+        assert_eq!(steps[1].view_state.dice, (3, 5));
+        assert_eq!(steps[1].view_state.scores[1].name, "Bob");
+        assert!(steps[1].view_state.history.is_empty());
+    }
+
+    #[test]
+    fn impossible_event_is_reported() {
+        let mut events = opening();
+        events.push(GameEvent::Move {
+            player_id: GUEST_PLAYER_ID,
+            moves: (CheckerMove::default(), CheckerMove::default()),
+        });
+        assert_eq!(replay(&record(events)).err(), Some(ReplayError { index: 3 }));
+    }
+
+    #[test]
+    fn record_round_trips_through_json() {
+        let r = record(opening());
+        assert_eq!(GameRecord::from_json(&r.to_json()), Ok(r));
+        assert!(GameRecord::from_json("not a record").is_err());
+    }
+}
@@ -0,0 +1,381 @@
+//! Lessons of the tutorial.
+//!
+//! Each exercise is a scripted position, with White to play a roll, and what
+//! the learner is expected to do with it: play any legal sequence, play a
+//! given sequence, make a jan, or mark the points the roll scores. The moves
+//! are checked by the store's rules exactly as in a game, so a refused move
+//! comes with the [`MoveError`] that explains it.
+
+use trictrac_store::{Board, CheckerMove, Color, Dice, Jan, MoveError, MoveRules, PointsRules};
+
+use super::analysis::analyse;
+use super::types::JanEntry;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LessonKey {
+    Moving,
+    RestCorner,
+    Exits,
+    Filling,
+    Jans,
+}
+
+/// Names the text of an exercise.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExerciseKey {
+    FirstMove,
+    CornerByEffect,
+    CornerByPuissance,
+    CornerLeaving,
+    ExitExact,
+    ExitAllHome,
+    FillSmallJan,
+    KeepSmallJan,
+    OpponentQuarter,
+    TrueHitSmallJan,
+    TrueHitBigJan,
+    FalseHit,
+    TrueHitCorner,
+    TwoTables,
+    ContreTwoTables,
+    Mezeas,
+    SixTables,
+    FirstToExit,
+    HelplessMan,
+}
+
+/// What the learner has to do with the roll.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Goal {
+    /// Play any legal sequence.
+    AnyLegal,
+    /// Play one of these sequences, in either order.
+    Play(Vec<(CheckerMove, CheckerMove)>),
+    /// Play a legal sequence that makes this jan.
+    MakeJan(Jan),
+    /// Mark the points the roll scores, for White and for Black.
+    Mark,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Exercise {
+    pub key: ExerciseKey,
+    /// White to play, in board coordinates.
+    pub board: [i8; 24],
+    pub dice: (u8, u8),
+    /// Third roll of the game, when the jan de six tables can be made.
+    pub third_roll: bool,
+    pub goal: Goal,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lesson {
+    pub key: LessonKey,
+    pub exercises: Vec<Exercise>,
+}
+
+/// Outcome of an answer.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Verdict {
+    Correct,
+    /// The rules refuse the moves; `None` when they are not possible on the
+    /// board or with the dice.
+    Illegal(Option<MoveError>),
+    /// Legal, but not what the exercise asks.
+    Missed,
+    /// The roll scores these points, for White and for Black.
+    WrongMark((u8, u8)),
+}
+
+impl Exercise {
+    fn new(key: ExerciseKey, board: [i8; 24], dice: (u8, u8), goal: Goal) -> Self {
+        Exercise {
+            key,
+            board,
+            dice,
+            third_roll: false,
+            goal,
+        }
+    }
+
+    /// Everything the roll scores.
+    pub fn jans(&self) -> Vec<JanEntry> {
+        analyse(&self.board, self.dice, Color::White, self.third_roll).jans
+    }
+
+    /// Points the roll scores for White and for Black.
+    pub fn points(&self) -> (u8, u8) {
+        roll_points(&self.board, self.dice, self.third_roll)
+    }
+
+    pub fn check_moves(&self, moves: (CheckerMove, CheckerMove)) -> Verdict {
+        check_sequence(&self.board, self.dice, self.third_roll, &self.goal, moves)
+    }
+
+    pub fn check_mark(&self, points: (u8, u8)) -> Verdict {
+        let expected = self.points();
+        if points == expected {
+            Verdict::Correct
+        } else {
+            Verdict::WrongMark(expected)
+        }
+    }
+}
+
+/// The store's board for White's `board`.
+pub(super) fn store_board(board: &[i8; 24]) -> Board {
+    let mut store = Board::new();
+    store.set_positions(&Color::White, *board);
+    store
+}
+
+/// Points the roll scores for White and for Black, White to play.
+pub(super) fn roll_points(board: &[i8; 24], dice: (u8, u8), third_roll: bool) -> (u8, u8) {
+    PointsRules::new(&Color::White, &store_board(board), Dice { values: dice })
+        .get_points(if third_roll { 3 } else { 0 })
+}
+
+/// Checks White's `moves` with `dice` on `board` against the rules, then
+/// against `goal`.
+pub(super) fn check_sequence(
+    board: &[i8; 24],
+    dice: (u8, u8),
+    third_roll: bool,
+    goal: &Goal,
+    moves: (CheckerMove, CheckerMove),
+) -> Verdict {
+    let rules = MoveRules::new(&Color::White, &store_board(board), Dice { values: dice });
+    if !rules.moves_follow_rules(&moves) {
+        return Verdict::Illegal(rules.moves_allowed(&moves).err());
+    }
+    let swapped = (moves.1, moves.0);
+    let reached = match goal {
+        Goal::AnyLegal | Goal::Mark => true,
+        Goal::Play(solutions) => solutions.contains(&moves) || solutions.contains(&swapped),
+        Goal::MakeJan(jan) => analyse(board, dice, Color::White, third_roll)
+            .jans
+            .iter()
+            .any(|e| e.jan == *jan && (e.moves.contains(&moves) || e.moves.contains(&swapped))),
+    };
+    if reached {
+        Verdict::Correct
+    } else {
+        Verdict::Missed
+    }
+}
+
+/// A board from White's and Black's `(field, checkers)`.
+fn position(white: &[(usize, i8)], black: &[(usize, i8)]) -> [i8; 24] {
+    let mut board = [0i8; 24];
+    for &(field, n) in white {
+        board[field - 1] = n;
+    }
+    for &(field, n) in black {
+        board[field - 1] = -n;
+    }
+    board
+}
+
+fn seq(m1: (usize, usize), m2: (usize, usize)) -> (CheckerMove, CheckerMove) {
+    (
+        CheckerMove::new(m1.0, m1.1).unwrap_or_default(),
+        CheckerMove::new(m2.0, m2.1).unwrap_or_default(),
+    )
+}
+
+/// The lessons, in teaching order.
+pub fn lessons() -> Vec<Lesson> {
+    use ExerciseKey::*;
+
+    let black_talon = [(24, 15)];
+    let jan = |key, board, dice| Exercise::new(key, board, dice, Goal::Mark);
+    vec![
+        Lesson {
+            key: LessonKey::Moving,
+            exercises: vec![Exercise::new(
+                FirstMove,
+                position(&[(1, 15)], &black_talon),
+                (6, 5),
+                Goal::AnyLegal,
+            )],
+        },
+        Lesson {
+            key: LessonKey::RestCorner,
+            exercises: vec![
+                Exercise::new(
+                    CornerByEffect,
+                    position(&[(1, 13), (6, 1), (7, 1)], &black_talon),
+                    (6, 5),
+                    Goal::Play(vec![seq((6, 12), (7, 12))]),
+                ),
+                Exercise::new(
+                    CornerByPuissance,
+                    position(&[(1, 13), (7, 2)], &black_talon),
+                    (6, 6),
+                    Goal::Play(vec![seq((7, 12), (7, 12))]),
+                ),
+                Exercise::new(
+                    CornerLeaving,
+                    position(&[(1, 10), (4, 3), (12, 2)], &black_talon),
+                    (2, 3),
+                    Goal::AnyLegal,
+                ),
+            ],
+        },
+        Lesson {
+            key: LessonKey::Exits,
+            exercises: vec![
+                Exercise::new(
+                    ExitExact,
+                    position(&[(19, 2), (20, 3), (23, 2)], &[(1, 15)]),
+                    (5, 5),
+                    Goal::Play(vec![seq((20, 0), (20, 0))]),
+                ),
+                Exercise::new(
+                    ExitAllHome,
+                    position(&[(17, 1), (20, 3), (22, 2), (23, 2)], &[(1, 15)]),
+                    (5, 5),
+                    Goal::Play(vec![seq((17, 22), (20, 0))]),
+                ),
+            ],
+        },
+        Lesson {
+            key: LessonKey::Filling,
+            exercises: vec![
+                Exercise::new(
+                    FillSmallJan,
+                    position(&[(1, 5), (2, 2), (3, 2), (4, 2), (5, 2), (8, 2)], &black_talon),
+                    (5, 5),
+                    Goal::MakeJan(Jan::FilledQuarter),
+                ),
+                Exercise::new(
+                    KeepSmallJan,
+                    position(&[(1, 5), (2, 2), (3, 2), (4, 2), (5, 2), (6, 2)], &black_talon),
+                    (6, 4),
+                    Goal::MakeJan(Jan::FilledQuarter),
+                ),
+                Exercise::new(
+                    OpponentQuarter,
+                    position(&[(1, 11), (10, 2), (11, 2)], &black_talon),
+                    (5, 4),
+                    Goal::AnyLegal,
+                ),
+            ],
+        },
+        Lesson {
+            key: LessonKey::Jans,
+            exercises: vec![
+                jan(TrueHitSmallJan, position(&[(1, 15)], &[(5, 1), (24, 14)]), (2, 2)),
+                jan(TrueHitBigJan, position(&[(1, 15)], &[(9, 1), (24, 14)]), (4, 4)),
+                jan(FalseHit, position(&[(1, 15)], &[(5, 2), (9, 1), (24, 12)]), (4, 4)),
+                jan(
+                    TrueHitCorner,
+                    position(&[(1, 9), (7, 2), (8, 2), (12, 2)], &black_talon),
+                    (6, 5),
+                ),
+                jan(TwoTables, position(&[(1, 13), (8, 1), (9, 1)], &black_talon), (4, 4)),
+                jan(
+                    ContreTwoTables,
+                    position(&[(1, 13), (8, 1), (9, 1)], &[(13, 2), (24, 13)]),
+                    (4, 4),
+                ),
+                jan(Mezeas, position(&[(1, 13), (12, 2)], &black_talon), (1, 3)),
+                Exercise {
+                    third_roll: true,
+                    ..jan(
+                        SixTables,
+                        position(&[(1, 11), (2, 1), (3, 1), (4, 1), (5, 1)], &black_talon),
+                        (5, 6),
+                    )
+                },
+                jan(FirstToExit, position(&[(22, 1), (23, 1)], &[(1, 15)]), (3, 4)),
+                jan(HelplessMan, position(&[(1, 15)], &[(7, 2), (24, 13)]), (6, 6)),
+            ],
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exercise(key: ExerciseKey) -> Exercise {
+        lessons()
+            .into_iter()
+            .flat_map(|l| l.exercises)
+            .find(|e| e.key == key)
+            .unwrap()
+    }
+
+    #[test]
+    fn every_exercise_can_be_solved() {
+        for ex in lessons().into_iter().flat_map(|l| l.exercises) {
+            let solvable = match &ex.goal {
+                Goal::Play(solutions) => solutions.iter().all(|s| ex.check_moves(*s) == Verdict::Correct),
+                Goal::AnyLegal | Goal::MakeJan(_) => {
+                    let rules = MoveRules::new(&Color::White, &store_board(&ex.board), Dice { values: ex.dice });
+                    let sequences = rules.get_possible_moves_sequences(true, vec![]);
+                    sequences.iter().any(|s| ex.check_moves(*s) == Verdict::Correct)
+                }
+                Goal::Mark => true,
+            };
+            assert!(solvable, "{:?} has no solution", ex.key);
+            assert_eq!(ex.check_mark(ex.points()), Verdict::Correct);
+        }
+    }
+
+    #[test]
+    fn jan_exercises_score_their_jan() {
+        let cases = [
+            (ExerciseKey::TrueHitSmallJan, Jan::TrueHitSmallJan),
+            (ExerciseKey::TrueHitBigJan, Jan::TrueHitBigJan),
+            (ExerciseKey::FalseHit, Jan::FalseHitBigJan),
+            (ExerciseKey::TrueHitCorner, Jan::TrueHitOpponentCorner),
+            (ExerciseKey::TwoTables, Jan::TwoTables),
+            (ExerciseKey::ContreTwoTables, Jan::ContreTwoTables),
+            (ExerciseKey::Mezeas, Jan::Mezeas),
+            (ExerciseKey::SixTables, Jan::SixTables),
+            (ExerciseKey::FirstToExit, Jan::FirstPlayerToExit),
+            (ExerciseKey::HelplessMan, Jan::HelplessMan),
+            (ExerciseKey::KeepSmallJan, Jan::FilledQuarter),
+        ];
+        for (key, jan) in cases {
+            let ex = exercise(key);
+            assert!(ex.jans().iter().any(|e| e.jan == jan), "{key:?} does not score {jan:?}");
+        }
+        // A false hit scores for the opponent.
+        let false_hit = exercise(ExerciseKey::FalseHit);
+        assert!(false_hit.points().1 > 0);
+        assert!(matches!(false_hit.check_mark((0, 0)), Verdict::WrongMark(_)));
+    }
+
+    #[test]
+    fn mistakes_are_explained() {
+        let cases = [
+            (ExerciseKey::FirstMove, seq((1, 7), (7, 12)), MoveError::CornerNeedsTwoCheckers),
+            (ExerciseKey::CornerByEffect, seq((6, 12), (1, 6)), MoveError::CornerNeedsTwoCheckers),
+            (ExerciseKey::CornerByPuissance, seq((7, 13), (1, 7)), MoveError::OpponentCorner),
+            (ExerciseKey::CornerLeaving, seq((12, 14), (4, 7)), MoveError::CornerNeedsTwoCheckers),
+            (ExerciseKey::ExitExact, seq((20, 0), (23, 0)), MoveError::ExitByEffectPossible),
+            (ExerciseKey::ExitAllHome, seq((20, 0), (17, 22)), MoveError::ExitNeedsAllCheckersOnLastQuarter),
+            (ExerciseKey::FillSmallJan, seq((1, 6), (2, 7)), MoveError::MustFillQuarter),
+            (ExerciseKey::KeepSmallJan, seq((2, 8), (1, 5)), MoveError::MustFillQuarter),
+            (ExerciseKey::OpponentQuarter, seq((10, 15), (11, 15)), MoveError::OpponentCanFillQuarter),
+        ];
+        for (key, moves, error) in cases {
+            assert_eq!(exercise(key).check_moves(moves), Verdict::Illegal(Some(error)), "{key:?}");
+        }
+        // Not possible with the dice: no rule to name.
+        assert_eq!(
+            exercise(ExerciseKey::FirstMove).check_moves(seq((1, 4), (1, 5))),
+            Verdict::Illegal(None)
+        );
+    }
+
+    #[test]
+    fn legal_moves_beside_the_goal_are_missed() {
+        let ex = exercise(ExerciseKey::CornerByEffect);
+        assert_eq!(ex.check_moves(seq((1, 7), (1, 6))), Verdict::Missed);
+        assert_eq!(ex.check_moves(seq((7, 12), (6, 12))), Verdict::Correct);
+    }
+}
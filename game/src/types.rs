@@ -0,0 +1,636 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use trictrac_store::{CheckerMove, GameEvent, GameState, Jan, Stage, TurnStage};
+
+/// Version of the serialized form of [`PlayerAction`], [`GameDelta`] and [`ViewState`].
+/// Bump it with every change to them: the relay keeps pages with another version out
+/// of the room, instead of letting them misread each other's messages.
+pub const SCHEMA_VERSION: u16 = 3;
+
+// ── Actions sent by a player to the host backend ─────────────────────────────
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum PlayerAction {
+    /// Active player requests a dice roll.
+    Roll,
+    /// Both checker moves for this turn. Use `EMPTY_MOVE` (from=0, to=0) when a die
+    /// has no valid move.
+    Move(CheckerMove, CheckerMove),
+    /// Choose to "go" (advance) during HoldOrGoChoice.
+    Go,
+    /// Acknowledge point marking (hold / advance points).
+    Mark,
+    /// Roll a single die during the pre-game ceremony to decide who goes first.
+    PreGameRoll,
+    /// Declare the player's display name; sent once immediately after connecting.
+    SetName(String),
+    /// The player looked at the bot's suggestion; counted in the game record.
+    Hint,
+}
+
+// ── Incremental state update broadcast to all clients ────────────────────────
+
+/// The changes from one view state to the next.
+///
+/// Deltas are numbered: `seq` is the [`ViewState::seq`] the delta leads to and
+/// `checksum` the [`ViewState::checksum`] of the result. A client whose state
+/// does not match asks the host for a full update.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GameDelta {
+    pub seq: u32,
+    pub changes: Vec<StateChange>,
+    pub checksum: u32,
+}
+
+/// One part of the [`ViewState`] that changed.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum StateChange {
+    /// Fields whose checker count changed: (index, new count).
+    Board(Vec<(u8, i8)>),
+    /// The last two checker moves played.
+    Moves(CheckerMove, CheckerMove),
+    Dice(u8, u8),
+    /// Jans triggered by the last roll.
+    Jans(Vec<JanEntry>),
+    /// Score of the player with this multiplayer player_id.
+    Score(u16, PlayerScore),
+    Stage(SerStage),
+    TurnStage(SerTurnStage),
+    ActivePlayer(Option<u16>),
+    PreGameRoll(Option<PreGameRollState>),
+    Spectators(u16),
+    /// New chat messages. The oldest ones drop out beyond `CHAT_HISTORY_LEN`.
+    ChatAppended(Vec<ChatEntry>),
+    /// The whole chat history, when it did not just grow.
+    Chat(Vec<ChatEntry>),
+    Clock(Option<ClockView>),
+    /// Events played since the last delta.
+    HistoryAppended(Vec<GameEvent>),
+    /// The whole event history, when it did not just grow.
+    History(Vec<GameEvent>),
+    Hints(HintUse),
+}
+
+// ── Full game snapshot ────────────────────────────────────────────────────────
+
+/// State of the pre-game ceremony where each player rolls one die to decide
+/// who goes first. Present only when `stage == SerStage::PreGameRoll`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PreGameRollState {
+    /// Die value (1–6) rolled by the host; `None` = not yet rolled this round.
+    pub host_die: Option<u8>,
+    /// Die value (1–6) rolled by the guest; `None` = not yet rolled this round.
+    pub guest_die: Option<u8>,
+    /// Number of tied rounds so far (0 on the first round).
+    pub tie_count: u8,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct ViewState {
+    /// Board positions: index i = field i+1. Positive = white, negative = black.
+    pub board: [i8; 24],
+    pub stage: SerStage,
+    pub turn_stage: SerTurnStage,
+    /// Which multiplayer player_id (0 = host, 1 = guest) is the active player.
+    pub active_mp_player: Option<u16>,
+    /// Scores indexed by multiplayer player_id (0 = host, 1 = guest).
+    pub scores: [PlayerScore; 2],
+    /// Last rolled dice values.
+    pub dice: (u8, u8),
+    /// Jans (scoring events) triggered by the last dice roll.
+    pub dice_jans: Vec<JanEntry>,
+    /// Last two checker moves played; default when no move has occurred yet.
+    pub dice_moves: (CheckerMove, CheckerMove),
+    /// Present while the pre-game ceremony is in progress.
+    #[serde(default)]
+    pub pre_game_roll: Option<PreGameRollState>,
+    /// Number of spectators currently watching the room.
+    #[serde(default)]
+    pub spectators: u16,
+    /// Most recent chat messages, oldest first (at most `CHAT_HISTORY_LEN`).
+    #[serde(default)]
+    pub chat: Vec<ChatEntry>,
+    /// Game clocks; `None` for untimed games.
+    #[serde(default)]
+    pub clock: Option<ClockView>,
+    /// Number of deltas the host has sent since the game started.
+    #[serde(default)]
+    pub seq: u32,
+    /// Events played so far, oldest first. The host's has every event, enough
+    /// to replay the game; clients only get the events sent in deltas since
+    /// they joined, as it is left out of full updates and checksums.
+    #[serde(skip)]
+    pub history: Vec<GameEvent>,
+    #[serde(default)]
+    pub hints: HintUse,
+}
+
+/// Whether the players may ask the bot for hints, and how many they did.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct HintUse {
+    /// False in rated games.
+    pub allowed: bool,
+    /// Hints asked for, indexed by multiplayer player_id.
+    pub used: [u16; 2],
+}
+
+/// One scoring event from a dice roll.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct JanEntry {
+    pub jan: Jan,
+    /// True when the dice are doubles (both same value) — changes the point value.
+    /// Special case for HelplessMan: true when *both* dice are unplayable.
+    pub is_double: bool,
+    /// Number of distinct move pairs that produce this jan.
+    pub ways: usize,
+    /// Points per way (negative = scored against the active player).
+    pub points_per: i8,
+    /// Total = points_per × ways.
+    pub total: i8,
+    /// The move pairs that produce this jan (for move display).
+    pub moves: Vec<(CheckerMove, CheckerMove)>,
+}
+
+impl ViewState {
+    pub fn default_with_names(host_name: &str, guest_name: &str) -> Self {
+        ViewState {
+            board: [0i8; 24],
+            stage: SerStage::PreGame,
+            turn_stage: SerTurnStage::RollDice,
+            active_mp_player: None,
+            scores: [
+                PlayerScore {
+                    name: host_name.to_string(),
+                    points: 0,
+                    holes: 0,
+                    can_bredouille: false,
+                },
+                PlayerScore {
+                    name: guest_name.to_string(),
+                    points: 0,
+                    holes: 0,
+                    can_bredouille: false,
+                },
+            ],
+            dice: (0, 0),
+            dice_jans: Vec::new(),
+            dice_moves: (CheckerMove::default(), CheckerMove::default()),
+            pre_game_roll: None,
+            spectators: 0,
+            chat: Vec::new(),
+            clock: None,
+            seq: 0,
+            history: Vec::new(),
+            hints: HintUse::default(),
+        }
+    }
+
+    /// The changes turning `self` into `next`. `seq` is left to the caller.
+    pub fn diff(&self, next: &ViewState) -> Vec<StateChange> {
+        let mut changes = Vec::new();
+        let board: Vec<(u8, i8)> = (0..self.board.len())
+            .filter(|&i| self.board[i] != next.board[i])
+            .map(|i| (i as u8, next.board[i]))
+            .collect();
+        if !board.is_empty() {
+            changes.push(StateChange::Board(board));
+        }
+        if self.dice_moves != next.dice_moves {
+            changes.push(StateChange::Moves(next.dice_moves.0, next.dice_moves.1));
+        }
+        if self.dice != next.dice {
+            changes.push(StateChange::Dice(next.dice.0, next.dice.1));
+        }
+        if self.dice_jans != next.dice_jans {
+            changes.push(StateChange::Jans(next.dice_jans.clone()));
+        }
+        for (player, (score, next_score)) in self.scores.iter().zip(&next.scores).enumerate() {
+            if score != next_score {
+                changes.push(StateChange::Score(player as u16, next_score.clone()));
+            }
+        }
+        if self.stage != next.stage {
+            changes.push(StateChange::Stage(next.stage.clone()));
+        }
+        if self.turn_stage != next.turn_stage {
+            changes.push(StateChange::TurnStage(next.turn_stage.clone()));
+        }
+        if self.active_mp_player != next.active_mp_player {
+            changes.push(StateChange::ActivePlayer(next.active_mp_player));
+        }
+        if self.pre_game_roll != next.pre_game_roll {
+            changes.push(StateChange::PreGameRoll(next.pre_game_roll.clone()));
+        }
+        if self.spectators != next.spectators {
+            changes.push(StateChange::Spectators(next.spectators));
+        }
+        if self.chat != next.chat {
+            changes.push(chat_change(&self.chat, &next.chat));
+        }
+        if self.clock != next.clock {
+            changes.push(StateChange::Clock(next.clock.clone()));
+        }
+        if self.history != next.history {
+            changes.push(match next.history.strip_prefix(self.history.as_slice()) {
+                Some(appended) => StateChange::HistoryAppended(appended.to_vec()),
+                None => StateChange::History(next.history.clone()),
+            });
+        }
+        if self.hints != next.hints {
+            changes.push(StateChange::Hints(next.hints.clone()));
+        }
+        changes
+    }
+
+    fn apply_change(&mut self, change: &StateChange) {
+        match change {
+            StateChange::Board(fields) => {
+                for &(i, count) in fields {
+                    if let Some(field) = self.board.get_mut(i as usize) {
+                        *field = count;
+                    }
+                }
+            }
+            StateChange::Moves(m1, m2) => self.dice_moves = (*m1, *m2),
+            StateChange::Dice(d1, d2) => self.dice = (*d1, *d2),
+            StateChange::Jans(jans) => self.dice_jans = jans.clone(),
+            StateChange::Score(player, score) => {
+                if let Some(s) = self.scores.get_mut(*player as usize) {
+                    *s = score.clone();
+                }
+            }
+            StateChange::Stage(stage) => self.stage = stage.clone(),
+            StateChange::TurnStage(turn_stage) => self.turn_stage = turn_stage.clone(),
+            StateChange::ActivePlayer(player) => self.active_mp_player = *player,
+            StateChange::PreGameRoll(pgr) => self.pre_game_roll = pgr.clone(),
+            StateChange::Spectators(n) => self.spectators = *n,
+            StateChange::ChatAppended(entries) => append_chat(&mut self.chat, entries),
+            StateChange::Chat(chat) => self.chat = chat.clone(),
+            StateChange::Clock(clock) => self.clock = clock.clone(),
+            StateChange::HistoryAppended(events) => self.history.extend_from_slice(events),
+            StateChange::History(history) => self.history = history.clone(),
+            StateChange::Hints(hints) => self.hints = hints.clone(),
+        }
+    }
+
+    /// Applies a delta from the host. Returns `false` when the delta does not
+    /// follow this state or the result differs from the host's: the state is
+    /// then out of sync until the next full update.
+    pub fn apply_delta(&mut self, delta: &GameDelta) -> bool {
+        if delta.seq != self.seq.wrapping_add(1) {
+            return false;
+        }
+        for change in &delta.changes {
+            self.apply_change(change);
+        }
+        self.seq = delta.seq;
+        self.checksum() == delta.checksum
+    }
+
+    /// FNV-1a hash of the state, used to detect clients out of sync.
+    /// Player names are left out since clients show their own name in place
+    /// of the one the host knows.
+    pub fn checksum(&self) -> u32 {
+        let mut state = self.clone();
+        for score in &mut state.scores {
+            score.name.clear();
+        }
+        let bytes = serde_json::to_vec(&state).expect("view state serializes");
+        bytes.iter().fold(0x811c_9dc5, |hash, &b| {
+            (hash ^ b as u32).wrapping_mul(0x0100_0193)
+        })
+    }
+
+    /// True when `other` differs from `self` in the chat history or the hint
+    /// counts only, which the game screen is not redrawn for.
+    pub fn differs_only_in_chat_or_hints(&self, other: &ViewState) -> bool {
+        (self.chat != other.chat || self.hints != other.hints)
+            && ViewState {
+                chat: other.chat.clone(),
+                seq: other.seq,
+                hints: other.hints.clone(),
+                ..self.clone()
+            } == *other
+    }
+
+    /// Convert a store `GameState` to a `ViewState`.
+    /// `host_store_id` and `guest_store_id` are the trictrac `PlayerId`s assigned
+    /// to the host (mp player 0) and guest (mp player 1) respectively.
+    pub fn from_game_state(gs: &GameState, host_store_id: u64, guest_store_id: u64) -> Self {
+        let board_vec = gs.board.to_vec();
+        let board: [i8; 24] = board_vec.try_into().expect("board is always 24 fields");
+
+        let stage = match gs.stage {
+            Stage::PreGame => SerStage::PreGame,
+            Stage::InGame => SerStage::InGame,
+            Stage::Ended => SerStage::Ended,
+        };
+        let turn_stage = match gs.turn_stage {
+            TurnStage::RollDice => SerTurnStage::RollDice,
+            TurnStage::RollWaiting => SerTurnStage::RollWaiting,
+            TurnStage::MarkPoints => SerTurnStage::MarkPoints,
+            TurnStage::HoldOrGoChoice => SerTurnStage::HoldOrGoChoice,
+            TurnStage::Move => SerTurnStage::Move,
+            TurnStage::MarkAdvPoints => SerTurnStage::MarkAdvPoints,
+        };
+
+        let active_mp_player = if gs.active_player_id == host_store_id {
+            Some(0)
+        } else if gs.active_player_id == guest_store_id {
+            Some(1)
+        } else {
+            None
+        };
+
+        let score_for = |store_id: u64| -> PlayerScore {
+            gs.players
+                .get(&store_id)
+                .map(|p| PlayerScore {
+                    name: p.name.clone(),
+                    points: p.points,
+                    holes: p.holes,
+                    can_bredouille: p.can_bredouille,
+                })
+                .unwrap_or_else(|| PlayerScore {
+                    name: String::new(),
+                    points: 0,
+                    holes: 0,
+                    can_bredouille: false,
+                })
+        };
+
+        let dice_jans = jan_entries(&gs.dice_jans, (gs.dice.values.0, gs.dice.values.1));
+
+        ViewState {
+            board,
+            stage,
+            turn_stage,
+            active_mp_player,
+            scores: [score_for(host_store_id), score_for(guest_store_id)],
+            dice: (gs.dice.values.0, gs.dice.values.1),
+            dice_jans,
+            dice_moves: gs.dice_moves,
+            pre_game_roll: None,
+            spectators: 0,
+            chat: Vec::new(),
+            clock: None,
+            seq: 0,
+            history: gs.history.clone(),
+            hints: HintUse::default(),
+        }
+    }
+}
+
+/// The scoring entries for the jans of a roll of `dice`, highest total first.
+pub fn jan_entries(
+    jans: &HashMap<Jan, Vec<(CheckerMove, CheckerMove)>>,
+    dice: (u8, u8),
+) -> Vec<JanEntry> {
+    // is_double for scoring: dice show the same value (both dice identical).
+    // Exception: HelplessMan uses a special rule (see below).
+    let dice_are_double = dice.0 == dice.1;
+
+    // Build JanEntry list from the PossibleJans map.
+    let empty_move = CheckerMove::new(0, 0).unwrap_or_default();
+    let mut entries: Vec<JanEntry> = jans
+        .iter()
+        .map(|(jan, moves)| {
+            // HelplessMan: is_double = true only when *both* dice are unplayable
+            // (the moves list contains a single (empty, empty) sentinel).
+            let is_double = if *jan == Jan::HelplessMan {
+                moves
+                    .first()
+                    .map(|&(m1, m2)| m1 == empty_move && m2 == empty_move)
+                    .unwrap_or(false)
+            } else {
+                dice_are_double
+            };
+            let points_per = jan.get_points(is_double);
+            let ways = moves.len();
+            let total = points_per.saturating_mul(ways as i8);
+            JanEntry {
+                jan: jan.clone(),
+                is_double,
+                ways,
+                points_per,
+                total,
+                moves: moves.clone(),
+            }
+        })
+        .collect();
+    // Sort: highest total first, most-negative last; ties in a fixed order,
+    // whatever the map's.
+    entries.sort_by_key(|e| (std::cmp::Reverse(e.total), e.jan.clone()));
+    entries
+}
+
+// ── Chat ──────────────────────────────────────────────────────────────────────
+
+/// Number of chat messages kept in the view state.
+pub const CHAT_HISTORY_LEN: usize = 20;
+/// Maximum length of a chat message in characters.
+pub const MAX_CHAT_CHARS: usize = 200;
+
+/// Predefined messages. They travel as a shortcode so that every client shows
+/// them in its own language.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum QuickPhrase {
+    Hello,
+    WellPlayed,
+    JAdoube,
+    Ecole,
+    Thanks,
+}
+
+impl QuickPhrase {
+    pub const ALL: [QuickPhrase; 5] = [
+        QuickPhrase::Hello,
+        QuickPhrase::WellPlayed,
+        QuickPhrase::JAdoube,
+        QuickPhrase::Ecole,
+        QuickPhrase::Thanks,
+    ];
+
+    /// The shortcode sent over the wire instead of the localized text.
+    pub fn code(self) -> &'static str {
+        match self {
+            QuickPhrase::Hello => ":hello:",
+            QuickPhrase::WellPlayed => ":well_played:",
+            QuickPhrase::JAdoube => ":j_adoube:",
+            QuickPhrase::Ecole => ":ecole:",
+            QuickPhrase::Thanks => ":thanks:",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|q| q.code() == code)
+    }
+}
+
+/// Appends chat messages, dropping the oldest beyond `CHAT_HISTORY_LEN`.
+fn append_chat(chat: &mut Vec<ChatEntry>, entries: &[ChatEntry]) {
+    chat.extend_from_slice(entries);
+    let excess = chat.len().saturating_sub(CHAT_HISTORY_LEN);
+    chat.drain(..excess);
+}
+
+/// The smallest chat change turning `chat` into `next`.
+fn chat_change(chat: &[ChatEntry], next: &[ChatEntry]) -> StateChange {
+    (1..=next.len())
+        .map(|n| &next[next.len() - n..])
+        .find(|appended| {
+            let mut result = chat.to_vec();
+            append_chat(&mut result, appended);
+            result == next
+        })
+        .map_or_else(
+            || StateChange::Chat(next.to_vec()),
+            |appended| StateChange::ChatAppended(appended.to_vec()),
+        )
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ChatBody {
+    Text(String),
+    Quick(QuickPhrase),
+}
+
+/// One chat message.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ChatEntry {
+    /// Sender: 0 = host, 1 = guest.
+    pub from: u16,
+    pub body: ChatBody,
+}
+
+// ── Time control ──────────────────────────────────────────────────────────────
+
+/// How the per-move bonus is applied.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ClockMode {
+    /// The bonus is added to the player's time once their turn ends.
+    Fischer,
+    /// The clock only starts counting after the bonus has elapsed each turn.
+    Delay,
+}
+
+/// Bit of a room's `rule_variation` marking a casual game: the result is
+/// recorded but the game is not rated, and the players may ask for hints.
+pub const CASUAL_GAME: u16 = 0x0080;
+
+/// Whether `rule_variation` is that of a casual game.
+pub fn is_casual(rule_variation: u16) -> bool {
+    rule_variation & CASUAL_GAME != 0
+}
+
+/// Clock settings of a room, carried in the room's `rule_variation`.
+///
+/// Layout of the `u16`: bits 0–6 = initial minutes (0 = untimed),
+/// bit 7 = [`CASUAL_GAME`], bits 8–14 = bonus seconds,
+/// bit 15 = mode (0 = Fischer, 1 = delay).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeControl {
+    pub minutes: u8,
+    pub bonus_secs: u8,
+    pub mode: ClockMode,
+}
+
+impl TimeControl {
+    /// Choices offered when creating a room.
+    pub const PRESETS: [TimeControl; 4] = [
+        TimeControl { minutes: 5, bonus_secs: 5, mode: ClockMode::Fischer },
+        TimeControl { minutes: 10, bonus_secs: 10, mode: ClockMode::Fischer },
+        TimeControl { minutes: 15, bonus_secs: 15, mode: ClockMode::Fischer },
+        TimeControl { minutes: 10, bonus_secs: 10, mode: ClockMode::Delay },
+    ];
+
+    /// Decode a room's rule variation; `None` for untimed games.
+    pub fn from_rule_variation(rule_variation: u16) -> Option<Self> {
+        let minutes = (rule_variation & 0x7f) as u8;
+        if minutes == 0 {
+            return None;
+        }
+        Some(TimeControl {
+            minutes,
+            bonus_secs: ((rule_variation >> 8) & 0x7f) as u8,
+            mode: if rule_variation & 0x8000 != 0 {
+                ClockMode::Delay
+            } else {
+                ClockMode::Fischer
+            },
+        })
+    }
+
+    pub fn to_rule_variation(self) -> u16 {
+        let mode = match self.mode {
+            ClockMode::Fischer => 0,
+            ClockMode::Delay => 0x8000,
+        };
+        (self.minutes as u16 & 0x7f) | ((self.bonus_secs as u16 & 0x7f) << 8) | mode
+    }
+}
+
+/// Clock state as sent to clients. Times are those at the moment the clock
+/// last started or stopped; clients count down locally from there while
+/// `running` is set.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ClockView {
+    pub control: TimeControl,
+    /// Time left in milliseconds, indexed by multiplayer player_id.
+    pub remaining_ms: [u64; 2],
+    /// Unused per-move delay of the current turn (delay mode only).
+    pub delay_ms: u64,
+    /// Player whose clock is counting down; `None` while paused.
+    pub running: Option<u16>,
+    /// Player who lost on time, once the game has ended that way.
+    pub flagged: Option<u16>,
+}
+
+// ── Scored event (notification) ──────────────────────────────────────────
+
+/// Points scored in a single scoring event, used for the notification panel.
+#[derive(Clone, PartialEq)]
+pub struct ScoredEvent {
+    /// Raw points earned (sum of jan values; before hole wrapping).
+    pub points_earned: u8,
+    /// Number of holes gained (0 = no hole).
+    pub holes_gained: u8,
+    /// Total holes after this event.
+    pub holes_total: u8,
+    /// Was bredouille active when the hole was made (doubles hole count)?
+    pub bredouille: bool,
+    /// Contributing jans from this player's perspective (totals always positive).
+    pub jans: Vec<JanEntry>,
+}
+
+// ── Score snapshot ────────────────────────────────────────────────────────────
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PlayerScore {
+    pub name: String,
+    pub points: u8,
+    pub holes: u8,
+    pub can_bredouille: bool,
+}
+
+// ── Serialisable mirrors of store enums ──────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SerStage {
+    PreGame,
+    /// Both players have arrived; ceremony in progress to decide who goes first.
+    PreGameRoll,
+    InGame,
+    Ended,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SerTurnStage {
+    RollDice,
+    RollWaiting,
+    MarkPoints,
+    HoldOrGoChoice,
+    Move,
+    MarkAdvPoints,
+}
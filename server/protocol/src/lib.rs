@@ -24,6 +24,39 @@ pub const SERVER_RPC: u8 = 2;
 /// The disconnection message that is used for disconnecting without any arguments, that gets passed through the web socket layer.
 pub const CLIENT_DISCONNECTS_SELF: u8 = 3;
 
+/// A spectator joined the room (Client->Server) followed by u16 client id. Same layout as [`NEW_CLIENT`].
+/// Spectators receive the broadcast stream but may not send RPCs.
+/// Numbered after [`HAND_SHAKE_RESPONSE`] because the host parses these alongside [`SERVER_ERROR`].
+pub const NEW_SPECTATOR: u8 = 7;
+
+/// A spectator left the room (Client->Server) followed by u16 client id. Same layout as [`CLIENT_DISCONNECTS`].
+pub const SPECTATOR_DISCONNECTS: u8 = 8;
+
+/// A chat message (Client->Server). The client sends the UTF-8 text right after the header,
+/// the relay injects the u16 client id before forwarding: header, u16 client id, UTF-8 text.
+pub const CHAT_MESSAGE: u8 = 9;
+
+/// The maximum size of the UTF-8 text of one chat message in bytes. Longer messages get dropped by the relay.
+pub const MAX_CHAT_MESSAGE_SIZE: usize = 512;
+
+/// The maximum amount of chat messages a client may send within [`CHAT_RATE_WINDOW_SECS`].
+pub const CHAT_RATE_LIMIT: usize = 5;
+
+/// The sliding window for [`CHAT_RATE_LIMIT`] in seconds.
+pub const CHAT_RATE_WINDOW_SECS: u64 = 10;
+
+/// A client lost track of the delta updates and asks the host for a [`FULL_UPDATE`] (Client->Server).
+/// The client sends the header alone, the relay appends the u16 client id before forwarding.
+/// Spectators may send it as well. Numbered after [`VERSION_MISMATCH`].
+pub const REQUEST_FULL_UPDATE: u8 = 11;
+
+/// The full update request message size (Header + Client Id) (u8 + u16)
+pub const REQUEST_FULL_UPDATE_MSG_SIZE: usize = 3;
+
+/// The minimum time between two full update requests of a client in milliseconds.
+/// The relay drops requests arriving sooner.
+pub const FULL_UPDATE_REQUEST_INTERVAL_MS: u64 = 1000;
+
 // Server -> Client
 
 /// The server disconnects from the game and the room gets closed.
@@ -49,14 +82,43 @@ pub const SERVER_ERROR: u8 = 5;
 /// The response message for the handshake.
 pub const HAND_SHAKE_RESPONSE: u8 = 6;
 
+/// The handshake is refused because the endpoint runs an incompatible version and has to reload.
+/// Followed by the lowest and highest protocol version the relay supports (u16 each), then a UTF-8 reason.
+/// Numbered after [`CHAT_MESSAGE`] so that it can not be mistaken for any other message.
+pub const VERSION_MISMATCH: u8 = 10;
+
+// Versions.
+
+/// The version of this protocol: message ids and layouts, the join request and the handshake response.
+pub const PROTOCOL_VERSION: u16 = 3;
+
+/// The oldest protocol version the relay still accepts. Clients only speak [`PROTOCOL_VERSION`],
+/// so this moves up with it until a version is kept for older clients.
+pub const MIN_PROTOCOL_VERSION: u16 = PROTOCOL_VERSION;
+
+/// Picks the protocol version spoken with an endpoint supporting versions up to `requested`.
+/// `None` if there is no version both sides speak.
+pub fn negotiate_protocol_version(requested: u16) -> Option<u16> {
+    let version = requested.min(PROTOCOL_VERSION);
+    (version >= MIN_PROTOCOL_VERSION).then_some(version)
+}
+
 // Sizes of entries.
-/// For the handshake we respond with player id (u16), rule variation (u16), and reconnect token (u64).
-pub const HAND_SHAKE_RESPONSE_SIZE: usize = 13;
+/// For the handshake we respond with player id (u16), rule variation (u16), reconnect token (u64),
+/// the negotiated protocol version (u16) and the lowest and highest version the relay supports (u16 each).
+pub const HAND_SHAKE_RESPONSE_SIZE: usize = 19;
+
+/// The version mismatch message without its reason (Header + lowest + highest version) (u8 + u16 + u16)
+pub const VERSION_MISMATCH_HEADER_SIZE: usize = 5;
 
 /// The size of a new client. (u16)
 pub const CLIENT_ID_SIZE: usize = 2;
 
 /// The join request. This struct is used on the server and on the client.
+///
+/// New fields go at the end. The relay recognises requests of clients predating
+/// the versions by their layout and asks them to reload; requests it can not read
+/// at all are refused with the same advice.
 #[derive(Deserialize, Serialize)]
 pub struct JoinRequest {
     /// Which game do we want to join.
@@ -69,4 +131,12 @@ pub struct JoinRequest {
     pub create_room: bool,
     /// Reconnect token from a previous session. `None` = fresh join/create, `Some` = reconnect.
     pub reconnect_token: Option<u64>,
+    /// Do we only want to watch? Spectators get the broadcast stream, do not count towards the
+    /// player limit and have their RPCs rejected. Ignored when `create_room` is set.
+    pub spectate: bool,
+    /// The highest protocol version the client speaks, usually [`PROTOCOL_VERSION`].
+    pub protocol_version: u16,
+    /// The version of the game's own messages (actions, deltas, view state). Everybody in a
+    /// room has to use the version the host opened it with.
+    pub schema_version: u16,
 }
@@ -0,0 +1,35 @@
+CREATE TABLE IF NOT EXISTS tournaments (
+    id             BIGSERIAL PRIMARY KEY,
+    name           TEXT      NOT NULL,
+    game_id        TEXT      NOT NULL,
+    format         TEXT      NOT NULL,
+    rule_variation INTEGER   NOT NULL DEFAULT 0,
+    rounds         INTEGER   NOT NULL DEFAULT 0,
+    status         TEXT      NOT NULL DEFAULT 'registration',
+    current_round  INTEGER   NOT NULL DEFAULT 0,
+    organizer_id   BIGINT    REFERENCES users(id) ON DELETE SET NULL,
+    created_at     BIGINT    NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS tournament_players (
+    tournament_id BIGINT NOT NULL REFERENCES tournaments(id) ON DELETE CASCADE,
+    user_id       BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    registered_at BIGINT NOT NULL,
+    PRIMARY KEY (tournament_id, user_id)
+);
+
+CREATE TABLE IF NOT EXISTS tournament_games (
+    id             BIGSERIAL PRIMARY KEY,
+    tournament_id  BIGINT  NOT NULL REFERENCES tournaments(id) ON DELETE CASCADE,
+    round          INTEGER NOT NULL,
+    board          INTEGER NOT NULL,
+    host_user_id   BIGINT  REFERENCES users(id) ON DELETE SET NULL,
+    guest_user_id  BIGINT  REFERENCES users(id) ON DELETE SET NULL,
+    is_bye         BOOLEAN NOT NULL DEFAULT FALSE,
+    room_code      TEXT    NOT NULL UNIQUE,
+    game_record_id BIGINT  REFERENCES game_records(id),
+    outcome        TEXT,
+    UNIQUE (tournament_id, round, board)
+);
+
+CREATE INDEX IF NOT EXISTS idx_tournament_games_room ON tournament_games(room_code);
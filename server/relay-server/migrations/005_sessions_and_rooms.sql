@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS sessions (
+    id          TEXT   PRIMARY KEY,
+    data        TEXT   NOT NULL,
+    expiry_date BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_sessions_expiry ON sessions(expiry_date);
+
+CREATE TABLE IF NOT EXISTS rooms (
+    room_id    TEXT   PRIMARY KEY,
+    data       TEXT   NOT NULL,
+    updated_at BIGINT NOT NULL
+);
@@ -0,0 +1,33 @@
+ALTER TABLE users ADD COLUMN IF NOT EXISTS email_notifications BOOLEAN NOT NULL DEFAULT TRUE;
+
+-- One row per pair of users; `requester_id` sent the request.
+CREATE TABLE IF NOT EXISTS friendships (
+    requester_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    addressee_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    status       TEXT   NOT NULL DEFAULT 'pending',
+    created_at   BIGINT NOT NULL,
+    PRIMARY KEY (requester_id, addressee_id)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_friendships_pair
+    ON friendships (LEAST(requester_id, addressee_id), GREATEST(requester_id, addressee_id));
+
+CREATE TABLE IF NOT EXISTS user_blocks (
+    blocker_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    blocked_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at BIGINT NOT NULL,
+    PRIMARY KEY (blocker_id, blocked_id)
+);
+
+CREATE TABLE IF NOT EXISTS challenges (
+    id             BIGSERIAL PRIMARY KEY,
+    challenger_id  BIGINT  NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    invitee_id     BIGINT  NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    game_id        TEXT    NOT NULL,
+    rule_variation INTEGER NOT NULL DEFAULT 0,
+    room_code      TEXT    NOT NULL UNIQUE,
+    status         TEXT    NOT NULL DEFAULT 'pending',
+    created_at     BIGINT  NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_challenges_invitee ON challenges(invitee_id, status);
@@ -0,0 +1,11 @@
+-- Accounts at an OpenID Connect provider linked to local users. Users created
+-- through a provider have an empty `password_hash` until they set a password.
+CREATE TABLE IF NOT EXISTS user_identities (
+    issuer     TEXT   NOT NULL,
+    subject    TEXT   NOT NULL,
+    user_id    BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at BIGINT NOT NULL,
+    PRIMARY KEY (issuer, subject)
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_identities_user ON user_identities(user_id);
@@ -0,0 +1,24 @@
+-- Moderation. There is no endpoint to appoint admins: grant the role with
+-- `UPDATE users SET is_admin = TRUE WHERE username = '…'`.
+ALTER TABLE users ADD COLUMN IF NOT EXISTS is_admin  BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS banned_at BIGINT;
+
+-- Voided games stay in the table but no longer count in statistics.
+ALTER TABLE game_records ADD COLUMN IF NOT EXISTS voided_at BIGINT;
+
+CREATE TABLE IF NOT EXISTS banned_ips (
+    ip         INET   PRIMARY KEY,
+    reason     TEXT,
+    created_at BIGINT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS admin_audit_log (
+    id         BIGSERIAL PRIMARY KEY,
+    admin_id   BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    action     TEXT   NOT NULL,
+    target     TEXT   NOT NULL,
+    details    TEXT,
+    created_at BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_admin_audit_log_created ON admin_audit_log(created_at);
@@ -0,0 +1,3 @@
+-- The game-specific record of the moves played, uploaded with the result and
+-- served back verbatim for replays.
+ALTER TABLE game_records ADD COLUMN IF NOT EXISTS record TEXT;
@@ -0,0 +1,22 @@
+-- Curated positions, imported with `relay-server puzzles import`. The data is
+-- game-specific and served verbatim; the game checks the answers itself and
+-- reports whether they solved the puzzle.
+CREATE TABLE puzzles (
+    id         BIGSERIAL PRIMARY KEY,
+    game_id    TEXT   NOT NULL,
+    slug       TEXT   NOT NULL,
+    title      TEXT   NOT NULL,
+    data       TEXT   NOT NULL,
+    created_at BIGINT NOT NULL,
+    UNIQUE (game_id, slug)
+);
+
+CREATE TABLE puzzle_attempts (
+    id         BIGSERIAL PRIMARY KEY,
+    puzzle_id  BIGINT  NOT NULL REFERENCES puzzles(id) ON DELETE CASCADE,
+    user_id    BIGINT  NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    solved     BOOLEAN NOT NULL,
+    created_at BIGINT  NOT NULL
+);
+
+CREATE INDEX idx_puzzle_attempts_user ON puzzle_attempts(user_id, puzzle_id);
@@ -0,0 +1,137 @@
+//! Moderation: admin access, banned addresses, closing rooms by hand and
+//! disconnecting banned users.
+//!
+//! Admins are users with `is_admin` set in the database. The endpoints using
+//! these helpers live in [`crate::http`]; every action they take is written to
+//! the `admin_audit_log` table.
+
+use axum::extract::{FromRequestParts, Request, State};
+use axum::http::StatusCode;
+use axum::http::request::Parts;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum_login::AuthSession;
+use bytes::{BufMut, BytesMut};
+use deadpool_postgres::Pool;
+use bytes::Bytes;
+use protocol::{SERVER_DISCONNECT_MSG_SIZE, SERVER_DISCONNECTS};
+use std::collections::HashSet;
+use std::net::IpAddr;
+use std::sync::{Arc, RwLock};
+
+use crate::auth::AuthBackend;
+use crate::db;
+use crate::lobby::{AppState, forget_room};
+use crate::message_relay::CLOSE_HOST;
+use crate::rate_limit::ClientIp;
+
+// ── Admin extractor ───────────────────────────────────────────────────────────
+
+/// The logged-in user, if they are an admin. Rejects with 401 when nobody is
+/// logged in and 403 for everybody else.
+pub struct AdminUser(pub db::User);
+
+impl FromRequestParts<Arc<AppState>> for AdminUser {
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let auth_session = AuthSession::<AuthBackend>::from_request_parts(parts, state)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        match auth_session.user {
+            Some(user) if user.is_admin => Ok(AdminUser(user)),
+            Some(_) => Err(StatusCode::FORBIDDEN),
+            None => Err(StatusCode::UNAUTHORIZED),
+        }
+    }
+}
+
+// ── Banned addresses ──────────────────────────────────────────────────────────
+
+/// In-memory copy of the `banned_ips` table, checked on every request.
+#[derive(Default)]
+pub struct IpBans(RwLock<HashSet<IpAddr>>);
+
+impl IpBans {
+    /// Replaces the set with the addresses stored in the database.
+    pub async fn load(&self, pool: &Pool) -> Result<(), db::DbError> {
+        let banned = db::get_banned_ips(pool).await?;
+        *self.0.write().unwrap() = banned.into_iter().map(|b| b.ip).collect();
+        Ok(())
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        self.0.read().unwrap().contains(&ip)
+    }
+
+    pub fn insert(&self, ip: IpAddr) {
+        self.0.write().unwrap().insert(ip);
+    }
+
+    pub fn remove(&self, ip: IpAddr) {
+        self.0.write().unwrap().remove(&ip);
+    }
+}
+
+/// Middleware refusing every request, WebSocket upgrades included, from a banned address.
+pub async fn reject_banned_ips(
+    State(state): State<Arc<AppState>>,
+    ClientIp(ip): ClientIp,
+    request: Request,
+    next: Next,
+) -> Response {
+    if state.ip_bans.contains(ip) {
+        return (StatusCode::FORBIDDEN, "access from this address is blocked").into_response();
+    }
+    next.run(request).await
+}
+
+// ── Rooms ─────────────────────────────────────────────────────────────────────
+
+/// Closes a room the way its host would: players and spectators get
+/// [`SERVER_DISCONNECTS`] and the game record is closed without a result.
+/// The host's connection is ended too.
+/// Returns `false` if there is no such room.
+pub async fn terminate_room(state: &AppState, room_id: &str) -> bool {
+    let Some(room) = state.rooms.lock().await.remove(room_id) else {
+        return false;
+    };
+
+    let mut msg = BytesMut::with_capacity(SERVER_DISCONNECT_MSG_SIZE);
+    msg.put_u8(SERVER_DISCONNECTS);
+    let _ = room.host_to_client_broadcaster.send(msg.into());
+    let _ = room.to_host_sender.send(Bytes::from_static(&[CLOSE_HOST])).await;
+    tracing::info!(room_id, "Room terminated by an admin");
+    forget_room(state, room_id).await;
+
+    if let Some(record_id) = room.game_record_id
+        && let Err(e) = db::close_game_record(&state.db, record_id, None).await
+    {
+        tracing::warn!("Failed to close game record {record_id}: {e}");
+    }
+    true
+}
+
+// ── Users ─────────────────────────────────────────────────────────────────────
+
+/// Ends everything a banned user has open: their sessions, their notification
+/// sockets and the rooms they play in.
+pub async fn disconnect_user(state: &AppState, user_id: i64) -> Result<(), db::DbError> {
+    db::delete_user_sessions(&state.db, user_id).await?;
+    state.notifier.disconnect(user_id).await;
+    let room_ids: Vec<String> = state
+        .rooms
+        .lock()
+        .await
+        .iter()
+        .filter(|(_, room)| room.user_ids.values().any(|&id| id == Some(user_id)))
+        .map(|(room_id, _)| room_id.clone())
+        .collect();
+    for room_id in room_ids {
+        terminate_room(state, &room_id).await;
+    }
+    Ok(())
+}
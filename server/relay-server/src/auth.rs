@@ -1,15 +1,18 @@
 //! Authentication backend for axum-login.
 //!
 //! Implements [`AuthUser`] on [`db::User`] and provides [`AuthBackend`] which
-//! validates credentials against the database using Argon2 password hashing.
+//! validates credentials against the database using Argon2 password hashing,
+//! or signs in the owner of an identity asserted by an OpenID Connect provider.
 
 use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
 use argon2::password_hash::rand_core::OsRng;
 use argon2::Argon2;
 use axum_login::{AuthUser, AuthnBackend, UserId};
 use deadpool_postgres::Pool;
+use rand::Rng;
 
 use crate::db;
+use crate::oidc::Identity;
 
 // ── AuthUser ─────────────────────────────────────────────────────────────────
 
@@ -29,10 +32,14 @@ impl AuthUser for db::User {
 // ── Credentials ──────────────────────────────────────────────────────────────
 
 #[derive(Clone)]
-pub struct Credentials {
-    /// Accepts either a username or an email address.
-    pub login: String,
-    pub password: String,
+pub enum Credentials {
+    Password {
+        /// Accepts either a username or an email address.
+        login: String,
+        password: String,
+    },
+    /// A verified ID token. Its owner is signed in, or registered on first use.
+    Oidc(Identity),
 }
 
 // ── Error ────────────────────────────────────────────────────────────────────
@@ -43,6 +50,16 @@ pub enum AuthError {
     Database(#[from] db::DbError),
     #[error("password hashing error")]
     PasswordHash,
+    /// The provider gave no email address, which every account needs.
+    #[error("the identity has no email address")]
+    EmailRequired,
+    /// Another account uses the email address and it could not be proven to
+    /// belong to the same person.
+    #[error("email address already in use")]
+    EmailInUse,
+    /// The credentials are valid but an admin banned the account.
+    #[error("account banned")]
+    Banned,
 }
 
 // ── Backend ───────────────────────────────────────────────────────────────────
@@ -67,21 +84,106 @@ impl AuthnBackend for AuthBackend {
         &self,
         creds: Self::Credentials,
     ) -> Result<Option<Self::User>, Self::Error> {
-        let Some(user) = db::get_user_by_username_or_email(&self.pool, &creds.login).await? else {
+        let user = match creds {
+            Credentials::Password { login, password } => {
+                self.authenticate_password(&login, &password).await?
+            }
+            Credentials::Oidc(identity) => self.authenticate_identity(identity).await?,
+        };
+        match user {
+            Some(user) if user.banned_at.is_some() => Err(AuthError::Banned),
+            user => Ok(user),
+        }
+    }
+
+    /// Banned users are treated as unknown, which ends their sessions.
+    async fn get_user(&self, user_id: &UserId<Self>) -> Result<Option<Self::User>, Self::Error> {
+        let user = db::get_user_by_id(&self.pool, *user_id).await?;
+        Ok(user.filter(|u| u.banned_at.is_none()))
+    }
+}
+
+impl AuthBackend {
+    async fn authenticate_password(
+        &self,
+        login: &str,
+        password: &str,
+    ) -> Result<Option<db::User>, AuthError> {
+        let Some(user) = db::get_user_by_username_or_email(&self.pool, login).await? else {
             return Ok(None);
         };
+        // Accounts created through a provider have no password until one is set.
+        if user.password_hash.is_empty() {
+            return Ok(None);
+        }
 
         let parsed = PasswordHash::new(&user.password_hash).map_err(|_| AuthError::PasswordHash)?;
         let valid = Argon2::default()
-            .verify_password(creds.password.as_bytes(), &parsed)
+            .verify_password(password.as_bytes(), &parsed)
             .is_ok();
 
         Ok(valid.then_some(user))
     }
 
-    async fn get_user(&self, user_id: &UserId<Self>) -> Result<Option<Self::User>, Self::Error> {
-        Ok(db::get_user_by_id(&self.pool, *user_id).await?)
+    /// Returns the user the identity is linked to. An unknown identity is
+    /// linked to the account with the same verified email, or gets a new account.
+    async fn authenticate_identity(
+        &self,
+        identity: Identity,
+    ) -> Result<Option<db::User>, AuthError> {
+        if let Some(user) =
+            db::get_user_by_identity(&self.pool, &identity.issuer, &identity.subject).await?
+        {
+            return Ok(Some(user));
+        }
+        let email = identity.email.as_deref().ok_or(AuthError::EmailRequired)?;
+
+        if let Some(user) = db::get_user_by_email(&self.pool, email).await? {
+            if !(user.email_verified && identity.email_verified) {
+                return Err(AuthError::EmailInUse);
+            }
+            db::link_identity(&self.pool, user.id, &identity.issuer, &identity.subject).await?;
+            return Ok(Some(user));
+        }
+
+        let base = username_base(&identity, email);
+        let mut username = base.clone();
+        let mut attempts = 0;
+        let user_id = loop {
+            attempts += 1;
+            match db::create_user(&self.pool, &username, email, "").await {
+                Ok(id) => break id,
+                Err(e) if e.is_unique_violation() && attempts < 5 => {
+                    let suffix: u16 = rand::thread_rng().gen_range(1000..10000);
+                    username = format!("{base}{suffix}");
+                }
+                Err(e) => return Err(e.into()),
+            }
+        };
+        db::link_identity(&self.pool, user_id, &identity.issuer, &identity.subject).await?;
+        if identity.email_verified {
+            db::set_email_verified(&self.pool, user_id).await?;
+        }
+        Ok(db::get_user_by_id(&self.pool, user_id).await?)
+    }
+}
+
+/// Derives a valid username (3–26 characters, leaving room for a numeric
+/// suffix) from the provider's username or the email address.
+fn username_base(identity: &Identity, email: &str) -> String {
+    let source = identity
+        .preferred_username
+        .as_deref()
+        .unwrap_or_else(|| email.split('@').next().unwrap_or_default());
+    let mut name: String = source
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '-')
+        .take(26)
+        .collect();
+    if name.len() < 3 {
+        name.insert_str(0, "player");
     }
+    name
 }
 
 // ── Password hashing helper ───────────────────────────────────────────────────
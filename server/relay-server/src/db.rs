@@ -4,9 +4,15 @@
 //! `Result<_, DbError>` so callers can handle errors uniformly.
 
 use deadpool_postgres::{Manager, ManagerConfig, Pool, RecyclingMethod};
+use rand::Rng;
+use rand::distributions::Alphanumeric;
 use tokio_postgres::{NoTls, error::SqlState};
+use std::net::IpAddr;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use crate::migrate;
+use crate::tournament::{self, Outcome, Pairing, Status};
+
 /// A registered user as stored in the database.
 #[derive(Clone, Debug)]
 pub struct User {
@@ -16,6 +22,11 @@ pub struct User {
     pub password_hash: String,
     pub created_at: i64,
     pub email_verified: bool,
+    /// Whether challenges are also sent by email while the user is offline.
+    pub email_notifications: bool,
+    pub is_admin: bool,
+    /// When an admin banned the account. Banned users cannot sign in.
+    pub banned_at: Option<i64>,
 }
 
 /// Aggregated game statistics for a user's public profile.
@@ -37,6 +48,109 @@ pub struct GameSummary {
     pub outcome: Option<String>,
 }
 
+/// A tournament with its organizer's name and number of registered players.
+pub struct Tournament {
+    pub id: i64,
+    pub name: String,
+    pub game_id: String,
+    pub format: String,
+    pub rule_variation: i32,
+    pub rounds: i32,
+    pub status: String,
+    pub current_round: i32,
+    pub organizer_id: Option<i64>,
+    pub organizer: Option<String>,
+    pub created_at: i64,
+    pub player_count: i64,
+}
+
+/// A registered tournament player, returned in seed (registration) order.
+pub struct TournamentPlayer {
+    pub user_id: i64,
+    pub username: String,
+}
+
+/// A paired tournament game. User ids are `None` once the account is deleted.
+pub struct TournamentGame {
+    pub id: i64,
+    pub round: i32,
+    pub board: i32,
+    pub host_user_id: Option<i64>,
+    pub guest_user_id: Option<i64>,
+    pub host_username: Option<String>,
+    pub guest_username: Option<String>,
+    pub is_bye: bool,
+    pub room_code: String,
+    pub game_record_id: Option<i64>,
+    pub outcome: Option<String>,
+}
+
+/// Who may play in a reserved room (tournament game or challenge), and with which rules.
+pub struct RoomSeat {
+    pub host_user_id: Option<i64>,
+    pub guest_user_id: Option<i64>,
+    pub rule_variation: i32,
+    /// No game can be played in the room any more.
+    pub closed: bool,
+}
+
+/// The other users a user is related to, by username.
+pub struct FriendLists {
+    /// Accepted friends as `(user_id, username)`.
+    pub friends: Vec<(i64, String)>,
+    /// Pending requests sent to the user.
+    pub incoming: Vec<String>,
+    /// Pending requests sent by the user.
+    pub outgoing: Vec<String>,
+    pub blocked: Vec<String>,
+}
+
+/// A game challenge from one user to another.
+pub struct Challenge {
+    pub id: i64,
+    pub challenger_id: i64,
+    pub challenger: String,
+    pub invitee_id: i64,
+    pub invitee: String,
+    pub game_id: String,
+    pub rule_variation: i32,
+    pub room_code: String,
+    pub status: String,
+    pub created_at: i64,
+}
+
+/// An address barred from the relay.
+pub struct BannedIp {
+    pub ip: IpAddr,
+    pub reason: Option<String>,
+    pub created_at: i64,
+}
+
+/// One admin action, as recorded by [`insert_audit_entry`].
+pub struct AuditEntry {
+    pub id: i64,
+    /// `None` once the admin's account is deleted.
+    pub admin: Option<String>,
+    pub action: String,
+    pub target: String,
+    pub details: Option<String>,
+    pub created_at: i64,
+}
+
+/// A puzzle, with how the requesting user fared on it.
+pub struct Puzzle {
+    pub id: i64,
+    pub game_id: String,
+    pub slug: String,
+    pub title: String,
+    /// Game-specific JSON: the position and its solutions.
+    pub data: String,
+    pub created_at: i64,
+    /// Attempts by the user; 0 without one.
+    pub attempts: i64,
+    pub solved: bool,
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum DbError {
     #[error("connection pool error: {0}")]
@@ -62,38 +176,35 @@ pub fn now_unix() -> i64 {
         .as_secs() as i64
 }
 
-/// Connects to the PostgreSQL database at `url` and runs all pending migrations.
-pub async fn init_db(url: &str) -> Pool {
+/// Builds the connection pool for the PostgreSQL database at `url`.
+pub fn connect(url: &str) -> Pool {
     let pg_config: tokio_postgres::Config = url.parse().expect("Invalid DATABASE_URL");
     let manager = Manager::from_config(
         pg_config,
         NoTls,
         ManagerConfig { recycling_method: RecyclingMethod::Fast },
     );
-    let pool = Pool::builder(manager)
+    Pool::builder(manager)
         .max_size(5)
         .build()
-        .expect("Failed to build connection pool");
-
-    let client = pool.get().await.expect("Failed to get connection for migrations");
-    client
-        .batch_execute(include_str!("../migrations/001_init.sql"))
-        .await
-        .expect("Migration 001 failed");
-    client
-        .batch_execute(include_str!("../migrations/002_participants_unique.sql"))
-        .await
-        .expect("Migration 002 failed");
-    client
-        .batch_execute(include_str!("../migrations/003_email_verification.sql"))
-        .await
-        .expect("Migration 003 failed");
+        .expect("Failed to build connection pool")
+}
 
+/// Connects to the PostgreSQL database at `url` and runs all pending migrations.
+pub async fn init_db(url: &str) -> Pool {
+    let pool = connect(url);
+    if let Err(e) = migrate::run(&pool).await {
+        tracing::error!("{e}");
+        panic!("Migrations failed: {e}");
+    }
     pool
 }
 
 // ── Users ────────────────────────────────────────────────────────────────────
 
+const USER_COLUMNS: &str = "u.id, u.username, u.email, u.password_hash, u.created_at, \
+     u.email_verified, u.email_notifications, u.is_admin, u.banned_at";
+
 fn user_from_row(r: &tokio_postgres::Row) -> User {
     User {
         id: r.get("id"),
@@ -102,6 +213,9 @@ fn user_from_row(r: &tokio_postgres::Row) -> User {
         password_hash: r.get("password_hash"),
         created_at: r.get("created_at"),
         email_verified: r.get("email_verified"),
+        email_notifications: r.get("email_notifications"),
+        is_admin: r.get("is_admin"),
+        banned_at: r.get("banned_at"),
     }
 }
 
@@ -126,8 +240,7 @@ pub async fn get_user_by_id(pool: &Pool, id: i64) -> Result<Option<User>, DbErro
     let client = pool.get().await?;
     let row = client
         .query_opt(
-            "SELECT id, username, email, password_hash, created_at, email_verified \
-             FROM users WHERE id = $1",
+            &format!("SELECT {USER_COLUMNS} FROM users u WHERE u.id = $1"),
             &[&id],
         )
         .await?;
@@ -138,8 +251,7 @@ pub async fn get_user_by_username(pool: &Pool, username: &str) -> Result<Option<
     let client = pool.get().await?;
     let row = client
         .query_opt(
-            "SELECT id, username, email, password_hash, created_at, email_verified \
-             FROM users WHERE username = $1",
+            &format!("SELECT {USER_COLUMNS} FROM users u WHERE u.username = $1"),
             &[&username],
         )
         .await?;
@@ -150,8 +262,7 @@ pub async fn get_user_by_email(pool: &Pool, email: &str) -> Result<Option<User>,
     let client = pool.get().await?;
     let row = client
         .query_opt(
-            "SELECT id, username, email, password_hash, created_at, email_verified \
-             FROM users WHERE email = $1",
+            &format!("SELECT {USER_COLUMNS} FROM users u WHERE u.email = $1"),
             &[&email],
         )
         .await?;
@@ -177,6 +288,21 @@ pub async fn set_email_verified(pool: &Pool, user_id: i64) -> Result<(), DbError
     Ok(())
 }
 
+pub async fn set_email_notifications(
+    pool: &Pool,
+    user_id: i64,
+    enabled: bool,
+) -> Result<(), DbError> {
+    let client = pool.get().await?;
+    client
+        .execute(
+            "UPDATE users SET email_notifications = $1 WHERE id = $2",
+            &[&enabled, &user_id],
+        )
+        .await?;
+    Ok(())
+}
+
 pub async fn update_password_hash(pool: &Pool, user_id: i64, hash: &str) -> Result<(), DbError> {
     let client = pool.get().await?;
     client
@@ -207,6 +333,45 @@ pub async fn delete_user(pool: &Pool, user_id: i64) -> Result<(), DbError> {
     Ok(())
 }
 
+// ── Linked identities ────────────────────────────────────────────────────────
+
+/// Finds the user an OpenID Connect account is linked to.
+pub async fn get_user_by_identity(
+    pool: &Pool,
+    issuer: &str,
+    subject: &str,
+) -> Result<Option<User>, DbError> {
+    let client = pool.get().await?;
+    let row = client
+        .query_opt(
+            &format!(
+                "SELECT {USER_COLUMNS} \
+                 FROM user_identities i JOIN users u ON u.id = i.user_id \
+                 WHERE i.issuer = $1 AND i.subject = $2"
+            ),
+            &[&issuer, &subject],
+        )
+        .await?;
+    Ok(row.as_ref().map(user_from_row))
+}
+
+pub async fn link_identity(
+    pool: &Pool,
+    user_id: i64,
+    issuer: &str,
+    subject: &str,
+) -> Result<(), DbError> {
+    let client = pool.get().await?;
+    client
+        .execute(
+            "INSERT INTO user_identities (issuer, subject, user_id, created_at) \
+             VALUES ($1, $2, $3, $4)",
+            &[&issuer, &subject, &user_id, &now_unix()],
+        )
+        .await?;
+    Ok(())
+}
+
 // ── Email tokens ──────────────────────────────────────────────────────────────
 
 pub async fn create_email_token(
@@ -302,6 +467,32 @@ pub async fn close_game_record(
     Ok(())
 }
 
+/// Stores the opaque game record (the moves played) supplied by the game.
+/// The first record stored wins, like the result.
+pub async fn store_game_record(
+    pool: &Pool,
+    record_id: i64,
+    record_json: &str,
+) -> Result<(), DbError> {
+    let client = pool.get().await?;
+    client
+        .execute(
+            "UPDATE game_records SET record = $1 WHERE id = $2 AND record IS NULL",
+            &[&record_json, &record_id],
+        )
+        .await?;
+    Ok(())
+}
+
+/// The game record stored for `record_id`; `None` when the game or its record is missing.
+pub async fn get_game_record(pool: &Pool, record_id: i64) -> Result<Option<String>, DbError> {
+    let client = pool.get().await?;
+    let row = client
+        .query_opt("SELECT record FROM game_records WHERE id = $1", &[&record_id])
+        .await?;
+    Ok(row.and_then(|r| r.get("record")))
+}
+
 /// Records a player's participation in a game. `user_id` is `None` for anonymous players.
 pub async fn insert_participant(
     pool: &Pool,
@@ -322,6 +513,7 @@ pub async fn insert_participant(
 }
 
 /// Returns win/loss/draw counts for a user. All values are 0 when the user has no games.
+/// Voided games are not counted.
 pub async fn get_user_stats(pool: &Pool, user_id: i64) -> Result<UserStats, DbError> {
     let client = pool.get().await?;
     let row = client
@@ -331,8 +523,9 @@ pub async fn get_user_stats(pool: &Pool, user_id: i64) -> Result<UserStats, DbEr
                  COALESCE(SUM(CASE WHEN outcome = 'win'  THEN 1 ELSE 0 END), 0::BIGINT) as wins,
                  COALESCE(SUM(CASE WHEN outcome = 'loss' THEN 1 ELSE 0 END), 0::BIGINT) as losses,
                  COALESCE(SUM(CASE WHEN outcome = 'draw' THEN 1 ELSE 0 END), 0::BIGINT) as draws
-             FROM game_participants
-             WHERE user_id = $1",
+             FROM game_participants gp
+             JOIN game_records gr ON gr.id = gp.game_record_id
+             WHERE gp.user_id = $1 AND gr.voided_at IS NULL",
             &[&user_id],
         )
         .await?;
@@ -344,7 +537,8 @@ pub async fn get_user_stats(pool: &Pool, user_id: i64) -> Result<UserStats, DbEr
     })
 }
 
-/// Returns a paginated list of games a user participated in, newest first.
+/// Returns a paginated list of games a user participated in, newest first,
+/// leaving out voided games.
 pub async fn get_user_games(
     pool: &Pool,
     user_id: i64,
@@ -357,7 +551,7 @@ pub async fn get_user_games(
             "SELECT gr.id, gr.game_id, gr.room_code, gr.started_at, gr.ended_at, gr.result, gp.outcome
              FROM game_records gr
              JOIN game_participants gp ON gp.game_record_id = gr.id
-             WHERE gp.user_id = $1
+             WHERE gp.user_id = $1 AND gr.voided_at IS NULL
              ORDER BY gr.started_at DESC
              LIMIT $2 OFFSET $3",
             &[&user_id, &per_page, &(page * per_page)],
@@ -376,3 +570,954 @@ pub async fn get_user_games(
         })
         .collect())
 }
+
+// ── Tournaments ──────────────────────────────────────────────────────────────
+
+const TOURNAMENT_COLUMNS: &str =
+    "t.id, t.name, t.game_id, t.format, t.rule_variation, t.rounds, t.status, \
+     t.current_round, t.organizer_id, u.username AS organizer, t.created_at, \
+     (SELECT COUNT(*) FROM tournament_players tp WHERE tp.tournament_id = t.id) AS player_count";
+
+fn tournament_from_row(r: &tokio_postgres::Row) -> Tournament {
+    Tournament {
+        id: r.get("id"),
+        name: r.get("name"),
+        game_id: r.get("game_id"),
+        format: r.get("format"),
+        rule_variation: r.get("rule_variation"),
+        rounds: r.get("rounds"),
+        status: r.get("status"),
+        current_round: r.get("current_round"),
+        organizer_id: r.get("organizer_id"),
+        organizer: r.get("organizer"),
+        created_at: r.get("created_at"),
+        player_count: r.get("player_count"),
+    }
+}
+
+pub async fn create_tournament(
+    pool: &Pool,
+    name: &str,
+    game_id: &str,
+    format: &str,
+    rule_variation: i32,
+    rounds: i32,
+    organizer_id: i64,
+) -> Result<i64, DbError> {
+    let client = pool.get().await?;
+    let row = client
+        .query_one(
+            "INSERT INTO tournaments \
+             (name, game_id, format, rule_variation, rounds, organizer_id, created_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING id",
+            &[&name, &game_id, &format, &rule_variation, &rounds, &organizer_id, &now_unix()],
+        )
+        .await?;
+    Ok(row.get(0))
+}
+
+/// Returns all tournaments, newest first.
+pub async fn list_tournaments(pool: &Pool) -> Result<Vec<Tournament>, DbError> {
+    let client = pool.get().await?;
+    let rows = client
+        .query(
+            &format!(
+                "SELECT {TOURNAMENT_COLUMNS} FROM tournaments t \
+                 LEFT JOIN users u ON u.id = t.organizer_id \
+                 ORDER BY t.created_at DESC, t.id DESC"
+            ),
+            &[],
+        )
+        .await?;
+    Ok(rows.iter().map(tournament_from_row).collect())
+}
+
+pub async fn get_tournament(pool: &Pool, id: i64) -> Result<Option<Tournament>, DbError> {
+    let client = pool.get().await?;
+    let row = client
+        .query_opt(
+            &format!(
+                "SELECT {TOURNAMENT_COLUMNS} FROM tournaments t \
+                 LEFT JOIN users u ON u.id = t.organizer_id \
+                 WHERE t.id = $1"
+            ),
+            &[&id],
+        )
+        .await?;
+    Ok(row.as_ref().map(tournament_from_row))
+}
+
+/// Registers a player. Fails with a unique violation if already registered.
+pub async fn add_tournament_player(
+    pool: &Pool,
+    tournament_id: i64,
+    user_id: i64,
+) -> Result<(), DbError> {
+    let client = pool.get().await?;
+    client
+        .execute(
+            "INSERT INTO tournament_players (tournament_id, user_id, registered_at) \
+             VALUES ($1, $2, $3)",
+            &[&tournament_id, &user_id, &now_unix()],
+        )
+        .await?;
+    Ok(())
+}
+
+/// Withdraws a player. Returns `false` if they were not registered.
+pub async fn remove_tournament_player(
+    pool: &Pool,
+    tournament_id: i64,
+    user_id: i64,
+) -> Result<bool, DbError> {
+    let client = pool.get().await?;
+    let n = client
+        .execute(
+            "DELETE FROM tournament_players WHERE tournament_id = $1 AND user_id = $2",
+            &[&tournament_id, &user_id],
+        )
+        .await?;
+    Ok(n > 0)
+}
+
+/// Registered players in seed order.
+pub async fn get_tournament_players(
+    pool: &Pool,
+    tournament_id: i64,
+) -> Result<Vec<TournamentPlayer>, DbError> {
+    let client = pool.get().await?;
+    let rows = client
+        .query(
+            "SELECT tp.user_id, u.username
+             FROM tournament_players tp
+             JOIN users u ON u.id = tp.user_id
+             WHERE tp.tournament_id = $1
+             ORDER BY tp.registered_at, tp.user_id",
+            &[&tournament_id],
+        )
+        .await?;
+    Ok(rows
+        .into_iter()
+        .map(|r| TournamentPlayer {
+            user_id: r.get("user_id"),
+            username: r.get("username"),
+        })
+        .collect())
+}
+
+/// All games of a tournament, ordered by round and board.
+pub async fn get_tournament_games(
+    pool: &Pool,
+    tournament_id: i64,
+) -> Result<Vec<TournamentGame>, DbError> {
+    let client = pool.get().await?;
+    let rows = client
+        .query(
+            "SELECT tg.id, tg.round, tg.board, tg.host_user_id, tg.guest_user_id,
+                    h.username AS host_username, g.username AS guest_username,
+                    tg.is_bye, tg.room_code, tg.game_record_id, tg.outcome
+             FROM tournament_games tg
+             LEFT JOIN users h ON h.id = tg.host_user_id
+             LEFT JOIN users g ON g.id = tg.guest_user_id
+             WHERE tg.tournament_id = $1
+             ORDER BY tg.round, tg.board",
+            &[&tournament_id],
+        )
+        .await?;
+    Ok(rows
+        .into_iter()
+        .map(|r| TournamentGame {
+            id: r.get("id"),
+            round: r.get("round"),
+            board: r.get("board"),
+            host_user_id: r.get("host_user_id"),
+            guest_user_id: r.get("guest_user_id"),
+            host_username: r.get("host_username"),
+            guest_username: r.get("guest_username"),
+            is_bye: r.get("is_bye"),
+            room_code: r.get("room_code"),
+            game_record_id: r.get("game_record_id"),
+            outcome: r.get("outcome"),
+        })
+        .collect())
+}
+
+/// Random part of a tournament room code.
+fn room_secret() -> String {
+    rand::thread_rng()
+        .sample_iter(Alphanumeric)
+        .take(12)
+        .map(|b| char::from(b).to_ascii_lowercase())
+        .collect()
+}
+
+/// Stores the pairings of `round` and makes it the current round, in one
+/// transaction. Byes are stored as already won by the host.
+pub async fn start_tournament_round(
+    pool: &Pool,
+    tournament_id: i64,
+    round: i32,
+    pairings: &[Pairing],
+) -> Result<(), DbError> {
+    let mut client = pool.get().await?;
+    let tx = client.transaction().await?;
+    for (i, pairing) in pairings.iter().enumerate() {
+        let board = i as i32 + 1;
+        let is_bye = pairing.guest.is_none();
+        let outcome = is_bye.then_some(Outcome::HostWon.as_str());
+        tx.execute(
+            "INSERT INTO tournament_games \
+             (tournament_id, round, board, host_user_id, guest_user_id, is_bye, room_code, outcome) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+            &[
+                &tournament_id,
+                &round,
+                &board,
+                &pairing.host,
+                &pairing.guest,
+                &is_bye,
+                &tournament::room_code(tournament_id, round, board, &room_secret()),
+                &outcome,
+            ],
+        )
+        .await?;
+    }
+    tx.execute(
+        "UPDATE tournaments SET status = $1, current_round = $2 WHERE id = $3",
+        &[&Status::Running.as_str(), &round, &tournament_id],
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+pub async fn finish_tournament(pool: &Pool, tournament_id: i64) -> Result<(), DbError> {
+    let client = pool.get().await?;
+    client
+        .execute(
+            "UPDATE tournaments SET status = $1 WHERE id = $2",
+            &[&Status::Finished.as_str(), &tournament_id],
+        )
+        .await?;
+    Ok(())
+}
+
+/// Sets the outcome of a tournament game by hand (forfeits, disputes).
+/// Returns `false` if the game does not belong to the tournament or is a bye.
+pub async fn set_tournament_game_outcome(
+    pool: &Pool,
+    tournament_id: i64,
+    game_id: i64,
+    outcome: &str,
+) -> Result<bool, DbError> {
+    let client = pool.get().await?;
+    let n = client
+        .execute(
+            "UPDATE tournament_games SET outcome = $1 \
+             WHERE id = $2 AND tournament_id = $3 AND NOT is_bye",
+            &[&outcome, &game_id, &tournament_id],
+        )
+        .await?;
+    Ok(n > 0)
+}
+
+/// Stores the result of the game played in a tournament room, if `room_code`
+/// belongs to one and `host_user_id` is its paired host. The first recorded
+/// result wins, and a voided game records none.
+pub async fn record_tournament_result(
+    pool: &Pool,
+    room_code: &str,
+    host_user_id: i64,
+    game_record_id: i64,
+    outcome: &str,
+) -> Result<(), DbError> {
+    let client = pool.get().await?;
+    client
+        .execute(
+            "UPDATE tournament_games SET outcome = $1, game_record_id = $2 \
+             WHERE room_code = $3 AND host_user_id = $4 AND outcome IS NULL \
+               AND NOT EXISTS (SELECT 1 FROM game_records \
+                               WHERE id = $2 AND voided_at IS NOT NULL)",
+            &[&outcome, &game_record_id, &room_code, &host_user_id],
+        )
+        .await?;
+    Ok(())
+}
+
+/// Looks up the tournament game reserved under `room_code` for `game_id`.
+pub async fn get_tournament_seat(
+    pool: &Pool,
+    room_code: &str,
+    game_id: &str,
+) -> Result<Option<RoomSeat>, DbError> {
+    let client = pool.get().await?;
+    let row = client
+        .query_opt(
+            "SELECT tg.host_user_id, tg.guest_user_id, tg.outcome, t.rule_variation
+             FROM tournament_games tg
+             JOIN tournaments t ON t.id = tg.tournament_id
+             WHERE tg.room_code = $1 AND t.game_id = $2",
+            &[&room_code, &game_id],
+        )
+        .await?;
+    Ok(row.map(|r| RoomSeat {
+        host_user_id: r.get("host_user_id"),
+        guest_user_id: r.get("guest_user_id"),
+        rule_variation: r.get("rule_variation"),
+        closed: r.get::<_, Option<String>>("outcome").is_some(),
+    }))
+}
+
+// ── Friends ──────────────────────────────────────────────────────────────────
+
+pub async fn get_friend_lists(pool: &Pool, user_id: i64) -> Result<FriendLists, DbError> {
+    let client = pool.get().await?;
+    let rows = client
+        .query(
+            "SELECT f.requester_id, f.status, u.id AS other_id, u.username
+             FROM friendships f
+             JOIN users u ON u.id = CASE WHEN f.requester_id = $1
+                                         THEN f.addressee_id ELSE f.requester_id END
+             WHERE f.requester_id = $1 OR f.addressee_id = $1
+             ORDER BY u.username",
+            &[&user_id],
+        )
+        .await?;
+    let mut lists = FriendLists {
+        friends: Vec::new(),
+        incoming: Vec::new(),
+        outgoing: Vec::new(),
+        blocked: Vec::new(),
+    };
+    for r in rows {
+        let username: String = r.get("username");
+        let status: String = r.get("status");
+        if status == "accepted" {
+            lists.friends.push((r.get("other_id"), username));
+        } else if r.get::<_, i64>("requester_id") == user_id {
+            lists.outgoing.push(username);
+        } else {
+            lists.incoming.push(username);
+        }
+    }
+    lists.blocked = client
+        .query(
+            "SELECT u.username
+             FROM user_blocks b
+             JOIN users u ON u.id = b.blocked_id
+             WHERE b.blocker_id = $1
+             ORDER BY u.username",
+            &[&user_id],
+        )
+        .await?
+        .into_iter()
+        .map(|r| r.get("username"))
+        .collect();
+    Ok(lists)
+}
+
+/// Returns `(requester_id, status)` of the friendship between two users, in
+/// either direction.
+pub async fn get_friendship(
+    pool: &Pool,
+    user_a: i64,
+    user_b: i64,
+) -> Result<Option<(i64, String)>, DbError> {
+    let client = pool.get().await?;
+    let row = client
+        .query_opt(
+            "SELECT requester_id, status FROM friendships
+             WHERE (requester_id = $1 AND addressee_id = $2)
+                OR (requester_id = $2 AND addressee_id = $1)",
+            &[&user_a, &user_b],
+        )
+        .await?;
+    Ok(row.map(|r| (r.get("requester_id"), r.get("status"))))
+}
+
+/// Fails with a unique violation if the two users are already related.
+pub async fn insert_friend_request(
+    pool: &Pool,
+    requester_id: i64,
+    addressee_id: i64,
+) -> Result<(), DbError> {
+    let client = pool.get().await?;
+    client
+        .execute(
+            "INSERT INTO friendships (requester_id, addressee_id, status, created_at) \
+             VALUES ($1, $2, 'pending', $3)",
+            &[&requester_id, &addressee_id, &now_unix()],
+        )
+        .await?;
+    Ok(())
+}
+
+/// Accepts the pending request `requester_id` sent to `addressee_id`.
+/// Returns `false` if there is no such request.
+pub async fn accept_friend_request(
+    pool: &Pool,
+    requester_id: i64,
+    addressee_id: i64,
+) -> Result<bool, DbError> {
+    let client = pool.get().await?;
+    let n = client
+        .execute(
+            "UPDATE friendships SET status = 'accepted' \
+             WHERE requester_id = $1 AND addressee_id = $2 AND status = 'pending'",
+            &[&requester_id, &addressee_id],
+        )
+        .await?;
+    Ok(n > 0)
+}
+
+/// Removes a friendship or a pending request, whoever sent it.
+/// Returns `false` if the two users were not related.
+pub async fn delete_friendship(pool: &Pool, user_a: i64, user_b: i64) -> Result<bool, DbError> {
+    let client = pool.get().await?;
+    let n = client
+        .execute(
+            "DELETE FROM friendships
+             WHERE (requester_id = $1 AND addressee_id = $2)
+                OR (requester_id = $2 AND addressee_id = $1)",
+            &[&user_a, &user_b],
+        )
+        .await?;
+    Ok(n > 0)
+}
+
+pub async fn are_friends(pool: &Pool, user_a: i64, user_b: i64) -> Result<bool, DbError> {
+    Ok(matches!(
+        get_friendship(pool, user_a, user_b).await?,
+        Some((_, status)) if status == "accepted"
+    ))
+}
+
+/// True if either user blocked the other.
+pub async fn is_blocked_between(pool: &Pool, user_a: i64, user_b: i64) -> Result<bool, DbError> {
+    let client = pool.get().await?;
+    let row = client
+        .query_opt(
+            "SELECT 1 FROM user_blocks
+             WHERE (blocker_id = $1 AND blocked_id = $2)
+                OR (blocker_id = $2 AND blocked_id = $1)",
+            &[&user_a, &user_b],
+        )
+        .await?;
+    Ok(row.is_some())
+}
+
+/// Blocks a user: ends the friendship and cancels pending challenges between
+/// the two, in one transaction.
+pub async fn block_user(pool: &Pool, blocker_id: i64, blocked_id: i64) -> Result<(), DbError> {
+    let mut client = pool.get().await?;
+    let tx = client.transaction().await?;
+    tx.execute(
+        "INSERT INTO user_blocks (blocker_id, blocked_id, created_at) VALUES ($1, $2, $3) \
+         ON CONFLICT DO NOTHING",
+        &[&blocker_id, &blocked_id, &now_unix()],
+    )
+    .await?;
+    tx.execute(
+        "DELETE FROM friendships
+         WHERE (requester_id = $1 AND addressee_id = $2)
+            OR (requester_id = $2 AND addressee_id = $1)",
+        &[&blocker_id, &blocked_id],
+    )
+    .await?;
+    tx.execute(
+        "UPDATE challenges SET status = 'cancelled'
+         WHERE status = 'pending'
+           AND ((challenger_id = $1 AND invitee_id = $2)
+             OR (challenger_id = $2 AND invitee_id = $1))",
+        &[&blocker_id, &blocked_id],
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Returns `false` if the user was not blocked.
+pub async fn unblock_user(pool: &Pool, blocker_id: i64, blocked_id: i64) -> Result<bool, DbError> {
+    let client = pool.get().await?;
+    let n = client
+        .execute(
+            "DELETE FROM user_blocks WHERE blocker_id = $1 AND blocked_id = $2",
+            &[&blocker_id, &blocked_id],
+        )
+        .await?;
+    Ok(n > 0)
+}
+
+// ── Challenges ───────────────────────────────────────────────────────────────
+
+const CHALLENGE_COLUMNS: &str = "c.id, c.challenger_id, cu.username AS challenger, \
+     c.invitee_id, iu.username AS invitee, c.game_id, c.rule_variation, c.room_code, \
+     c.status, c.created_at";
+
+fn challenge_from_row(r: &tokio_postgres::Row) -> Challenge {
+    Challenge {
+        id: r.get("id"),
+        challenger_id: r.get("challenger_id"),
+        challenger: r.get("challenger"),
+        invitee_id: r.get("invitee_id"),
+        invitee: r.get("invitee"),
+        game_id: r.get("game_id"),
+        rule_variation: r.get("rule_variation"),
+        room_code: r.get("room_code"),
+        status: r.get("status"),
+        created_at: r.get("created_at"),
+    }
+}
+
+pub async fn create_challenge(
+    pool: &Pool,
+    challenger_id: i64,
+    invitee_id: i64,
+    game_id: &str,
+    rule_variation: i32,
+    room_code: &str,
+) -> Result<i64, DbError> {
+    let client = pool.get().await?;
+    let row = client
+        .query_one(
+            "INSERT INTO challenges \
+             (challenger_id, invitee_id, game_id, rule_variation, room_code, created_at) \
+             VALUES ($1, $2, $3, $4, $5, $6) RETURNING id",
+            &[&challenger_id, &invitee_id, &game_id, &rule_variation, &room_code, &now_unix()],
+        )
+        .await?;
+    Ok(row.get(0))
+}
+
+pub async fn get_challenge(pool: &Pool, id: i64) -> Result<Option<Challenge>, DbError> {
+    let client = pool.get().await?;
+    let row = client
+        .query_opt(
+            &format!(
+                "SELECT {CHALLENGE_COLUMNS} FROM challenges c \
+                 JOIN users cu ON cu.id = c.challenger_id \
+                 JOIN users iu ON iu.id = c.invitee_id \
+                 WHERE c.id = $1"
+            ),
+            &[&id],
+        )
+        .await?;
+    Ok(row.as_ref().map(challenge_from_row))
+}
+
+/// Pending challenges sent or received by a user since `since`, newest first.
+pub async fn get_pending_challenges(
+    pool: &Pool,
+    user_id: i64,
+    since: i64,
+) -> Result<Vec<Challenge>, DbError> {
+    let client = pool.get().await?;
+    let rows = client
+        .query(
+            &format!(
+                "SELECT {CHALLENGE_COLUMNS} FROM challenges c \
+                 JOIN users cu ON cu.id = c.challenger_id \
+                 JOIN users iu ON iu.id = c.invitee_id \
+                 WHERE (c.challenger_id = $1 OR c.invitee_id = $1) \
+                   AND c.status = 'pending' AND c.created_at >= $2 \
+                 ORDER BY c.created_at DESC"
+            ),
+            &[&user_id, &since],
+        )
+        .await?;
+    Ok(rows.iter().map(challenge_from_row).collect())
+}
+
+/// Moves a pending challenge to `status` ("accepted", "declined" or "cancelled").
+/// Returns `false` if the challenge was no longer pending.
+pub async fn close_challenge(pool: &Pool, id: i64, status: &str) -> Result<bool, DbError> {
+    let client = pool.get().await?;
+    let n = client
+        .execute(
+            "UPDATE challenges SET status = $1 WHERE id = $2 AND status = 'pending'",
+            &[&status, &id],
+        )
+        .await?;
+    Ok(n > 0)
+}
+
+/// Looks up the challenge reserved under `room_code` for `game_id`. Declined
+/// and cancelled challenges close the room.
+pub async fn get_challenge_seat(
+    pool: &Pool,
+    room_code: &str,
+    game_id: &str,
+) -> Result<Option<RoomSeat>, DbError> {
+    let client = pool.get().await?;
+    let row = client
+        .query_opt(
+            "SELECT challenger_id, invitee_id, rule_variation, status
+             FROM challenges
+             WHERE room_code = $1 AND game_id = $2",
+            &[&room_code, &game_id],
+        )
+        .await?;
+    Ok(row.map(|r| {
+        let status: String = r.get("status");
+        RoomSeat {
+            host_user_id: r.get("challenger_id"),
+            guest_user_id: r.get("invitee_id"),
+            rule_variation: r.get("rule_variation"),
+            closed: status == "declined" || status == "cancelled",
+        }
+    }))
+}
+
+// ── Sessions ─────────────────────────────────────────────────────────────────
+
+/// Inserts a new session. Returns `false` if the id is already taken.
+pub async fn insert_session(
+    pool: &Pool,
+    id: &str,
+    data: &str,
+    expiry_date: i64,
+) -> Result<bool, DbError> {
+    let client = pool.get().await?;
+    let n = client
+        .execute(
+            "INSERT INTO sessions (id, data, expiry_date) VALUES ($1, $2, $3) \
+             ON CONFLICT (id) DO NOTHING",
+            &[&id, &data, &expiry_date],
+        )
+        .await?;
+    Ok(n > 0)
+}
+
+pub async fn upsert_session(
+    pool: &Pool,
+    id: &str,
+    data: &str,
+    expiry_date: i64,
+) -> Result<(), DbError> {
+    let client = pool.get().await?;
+    client
+        .execute(
+            "INSERT INTO sessions (id, data, expiry_date) VALUES ($1, $2, $3) \
+             ON CONFLICT (id) DO UPDATE SET data = EXCLUDED.data, expiry_date = EXCLUDED.expiry_date",
+            &[&id, &data, &expiry_date],
+        )
+        .await?;
+    Ok(())
+}
+
+/// Returns `(data, expiry_date)` of a session that has not expired yet.
+pub async fn get_session(pool: &Pool, id: &str) -> Result<Option<(String, i64)>, DbError> {
+    let client = pool.get().await?;
+    let row = client
+        .query_opt(
+            "SELECT data, expiry_date FROM sessions WHERE id = $1 AND expiry_date > $2",
+            &[&id, &now_unix()],
+        )
+        .await?;
+    Ok(row.map(|r| (r.get("data"), r.get("expiry_date"))))
+}
+
+pub async fn delete_session(pool: &Pool, id: &str) -> Result<(), DbError> {
+    let client = pool.get().await?;
+    client
+        .execute("DELETE FROM sessions WHERE id = $1", &[&id])
+        .await?;
+    Ok(())
+}
+
+/// Logs a user out everywhere. Returns the number of sessions deleted.
+pub async fn delete_user_sessions(pool: &Pool, user_id: i64) -> Result<u64, DbError> {
+    let client = pool.get().await?;
+    let n = client
+        .execute(
+            "DELETE FROM sessions WHERE data::jsonb -> $1::text ->> 'user_id' = $2",
+            &[&crate::session_store::AUTH_DATA_KEY, &user_id.to_string()],
+        )
+        .await?;
+    Ok(n)
+}
+
+pub async fn delete_expired_sessions(pool: &Pool) -> Result<u64, DbError> {
+    let client = pool.get().await?;
+    let n = client
+        .execute("DELETE FROM sessions WHERE expiry_date <= $1", &[&now_unix()])
+        .await?;
+    Ok(n)
+}
+
+// ── Rooms ────────────────────────────────────────────────────────────────────
+
+/// Stores the serialised metadata of a room so it survives a restart.
+pub async fn save_room(pool: &Pool, room_id: &str, data: &str) -> Result<(), DbError> {
+    let client = pool.get().await?;
+    client
+        .execute(
+            "INSERT INTO rooms (room_id, data, updated_at) VALUES ($1, $2, $3) \
+             ON CONFLICT (room_id) DO UPDATE SET data = EXCLUDED.data, updated_at = EXCLUDED.updated_at",
+            &[&room_id, &data, &now_unix()],
+        )
+        .await?;
+    Ok(())
+}
+
+pub async fn delete_room(pool: &Pool, room_id: &str) -> Result<(), DbError> {
+    let client = pool.get().await?;
+    client
+        .execute("DELETE FROM rooms WHERE room_id = $1", &[&room_id])
+        .await?;
+    Ok(())
+}
+
+/// Returns `(room_id, data)` for every persisted room.
+pub async fn load_rooms(pool: &Pool) -> Result<Vec<(String, String)>, DbError> {
+    let client = pool.get().await?;
+    let rows = client
+        .query("SELECT room_id, data FROM rooms", &[])
+        .await?;
+    Ok(rows
+        .into_iter()
+        .map(|r| (r.get("room_id"), r.get("data")))
+        .collect())
+}
+
+// ── Moderation ───────────────────────────────────────────────────────────────
+
+/// Bans or unbans a user. Returns `false` if the user was already in that state.
+pub async fn set_user_banned(pool: &Pool, user_id: i64, banned: bool) -> Result<bool, DbError> {
+    let client = pool.get().await?;
+    let n = if banned {
+        client
+            .execute(
+                "UPDATE users SET banned_at = $1 WHERE id = $2 AND banned_at IS NULL",
+                &[&now_unix(), &user_id],
+            )
+            .await?
+    } else {
+        client
+            .execute(
+                "UPDATE users SET banned_at = NULL WHERE id = $1 AND banned_at IS NOT NULL",
+                &[&user_id],
+            )
+            .await?
+    };
+    Ok(n > 0)
+}
+
+/// Returns banned users, most recently banned first.
+pub async fn get_banned_users(pool: &Pool) -> Result<Vec<User>, DbError> {
+    let client = pool.get().await?;
+    let rows = client
+        .query(
+            &format!(
+                "SELECT {USER_COLUMNS} FROM users u \
+                 WHERE u.banned_at IS NOT NULL ORDER BY u.banned_at DESC"
+            ),
+            &[],
+        )
+        .await?;
+    Ok(rows.iter().map(user_from_row).collect())
+}
+
+/// Returns `false` if the address was already banned.
+pub async fn ban_ip(pool: &Pool, ip: IpAddr, reason: Option<&str>) -> Result<bool, DbError> {
+    let client = pool.get().await?;
+    let n = client
+        .execute(
+            "INSERT INTO banned_ips (ip, reason, created_at) VALUES ($1, $2, $3) \
+             ON CONFLICT DO NOTHING",
+            &[&ip, &reason, &now_unix()],
+        )
+        .await?;
+    Ok(n > 0)
+}
+
+/// Returns `false` if the address was not banned.
+pub async fn unban_ip(pool: &Pool, ip: IpAddr) -> Result<bool, DbError> {
+    let client = pool.get().await?;
+    let n = client
+        .execute("DELETE FROM banned_ips WHERE ip = $1", &[&ip])
+        .await?;
+    Ok(n > 0)
+}
+
+pub async fn get_banned_ips(pool: &Pool) -> Result<Vec<BannedIp>, DbError> {
+    let client = pool.get().await?;
+    let rows = client
+        .query(
+            "SELECT ip, reason, created_at FROM banned_ips ORDER BY created_at DESC",
+            &[],
+        )
+        .await?;
+    Ok(rows
+        .into_iter()
+        .map(|r| BannedIp {
+            ip: r.get("ip"),
+            reason: r.get("reason"),
+            created_at: r.get("created_at"),
+        })
+        .collect())
+}
+
+/// Marks a game as voided so that it no longer counts in its players'
+/// statistics, and clears the tournament result it produced so that the
+/// organizer can set it again. Returns `false` if the game does not exist or
+/// was already voided.
+pub async fn void_game_record(pool: &Pool, record_id: i64) -> Result<bool, DbError> {
+    let mut client = pool.get().await?;
+    let tx = client.transaction().await?;
+    let n = tx
+        .execute(
+            "UPDATE game_records SET voided_at = $1 WHERE id = $2 AND voided_at IS NULL",
+            &[&now_unix(), &record_id],
+        )
+        .await?;
+    if n == 0 {
+        return Ok(false);
+    }
+    tx.execute(
+        "UPDATE tournament_games SET outcome = NULL, game_record_id = NULL \
+         WHERE game_record_id = $1",
+        &[&record_id],
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(true)
+}
+
+// ── Puzzles ──────────────────────────────────────────────────────────────────
+
+/// Adds a puzzle, or updates the one with the same slug. Returns its id.
+pub async fn upsert_puzzle(
+    pool: &Pool,
+    game_id: &str,
+    slug: &str,
+    title: &str,
+    data: &str,
+) -> Result<i64, DbError> {
+    let client = pool.get().await?;
+    let row = client
+        .query_one(
+            "INSERT INTO puzzles (game_id, slug, title, data, created_at) \
+             VALUES ($1, $2, $3, $4, $5) \
+             ON CONFLICT (game_id, slug) DO UPDATE SET title = $3, data = $4 \
+             RETURNING id",
+            &[&game_id, &slug, &title, &data, &now_unix()],
+        )
+        .await?;
+    Ok(row.get(0))
+}
+
+const PUZZLE_SELECT: &str = "SELECT p.id, p.game_id, p.slug, p.title, p.data, p.created_at,
+            COUNT(a.id) AS attempts, COALESCE(BOOL_OR(a.solved), FALSE) AS solved
+     FROM puzzles p
+     LEFT JOIN puzzle_attempts a ON a.puzzle_id = p.id AND a.user_id = $1";
+
+fn puzzle_from_row(r: &tokio_postgres::Row) -> Puzzle {
+    Puzzle {
+        id: r.get("id"),
+        game_id: r.get("game_id"),
+        slug: r.get("slug"),
+        title: r.get("title"),
+        data: r.get("data"),
+        created_at: r.get("created_at"),
+        attempts: r.get("attempts"),
+        solved: r.get("solved"),
+    }
+}
+
+/// The puzzles of a game, oldest first, with `user_id`'s attempts
+/// (none for an anonymous visitor).
+pub async fn list_puzzles(
+    pool: &Pool,
+    game_id: &str,
+    user_id: Option<i64>,
+) -> Result<Vec<Puzzle>, DbError> {
+    let client = pool.get().await?;
+    let rows = client
+        .query(
+            &format!("{PUZZLE_SELECT} WHERE p.game_id = $2 GROUP BY p.id ORDER BY p.id"),
+            &[&user_id, &game_id],
+        )
+        .await?;
+    Ok(rows.iter().map(puzzle_from_row).collect())
+}
+
+pub async fn get_puzzle(
+    pool: &Pool,
+    id: i64,
+    user_id: Option<i64>,
+) -> Result<Option<Puzzle>, DbError> {
+    let client = pool.get().await?;
+    let row = client
+        .query_opt(&format!("{PUZZLE_SELECT} WHERE p.id = $2 GROUP BY p.id"), &[&user_id, &id])
+        .await?;
+    Ok(row.as_ref().map(puzzle_from_row))
+}
+
+/// Records an answer to a puzzle.
+pub async fn insert_puzzle_attempt(
+    pool: &Pool,
+    puzzle_id: i64,
+    user_id: i64,
+    solved: bool,
+) -> Result<(), DbError> {
+    let client = pool.get().await?;
+    client
+        .execute(
+            "INSERT INTO puzzle_attempts (puzzle_id, user_id, solved, created_at) \
+             VALUES ($1, $2, $3, $4)",
+            &[&puzzle_id, &user_id, &solved, &now_unix()],
+        )
+        .await?;
+    Ok(())
+}
+
+// ── Audit log ────────────────────────────────────────────────────────────────
+
+pub async fn insert_audit_entry(
+    pool: &Pool,
+    admin_id: i64,
+    action: &str,
+    target: &str,
+    details: Option<&str>,
+) -> Result<(), DbError> {
+    let client = pool.get().await?;
+    client
+        .execute(
+            "INSERT INTO admin_audit_log (admin_id, action, target, details, created_at) \
+             VALUES ($1, $2, $3, $4, $5)",
+            &[&admin_id, &action, &target, &details, &now_unix()],
+        )
+        .await?;
+    Ok(())
+}
+
+/// Returns a page of the audit log, newest first.
+pub async fn get_audit_log(
+    pool: &Pool,
+    page: i64,
+    per_page: i64,
+) -> Result<Vec<AuditEntry>, DbError> {
+    let client = pool.get().await?;
+    let rows = client
+        .query(
+            "SELECT a.id, u.username AS admin, a.action, a.target, a.details, a.created_at
+             FROM admin_audit_log a
+             LEFT JOIN users u ON u.id = a.admin_id
+             ORDER BY a.id DESC
+             LIMIT $1 OFFSET $2",
+            &[&per_page, &(page * per_page)],
+        )
+        .await?;
+    Ok(rows
+        .into_iter()
+        .map(|r| AuditEntry {
+            id: r.get("id"),
+            admin: r.get("admin"),
+            action: r.get("action"),
+            target: r.get("target"),
+            details: r.get("details"),
+            created_at: r.get("created_at"),
+        })
+        .collect())
+}
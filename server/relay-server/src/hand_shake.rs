@@ -1,12 +1,17 @@
 //! This module does the whole initialization and handshake thing.
 //! The general protocol of connecting is :
 //! WASM Client -> Websocket: postcard serialized join request.
-//! Websocket -> WASM Client: u16 player id, u16 rule variation, u64 reconnect token.
+//! Websocket -> WASM Client: u16 player id, u16 rule variation, u64 reconnect token,
+//! u16 negotiated protocol version, u16 lowest and u16 highest supported protocol version.
+//! Spectators get a regular player id but a zero reconnect token.
+//! Endpoints whose protocol version is not supported, or whose versions differ from the
+//! ones the room was opened with, get `VERSION_MISMATCH` instead.
 
 use crate::db;
 use crate::hand_shake::ClientServerSpecificData::{Client, Server};
 use crate::hand_shake::DisconnectEndpointSpecification::{DisconnectClient, DisconnectServer};
-use crate::lobby::{AppState, Room};
+use crate::lobby::{AppState, Room, forget_room, persist_room};
+use crate::metrics::HandshakeFailure;
 use axum::extract::ws::Message::Binary;
 use axum::extract::ws::{Message, WebSocket};
 use bytes::{BufMut, Bytes, BytesMut};
@@ -15,12 +20,16 @@ use futures_util::{sink::SinkExt, stream::StreamExt};
 use postcard::from_bytes;
 use protocol::{
     CHANNEL_BUFFER_SIZE, CLIENT_DISCONNECT_MSG_SIZE, CLIENT_DISCONNECTS, HAND_SHAKE_RESPONSE,
-    HAND_SHAKE_RESPONSE_SIZE, JoinRequest, NEW_CLIENT, NEW_CLIENT_MSG_SIZE,
-    SERVER_DISCONNECT_MSG_SIZE, SERVER_DISCONNECTS, SERVER_ERROR,
+    HAND_SHAKE_RESPONSE_SIZE, JoinRequest, MIN_PROTOCOL_VERSION, NEW_CLIENT, NEW_CLIENT_MSG_SIZE,
+    NEW_SPECTATOR, PROTOCOL_VERSION, SERVER_DISCONNECT_MSG_SIZE, SERVER_DISCONNECTS, SERVER_ERROR,
+    SPECTATOR_DISCONNECTS, VERSION_MISMATCH, VERSION_MISMATCH_HEADER_SIZE,
+    negotiate_protocol_version,
 };
 use rand::random;
 use std::collections::HashMap;
+use std::net::IpAddr;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
 use tokio::sync::mpsc::{Receiver, Sender};
 use tokio::sync::{broadcast, mpsc};
@@ -37,6 +46,41 @@ async fn send_closing_message(sender: &mut SplitSink<WebSocket, Message>, closin
     let _ = sender.send(Message::Close(None)).await;
 }
 
+/// Refuses a handshake: counts the failure, then tells the endpoint why.
+async fn refuse(
+    sender: &mut SplitSink<WebSocket, Message>,
+    state: &AppState,
+    reason: HandshakeFailure,
+    closing_message: String,
+) {
+    state.metrics.handshake_failed(reason);
+    send_closing_message(sender, closing_message).await;
+}
+
+/// Refuses an endpoint running an incompatible version. Unlike other refusals this
+/// carries the versions the relay supports, so that the client can ask for a reload.
+async fn refuse_version(
+    sender: &mut SplitSink<WebSocket, Message>,
+    state: &AppState,
+    reason: String,
+) {
+    state.metrics.handshake_failed(HandshakeFailure::VersionMismatch);
+    let raw_data = reason.as_bytes();
+    let mut msg = BytesMut::with_capacity(VERSION_MISMATCH_HEADER_SIZE + raw_data.len());
+    msg.put_u8(VERSION_MISMATCH);
+    msg.put_u16(MIN_PROTOCOL_VERSION);
+    msg.put_u16(PROTOCOL_VERSION);
+    msg.put_slice(raw_data);
+
+    let _ = sender.send(Message::Binary(msg.into())).await;
+    let _ = sender.send(Message::Close(None)).await;
+}
+
+/// The join request as sent by clients predating protocol versions: game id, room id, rule
+/// variation, create room and reconnect token. Only used to recognise them: they can not read
+/// `VERSION_MISMATCH`, so they get a plain error.
+type BaselineJoinRequest = (String, String, u16, bool, Option<u64>);
+
 /// The handshake result we get for the joining the room.
 pub struct HandshakeResult {
     /// The id of the player we play.
@@ -47,6 +91,10 @@ pub struct HandshakeResult {
     pub rule_variation: u16,
     /// The reconnect token for this player — sent back to the client for localStorage storage.
     pub token: u64,
+    /// Whether this connection only watches the game.
+    pub is_spectator: bool,
+    /// The protocol version spoken on this connection.
+    pub protocol_version: u16,
     /// The internal connection information.
     pub specific_data: ClientServerSpecificData,
 }
@@ -65,6 +113,8 @@ pub struct DisconnectData {
     pub player_id: u16,
     /// The complete identifier of the room as stored in the hashmap.
     pub room_id: String,
+    /// Whether the leaving connection was a spectator.
+    pub is_spectator: bool,
     /// The sender we use.
     pub sender: DisconnectEndpointSpecification,
 }
@@ -84,11 +134,13 @@ impl From<&HandshakeResult> for DisconnectData {
             Server(_, internal_sender) => DisconnectData {
                 player_id: value.player_id,
                 room_id: value.room_id.clone(),
+                is_spectator: false,
                 sender: DisconnectServer(internal_sender.clone()),
             },
             Client(_, internal_sender) => DisconnectData {
                 player_id: value.player_id,
                 room_id: value.room_id.clone(),
+                is_spectator: value.is_spectator,
                 sender: DisconnectClient(internal_sender.clone()),
             },
         }
@@ -112,6 +164,12 @@ struct InitialConnectionResult {
     max_players: u16,
     /// Reconnect token from the client, if this is a reconnect attempt.
     reconnect_token: Option<u64>,
+    /// Flags, if we only want to watch the room.
+    spectate: bool,
+    /// The protocol version negotiated with the endpoint.
+    protocol_version: u16,
+    /// The game schema version of the endpoint.
+    schema_version: u16,
 }
 
 /// Reads in the join request from the web socket, verifies if game exists and generates the final room name.
@@ -124,13 +182,25 @@ async fn get_initial_query(
     let my_data = loop {
         let Some(raw_data) = receiver.next().await else {
             tracing::warn!("WebSocket closed before handshake completed");
-            send_closing_message(sender, "Initial error during handshake.".into()).await;
+            refuse(
+                sender,
+                &state,
+                HandshakeFailure::BadRequest,
+                "Initial error during handshake.".into(),
+            )
+            .await;
             return None;
         };
         match raw_data {
             Err(err) => {
                 tracing::error!(?err, "Initial error during handshake.");
-                send_closing_message(sender, "Initial error during handshake.".into()).await;
+                refuse(
+                    sender,
+                    &state,
+                    HandshakeFailure::BadRequest,
+                    "Initial error during handshake.".into(),
+                )
+                .await;
                 return None;
             }
             Ok(Binary(data)) => {
@@ -144,13 +214,48 @@ async fn get_initial_query(
     // Now we get some data and we try to convert it into the required format.
     let working_struct = match from_bytes::<JoinRequest>(&my_data) {
         Ok(req) => req,
+        Err(_) if from_bytes::<BaselineJoinRequest>(&my_data).is_ok() => {
+            tracing::info!("Refused a client predating protocol versions");
+            refuse(
+                sender,
+                &state,
+                HandshakeFailure::VersionMismatch,
+                "This version of the game is out of date. Please reload the page.".into(),
+            )
+            .await;
+            return None;
+        }
         Err(e) => {
             tracing::error!(error = ?e, "Failed to parse join request");
-            send_closing_message(sender, "Failed to parse join request.".into()).await;
+            refuse(
+                sender,
+                &state,
+                HandshakeFailure::BadRequest,
+                "Failed to parse join request. Please reload the page.".into(),
+            )
+            .await;
             return None;
         }
     };
 
+    let Some(protocol_version) = negotiate_protocol_version(working_struct.protocol_version) else {
+        tracing::info!(
+            requested = working_struct.protocol_version,
+            "Refused an unsupported protocol version"
+        );
+        refuse_version(
+            sender,
+            &state,
+            format!(
+                "This version of the game speaks protocol {}, the server supports {} to {}. \
+                 Please reload the page.",
+                working_struct.protocol_version, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION
+            ),
+        )
+        .await;
+        return None;
+    };
+
     // Let us take a look, if the game exists.
     let games = state.configs.read().await;
     let game_exists = games.contains_key(&working_struct.game_id);
@@ -166,7 +271,13 @@ async fn get_initial_query(
             optional_game = working_struct.game_id,
             "Requested illegal game."
         );
-        send_closing_message(sender, format!("Unknown game {}.", &working_struct.game_id)).await;
+        refuse(
+            sender,
+            &state,
+            HandshakeFailure::UnknownGame,
+            format!("Unknown game {}.", &working_struct.game_id),
+        )
+        .await;
         return None;
     }
 
@@ -186,22 +297,45 @@ async fn get_initial_query(
         rule_variation: working_struct.rule_variation,
         max_players,
         reconnect_token: working_struct.reconnect_token,
+        spectate: working_struct.spectate,
+        protocol_version,
+        schema_version: working_struct.schema_version,
     })
 }
 
+/// Why an endpoint may not enter a room opened with other versions, if it may not.
+fn version_conflict(room: &Room, initial_result: &InitialConnectionResult) -> Option<String> {
+    if room.protocol_version != initial_result.protocol_version {
+        return Some(format!(
+            "Room {} speaks protocol {}, this version of the game speaks {}. Please reload the page.",
+            initial_result.room_id, room.protocol_version, initial_result.protocol_version
+        ));
+    }
+    if room.schema_version != initial_result.schema_version {
+        return Some(format!(
+            "Room {} was opened with game version {}, this page runs version {}. Please reload the page.",
+            initial_result.room_id, room.schema_version, initial_result.schema_version
+        ));
+    }
+    None
+}
+
 /// Connects and eventually establishes a room.
 pub async fn init_and_connect(
     sender: &mut SplitSink<WebSocket, Message>,
     receiver: &mut SplitStream<WebSocket>,
     state: Arc<AppState>,
     user_id: Option<i64>,
+    ip: IpAddr,
 ) -> Option<HandshakeResult> {
     let start_result = get_initial_query(sender, receiver, state.clone()).await?;
 
     if let Some(token) = start_result.reconnect_token {
         process_handshake_reconnect(sender, state, start_result, token, user_id).await
     } else if start_result.is_server {
-        process_handshake_server(sender, state, start_result, user_id).await
+        process_handshake_server(sender, state, start_result, user_id, ip).await
+    } else if start_result.spectate {
+        process_handshake_spectator(sender, state, start_result).await
     } else {
         process_handshake_client(sender, state, start_result, user_id).await
     }
@@ -217,8 +351,10 @@ async fn process_handshake_client(
     let mut rooms = state.rooms.lock().await;
     let Some(local_room) = rooms.get_mut(&initial_result.compound_room_id) else {
         drop(rooms);
-        send_closing_message(
+        refuse(
             sender,
+            &state,
+            HandshakeFailure::RoomNotFound,
             format!(
                 "Room {} does not exist for game {}.",
                 &initial_result.room_id, &initial_result.game_id
@@ -228,12 +364,33 @@ async fn process_handshake_client(
         return None;
     };
 
+    if let Some(reason) = version_conflict(local_room, &initial_result) {
+        drop(rooms);
+        refuse_version(sender, &state, reason).await;
+        return None;
+    }
+
+    // Tournament and challenge rooms only admit the expected opponent.
+    if local_room.reserved_guest.is_some() && user_id != local_room.reserved_guest {
+        drop(rooms);
+        refuse(
+            sender,
+            &state,
+            HandshakeFailure::Reserved,
+            format!("Room {} is reserved for other players.", &initial_result.room_id),
+        )
+        .await;
+        return None;
+    }
+
     // Do we fit in? max_players == 0 means "infinite".
     if initial_result.max_players != 0 && local_room.amount_of_players >= initial_result.max_players
     {
         drop(rooms);
-        send_closing_message(
+        refuse(
             sender,
+            &state,
+            HandshakeFailure::RoomFull,
             format!(
                 "Room  {} exceeded max amount of players {}.",
                 &initial_result.room_id, initial_result.max_players
@@ -246,8 +403,10 @@ async fn process_handshake_client(
     // Save guard against the case, that we have run out of client ids.
     if local_room.next_client_id > u16::MAX - 100 {
         drop(rooms);
-        send_closing_message(
+        refuse(
             sender,
+            &state,
+            HandshakeFailure::OutOfIds,
             format!("Room {} run out of client ids.", &initial_result.room_id),
         )
         .await;
@@ -267,7 +426,9 @@ async fn process_handshake_client(
     let to_server_sender = local_room.to_host_sender.clone();
     let receiver = local_room.host_to_client_broadcaster.subscribe();
     let rule_variation = local_room.rule_variation;
+    let snapshot = local_room.snapshot();
     drop(rooms);
+    persist_room(&state, &initial_result.compound_room_id, snapshot).await;
 
     // Here we send a message to the server, that a new client has joined.
     let mut msg = BytesMut::with_capacity(NEW_CLIENT_MSG_SIZE);
@@ -278,13 +439,23 @@ async fn process_handshake_client(
     if let Err(error) = result {
         // We have to leave the room again.
         let mut rooms = state.rooms.lock().await;
-        if let Some(room) = rooms.get_mut(&initial_result.compound_room_id) {
+        let snapshot = rooms.get_mut(&initial_result.compound_room_id).map(|room| {
             room.amount_of_players -= 1;
             room.player_tokens.remove(&player_id);
-        }
+            room.snapshot()
+        });
         drop(rooms);
+        if let Some(snapshot) = snapshot {
+            persist_room(&state, &initial_result.compound_room_id, snapshot).await;
+        }
         tracing::error!(?error, "Server unexpectedly left during handshake");
-        send_closing_message(sender, "Server unexpectedly left during handshake".into()).await;
+        refuse(
+            sender,
+            &state,
+            HandshakeFailure::HostGone,
+            "Server unexpectedly left during handshake".into(),
+        )
+        .await;
         return None;
     }
 
@@ -293,6 +464,94 @@ async fn process_handshake_client(
         player_id,
         rule_variation,
         token,
+        is_spectator: false,
+        protocol_version: initial_result.protocol_version,
+        specific_data: Client(receiver, to_server_sender),
+    })
+}
+
+/// Does the handshake for a spectator. Spectators skip the player limit, get no
+/// reconnect token and are announced to the host via `NEW_SPECTATOR`.
+async fn process_handshake_spectator(
+    sender: &mut SplitSink<WebSocket, Message>,
+    state: Arc<AppState>,
+    initial_result: InitialConnectionResult,
+) -> Option<HandshakeResult> {
+    let mut rooms = state.rooms.lock().await;
+    let Some(local_room) = rooms.get_mut(&initial_result.compound_room_id) else {
+        drop(rooms);
+        refuse(
+            sender,
+            &state,
+            HandshakeFailure::RoomNotFound,
+            format!(
+                "Room {} does not exist for game {}.",
+                &initial_result.room_id, &initial_result.game_id
+            ),
+        )
+        .await;
+        return None;
+    };
+
+    if let Some(reason) = version_conflict(local_room, &initial_result) {
+        drop(rooms);
+        refuse_version(sender, &state, reason).await;
+        return None;
+    }
+
+    // Save guard against the case, that we have run out of client ids.
+    if local_room.next_client_id > u16::MAX - 100 {
+        drop(rooms);
+        refuse(
+            sender,
+            &state,
+            HandshakeFailure::OutOfIds,
+            format!("Room {} run out of client ids.", &initial_result.room_id),
+        )
+        .await;
+        tracing::error!("Server run out of client ids.");
+        return None;
+    }
+
+    let player_id = local_room.next_client_id;
+    local_room.next_client_id += 1;
+    local_room.spectators.push(player_id);
+
+    let to_server_sender = local_room.to_host_sender.clone();
+    let receiver = local_room.host_to_client_broadcaster.subscribe();
+    let rule_variation = local_room.rule_variation;
+    drop(rooms);
+
+    // Here we send a message to the server, that a new spectator has joined.
+    let mut msg = BytesMut::with_capacity(NEW_CLIENT_MSG_SIZE);
+    msg.put_u8(NEW_SPECTATOR); // Message-Type
+    msg.put_u16(player_id); // spectator id.
+
+    if let Err(error) = to_server_sender.send(msg.into()).await {
+        let mut rooms = state.rooms.lock().await;
+        if let Some(room) = rooms.get_mut(&initial_result.compound_room_id) {
+            room.spectators.retain(|&p| p != player_id);
+        }
+        drop(rooms);
+        tracing::error!(?error, "Server unexpectedly left during handshake");
+        refuse(
+            sender,
+            &state,
+            HandshakeFailure::HostGone,
+            "Server unexpectedly left during handshake".into(),
+        )
+        .await;
+        return None;
+    }
+
+    Some(HandshakeResult {
+        room_id: initial_result.compound_room_id,
+        player_id,
+        rule_variation,
+        // Spectators can not reconnect into a player slot.
+        token: 0,
+        is_spectator: true,
+        protocol_version: initial_result.protocol_version,
         specific_data: Client(receiver, to_server_sender),
     })
 }
@@ -303,7 +562,58 @@ async fn process_handshake_server(
     state: Arc<AppState>,
     initial_result: InitialConnectionResult,
     user_id: Option<i64>,
+    ip: IpAddr,
 ) -> Option<HandshakeResult> {
+    // Tournament and challenge rooms are reserved for the paired players and
+    // use the rules chosen when the game was set up.
+    let mut rule_variation = initial_result.rule_variation;
+    let mut reserved_guest = None;
+    let mut reserved = false;
+    match reserved_seat(&state, &initial_result).await {
+        Ok(Some(seat)) => {
+            reserved = true;
+            if seat.closed {
+                refuse(
+                    sender,
+                    &state,
+                    HandshakeFailure::RoomClosed,
+                    format!("Room {} is closed.", &initial_result.room_id),
+                )
+                .await;
+                return None;
+            }
+            if user_id.is_none() || user_id != seat.host_user_id {
+                refuse(
+                    sender,
+                    &state,
+                    HandshakeFailure::Reserved,
+                    format!("Room {} is reserved for other players.", &initial_result.room_id),
+                )
+                .await;
+                return None;
+            }
+            rule_variation = seat.rule_variation as u16;
+            reserved_guest = seat.guest_user_id;
+        }
+        Ok(None) => {}
+        Err(e) => {
+            tracing::warn!("Failed to look up reserved seat for room {}: {e}", initial_result.room_id);
+        }
+    }
+
+    // Reserved rooms were set up by the relay, so only other rooms count
+    // towards the creation quota.
+    if !reserved && !state.limits.allow_room(ip, user_id) {
+        refuse(
+            sender,
+            &state,
+            HandshakeFailure::RateLimited,
+            "Too many rooms opened, try again later.".into(),
+        )
+        .await;
+        return None;
+    }
+
     // Insert a game record before taking the rooms lock (best-effort: failures don't abort the handshake).
     let game_record_id =
         match db::insert_game_record(&state.db, &initial_result.game_id, &initial_result.room_id)
@@ -319,8 +629,10 @@ async fn process_handshake_server(
     let mut rooms = state.rooms.lock().await;
     if rooms.contains_key(&initial_result.compound_room_id) {
         drop(rooms);
-        send_closing_message(
+        refuse(
             sender,
+            &state,
+            HandshakeFailure::RoomExists,
             format!(
                 "Room {} already exists for game {}.",
                 &initial_result.room_id, &initial_result.game_id
@@ -341,27 +653,49 @@ async fn process_handshake_server(
     let new_room = Room {
         next_client_id: 1,
         amount_of_players: 1,
-        rule_variation: initial_result.rule_variation,
+        rule_variation,
         to_host_sender: to_server_sender,
+        host_receiver: None,
         host_to_client_broadcaster: to_client_sender.clone(),
         player_tokens,
         host_connected: true,
         connected_players: Vec::new(),
+        spectators: Vec::new(),
         game_record_id,
         user_ids,
+        reserved_guest,
+        protocol_version: initial_result.protocol_version,
+        schema_version: initial_result.schema_version,
     };
+    let snapshot = new_room.snapshot();
     rooms.insert(initial_result.compound_room_id.clone(), new_room);
     drop(rooms);
+    persist_room(&state, &initial_result.compound_room_id, snapshot).await;
     let hand_shake_result = HandshakeResult {
         room_id: initial_result.compound_room_id,
         player_id: 0,
-        rule_variation: initial_result.rule_variation,
+        rule_variation,
         token,
+        is_spectator: false,
+        protocol_version: initial_result.protocol_version,
         specific_data: Server(to_server_receiver, to_client_sender),
     };
     Some(hand_shake_result)
 }
 
+/// The tournament game or challenge the room was set up for, if any.
+async fn reserved_seat(
+    state: &AppState,
+    initial_result: &InitialConnectionResult,
+) -> Result<Option<db::RoomSeat>, db::DbError> {
+    let room = &initial_result.room_id;
+    let game = &initial_result.game_id;
+    match db::get_tournament_seat(&state.db, room, game).await? {
+        Some(seat) => Ok(Some(seat)),
+        None => db::get_challenge_seat(&state.db, room, game).await,
+    }
+}
+
 /// Reconnects a previously connected player (host or client) using their stored token.
 ///
 /// **Client reconnect**: resubscribes to the broadcast channel and notifies the host
@@ -380,8 +714,10 @@ async fn process_handshake_reconnect(
     let mut rooms = state.rooms.lock().await;
     let Some(local_room) = rooms.get_mut(&initial_result.compound_room_id) else {
         drop(rooms);
-        send_closing_message(
+        refuse(
             sender,
+            &state,
+            HandshakeFailure::RoomNotFound,
             format!(
                 "Room {} no longer exists for game {}.",
                 &initial_result.room_id, &initial_result.game_id
@@ -402,31 +738,63 @@ async fn process_handshake_reconnect(
         None => {
             drop(rooms);
             tracing::warn!("Reconnect attempt with invalid token in room {}", &initial_result.room_id);
-            send_closing_message(sender, "Invalid reconnect token.".into()).await;
+            refuse(
+                sender,
+                &state,
+                HandshakeFailure::InvalidToken,
+                "Invalid reconnect token.".into(),
+            )
+            .await;
             return None;
         }
     };
 
+    if let Some(reason) = version_conflict(local_room, &initial_result) {
+        drop(rooms);
+        refuse_version(sender, &state, reason).await;
+        return None;
+    }
+
     // ------------------------------------------------------------------ Host reconnect
     if player_id == 0 {
         if local_room.host_connected {
             drop(rooms);
-            send_closing_message(sender, "Host is already connected.".into()).await;
+            refuse(
+                sender,
+                &state,
+                HandshakeFailure::HostAlreadyConnected,
+                "Host is already connected.".into(),
+            )
+            .await;
             return None;
         }
 
-        // Create a fresh mpsc channel (the previous receiver was dropped when the
-        // host's WebSocket closed).
-        let (new_sender, new_receiver) = mpsc::channel(CHANNEL_BUFFER_SIZE);
-        local_room.to_host_sender = new_sender.clone();
+        // A restored room kept its receiver: take it over, without what was queued
+        // for the previous host (the arrivals are replayed below). Otherwise create a
+        // fresh mpsc channel (the previous receiver was dropped when the host's
+        // WebSocket closed).
+        let new_receiver = match local_room.host_receiver.take() {
+            Some(mut receiver) => {
+                while receiver.try_recv().is_ok() {}
+                receiver
+            }
+            None => {
+                let (new_sender, new_receiver) = mpsc::channel(CHANNEL_BUFFER_SIZE);
+                local_room.to_host_sender = new_sender;
+                new_receiver
+            }
+        };
+        let new_sender = local_room.to_host_sender.clone();
         local_room.host_connected = true;
         local_room.user_ids.insert(0u16, user_id);
 
         let broadcaster = local_room.host_to_client_broadcaster.clone();
         let rule_variation = local_room.rule_variation;
+        let snapshot = local_room.snapshot();
 
         // Collect the players we need to notify about.
         let connected = local_room.connected_players.clone();
+        let spectators = local_room.spectators.clone();
         let all_non_host: Vec<u16> = local_room
             .player_tokens
             .keys()
@@ -434,6 +802,7 @@ async fn process_handshake_reconnect(
             .copied()
             .collect();
         drop(rooms);
+        persist_room(&state, &initial_result.compound_room_id, snapshot).await;
 
         // Queue NEW_CLIENT for every currently connected player so the host backend
         // increments remote_player_count and sends a FULL_UPDATE.
@@ -443,6 +812,13 @@ async fn process_handshake_reconnect(
             msg.put_u16(*pid);
             let _ = new_sender.send(msg.into()).await;
         }
+        // Spectators still watching get announced again as well.
+        for pid in &spectators {
+            let mut msg = BytesMut::with_capacity(NEW_CLIENT_MSG_SIZE);
+            msg.put_u8(NEW_SPECTATOR);
+            msg.put_u16(*pid);
+            let _ = new_sender.send(msg.into()).await;
+        }
         // Queue CLIENT_DISCONNECTS for players who left while the host was away so
         // the backend can start their grace-period timers.
         for pid in all_non_host {
@@ -461,6 +837,8 @@ async fn process_handshake_reconnect(
             player_id: 0,
             rule_variation,
             token: reconnect_token,
+            is_spectator: false,
+            protocol_version: initial_result.protocol_version,
             specific_data: Server(new_receiver, broadcaster),
         });
     }
@@ -472,14 +850,25 @@ async fn process_handshake_reconnect(
     let to_server_sender = local_room.to_host_sender.clone();
     let broadcast_receiver = local_room.host_to_client_broadcaster.subscribe();
     let rule_variation = local_room.rule_variation;
+    let host_connected = local_room.host_connected;
+    let snapshot = local_room.snapshot();
     drop(rooms);
+    persist_room(&state, &initial_result.compound_room_id, snapshot).await;
 
     // Notify the host that this player has rejoined so it sends a FULL_UPDATE.
+    // While the host is away (grace period, restart) the host reconnect replays
+    // NEW_CLIENT for every connected player instead.
     let mut msg = BytesMut::with_capacity(NEW_CLIENT_MSG_SIZE);
     msg.put_u8(NEW_CLIENT);
     msg.put_u16(player_id);
 
-    if let Err(error) = to_server_sender.send(msg.into()).await {
+    if !host_connected {
+        tracing::info!(
+            player_id,
+            room = &initial_result.room_id,
+            "Player reconnected while the host is away"
+        );
+    } else if let Err(error) = to_server_sender.send(msg.into()).await {
         let mut rooms = state.rooms.lock().await;
         if let Some(room) = rooms.get_mut(&initial_result.compound_room_id) {
             room.amount_of_players -= 1;
@@ -487,7 +876,13 @@ async fn process_handshake_reconnect(
         }
         drop(rooms);
         tracing::error!(?error, "Host unavailable during reconnect handshake");
-        send_closing_message(sender, "Host is no longer available.".into()).await;
+        refuse(
+            sender,
+            &state,
+            HandshakeFailure::HostGone,
+            "Host is no longer available.".into(),
+        )
+        .await;
         return None;
     }
 
@@ -502,6 +897,8 @@ async fn process_handshake_reconnect(
         player_id,
         rule_variation,
         token: reconnect_token,
+        is_spectator: false,
+        protocol_version: initial_result.protocol_version,
         specific_data: Client(broadcast_receiver, to_server_sender),
     })
 }
@@ -516,11 +913,55 @@ pub async fn inform_client_of_connection(
     msg.put_u16(status.player_id);
     msg.put_u16(status.rule_variation);
     msg.put_u64(status.token);
+    msg.put_u16(status.protocol_version);
+    msg.put_u16(MIN_PROTOCOL_VERSION);
+    msg.put_u16(PROTOCOL_VERSION);
 
     let result = sender.send(Message::Binary(msg.into())).await;
     result.is_ok()
 }
 
+/// Removes the room after `grace` unless the host has reconnected by then.
+/// On expiry the remaining clients get `SERVER_DISCONNECTS` and the game record is closed.
+pub fn spawn_host_grace_period(
+    state: Arc<AppState>,
+    room_id: String,
+    broadcaster: broadcast::Sender<Bytes>,
+    grace: Duration,
+) {
+    tokio::spawn(async move {
+        tokio::time::sleep(grace).await;
+
+        let game_record_id = {
+            let mut rooms = state.rooms.lock().await;
+            if let Some(room) = rooms.get(&room_id) {
+                if !room.host_connected {
+                    let record_id = room.game_record_id;
+                    rooms.remove(&room_id);
+                    record_id
+                } else {
+                    return; // host reconnected
+                }
+            } else {
+                return; // room already removed
+            }
+        };
+
+        // Room lock released — broadcast and close the DB record.
+        let mut msg = BytesMut::with_capacity(SERVER_DISCONNECT_MSG_SIZE);
+        msg.put_u8(SERVER_DISCONNECTS);
+        let _ = broadcaster.send(msg.into());
+        tracing::info!(room_id, "Host grace period expired — room removed");
+        forget_room(&state, &room_id).await;
+
+        if let Some(record_id) = game_record_id
+            && let Err(e) = db::close_game_record(&state.db, record_id, None).await
+        {
+            tracing::warn!("Failed to close game record {record_id}: {e}");
+        }
+    });
+}
+
 /// Performs the shutdown of the system and sends a last message.
 pub async fn shutdown_connection(
     wrapped_sender: Arc<Mutex<SplitSink<WebSocket, Message>>>,
@@ -530,7 +971,7 @@ pub async fn shutdown_connection(
 ) {
     match disconnect_data.sender {
         DisconnectServer(broadcaster) => {
-            // Mark the host as disconnected and start a 30-second grace period.
+            // Mark the host as disconnected and start the grace period.
             // If the host reconnects within that window the grace task does nothing;
             // otherwise it broadcasts SERVER_DISCONNECTS and removes the room.
             {
@@ -540,38 +981,23 @@ pub async fn shutdown_connection(
                 }
             }
 
-            let state_clone = app_state.clone();
-            let room_id = disconnect_data.room_id.clone();
-            tokio::spawn(async move {
-                tokio::time::sleep(tokio::time::Duration::from_secs(30)).await;
-
-                let game_record_id = {
-                    let mut rooms = state_clone.rooms.lock().await;
-                    if let Some(room) = rooms.get(&room_id) {
-                        if !room.host_connected {
-                            let record_id = room.game_record_id;
-                            rooms.remove(&room_id);
-                            record_id
-                        } else {
-                            return; // host reconnected
-                        }
-                    } else {
-                        return; // room already removed
-                    }
-                };
-
-                // Room lock released — broadcast and close the DB record.
-                let mut msg = BytesMut::with_capacity(SERVER_DISCONNECT_MSG_SIZE);
-                msg.put_u8(SERVER_DISCONNECTS);
-                let _ = broadcaster.send(msg.into());
-                tracing::info!(room_id, "Host grace period expired — room removed");
-
-                if let Some(record_id) = game_record_id {
-                    if let Err(e) = db::close_game_record(&state_clone.db, record_id, None).await {
-                        tracing::warn!("Failed to close game record {record_id}: {e}");
-                    }
-                }
-            });
+            spawn_host_grace_period(
+                app_state.clone(),
+                disconnect_data.room_id.clone(),
+                broadcaster,
+                app_state.config.rooms.host_grace(),
+            );
+        }
+        DisconnectClient(sender) if disconnect_data.is_spectator => {
+            let mut msg = BytesMut::with_capacity(CLIENT_DISCONNECT_MSG_SIZE);
+            msg.put_u8(SPECTATOR_DISCONNECTS);
+            msg.put_u16(disconnect_data.player_id);
+            let _ = sender.send(msg.into()).await;
+            let mut rooms = app_state.rooms.lock().await;
+            if let Some(room) = rooms.get_mut(&disconnect_data.room_id) {
+                room.spectators.retain(|&p| p != disconnect_data.player_id);
+            }
+            drop(rooms);
         }
         DisconnectClient(sender) => {
             // Inform server first.
@@ -597,3 +1023,43 @@ pub async fn shutdown_connection(
     // Send the message to the WASM point.
     send_closing_message(&mut sender, error_message.into()).await;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use postcard::to_slice;
+
+    #[test]
+    fn join_requests_carry_versions() {
+        let current = JoinRequest {
+            game_id: "trictrac".into(),
+            room_id: "room".into(),
+            rule_variation: 0,
+            create_room: true,
+            reconnect_token: Some(7),
+            spectate: false,
+            protocol_version: PROTOCOL_VERSION,
+            schema_version: 3,
+        };
+        let mut buf = [0u8; 64];
+        let request = from_bytes::<JoinRequest>(to_slice(&current, &mut buf).unwrap()).unwrap();
+        assert_eq!(request.protocol_version, PROTOCOL_VERSION);
+        assert_eq!(request.schema_version, 3);
+    }
+
+    #[test]
+    fn baseline_join_requests_are_recognised() {
+        let baseline: BaselineJoinRequest = ("trictrac".into(), "room".into(), 0, true, None);
+        let mut buf = [0u8; 64];
+        let bytes = to_slice(&baseline, &mut buf).unwrap();
+        assert!(from_bytes::<JoinRequest>(bytes).is_err());
+        assert!(from_bytes::<BaselineJoinRequest>(bytes).is_ok());
+    }
+
+    #[test]
+    fn protocol_versions_are_negotiated() {
+        assert_eq!(negotiate_protocol_version(PROTOCOL_VERSION), Some(PROTOCOL_VERSION));
+        assert_eq!(negotiate_protocol_version(PROTOCOL_VERSION + 1), Some(PROTOCOL_VERSION));
+        assert_eq!(negotiate_protocol_version(MIN_PROTOCOL_VERSION - 1), None);
+    }
+}
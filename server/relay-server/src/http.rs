@@ -12,14 +12,50 @@
 //!   GET  /users/:username
 //!   GET  /users/:username/games?page=0&per_page=20
 //!   GET  /games/:id
+//!   GET  /games/:id/record
 //!   POST /games/result
+//!   GET  /tournaments
+//!   POST /tournaments
+//!   GET  /tournaments/:id
+//!   POST /tournaments/:id/register
+//!   DELETE /tournaments/:id/register
+//!   POST /tournaments/:id/rounds
+//!   POST /tournaments/:id/games/:game_id/result
+//!   PUT  /auth/preferences
+//!   GET  /auth/oidc
+//!   GET  /auth/oidc/login
+//!   GET  /auth/oidc/callback?code=…&state=…
+//!   GET  /friends
+//!   POST /friends/:username
+//!   DELETE /friends/:username
+//!   POST /friends/:username/accept
+//!   POST /friends/:username/block
+//!   DELETE /friends/:username/block
+//!   GET  /challenges
+//!   POST /challenges
+//!   DELETE /challenges/:id
+//!   POST /challenges/:id/accept
+//!   POST /challenges/:id/decline
+//!   GET  /puzzles?game_id=…
+//!   GET  /puzzles/daily?game_id=…
+//!   GET  /puzzles/:id
+//!   POST /puzzles/:id/attempts
+//!   GET  /admin/rooms
+//!   DELETE /admin/rooms/:game_id/:room_code
+//!   GET  /admin/bans
+//!   POST /admin/users/:username/ban
+//!   DELETE /admin/users/:username/ban
+//!   POST /admin/ips/:ip/ban
+//!   DELETE /admin/ips/:ip/ban
+//!   POST /admin/games/:id/void
+//!   GET  /admin/audit?page=0&per_page=20
 
 use axum::{
     Json, Router,
-    extract::{Path, Query, State},
-    http::StatusCode,
-    response::{IntoResponse, Response},
-    routing::{delete, get, post},
+    extract::{DefaultBodyLimit, Path, Query, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Redirect, Response},
+    routing::{delete, get, post, put},
 };
 use axum_login::AuthSession;
 use rand::distributions::Alphanumeric;
@@ -27,14 +63,24 @@ use rand::Rng;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use std::collections::HashMap;
+use std::net::IpAddr;
 use std::sync::Arc;
 
-use crate::auth::{AuthBackend, Credentials, hash_password};
+use crate::admin::{self, AdminUser};
+use crate::auth::{AuthBackend, AuthError, Credentials, hash_password};
 use crate::db::{self, now_unix};
 use crate::lobby::AppState;
+use crate::notify::Notification;
+use crate::oidc::{OidcClient, PendingLogin};
+use crate::puzzle;
+use crate::rate_limit::ClientIp;
+use crate::tournament::{self, Format, Outcome, PlayedGame, Status};
 
 const VERIFY_TOKEN_EXPIRY: i64 = 86_400; // 24 hours
 const RESET_TOKEN_EXPIRY: i64 = 3_600;   // 1 hour
+const CHALLENGE_EXPIRY: i64 = 3_600;     // 1 hour
+/// Room for the record of a long game, a few hundred rolls of a few events each.
+const GAME_RESULT_BODY_LIMIT: usize = 512 * 1024;
 
 // ── Router ────────────────────────────────────────────────────────────────────
 
@@ -49,11 +95,49 @@ pub fn router() -> Router<Arc<AppState>> {
         .route("/auth/forgot-password", post(forgot_password))
         .route("/auth/reset-password", post(reset_password))
         .route("/auth/account", delete(delete_account))
+        .route("/auth/preferences", put(update_preferences))
+        .route("/auth/oidc", get(oidc_provider))
+        .route("/auth/oidc/login", get(oidc_login))
+        .route("/auth/oidc/callback", get(oidc_callback))
         .route("/users/{username}", get(user_profile))
         .route("/users/{username}/games", get(user_games))
-        .route("/games/result", post(game_result))
+        .route(
+            "/games/result",
+            post(game_result).layer(DefaultBodyLimit::max(GAME_RESULT_BODY_LIMIT)),
+        )
         .route("/games/{id}", get(game_detail))
+        .route("/games/{id}/record", get(game_record))
+        .route("/tournaments", get(list_tournaments).post(create_tournament))
+        .route("/tournaments/{id}", get(tournament_detail))
+        .route(
+            "/tournaments/{id}/register",
+            post(register_for_tournament).delete(withdraw_from_tournament),
+        )
+        .route("/tournaments/{id}/rounds", post(next_tournament_round))
+        .route(
+            "/tournaments/{id}/games/{game_id}/result",
+            post(set_tournament_result),
+        )
+        .route("/friends", get(list_friends))
+        .route("/friends/{username}", post(add_friend).delete(remove_friend))
+        .route("/friends/{username}/accept", post(accept_friend))
+        .route("/friends/{username}/block", post(block_user).delete(unblock_user))
+        .route("/challenges", get(list_challenges).post(create_challenge))
+        .route("/challenges/{id}", delete(cancel_challenge))
+        .route("/challenges/{id}/accept", post(accept_challenge))
+        .route("/challenges/{id}/decline", post(decline_challenge))
+        .route("/puzzles", get(list_puzzles))
+        .route("/puzzles/daily", get(daily_puzzle))
+        .route("/puzzles/{id}", get(get_puzzle))
+        .route("/puzzles/{id}/attempts", post(record_puzzle_attempt))
         .route("/pages/{slug}", get(get_page))
+        .route("/admin/rooms", get(admin_list_rooms))
+        .route("/admin/rooms/{game_id}/{room_code}", delete(admin_terminate_room))
+        .route("/admin/bans", get(admin_list_bans))
+        .route("/admin/users/{username}/ban", post(admin_ban_user).delete(admin_unban_user))
+        .route("/admin/ips/{ip}/ban", post(admin_ban_ip).delete(admin_unban_ip))
+        .route("/admin/games/{id}/void", post(admin_void_game))
+        .route("/admin/audit", get(admin_audit_log))
 }
 
 // ── Token generation ──────────────────────────────────────────────────────────
@@ -74,6 +158,10 @@ enum AppError {
     Conflict(&'static str),
     BadRequest(&'static str),
     Unauthorized,
+    Forbidden,
+    /// An admin banned the account.
+    Banned,
+    TooManyRequests(&'static str),
     Internal,
 }
 
@@ -88,6 +176,9 @@ impl IntoResponse for AppError {
             AppError::Conflict(msg) => (StatusCode::CONFLICT, msg).into_response(),
             AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg).into_response(),
             AppError::Unauthorized => StatusCode::UNAUTHORIZED.into_response(),
+            AppError::Forbidden => StatusCode::FORBIDDEN.into_response(),
+            AppError::Banned => (StatusCode::FORBIDDEN, "this account is banned").into_response(),
+            AppError::TooManyRequests(msg) => (StatusCode::TOO_MANY_REQUESTS, msg).into_response(),
             AppError::Internal => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
         }
     }
@@ -135,6 +226,23 @@ struct MeResponse {
     id: i64,
     username: String,
     email_verified: bool,
+    email_notifications: bool,
+}
+
+#[derive(Deserialize)]
+struct PreferencesBody {
+    email_notifications: bool,
+}
+
+#[derive(Serialize)]
+struct OidcProviderResponse {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct OidcCallbackQuery {
+    code: Option<String>,
+    state: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -194,9 +302,13 @@ impl From<db::GameSummary> for GameSummaryResponse {
 
 async fn register(
     mut auth_session: AuthSession<AuthBackend>,
+    ClientIp(ip): ClientIp,
     State(state): State<Arc<AppState>>,
     Json(body): Json<RegisterBody>,
 ) -> Result<impl IntoResponse, AppError> {
+    if !state.limits.allow_auth(ip) {
+        return Err(AppError::TooManyRequests("too many requests, try again later"));
+    }
     if body.username.len() < 3 || body.username.len() > 30 {
         return Err(AppError::BadRequest("username must be 3–30 characters"));
     }
@@ -226,9 +338,10 @@ async fn register(
     // Send verification email (best-effort).
     let token = generate_token();
     let expires_at = now_unix() + VERIFY_TOKEN_EXPIRY;
-    if db::create_email_token(&state.db, user_id, &token, "verify", expires_at)
-        .await
-        .is_ok()
+    if state.limits.allow_email(&body.email)
+        && db::create_email_token(&state.db, user_id, &token, "verify", expires_at)
+            .await
+            .is_ok()
     {
         state.mailer.send_verification(&body.email, &token).await;
     }
@@ -241,25 +354,47 @@ async fn register(
             id: user.id,
             username: user.username,
             email_verified: user.email_verified,
+            email_notifications: user.email_notifications,
         }),
     ))
 }
 
 async fn login(
     mut auth_session: AuthSession<AuthBackend>,
+    ClientIp(ip): ClientIp,
     State(state): State<Arc<AppState>>,
     Json(body): Json<LoginBody>,
 ) -> Result<impl IntoResponse, AppError> {
-    let creds = Credentials {
+    if !state.limits.allow_auth(ip) {
+        return Err(AppError::TooManyRequests("too many requests, try again later"));
+    }
+    // Counted per account, so that its username and email share the failures.
+    let account = db::get_user_by_username_or_email(&state.db, &body.username)
+        .await?
+        .map(|user| user.id);
+    if account.is_some_and(|id| state.limits.login_locked(id)) {
+        return Err(AppError::TooManyRequests(
+            "too many failed logins, account temporarily locked",
+        ));
+    }
+
+    let creds = Credentials::Password {
         login: body.username,
         password: body.password,
     };
 
     let user = match auth_session.authenticate(creds).await {
         Ok(Some(u)) => u,
-        Ok(None) => return Err(AppError::Unauthorized),
+        Ok(None) => {
+            if let Some(id) = account {
+                state.limits.login_failed(id);
+            }
+            return Err(AppError::Unauthorized);
+        }
+        Err(axum_login::Error::Backend(AuthError::Banned)) => return Err(AppError::Banned),
         Err(_) => return Err(AppError::Internal),
     };
+    state.limits.login_succeeded(user.id);
 
     auth_session.login(&user).await.map_err(|_| AppError::Internal)?;
 
@@ -267,9 +402,10 @@ async fn login(
         let _ = db::delete_email_tokens(&state.db, user.id, "verify").await;
         let token = generate_token();
         let expires_at = now_unix() + VERIFY_TOKEN_EXPIRY;
-        if db::create_email_token(&state.db, user.id, &token, "verify", expires_at)
-            .await
-            .is_ok()
+        if state.limits.allow_email(&user.email)
+            && db::create_email_token(&state.db, user.id, &token, "verify", expires_at)
+                .await
+                .is_ok()
         {
             state.mailer.send_verification(&user.email, &token).await;
         }
@@ -279,6 +415,7 @@ async fn login(
         id: user.id,
         username: user.username,
         email_verified: user.email_verified,
+        email_notifications: user.email_notifications,
     }))
 }
 
@@ -303,12 +440,23 @@ async fn me(auth_session: AuthSession<AuthBackend>) -> Result<impl IntoResponse,
             id: user.id,
             username: user.username,
             email_verified: user.email_verified,
+            email_notifications: user.email_notifications,
         })
         .into_response()),
         None => Ok(StatusCode::UNAUTHORIZED.into_response()),
     }
 }
 
+async fn update_preferences(
+    auth_session: AuthSession<AuthBackend>,
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<PreferencesBody>,
+) -> Result<StatusCode, AppError> {
+    let user = auth_session.user.ok_or(AppError::Unauthorized)?;
+    db::set_email_notifications(&state.db, user.id, body.email_notifications).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
 async fn verify_email(
     State(state): State<Arc<AppState>>,
     Query(params): Query<TokenQuery>,
@@ -331,6 +479,9 @@ async fn resend_verification(
     if user.email_verified {
         return Ok(StatusCode::OK);
     }
+    if !state.limits.allow_email(&user.email) {
+        return Err(AppError::TooManyRequests("too many emails sent, try again later"));
+    }
 
     db::delete_email_tokens(&state.db, user.id, "verify").await?;
 
@@ -344,10 +495,17 @@ async fn resend_verification(
 }
 
 async fn forgot_password(
+    ClientIp(ip): ClientIp,
     State(state): State<Arc<AppState>>,
     Json(body): Json<ForgotPasswordBody>,
 ) -> StatusCode {
+    if !state.limits.allow_auth(ip) {
+        return StatusCode::TOO_MANY_REQUESTS;
+    }
     // Always return 200 to avoid leaking which email addresses are registered.
+    if !state.limits.allow_email(&body.email) {
+        return StatusCode::OK;
+    }
     if let Ok(Some(user)) = db::get_user_by_email(&state.db, &body.email).await {
         let _ = db::delete_email_tokens(&state.db, user.id, "reset").await;
         let token = generate_token();
@@ -363,9 +521,13 @@ async fn forgot_password(
 }
 
 async fn reset_password(
+    ClientIp(ip): ClientIp,
     State(state): State<Arc<AppState>>,
     Json(body): Json<ResetPasswordBody>,
 ) -> Result<StatusCode, AppError> {
+    if !state.limits.allow_auth(ip) {
+        return Err(AppError::TooManyRequests("too many requests, try again later"));
+    }
     if body.new_password.len() < 8 {
         return Err(AppError::BadRequest("password must be at least 8 characters"));
     }
@@ -380,6 +542,116 @@ async fn reset_password(
     Ok(StatusCode::OK)
 }
 
+// ── OpenID Connect ────────────────────────────────────────────────────────────
+
+/// Session key holding the [`PendingLogin`] between redirect and callback.
+const OIDC_PENDING_KEY: &str = "oidc.pending";
+
+async fn oidc_provider(
+    ClientIp(ip): ClientIp,
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    let oidc = state.oidc.as_ref().ok_or(AppError::NotFound)?;
+    if !state.limits.allow_auth(ip) {
+        return Err(AppError::TooManyRequests("too many requests, try again later"));
+    }
+    Ok(Json(OidcProviderResponse { name: oidc.provider_name().to_string() }))
+}
+
+/// Sends the browser to the provider. When a user is logged in, the identity
+/// the provider returns is linked to their account instead of signing in.
+async fn oidc_login(
+    auth_session: AuthSession<AuthBackend>,
+    ClientIp(ip): ClientIp,
+    State(state): State<Arc<AppState>>,
+) -> Result<Redirect, AppError> {
+    let oidc = state.oidc.as_ref().ok_or(AppError::NotFound)?;
+    if !state.limits.allow_auth(ip) {
+        return Err(AppError::TooManyRequests("too many requests, try again later"));
+    }
+    let (url, pending) = oidc.authorization_url().await.map_err(|e| {
+        tracing::error!("OIDC discovery failed: {e}");
+        AppError::Internal
+    })?;
+    auth_session
+        .session
+        .insert(OIDC_PENDING_KEY, pending)
+        .await
+        .map_err(|_| AppError::Internal)?;
+    Ok(Redirect::to(&url))
+}
+
+/// Lands the browser back on the frontend, with `?oidc_error=<reason>` if the
+/// login failed.
+async fn oidc_callback(
+    mut auth_session: AuthSession<AuthBackend>,
+    ClientIp(ip): ClientIp,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<OidcCallbackQuery>,
+) -> Result<Redirect, AppError> {
+    let oidc = state.oidc.as_ref().ok_or(AppError::NotFound)?;
+    let pending: Option<PendingLogin> = auth_session
+        .session
+        .remove(OIDC_PENDING_KEY)
+        .await
+        .map_err(|_| AppError::Internal)?;
+
+    let page = match &auth_session.user {
+        Some(user) => format!("/profile/{}", user.username),
+        None => "/account".to_string(),
+    };
+    let result = if state.limits.allow_auth(ip) {
+        finish_oidc_login(&mut auth_session, &state, oidc, query, pending).await
+    } else {
+        Err("rate_limited")
+    };
+    let target = match result {
+        Ok(()) => format!("{}{page}", oidc.app_url()),
+        Err(reason) => format!("{}{page}?oidc_error={reason}", oidc.app_url()),
+    };
+    Ok(Redirect::to(&target))
+}
+
+async fn finish_oidc_login(
+    auth_session: &mut AuthSession<AuthBackend>,
+    state: &AppState,
+    oidc: &OidcClient,
+    query: OidcCallbackQuery,
+    pending: Option<PendingLogin>,
+) -> Result<(), &'static str> {
+    let (Some(pending), Some(code)) = (pending, query.code) else {
+        return Err("cancelled");
+    };
+    if query.state.as_deref() != Some(pending.state.as_str()) {
+        return Err("state_mismatch");
+    }
+    let identity = oidc.exchange_code(&code, &pending).await.map_err(|e| {
+        tracing::warn!("OIDC login failed: {e}");
+        "provider"
+    })?;
+
+    if let Some(user) = &auth_session.user {
+        return match db::get_user_by_identity(&state.db, &identity.issuer, &identity.subject).await {
+            Ok(Some(owner)) if owner.id == user.id => Ok(()),
+            Ok(Some(_)) => Err("identity_in_use"),
+            Ok(None) => db::link_identity(&state.db, user.id, &identity.issuer, &identity.subject)
+                .await
+                .map_err(|_| "internal"),
+            Err(_) => Err("internal"),
+        };
+    }
+
+    let user = match auth_session.authenticate(Credentials::Oidc(identity)).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return Err("internal"),
+        Err(axum_login::Error::Backend(AuthError::EmailRequired)) => return Err("email_required"),
+        Err(axum_login::Error::Backend(AuthError::EmailInUse)) => return Err("email_in_use"),
+        Err(axum_login::Error::Backend(AuthError::Banned)) => return Err("banned"),
+        Err(_) => return Err("internal"),
+    };
+    auth_session.login(&user).await.map_err(|_| "internal")
+}
+
 // ── Profile handlers ──────────────────────────────────────────────────────────
 
 async fn user_profile(
@@ -439,6 +711,10 @@ struct GameDetailResponse {
     started_at: i64,
     ended_at: Option<i64>,
     result: Option<String>,
+    /// Set when an admin voided the game; it then counts in nobody's statistics.
+    voided_at: Option<i64>,
+    /// Whether `GET /games/:id/record` has the moves for a replay.
+    has_record: bool,
     participants: Vec<ParticipantWithUsername>,
 }
 
@@ -450,7 +726,8 @@ async fn game_detail(
 
     let record = client
         .query_opt(
-            "SELECT id, game_id, room_code, started_at, ended_at, result
+            "SELECT id, game_id, room_code, started_at, ended_at, result, voided_at,
+                    record IS NOT NULL AS has_record
              FROM game_records WHERE id = $1",
             &[&id],
         )
@@ -486,10 +763,23 @@ async fn game_detail(
         started_at: record.get("started_at"),
         ended_at: record.get("ended_at"),
         result: record.get("result"),
+        voided_at: record.get("voided_at"),
+        has_record: record.get("has_record"),
         participants,
     }))
 }
 
+/// The record uploaded with the game's result, verbatim.
+async fn game_record(
+    Path(id): Path<i64>,
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    let record = db::get_game_record(&state.db, id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    Ok(([(header::CONTENT_TYPE, "application/json")], record))
+}
+
 // ── Game result recording ─────────────────────────────────────────────────────
 
 #[derive(Deserialize)]
@@ -502,6 +792,10 @@ struct GameResultBody {
     /// Accepted values: "win", "loss", "draw". Missing keys → NULL outcome.
     #[serde(default)]
     outcomes: HashMap<String, String>,
+    /// Opaque game-specific record of the moves, served back verbatim by
+    /// `GET /games/:id/record` for replays.
+    #[serde(default)]
+    record: Option<JsonValue>,
 }
 
 #[derive(Serialize)]
@@ -514,7 +808,9 @@ struct GameResultResponse {
 /// The room code + game ID act as the shared secret (same trust level as WS join).
 /// `close_game_record` is idempotent (no-op if already closed), and participant
 /// inserts use `ON CONFLICT DO NOTHING`, so safe retries are supported.
+/// Tournament outcomes are only taken from the room's host, logged in.
 async fn game_result(
+    auth_session: AuthSession<AuthBackend>,
     State(state): State<Arc<AppState>>,
     Json(body): Json<GameResultBody>,
 ) -> Result<impl IntoResponse, AppError> {
@@ -533,12 +829,35 @@ async fn game_result(
         .map_err(|_| AppError::BadRequest("could not serialise result"))?;
 
     db::close_game_record(&state.db, game_record_id, Some(&result_json)).await?;
+    if let Some(record) = &body.record {
+        let record_json = serde_json::to_string(record)
+            .map_err(|_| AppError::BadRequest("could not serialise record"))?;
+        db::store_game_record(&state.db, game_record_id, &record_json).await?;
+    }
 
     for (player_id, user_id) in &user_ids {
         let outcome = body.outcomes.get(&player_id.to_string()).map(String::as_str);
         db::insert_participant(&state.db, game_record_id, *user_id, *player_id, outcome).await?;
     }
 
+    // Tournament rooms report the host's outcome back to the pairing table.
+    let host_user_id = user_ids.get(&0).copied().flatten();
+    if let Some(user) = auth_session.user.filter(|u| host_user_id == Some(u.id))
+        && let Some(outcome) = body
+            .outcomes
+            .get("0")
+            .and_then(|o| Outcome::from_host_outcome(o))
+    {
+        db::record_tournament_result(
+            &state.db,
+            &body.room_code,
+            user.id,
+            game_record_id,
+            outcome.as_str(),
+        )
+        .await?;
+    }
+
     tracing::info!(
         game_record_id,
         room = body.room_code,
@@ -548,6 +867,1007 @@ async fn game_result(
     Ok(Json(GameResultResponse { game_record_id }))
 }
 
+// ── Tournaments ───────────────────────────────────────────────────────────────
+
+#[derive(Deserialize)]
+struct CreateTournamentBody {
+    name: String,
+    game_id: String,
+    format: String,
+    #[serde(default)]
+    rule_variation: u16,
+    /// Swiss only; 0 picks enough rounds for the number of players.
+    #[serde(default)]
+    rounds: i32,
+}
+
+#[derive(Serialize)]
+struct TournamentSummaryResponse {
+    id: i64,
+    name: String,
+    game_id: String,
+    format: String,
+    rule_variation: i32,
+    status: String,
+    current_round: i32,
+    total_rounds: i32,
+    organizer: Option<String>,
+    created_at: i64,
+    player_count: i64,
+}
+
+impl From<&db::Tournament> for TournamentSummaryResponse {
+    fn from(t: &db::Tournament) -> Self {
+        let format = Format::parse(&t.format).unwrap_or(Format::Swiss);
+        Self {
+            id: t.id,
+            name: t.name.clone(),
+            game_id: t.game_id.clone(),
+            format: t.format.clone(),
+            rule_variation: t.rule_variation,
+            status: t.status.clone(),
+            current_round: t.current_round,
+            total_rounds: tournament::total_rounds(format, t.rounds, t.player_count as usize),
+            organizer: t.organizer.clone(),
+            created_at: t.created_at,
+            player_count: t.player_count,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct TournamentsResponse {
+    tournaments: Vec<TournamentSummaryResponse>,
+}
+
+#[derive(Serialize)]
+struct StandingResponse {
+    rank: usize,
+    username: String,
+    score: f32,
+    buchholz: f32,
+    sonneborn_berger: f32,
+    wins: u32,
+}
+
+#[derive(Serialize)]
+struct TournamentGameResponse {
+    id: i64,
+    round: i32,
+    board: i32,
+    host: Option<String>,
+    /// `None` for a bye (or a deleted account).
+    guest: Option<String>,
+    is_bye: bool,
+    /// Only shown to the two players of the game.
+    room_code: Option<String>,
+    game_record_id: Option<i64>,
+    outcome: Option<String>,
+}
+
+#[derive(Serialize)]
+struct TournamentDetailResponse {
+    #[serde(flatten)]
+    summary: TournamentSummaryResponse,
+    standings: Vec<StandingResponse>,
+    games: Vec<TournamentGameResponse>,
+}
+
+/// Converts stored games to the form the pairing logic works on.
+/// Games whose host account was deleted are ignored.
+fn played_games(games: &[db::TournamentGame]) -> Vec<PlayedGame> {
+    games
+        .iter()
+        .filter_map(|g| {
+            Some(PlayedGame {
+                round: g.round,
+                board: g.board,
+                host: g.host_user_id?,
+                guest: g.guest_user_id,
+                outcome: g.outcome.as_deref().and_then(Outcome::parse),
+            })
+        })
+        .collect()
+}
+
+/// Loads a tournament and checks that `user_id` organizes it.
+async fn organized_tournament(
+    state: &AppState,
+    id: i64,
+    user_id: i64,
+) -> Result<db::Tournament, AppError> {
+    let t = db::get_tournament(&state.db, id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    if t.organizer_id != Some(user_id) {
+        return Err(AppError::Forbidden);
+    }
+    Ok(t)
+}
+
+async fn list_tournaments(
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    let tournaments = db::list_tournaments(&state.db).await?;
+    Ok(Json(TournamentsResponse {
+        tournaments: tournaments.iter().map(Into::into).collect(),
+    }))
+}
+
+async fn create_tournament(
+    auth_session: AuthSession<AuthBackend>,
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<CreateTournamentBody>,
+) -> Result<impl IntoResponse, AppError> {
+    let user = auth_session.user.ok_or(AppError::Unauthorized)?;
+    if !user.email_verified {
+        return Err(AppError::BadRequest("email address must be verified"));
+    }
+    let name = body.name.trim();
+    if name.is_empty() || name.chars().count() > 60 {
+        return Err(AppError::BadRequest("name must be 1–60 characters"));
+    }
+    let format = Format::parse(&body.format).ok_or(AppError::BadRequest("unknown format"))?;
+    if !(0..=20).contains(&body.rounds) {
+        return Err(AppError::BadRequest("rounds must be 0–20"));
+    }
+    if !state.configs.read().await.contains_key(&body.game_id) {
+        return Err(AppError::BadRequest("unknown game"));
+    }
+
+    let id = db::create_tournament(
+        &state.db,
+        name,
+        &body.game_id,
+        format.as_str(),
+        body.rule_variation as i32,
+        body.rounds,
+        user.id,
+    )
+    .await?;
+    let t = db::get_tournament(&state.db, id)
+        .await?
+        .ok_or(AppError::Internal)?;
+
+    Ok((StatusCode::CREATED, Json(TournamentSummaryResponse::from(&t))))
+}
+
+async fn tournament_detail(
+    auth_session: AuthSession<AuthBackend>,
+    Path(id): Path<i64>,
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    let t = db::get_tournament(&state.db, id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    let players = db::get_tournament_players(&state.db, id).await?;
+    let games = db::get_tournament_games(&state.db, id).await?;
+
+    let seeds: Vec<i64> = players.iter().map(|p| p.user_id).collect();
+    let names: HashMap<i64, &str> = players
+        .iter()
+        .map(|p| (p.user_id, p.username.as_str()))
+        .collect();
+    let standings = tournament::standings(&seeds, &played_games(&games))
+        .into_iter()
+        .enumerate()
+        .map(|(i, s)| StandingResponse {
+            rank: i + 1,
+            username: names[&s.user_id].to_string(),
+            score: s.score,
+            buchholz: s.buchholz,
+            sonneborn_berger: s.sonneborn_berger,
+            wins: s.wins,
+        })
+        .collect();
+
+    let viewer = auth_session.user.map(|u| u.id);
+    Ok(Json(TournamentDetailResponse {
+        summary: (&t).into(),
+        standings,
+        games: games
+            .into_iter()
+            .map(|g| TournamentGameResponse {
+                id: g.id,
+                round: g.round,
+                board: g.board,
+                host: g.host_username,
+                guest: g.guest_username,
+                is_bye: g.is_bye,
+                room_code: (viewer.is_some()
+                    && (viewer == g.host_user_id || viewer == g.guest_user_id))
+                    .then_some(g.room_code),
+                game_record_id: g.game_record_id,
+                outcome: g.outcome,
+            })
+            .collect(),
+    }))
+}
+
+async fn register_for_tournament(
+    auth_session: AuthSession<AuthBackend>,
+    Path(id): Path<i64>,
+    State(state): State<Arc<AppState>>,
+) -> Result<StatusCode, AppError> {
+    let user = auth_session.user.ok_or(AppError::Unauthorized)?;
+    let t = db::get_tournament(&state.db, id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    if Status::parse(&t.status) != Some(Status::Registration) {
+        return Err(AppError::Conflict("registration is closed"));
+    }
+    db::add_tournament_player(&state.db, id, user.id)
+        .await
+        .map_err(|e| {
+            if e.is_unique_violation() {
+                AppError::Conflict("already registered")
+            } else {
+                AppError::Database(e)
+            }
+        })?;
+    Ok(StatusCode::CREATED)
+}
+
+async fn withdraw_from_tournament(
+    auth_session: AuthSession<AuthBackend>,
+    Path(id): Path<i64>,
+    State(state): State<Arc<AppState>>,
+) -> Result<StatusCode, AppError> {
+    let user = auth_session.user.ok_or(AppError::Unauthorized)?;
+    let t = db::get_tournament(&state.db, id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    if Status::parse(&t.status) != Some(Status::Registration) {
+        return Err(AppError::Conflict("registration is closed"));
+    }
+    if !db::remove_tournament_player(&state.db, id, user.id).await? {
+        return Err(AppError::NotFound);
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Organizer only: closes registration and pairs the first round, pairs the
+/// next round once every game of the current one has a result, or marks the
+/// tournament finished after the last round.
+async fn next_tournament_round(
+    auth_session: AuthSession<AuthBackend>,
+    Path(id): Path<i64>,
+    State(state): State<Arc<AppState>>,
+) -> Result<StatusCode, AppError> {
+    let user = auth_session.user.ok_or(AppError::Unauthorized)?;
+    let t = organized_tournament(&state, id, user.id).await?;
+    let status = Status::parse(&t.status).ok_or(AppError::Internal)?;
+    let format = Format::parse(&t.format).ok_or(AppError::Internal)?;
+    if status == Status::Finished {
+        return Err(AppError::Conflict("tournament is finished"));
+    }
+
+    let players: Vec<i64> = db::get_tournament_players(&state.db, id)
+        .await?
+        .into_iter()
+        .map(|p| p.user_id)
+        .collect();
+    if players.len() < 2 {
+        return Err(AppError::BadRequest("at least two players are needed"));
+    }
+    let games = db::get_tournament_games(&state.db, id).await?;
+    if games
+        .iter()
+        .any(|g| g.round == t.current_round && g.outcome.is_none())
+    {
+        return Err(AppError::Conflict("current round is not finished"));
+    }
+
+    let played = played_games(&games);
+    let round = t.current_round + 1;
+    let pairings = if round > tournament::total_rounds(format, t.rounds, players.len()) {
+        Vec::new()
+    } else {
+        match format {
+            Format::Swiss => tournament::pair_swiss(&players, &played),
+            Format::Knockout => tournament::pair_knockout(&players, &played, round),
+        }
+    };
+
+    if pairings.is_empty() {
+        db::finish_tournament(&state.db, id).await?;
+        tracing::info!(tournament = id, "Tournament finished");
+        return Ok(StatusCode::OK);
+    }
+    db::start_tournament_round(&state.db, id, round, &pairings).await?;
+    tracing::info!(tournament = id, round, "Tournament round paired");
+    Ok(StatusCode::CREATED)
+}
+
+#[derive(Deserialize)]
+struct TournamentResultBody {
+    /// "host", "guest" or "draw".
+    outcome: String,
+}
+
+/// Organizer only: sets a game's result by hand, e.g. for a forfeit.
+async fn set_tournament_result(
+    auth_session: AuthSession<AuthBackend>,
+    Path((id, game_id)): Path<(i64, i64)>,
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<TournamentResultBody>,
+) -> Result<StatusCode, AppError> {
+    let user = auth_session.user.ok_or(AppError::Unauthorized)?;
+    let t = organized_tournament(&state, id, user.id).await?;
+    if Status::parse(&t.status) == Some(Status::Finished) {
+        return Err(AppError::Conflict("tournament is finished"));
+    }
+    let outcome = Outcome::parse(&body.outcome).ok_or(AppError::BadRequest("unknown outcome"))?;
+    if !db::set_tournament_game_outcome(&state.db, id, game_id, outcome.as_str()).await? {
+        return Err(AppError::NotFound);
+    }
+    Ok(StatusCode::OK)
+}
+
+// ── Friends ───────────────────────────────────────────────────────────────────
+
+#[derive(Serialize)]
+struct FriendResponse {
+    username: String,
+    /// The friend has a notification socket open.
+    online: bool,
+}
+
+#[derive(Serialize)]
+struct FriendsResponse {
+    friends: Vec<FriendResponse>,
+    incoming: Vec<String>,
+    outgoing: Vec<String>,
+    blocked: Vec<String>,
+}
+
+/// Looks up the user named in the path, who must be someone other than `user_id`.
+async fn other_user(state: &AppState, username: &str, user_id: i64) -> Result<db::User, AppError> {
+    let other = db::get_user_by_username(&state.db, username)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    if other.id == user_id {
+        return Err(AppError::BadRequest("cannot do this with yourself"));
+    }
+    Ok(other)
+}
+
+async fn list_friends(
+    auth_session: AuthSession<AuthBackend>,
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    let user = auth_session.user.ok_or(AppError::Unauthorized)?;
+    let lists = db::get_friend_lists(&state.db, user.id).await?;
+    let mut friends = Vec::with_capacity(lists.friends.len());
+    for (id, username) in lists.friends {
+        friends.push(FriendResponse {
+            username,
+            online: state.notifier.is_online(id).await,
+        });
+    }
+    Ok(Json(FriendsResponse {
+        friends,
+        incoming: lists.incoming,
+        outgoing: lists.outgoing,
+        blocked: lists.blocked,
+    }))
+}
+
+/// Sends a friend request, or accepts the one the other user already sent.
+async fn add_friend(
+    auth_session: AuthSession<AuthBackend>,
+    Path(username): Path<String>,
+    State(state): State<Arc<AppState>>,
+) -> Result<StatusCode, AppError> {
+    let user = auth_session.user.ok_or(AppError::Unauthorized)?;
+    let other = other_user(&state, &username, user.id).await?;
+    if db::is_blocked_between(&state.db, user.id, other.id).await? {
+        return Err(AppError::Forbidden);
+    }
+    match db::get_friendship(&state.db, user.id, other.id).await? {
+        Some((requester, status)) if requester == other.id && status == "pending" => {
+            db::accept_friend_request(&state.db, other.id, user.id).await?;
+            let accepted = Notification::FriendAccepted { by: user.username };
+            state.notifier.send(other.id, &accepted).await;
+            return Ok(StatusCode::OK);
+        }
+        Some(_) => return Err(AppError::Conflict("already friends or requested")),
+        None => {}
+    }
+    db::insert_friend_request(&state.db, user.id, other.id)
+        .await
+        .map_err(|e| {
+            if e.is_unique_violation() {
+                AppError::Conflict("already friends or requested")
+            } else {
+                AppError::Database(e)
+            }
+        })?;
+    let request = Notification::FriendRequest { from: user.username };
+    state.notifier.send(other.id, &request).await;
+    Ok(StatusCode::CREATED)
+}
+
+async fn accept_friend(
+    auth_session: AuthSession<AuthBackend>,
+    Path(username): Path<String>,
+    State(state): State<Arc<AppState>>,
+) -> Result<StatusCode, AppError> {
+    let user = auth_session.user.ok_or(AppError::Unauthorized)?;
+    let other = other_user(&state, &username, user.id).await?;
+    if !db::accept_friend_request(&state.db, other.id, user.id).await? {
+        return Err(AppError::NotFound);
+    }
+    let accepted = Notification::FriendAccepted { by: user.username };
+    state.notifier.send(other.id, &accepted).await;
+    Ok(StatusCode::OK)
+}
+
+/// Removes a friend, withdraws a sent request or declines a received one.
+async fn remove_friend(
+    auth_session: AuthSession<AuthBackend>,
+    Path(username): Path<String>,
+    State(state): State<Arc<AppState>>,
+) -> Result<StatusCode, AppError> {
+    let user = auth_session.user.ok_or(AppError::Unauthorized)?;
+    let other = other_user(&state, &username, user.id).await?;
+    if !db::delete_friendship(&state.db, user.id, other.id).await? {
+        return Err(AppError::NotFound);
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn block_user(
+    auth_session: AuthSession<AuthBackend>,
+    Path(username): Path<String>,
+    State(state): State<Arc<AppState>>,
+) -> Result<StatusCode, AppError> {
+    let user = auth_session.user.ok_or(AppError::Unauthorized)?;
+    let other = other_user(&state, &username, user.id).await?;
+    db::block_user(&state.db, user.id, other.id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn unblock_user(
+    auth_session: AuthSession<AuthBackend>,
+    Path(username): Path<String>,
+    State(state): State<Arc<AppState>>,
+) -> Result<StatusCode, AppError> {
+    let user = auth_session.user.ok_or(AppError::Unauthorized)?;
+    let other = other_user(&state, &username, user.id).await?;
+    if !db::unblock_user(&state.db, user.id, other.id).await? {
+        return Err(AppError::NotFound);
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ── Challenges ────────────────────────────────────────────────────────────────
+
+#[derive(Deserialize)]
+struct CreateChallengeBody {
+    username: String,
+    game_id: String,
+    #[serde(default)]
+    rule_variation: u16,
+}
+
+#[derive(Serialize)]
+struct ChallengeResponse {
+    id: i64,
+    challenger: String,
+    invitee: String,
+    game_id: String,
+    rule_variation: i32,
+    room_code: String,
+    status: String,
+    created_at: i64,
+}
+
+impl From<db::Challenge> for ChallengeResponse {
+    fn from(c: db::Challenge) -> Self {
+        Self {
+            id: c.id,
+            challenger: c.challenger,
+            invitee: c.invitee,
+            game_id: c.game_id,
+            rule_variation: c.rule_variation,
+            room_code: c.room_code,
+            status: c.status,
+            created_at: c.created_at,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ChallengesResponse {
+    incoming: Vec<ChallengeResponse>,
+    outgoing: Vec<ChallengeResponse>,
+}
+
+/// Room codes of challenge games; the relay reserves these rooms for the two players.
+fn generate_challenge_room_code() -> String {
+    let suffix: String = rand::thread_rng()
+        .sample_iter(Alphanumeric)
+        .take(8)
+        .map(|b| char::from(b).to_ascii_lowercase())
+        .collect();
+    format!("c-{suffix}")
+}
+
+/// Moves a pending, unexpired challenge to `status`. Only `allowed_user_id`
+/// (the invitee or the challenger, depending on the action) may do this.
+async fn close_challenge(
+    state: &AppState,
+    id: i64,
+    allowed_user_id: i64,
+    status: &str,
+) -> Result<db::Challenge, AppError> {
+    let challenge = db::get_challenge(&state.db, id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    let is_allowed = match status {
+        "cancelled" => challenge.challenger_id == allowed_user_id,
+        _ => challenge.invitee_id == allowed_user_id,
+    };
+    if !is_allowed {
+        return Err(AppError::Forbidden);
+    }
+    if challenge.created_at < now_unix() - CHALLENGE_EXPIRY {
+        return Err(AppError::Conflict("challenge expired"));
+    }
+    if !db::close_challenge(&state.db, id, status).await? {
+        return Err(AppError::Conflict("challenge is no longer pending"));
+    }
+    Ok(challenge)
+}
+
+async fn list_challenges(
+    auth_session: AuthSession<AuthBackend>,
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    let user = auth_session.user.ok_or(AppError::Unauthorized)?;
+    let challenges =
+        db::get_pending_challenges(&state.db, user.id, now_unix() - CHALLENGE_EXPIRY).await?;
+    let (incoming, outgoing): (Vec<_>, Vec<_>) = challenges
+        .into_iter()
+        .partition(|c| c.invitee_id == user.id);
+    Ok(Json(ChallengesResponse {
+        incoming: incoming.into_iter().map(Into::into).collect(),
+        outgoing: outgoing.into_iter().map(Into::into).collect(),
+    }))
+}
+
+/// Challenges a friend. The challenger then opens the returned room and waits;
+/// the invitee is notified on their socket, or by email when offline.
+async fn create_challenge(
+    auth_session: AuthSession<AuthBackend>,
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<CreateChallengeBody>,
+) -> Result<impl IntoResponse, AppError> {
+    let user = auth_session.user.ok_or(AppError::Unauthorized)?;
+    let invitee = other_user(&state, &body.username, user.id).await?;
+    if !db::are_friends(&state.db, user.id, invitee.id).await? {
+        return Err(AppError::Forbidden);
+    }
+    if !state.configs.read().await.contains_key(&body.game_id) {
+        return Err(AppError::BadRequest("unknown game"));
+    }
+    let pending =
+        db::get_pending_challenges(&state.db, user.id, now_unix() - CHALLENGE_EXPIRY).await?;
+    if pending.iter().any(|c| c.invitee_id == invitee.id) {
+        return Err(AppError::Conflict("challenge already pending"));
+    }
+
+    let id = db::create_challenge(
+        &state.db,
+        user.id,
+        invitee.id,
+        &body.game_id,
+        body.rule_variation as i32,
+        &generate_challenge_room_code(),
+    )
+    .await?;
+    let challenge = db::get_challenge(&state.db, id)
+        .await?
+        .ok_or(AppError::Internal)?;
+
+    let notification = Notification::Challenge {
+        id,
+        from: user.username.clone(),
+        rule_variation: challenge.rule_variation,
+        room_code: challenge.room_code.clone(),
+    };
+    let delivered = state.notifier.send(invitee.id, &notification).await;
+    if !delivered && invitee.email_verified && invitee.email_notifications {
+        state.mailer.send_challenge(&invitee.email, &user.username).await;
+    }
+
+    Ok((StatusCode::CREATED, Json(ChallengeResponse::from(challenge))))
+}
+
+/// Invitee only. Returns the challenge so the client can join its room.
+async fn accept_challenge(
+    auth_session: AuthSession<AuthBackend>,
+    Path(id): Path<i64>,
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    let user = auth_session.user.ok_or(AppError::Unauthorized)?;
+    let challenge = close_challenge(&state, id, user.id, "accepted").await?;
+    let accepted = Notification::ChallengeAccepted {
+        id,
+        by: user.username,
+        room_code: challenge.room_code.clone(),
+    };
+    state.notifier.send(challenge.challenger_id, &accepted).await;
+    Ok(Json(ChallengeResponse::from(challenge)))
+}
+
+/// Invitee only.
+async fn decline_challenge(
+    auth_session: AuthSession<AuthBackend>,
+    Path(id): Path<i64>,
+    State(state): State<Arc<AppState>>,
+) -> Result<StatusCode, AppError> {
+    let user = auth_session.user.ok_or(AppError::Unauthorized)?;
+    let challenge = close_challenge(&state, id, user.id, "declined").await?;
+    let declined = Notification::ChallengeDeclined { id, by: user.username };
+    state.notifier.send(challenge.challenger_id, &declined).await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Challenger only.
+async fn cancel_challenge(
+    auth_session: AuthSession<AuthBackend>,
+    Path(id): Path<i64>,
+    State(state): State<Arc<AppState>>,
+) -> Result<StatusCode, AppError> {
+    let user = auth_session.user.ok_or(AppError::Unauthorized)?;
+    let challenge = close_challenge(&state, id, user.id, "cancelled").await?;
+    let cancelled = Notification::ChallengeCancelled { id, by: user.username };
+    state.notifier.send(challenge.invitee_id, &cancelled).await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ── Puzzles ───────────────────────────────────────────────────────────────────
+
+#[derive(Deserialize)]
+struct PuzzlesQuery {
+    game_id: String,
+}
+
+#[derive(Serialize)]
+struct PuzzleResponse {
+    id: i64,
+    game_id: String,
+    slug: String,
+    title: String,
+    /// The game-specific position and solutions.
+    puzzle: JsonValue,
+    created_at: i64,
+    /// The requesting user's attempts; 0 for an anonymous visitor.
+    attempts: i64,
+    solved: bool,
+}
+
+impl From<db::Puzzle> for PuzzleResponse {
+    fn from(p: db::Puzzle) -> Self {
+        Self {
+            id: p.id,
+            game_id: p.game_id,
+            slug: p.slug,
+            title: p.title,
+            puzzle: serde_json::from_str(&p.data).unwrap_or(JsonValue::Null),
+            created_at: p.created_at,
+            attempts: p.attempts,
+            solved: p.solved,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct PuzzlesResponse {
+    puzzles: Vec<PuzzleResponse>,
+}
+
+#[derive(Deserialize)]
+struct PuzzleAttemptBody {
+    solved: bool,
+}
+
+async fn list_puzzles(
+    auth_session: AuthSession<AuthBackend>,
+    Query(query): Query<PuzzlesQuery>,
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    let user_id = auth_session.user.map(|u| u.id);
+    let puzzles = db::list_puzzles(&state.db, &query.game_id, user_id).await?;
+    Ok(Json(PuzzlesResponse {
+        puzzles: puzzles.into_iter().map(Into::into).collect(),
+    }))
+}
+
+/// The same puzzle for everyone during a UTC day.
+async fn daily_puzzle(
+    auth_session: AuthSession<AuthBackend>,
+    Query(query): Query<PuzzlesQuery>,
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    let user_id = auth_session.user.map(|u| u.id);
+    let mut puzzles = db::list_puzzles(&state.db, &query.game_id, user_id).await?;
+    let index = puzzle::daily_index(puzzles.len(), now_unix()).ok_or(AppError::NotFound)?;
+    Ok(Json(PuzzleResponse::from(puzzles.swap_remove(index))))
+}
+
+async fn get_puzzle(
+    auth_session: AuthSession<AuthBackend>,
+    Path(id): Path<i64>,
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    let user_id = auth_session.user.map(|u| u.id);
+    let puzzle = db::get_puzzle(&state.db, id, user_id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    Ok(Json(PuzzleResponse::from(puzzle)))
+}
+
+/// Records an answer, checked by the game in the browser.
+async fn record_puzzle_attempt(
+    auth_session: AuthSession<AuthBackend>,
+    Path(id): Path<i64>,
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<PuzzleAttemptBody>,
+) -> Result<StatusCode, AppError> {
+    let user = auth_session.user.ok_or(AppError::Unauthorized)?;
+    if db::get_puzzle(&state.db, id, None).await?.is_none() {
+        return Err(AppError::NotFound);
+    }
+    db::insert_puzzle_attempt(&state.db, id, user.id, body.solved).await?;
+    Ok(StatusCode::CREATED)
+}
+
+// ── Admin ─────────────────────────────────────────────────────────────────────
+
+#[derive(Serialize)]
+struct AdminRoomResponse {
+    game_id: String,
+    room_code: String,
+    rule_variation: u16,
+    players: u16,
+    spectators: usize,
+    host_connected: bool,
+    game_record_id: Option<i64>,
+    /// Accounts of the players who were logged in when they joined.
+    user_ids: Vec<i64>,
+}
+
+#[derive(Serialize)]
+struct AdminRoomsResponse {
+    rooms: Vec<AdminRoomResponse>,
+}
+
+#[derive(Serialize)]
+struct BannedUserResponse {
+    username: String,
+    banned_at: i64,
+}
+
+#[derive(Serialize)]
+struct BannedIpResponse {
+    ip: IpAddr,
+    reason: Option<String>,
+    created_at: i64,
+}
+
+#[derive(Serialize)]
+struct BansResponse {
+    users: Vec<BannedUserResponse>,
+    ips: Vec<BannedIpResponse>,
+}
+
+/// Optional body of the moderation actions, kept in the audit log.
+#[derive(Deserialize)]
+struct ReasonBody {
+    reason: Option<String>,
+}
+
+#[derive(Serialize)]
+struct AuditEntryResponse {
+    id: i64,
+    admin: Option<String>,
+    action: String,
+    target: String,
+    details: Option<String>,
+    created_at: i64,
+}
+
+#[derive(Serialize)]
+struct AuditLogResponse {
+    entries: Vec<AuditEntryResponse>,
+}
+
+fn reason(body: &Option<Json<ReasonBody>>) -> Option<&str> {
+    body.as_ref().and_then(|Json(b)| b.reason.as_deref())
+}
+
+async fn audit(
+    state: &AppState,
+    admin: &db::User,
+    action: &str,
+    target: &str,
+    details: Option<&str>,
+) -> Result<(), AppError> {
+    tracing::info!(admin = admin.username, action, target, "Admin action");
+    db::insert_audit_entry(&state.db, admin.id, action, target, details).await?;
+    Ok(())
+}
+
+async fn admin_list_rooms(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    let rooms = state.rooms.lock().await;
+    let mut list: Vec<AdminRoomResponse> = rooms
+        .iter()
+        .map(|(room_id, room)| {
+            // Room ids are `{room_code}#{game_id}`.
+            let (room_code, game_id) = room_id.rsplit_once('#').unwrap_or((room_id, ""));
+            AdminRoomResponse {
+                game_id: game_id.to_string(),
+                room_code: room_code.to_string(),
+                rule_variation: room.rule_variation,
+                players: room.amount_of_players,
+                spectators: room.spectators.len(),
+                host_connected: room.host_connected,
+                game_record_id: room.game_record_id,
+                user_ids: room.user_ids.values().flatten().copied().collect(),
+            }
+        })
+        .collect();
+    list.sort_by(|a, b| (&a.game_id, &a.room_code).cmp(&(&b.game_id, &b.room_code)));
+    Ok(Json(AdminRoomsResponse { rooms: list }))
+}
+
+async fn admin_terminate_room(
+    AdminUser(admin): AdminUser,
+    Path((game_id, room_code)): Path<(String, String)>,
+    State(state): State<Arc<AppState>>,
+    body: Option<Json<ReasonBody>>,
+) -> Result<StatusCode, AppError> {
+    let room_id = format!("{room_code}#{game_id}");
+    if !admin::terminate_room(&state, &room_id).await {
+        return Err(AppError::NotFound);
+    }
+    audit(&state, &admin, "terminate_room", &room_id, reason(&body)).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn admin_list_bans(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    let users = db::get_banned_users(&state.db).await?;
+    let ips = db::get_banned_ips(&state.db).await?;
+    Ok(Json(BansResponse {
+        users: users
+            .into_iter()
+            .map(|u| BannedUserResponse {
+                username: u.username,
+                banned_at: u.banned_at.unwrap_or_default(),
+            })
+            .collect(),
+        ips: ips
+            .into_iter()
+            .map(|b| BannedIpResponse { ip: b.ip, reason: b.reason, created_at: b.created_at })
+            .collect(),
+    }))
+}
+
+async fn admin_ban_user(
+    AdminUser(admin): AdminUser,
+    Path(username): Path<String>,
+    State(state): State<Arc<AppState>>,
+    body: Option<Json<ReasonBody>>,
+) -> Result<StatusCode, AppError> {
+    let user = db::get_user_by_username(&state.db, &username)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    if user.id == admin.id {
+        return Err(AppError::BadRequest("you cannot ban yourself"));
+    }
+    if !db::set_user_banned(&state.db, user.id, true).await? {
+        return Err(AppError::Conflict("user already banned"));
+    }
+    admin::disconnect_user(&state, user.id).await?;
+    audit(&state, &admin, "ban_user", &user.username, reason(&body)).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn admin_unban_user(
+    AdminUser(admin): AdminUser,
+    Path(username): Path<String>,
+    State(state): State<Arc<AppState>>,
+    body: Option<Json<ReasonBody>>,
+) -> Result<StatusCode, AppError> {
+    let user = db::get_user_by_username(&state.db, &username)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    if !db::set_user_banned(&state.db, user.id, false).await? {
+        return Err(AppError::NotFound);
+    }
+    audit(&state, &admin, "unban_user", &user.username, reason(&body)).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn admin_ban_ip(
+    AdminUser(admin): AdminUser,
+    ClientIp(own_ip): ClientIp,
+    Path(ip): Path<IpAddr>,
+    State(state): State<Arc<AppState>>,
+    body: Option<Json<ReasonBody>>,
+) -> Result<StatusCode, AppError> {
+    if ip == own_ip {
+        return Err(AppError::BadRequest("you cannot ban your own address"));
+    }
+    if !db::ban_ip(&state.db, ip, reason(&body)).await? {
+        return Err(AppError::Conflict("address already banned"));
+    }
+    state.ip_bans.insert(ip);
+    audit(&state, &admin, "ban_ip", &ip.to_string(), reason(&body)).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn admin_unban_ip(
+    AdminUser(admin): AdminUser,
+    Path(ip): Path<IpAddr>,
+    State(state): State<Arc<AppState>>,
+    body: Option<Json<ReasonBody>>,
+) -> Result<StatusCode, AppError> {
+    if !db::unban_ip(&state.db, ip).await? {
+        return Err(AppError::NotFound);
+    }
+    state.ip_bans.remove(ip);
+    audit(&state, &admin, "unban_ip", &ip.to_string(), reason(&body)).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Voids a fraudulent game: it leaves its players' statistics and history,
+/// and a tournament game it decided goes back to having no result.
+async fn admin_void_game(
+    AdminUser(admin): AdminUser,
+    Path(id): Path<i64>,
+    State(state): State<Arc<AppState>>,
+    body: Option<Json<ReasonBody>>,
+) -> Result<StatusCode, AppError> {
+    if !db::void_game_record(&state.db, id).await? {
+        return Err(AppError::NotFound);
+    }
+    audit(&state, &admin, "void_game", &id.to_string(), reason(&body)).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn admin_audit_log(
+    _admin: AdminUser,
+    Query(query): Query<GamesQuery>,
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    let per_page = query.per_page.clamp(1, 100);
+    let page = query.page.max(0);
+    let entries = db::get_audit_log(&state.db, page, per_page).await?;
+    Ok(Json(AuditLogResponse {
+        entries: entries
+            .into_iter()
+            .map(|e| AuditEntryResponse {
+                id: e.id,
+                admin: e.admin,
+                action: e.action,
+                target: e.target,
+                details: e.details,
+                created_at: e.created_at,
+            })
+            .collect(),
+    }))
+}
+
 // ── Static content pages ──────────────────────────────────────────────────────
 
 #[derive(Deserialize)]
@@ -588,7 +1908,7 @@ async fn get_page(
         "en".to_string()
     };
 
-    let base = std::path::Path::new(&state.pages_dir);
+    let base = std::path::Path::new(&state.config.pages_dir);
     let primary = base.join(&slug).join(format!("{lang}.md"));
 
     let content = match tokio::fs::read_to_string(&primary).await {
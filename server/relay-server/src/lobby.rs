@@ -3,6 +3,8 @@
 //! - [`Room`]: A game session with host-to-client broadcast channels
 //! - [`AppState`]: Global state holding all active rooms and game configurations
 //! - [`reload_config`]: Hot-reloading of game settings from `GameConfig.json`
+//! - [`persist_room`] / [`restore_rooms`]: Room metadata kept in the database so
+//!   that players can reconnect after a restart of the server
 
 use bytes::Bytes;
 use serde::{Deserialize, Serialize};
@@ -13,6 +15,16 @@ use tokio::fs;
 use tokio::sync::{Mutex, RwLock};
 use tokio::sync::{broadcast, mpsc};
 
+use protocol::CHANNEL_BUFFER_SIZE;
+
+use crate::admin::IpBans;
+use crate::config::Config;
+use crate::db;
+use crate::hand_shake::spawn_host_grace_period;
+use crate::metrics::Metrics;
+use crate::notify::Notifier;
+use crate::oidc::OidcClient;
+use crate::rate_limit::RateLimits;
 use crate::smtp::Mailer;
 
 /// The game entry we have for one game.
@@ -36,6 +48,9 @@ pub struct Room {
     pub rule_variation: u16,
     /// The sender to send messages to the host.
     pub to_host_sender: mpsc::Sender<Bytes>, // Clone-able no Mutex!
+    /// The receiving end of `to_host_sender` while no host holds it. Restored rooms
+    /// keep it until their host is back, so that the senders handed out meanwhile work.
+    pub host_receiver: Option<mpsc::Receiver<Bytes>>,
     /// The broad case sender needed to subscribe for the clients.
     pub host_to_client_broadcaster: broadcast::Sender<Bytes>, // Clone-able -> no Mutex!
     /// Reconnect tokens keyed by player id. Used to authenticate reconnect attempts.
@@ -47,10 +62,134 @@ pub struct Room {
     /// IDs of non-host players whose WebSocket is currently active.
     /// Used to replay NEW_CLIENT / CLIENT_DISCONNECTS when the host reconnects.
     pub connected_players: Vec<u16>,
+    /// IDs of spectators whose WebSocket is currently active. Spectators do not count
+    /// towards `amount_of_players` and get no reconnect token.
+    pub spectators: Vec<u16>,
     /// Row id in `game_records` for this session. None when no authenticated player created the room.
     pub game_record_id: Option<i64>,
     /// Maps in-game player_id → database user_id. None means the player is anonymous.
     pub user_ids: HashMap<u16, Option<i64>>,
+    /// For tournament and challenge rooms, the only user allowed to join as guest.
+    pub reserved_guest: Option<i64>,
+    /// The protocol version negotiated with the host. Everybody joining has to speak it.
+    pub protocol_version: u16,
+    /// The game schema version of the host. Everybody joining has to use it.
+    pub schema_version: u16,
+}
+
+/// The part of a [`Room`] that survives a restart: everything needed to let the
+/// host and the players back in with their reconnect tokens.
+#[derive(Serialize, Deserialize)]
+pub struct RoomSnapshot {
+    pub next_client_id: u16,
+    pub rule_variation: u16,
+    pub player_tokens: HashMap<u16, u64>,
+    pub game_record_id: Option<i64>,
+    pub user_ids: HashMap<u16, Option<i64>>,
+    pub reserved_guest: Option<i64>,
+    /// Kept so that a restored room admits the same versions as before the restart.
+    pub protocol_version: u16,
+    pub schema_version: u16,
+}
+
+impl Room {
+    pub fn snapshot(&self) -> RoomSnapshot {
+        RoomSnapshot {
+            next_client_id: self.next_client_id,
+            rule_variation: self.rule_variation,
+            player_tokens: self.player_tokens.clone(),
+            game_record_id: self.game_record_id,
+            user_ids: self.user_ids.clone(),
+            reserved_guest: self.reserved_guest,
+            protocol_version: self.protocol_version,
+            schema_version: self.schema_version,
+        }
+    }
+
+    /// Rebuilds a room from a snapshot. Nobody is connected yet: the host is
+    /// expected to reconnect within the grace period.
+    fn restore(snapshot: RoomSnapshot) -> Self {
+        let (to_host_sender, host_receiver) = mpsc::channel(CHANNEL_BUFFER_SIZE);
+        let (host_to_client_broadcaster, _) = broadcast::channel(CHANNEL_BUFFER_SIZE);
+        Room {
+            next_client_id: snapshot.next_client_id,
+            amount_of_players: 1,
+            rule_variation: snapshot.rule_variation,
+            to_host_sender,
+            host_receiver: Some(host_receiver),
+            host_to_client_broadcaster,
+            player_tokens: snapshot.player_tokens,
+            host_connected: false,
+            connected_players: Vec::new(),
+            spectators: Vec::new(),
+            game_record_id: snapshot.game_record_id,
+            user_ids: snapshot.user_ids,
+            reserved_guest: snapshot.reserved_guest,
+            protocol_version: snapshot.protocol_version,
+            schema_version: snapshot.schema_version,
+        }
+    }
+}
+
+/// Writes the snapshot of a room to the database (best-effort).
+pub async fn persist_room(state: &AppState, room_id: &str, snapshot: RoomSnapshot) {
+    let data = match serde_json::to_string(&snapshot) {
+        Ok(data) => data,
+        Err(e) => {
+            tracing::error!("Failed to serialise room {room_id}: {e}");
+            return;
+        }
+    };
+    if let Err(e) = db::save_room(&state.db, room_id, &data).await {
+        tracing::warn!("Failed to persist room {room_id}: {e}");
+    }
+}
+
+/// Removes a room from the database once it is gone from memory (best-effort).
+pub async fn forget_room(state: &AppState, room_id: &str) {
+    if let Err(e) = db::delete_room(&state.db, room_id).await {
+        tracing::warn!("Failed to delete persisted room {room_id}: {e}");
+    }
+}
+
+/// Loads the rooms persisted before a restart. Each restored room waits for its
+/// host like after a regular host disconnect, with a longer grace period.
+pub async fn restore_rooms(state: &Arc<AppState>) {
+    let persisted = match db::load_rooms(&state.db).await {
+        Ok(rows) => rows,
+        Err(e) => {
+            tracing::error!("Failed to load persisted rooms: {e}");
+            return;
+        }
+    };
+
+    let mut restored = Vec::new();
+    let mut unreadable = Vec::new();
+    {
+        let mut rooms = state.rooms.lock().await;
+        for (room_id, data) in persisted {
+            match serde_json::from_str::<RoomSnapshot>(&data) {
+                Ok(snapshot) => {
+                    let room = Room::restore(snapshot);
+                    restored.push((room_id.clone(), room.host_to_client_broadcaster.clone()));
+                    rooms.insert(room_id, room);
+                }
+                Err(e) => {
+                    tracing::warn!("Dropping unreadable persisted room {room_id}: {e}");
+                    unreadable.push(room_id);
+                }
+            }
+        }
+    }
+    for room_id in unreadable {
+        forget_room(state, &room_id).await;
+    }
+
+    tracing::info!(count = restored.len(), "Restored rooms from the database");
+    for (room_id, broadcaster) in restored {
+        let grace = state.config.rooms.restored_grace();
+        spawn_host_grace_period(state.clone(), room_id, broadcaster, grace);
+    }
 }
 
 /// The application state.
@@ -63,18 +202,34 @@ pub struct AppState {
     pub db: Pool,
     /// SMTP mailer for email verification and password reset.
     pub mailer: Mailer,
-    /// Directory containing static content pages as `{slug}/{lang}.md` files.
-    pub pages_dir: String,
+    /// Settings read at startup.
+    pub config: Config,
+    /// Open notification sockets of logged-in users.
+    pub notifier: Notifier,
+    /// OpenID Connect provider, when one is configured.
+    pub oidc: Option<OidcClient>,
+    /// Per-IP and per-account throttling, with counts of refused requests.
+    pub limits: RateLimits,
+    /// Counters exported on `/metrics`, shared with the relay tasks.
+    pub metrics: Arc<Metrics>,
+    /// Addresses refused on every route.
+    pub ip_bans: IpBans,
 }
 
 impl AppState {
-    pub fn new(db: Pool, mailer: Mailer, pages_dir: String) -> Self {
+    pub fn new(db: Pool, mailer: Mailer, config: Config, oidc: Option<OidcClient>) -> Self {
+        let limits = RateLimits::new(&config.limits);
         Self {
             rooms: Mutex::new(HashMap::new()),
             configs: RwLock::new(HashMap::new()),
             db,
             mailer,
-            pages_dir,
+            config,
+            notifier: Notifier::default(),
+            oidc,
+            limits,
+            metrics: Arc::default(),
+            ip_bans: IpBans::default(),
         }
     }
 }
@@ -97,3 +252,37 @@ pub async fn reload_config(state: &Arc<AppState>) -> Result<(), String> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot() -> RoomSnapshot {
+        RoomSnapshot {
+            next_client_id: 3,
+            rule_variation: 0,
+            player_tokens: HashMap::from([(0, 11), (1, 12)]),
+            game_record_id: Some(7),
+            user_ids: HashMap::from([(0, Some(1)), (1, None)]),
+            reserved_guest: Some(2),
+            protocol_version: 2,
+            schema_version: 5,
+        }
+    }
+
+    #[test]
+    fn restored_room_queues_messages_for_its_host() {
+        let data = serde_json::to_string(&snapshot()).unwrap();
+        let mut room = Room::restore(serde_json::from_str(&data).unwrap());
+        assert_eq!(room.reserved_guest, Some(2));
+        assert_eq!((room.protocol_version, room.schema_version), (2, 5));
+        assert!(!room.host_connected);
+
+        // A client seated before the host is back can reach it.
+        room.to_host_sender
+            .try_send(Bytes::from_static(&[protocol::NEW_CLIENT, 0, 1]))
+            .unwrap();
+        let mut receiver = room.host_receiver.take().expect("the receiver is kept");
+        assert_eq!(receiver.try_recv().unwrap()[0], protocol::NEW_CLIENT);
+    }
+}
@@ -6,6 +6,8 @@
 //! - Route host broadcasts to subscribed clients
 //! - Forward client RPCs to the host with injected player IDs
 //! - Manage sync state so clients only receive deltas after a full update
+//! - Enforce length and rate limits on chat messages
+//! - Throttle the full update requests of clients that lost track of the deltas
 //!
 //! The relay server never interprets game logic — it only validates message types
 //! and routes bytes between endpoints.
@@ -15,13 +17,22 @@ use bytes::{Buf, BufMut, Bytes, BytesMut};
 use futures_util::stream::{SplitSink, SplitStream};
 use futures_util::{SinkExt, StreamExt};
 use protocol::*;
+use std::collections::VecDeque;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 use tokio::sync::broadcast;
 use tokio::sync::broadcast::Sender;
 use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::mpsc::Receiver;
 
+use crate::metrics::Metrics;
+
+/// Relay-internal message on a host's channel, asking its connection to end.
+/// Outside the protocol's message types, so no client can send it.
+pub const CLOSE_HOST: u8 = u8::MAX;
+
 /// Spawns bidirectional message handlers for a game host connection.
 ///
 /// Creates two concurrent tasks:
@@ -38,12 +49,16 @@ pub async fn handle_server_logic(
     receiver: SplitStream<WebSocket>,
     internal_receiver: Receiver<Bytes>,
     internal_sender: broadcast::Sender<Bytes>,
+    metrics: Arc<Metrics>,
 ) -> &'static str {
-    let mut send_task =
-        tokio::spawn(async move { send_logic_server(sender, internal_receiver).await });
+    let send_metrics = metrics.clone();
+    let mut send_task = tokio::spawn(async move {
+        send_logic_server(sender, internal_receiver, send_metrics).await
+    });
 
-    let mut receive_task =
-        tokio::spawn(async move { receive_logic_server(receiver, internal_sender).await });
+    let mut receive_task = tokio::spawn(async move {
+        receive_logic_server(receiver, internal_sender, metrics).await
+    });
 
     // If any one of the tasks run to completion, we abort the other.
     let result = tokio::select! {
@@ -70,6 +85,7 @@ pub async fn handle_server_logic(
 async fn receive_logic_server(
     mut receiver: SplitStream<WebSocket>,
     internal_sender: Sender<Bytes>,
+    metrics: Arc<Metrics>,
 ) -> &'static str {
     while let Some(state) = receiver.next().await {
         match state {
@@ -80,6 +96,7 @@ async fn receive_logic_server(
                 }
 
                 if bytes[0] == SERVER_DISCONNECTS {
+                    metrics.message_from_host(SERVER_DISCONNECTS);
                     // This something normal to be expected.
                     return "Server disconnected intentionally";
                 }
@@ -94,6 +111,7 @@ async fn receive_logic_server(
                     );
                     return "Illegal Server -> Client command.";
                 }
+                metrics.message_from_host(bytes[0]);
 
                 // All messages are simply passed through.
                 let res = internal_sender.send(bytes);
@@ -118,20 +136,40 @@ async fn receive_logic_server(
 /// Allowed message types to host:
 /// - [`NEW_CLIENT`]: Player joined notification
 /// - [`CLIENT_DISCONNECTS`]: Player left notification
+/// - [`NEW_SPECTATOR`]: Spectator joined notification
+/// - [`SPECTATOR_DISCONNECTS`]: Spectator left notification
+/// - [`CHAT_MESSAGE`]: Chat text from a client (with player ID prepended)
 /// - [`SERVER_RPC`]: Game action from a client (with player ID prepended)
+/// - [`REQUEST_FULL_UPDATE`]: A client asks for a full update (with player ID appended)
+///
+/// [`CLOSE_HOST`], sent when an admin closes the room, ends the connection instead
+/// of being forwarded.
 ///
 /// This task owns the WebSocket sender lock for its lifetime to ensure
 /// sequential message delivery to the host.
 async fn send_logic_server(
     sender: Arc<Mutex<SplitSink<WebSocket, Message>>>,
     mut internal_receiver: Receiver<Bytes>,
+    metrics: Arc<Metrics>,
 ) -> &'static str {
     while let Some(bytes) = internal_receiver.recv().await {
         if bytes.is_empty() {
             tracing::error!("Illegal internal empty message in send logic server.");
             return "Illegal empty message received.";
         }
-        if !matches!(bytes[0], NEW_CLIENT | CLIENT_DISCONNECTS | SERVER_RPC) {
+        if bytes[0] == CLOSE_HOST {
+            return "Room closed by an admin.";
+        }
+        if !matches!(
+            bytes[0],
+            NEW_CLIENT
+                | CLIENT_DISCONNECTS
+                | NEW_SPECTATOR
+                | SPECTATOR_DISCONNECTS
+                | SERVER_RPC
+                | CHAT_MESSAGE
+                | REQUEST_FULL_UPDATE
+        ) {
             tracing::error!(
                 message_type = bytes[0],
                 "Unknown internal Client->Server command"
@@ -139,11 +177,13 @@ async fn send_logic_server(
             return "Unknown internal Client->Server command";
         }
         // Simply pass on the message.
+        let message_type = bytes[0];
         let res = sender.lock().await.send(Message::Binary(bytes)).await;
         if let Err(err) = res {
             tracing::error!(?err, "Error in communication with server endpoint.");
             return "Error in communication with server endpoint.";
         }
+        metrics.message_to_host(message_type);
     }
     // In normal shutdown procedure that should not happen, because we are responsible for closing the channel.
     tracing::error!("Internal channel on server was unexpectedly closed.");
@@ -158,6 +198,7 @@ async fn send_logic_server(
 ///
 /// # Arguments
 /// * `player_id` - Unique identifier assigned to this client for the session
+/// * `is_spectator` - Spectators only watch: their RPCs are dropped
 ///
 /// # Returns
 /// A static string describing why the connection ended.
@@ -167,14 +208,20 @@ pub async fn handle_client_logic(
     internal_receiver: tokio::sync::broadcast::Receiver<Bytes>,
     internal_sender: tokio::sync::mpsc::Sender<Bytes>,
     player_id: u16,
+    is_spectator: bool,
+    metrics: Arc<Metrics>,
 ) -> &'static str {
-    let mut send_task =
-        tokio::spawn(async move { send_logic_client(sender, internal_receiver, player_id).await });
+    // Cleared by the receive task when the client asks for a full update, so that
+    // the send task lets the next one through.
+    let synced = Arc::new(AtomicBool::new(false));
+    let send_synced = synced.clone();
+    let mut send_task = tokio::spawn(async move {
+        send_logic_client(sender, internal_receiver, player_id, send_synced, metrics).await
+    });
 
-    let mut receive_task =
-        tokio::spawn(
-            async move { receive_logic_client(receiver, internal_sender, player_id).await },
-        );
+    let mut receive_task = tokio::spawn(async move {
+        receive_logic_client(receiver, internal_sender, player_id, is_spectator, synced).await
+    });
 
     // If any one of the tasks run to completion, we abort the other.
     let result = tokio::select! {
@@ -188,21 +235,69 @@ pub async fn handle_client_logic(
     })
 }
 
+/// Sliding window counter deciding whether a client may send another chat message.
+struct ChatRateLimiter {
+    /// Send times of the messages within the current window, oldest first.
+    sent: VecDeque<Instant>,
+}
+
+impl ChatRateLimiter {
+    fn new() -> Self {
+        Self {
+            sent: VecDeque::with_capacity(CHAT_RATE_LIMIT),
+        }
+    }
+
+    /// Records a message sent at `now` and returns `true` if it is within the limit.
+    fn allow(&mut self, now: Instant) -> bool {
+        let window = Duration::from_secs(CHAT_RATE_WINDOW_SECS);
+        while self
+            .sent
+            .front()
+            .is_some_and(|&t| now.duration_since(t) >= window)
+        {
+            self.sent.pop_front();
+        }
+        if self.sent.len() >= CHAT_RATE_LIMIT {
+            return false;
+        }
+        self.sent.push_back(now);
+        true
+    }
+}
+
 /// Receives messages from a client and forwards them to the host.
 ///
 /// Allowed message types from client:
 /// - [`SERVER_RPC`]: Game action — gets player ID injected before forwarding
+/// - [`CHAT_MESSAGE`]: Chat text — checked against [`MAX_CHAT_MESSAGE_SIZE`] and
+///   [`CHAT_RATE_LIMIT`], then forwarded with the player ID injected
+/// - [`REQUEST_FULL_UPDATE`]: Asks the host for a full update — at most once per
+///   [`FULL_UPDATE_REQUEST_INTERVAL_MS`], forwarded with the player ID appended.
+///   Marks the client unsynced, so that the host's answer reaches it.
 /// - [`CLIENT_DISCONNECTS_SELF`]: Graceful disconnect (triggers cleanup)
 ///
 /// # Player ID Injection
 /// RPC messages are transformed from `[SERVER_RPC, payload...]` to
 /// `[SERVER_RPC, player_id_high, player_id_low, payload...]` so the host
 /// knows which player sent the action.
+///
+/// # Spectators
+/// RPC and chat messages from spectators are dropped without being forwarded, so a
+/// spectator can never act in the game.
+///
+/// # Chat Limits
+/// Chat messages that are too long, not valid UTF-8 or over the rate limit are
+/// dropped. The connection stays open.
 async fn receive_logic_client(
     mut receiver: SplitStream<WebSocket>,
     internal_sender: tokio::sync::mpsc::Sender<Bytes>,
     player_id: u16,
+    is_spectator: bool,
+    synced: Arc<AtomicBool>,
 ) -> &'static str {
+    let mut chat_limiter = ChatRateLimiter::new();
+    let mut last_full_update_request: Option<Instant> = None;
     while let Some(state) = receiver.next().await {
         match state {
             Ok(Message::Binary(bytes)) => {
@@ -211,6 +306,9 @@ async fn receive_logic_client(
                     return "Illegal empty message received.";
                 }
                 match bytes[0] {
+                    SERVER_RPC if is_spectator => {
+                        tracing::warn!(player_id, "Rejected RPC from spectator.");
+                    }
                     SERVER_RPC => {
                         // Inject player ID after command byte
                         let mut msg = BytesMut::with_capacity(bytes.len() + CLIENT_ID_SIZE);
@@ -224,6 +322,57 @@ async fn receive_logic_client(
                             return "Error in internal broadcast.";
                         }
                     }
+                    CHAT_MESSAGE if is_spectator => {
+                        tracing::warn!(player_id, "Rejected chat message from spectator.");
+                    }
+                    CHAT_MESSAGE => {
+                        let text = &bytes[1..];
+                        if text.is_empty()
+                            || text.len() > MAX_CHAT_MESSAGE_SIZE
+                            || std::str::from_utf8(text).is_err()
+                        {
+                            tracing::warn!(
+                                player_id,
+                                size = text.len(),
+                                "Rejected malformed chat message."
+                            );
+                            continue;
+                        }
+                        if !chat_limiter.allow(Instant::now()) {
+                            tracing::warn!(player_id, "Chat rate limit exceeded.");
+                            continue;
+                        }
+                        let mut msg = BytesMut::with_capacity(bytes.len() + CLIENT_ID_SIZE);
+                        msg.put_u8(CHAT_MESSAGE);
+                        msg.put_u16(player_id);
+                        msg.put_slice(text);
+
+                        let res = internal_sender.send(msg.into()).await;
+                        if let Err(error) = res {
+                            tracing::error!(?error, "Error in internal broadcast.");
+                            return "Error in internal broadcast.";
+                        }
+                    }
+                    REQUEST_FULL_UPDATE => {
+                        let now = Instant::now();
+                        let interval = Duration::from_millis(FULL_UPDATE_REQUEST_INTERVAL_MS);
+                        if last_full_update_request.is_some_and(|t| now.duration_since(t) < interval)
+                        {
+                            tracing::debug!(player_id, "Dropped early full update request.");
+                            continue;
+                        }
+                        last_full_update_request = Some(now);
+                        synced.store(false, Ordering::SeqCst);
+                        let mut msg = BytesMut::with_capacity(REQUEST_FULL_UPDATE_MSG_SIZE);
+                        msg.put_u8(REQUEST_FULL_UPDATE);
+                        msg.put_u16(player_id);
+
+                        let res = internal_sender.send(msg.into()).await;
+                        if let Err(error) = res {
+                            tracing::error!(?error, "Error in internal broadcast.");
+                            return "Error in internal broadcast.";
+                        }
+                    }
                     CLIENT_DISCONNECTS_SELF => {
                         return "Client disconnected intentionally";
                     }
@@ -254,8 +403,12 @@ async fn receive_logic_client(
 /// [Unsynced] --RESET-------> [Synced]
 /// [Synced]   --DELTA_UPDATE--> [Synced] (forwarded)
 /// [Unsynced] --DELTA_UPDATE--> [Unsynced] (dropped)
+/// [Synced]   --REQUEST_FULL_UPDATE from the client--> [Unsynced]
 /// ```
 ///
+/// The state is shared with the receive task through `synced`, which clears it
+/// when the client asks for a full update.
+///
 /// # Filtered Messages
 /// - [`CLIENT_GETS_KICKED`]: Only terminates if `player_id` matches
 /// - [`SERVER_DISCONNECTS`]: Always terminates
@@ -267,8 +420,9 @@ async fn send_logic_client(
     sender: Arc<Mutex<SplitSink<WebSocket, Message>>>,
     mut internal_receiver: tokio::sync::broadcast::Receiver<Bytes>,
     player_id: u16,
+    synced: Arc<AtomicBool>,
+    metrics: Arc<Metrics>,
 ) -> &'static str {
-    let mut is_synced = false;
     loop {
         let state = internal_receiver.recv().await;
         match state {
@@ -277,6 +431,7 @@ async fn send_logic_client(
                 return "Internal channel closed.";
             }
             Err(RecvError::Lagged(skipped)) => {
+                metrics.broadcast_lagged(skipped);
                 tracing::warn!(
                     skipped_messages = skipped,
                     "Lagging started on internal channel."
@@ -305,7 +460,7 @@ async fn send_logic_client(
                         }
                     }
                     DELTA_UPDATE => {
-                        if is_synced {
+                        if synced.load(Ordering::SeqCst) {
                             let res = sender.lock().await.send(Message::Binary(bytes)).await;
                             if let Err(error) = res {
                                 tracing::error!(
@@ -318,8 +473,7 @@ async fn send_logic_client(
                         // Silently drop deltas for unsynced clients
                     }
                     FULL_UPDATE => {
-                        if !is_synced {
-                            is_synced = true;
+                        if !synced.swap(true, Ordering::SeqCst) {
                             let res = sender.lock().await.send(Message::Binary(bytes)).await;
                             if let Err(error) = res {
                                 tracing::error!(
@@ -333,7 +487,7 @@ async fn send_logic_client(
                     }
                     RESET => {
                         // We simply forward the message and are definitively synced here.
-                        is_synced = true;
+                        synced.store(true, Ordering::SeqCst);
                         let res = sender.lock().await.send(Message::Binary(bytes)).await;
                         if let Err(error) = res {
                             tracing::error!(?error, "Error in communication with client endpoint.");
@@ -352,3 +506,117 @@ async fn send_logic_client(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Router;
+    use axum::extract::WebSocketUpgrade;
+    use axum::routing::get;
+    use tokio::sync::{mpsc, oneshot};
+    use tokio_tungstenite::tungstenite::Message as ClientMessage;
+
+    type ClientSocket =
+        tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+    type Sink = Arc<Mutex<SplitSink<WebSocket, Message>>>;
+
+    /// Serves one connection with `logic` and connects to it. The receiver yields
+    /// why the relay ended the connection.
+    async fn serve<F, Fut>(logic: F) -> (ClientSocket, oneshot::Receiver<&'static str>)
+    where
+        F: FnOnce(Sink, SplitStream<WebSocket>) -> Fut + Send + 'static,
+        Fut: Future<Output = &'static str> + Send,
+    {
+        let (ended, reason) = oneshot::channel();
+        let logic = Arc::new(std::sync::Mutex::new(Some((logic, ended))));
+        let app = Router::new().route(
+            "/ws",
+            get(move |ws: WebSocketUpgrade| async move {
+                let (logic, ended) = logic.lock().unwrap().take().expect("one connection");
+                ws.on_upgrade(move |socket| async move {
+                    let (sender, receiver) = socket.split();
+                    let _ = ended.send(logic(Arc::new(Mutex::new(sender)), receiver).await);
+                })
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}/ws", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let (client, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+        (client, reason)
+    }
+
+    /// Serves one client connection relayed to the returned host channels, and
+    /// connects to it.
+    async fn connect_client() -> (broadcast::Sender<Bytes>, mpsc::Receiver<Bytes>, ClientSocket) {
+        let (to_clients, _) = broadcast::channel(CHANNEL_BUFFER_SIZE);
+        let (to_host, host_receiver) = mpsc::channel(CHANNEL_BUFFER_SIZE);
+        let subscription = to_clients.subscribe();
+        let (client, _) = serve(move |sender, receiver| {
+            let metrics = Arc::new(Metrics::default());
+            handle_client_logic(sender, receiver, subscription, to_host, 1, false, metrics)
+        })
+        .await;
+        (to_clients, host_receiver, client)
+    }
+
+    async fn next_binary(client: &mut ClientSocket) -> Bytes {
+        let message = tokio::time::timeout(Duration::from_secs(5), client.next())
+            .await
+            .expect("the relay forwards a message")
+            .unwrap()
+            .unwrap();
+        match message {
+            ClientMessage::Binary(bytes) => bytes,
+            other => panic!("unexpected message {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn requested_full_update_reaches_a_synced_client() {
+        let (to_clients, mut host, mut client) = connect_client().await;
+
+        to_clients.send(Bytes::from_static(&[FULL_UPDATE, 1])).unwrap();
+        assert_eq!(next_binary(&mut client).await[..], [FULL_UPDATE, 1]);
+        // Synced: the full updates meant for other clients are dropped.
+        to_clients.send(Bytes::from_static(&[FULL_UPDATE, 2])).unwrap();
+        to_clients.send(Bytes::from_static(&[DELTA_UPDATE, 2])).unwrap();
+        assert_eq!(next_binary(&mut client).await[..], [DELTA_UPDATE, 2]);
+
+        client
+            .send(ClientMessage::Binary(Bytes::from_static(&[REQUEST_FULL_UPDATE])))
+            .await
+            .unwrap();
+        let request = host.recv().await.unwrap();
+        assert_eq!(request[..], [REQUEST_FULL_UPDATE, 0, 1]);
+
+        // The host answers with a broadcast, sent twice as if another client had
+        // asked too: the requester takes exactly one.
+        to_clients.send(Bytes::from_static(&[FULL_UPDATE, 3])).unwrap();
+        to_clients.send(Bytes::from_static(&[FULL_UPDATE, 4])).unwrap();
+        to_clients.send(Bytes::from_static(&[DELTA_UPDATE, 5])).unwrap();
+        assert_eq!(next_binary(&mut client).await[..], [FULL_UPDATE, 3]);
+        assert_eq!(next_binary(&mut client).await[..], [DELTA_UPDATE, 5]);
+    }
+
+    #[tokio::test]
+    async fn closing_the_room_ends_the_host_connection() {
+        let (to_host, host_receiver) = mpsc::channel(CHANNEL_BUFFER_SIZE);
+        let (to_clients, _) = broadcast::channel(CHANNEL_BUFFER_SIZE);
+        let (mut host, reason) = serve(move |sender, receiver| {
+            let metrics = Arc::new(Metrics::default());
+            handle_server_logic(sender, receiver, host_receiver, to_clients, metrics)
+        })
+        .await;
+
+        let mut join = BytesMut::new();
+        join.put_u8(NEW_CLIENT);
+        join.put_u16(1);
+        to_host.send(join.into()).await.unwrap();
+        assert_eq!(next_binary(&mut host).await[..], [NEW_CLIENT, 0, 1]);
+
+        to_host.send(Bytes::from_static(&[CLOSE_HOST])).await.unwrap();
+        let reason = tokio::time::timeout(Duration::from_secs(5), reason).await.unwrap();
+        assert_eq!(reason.unwrap(), "Room closed by an admin.");
+    }
+}
@@ -0,0 +1,278 @@
+//! Prometheus metrics and health probes.
+//!
+//! Routes:
+//!   GET /metrics  — Prometheus text format
+//!   GET /healthz  — the process is up and its connection pool is open
+//!   GET /readyz   — PostgreSQL answers and the game config is loaded
+//!
+//! Counters are bumped where events happen; gauges (rooms, connections, pool
+//! usage) are read from the live state when `/metrics` is scraped.
+
+use axum::extract::State;
+use axum::http::{StatusCode, header};
+use axum::response::IntoResponse;
+use protocol::*;
+use std::fmt::{Display, Write};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::lobby::AppState;
+
+/// How long `/readyz` waits for the database.
+const READY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Message types the relay forwards to hosts.
+const TO_HOST_TYPES: [(u8, &str); 7] = [
+    (NEW_CLIENT, "new_client"),
+    (CLIENT_DISCONNECTS, "client_disconnects"),
+    (SERVER_RPC, "server_rpc"),
+    (NEW_SPECTATOR, "new_spectator"),
+    (SPECTATOR_DISCONNECTS, "spectator_disconnects"),
+    (CHAT_MESSAGE, "chat_message"),
+    (REQUEST_FULL_UPDATE, "request_full_update"),
+];
+
+/// Message types the relay accepts from hosts.
+const FROM_HOST_TYPES: [(u8, &str); 5] = [
+    (SERVER_DISCONNECTS, "server_disconnects"),
+    (CLIENT_GETS_KICKED, "client_gets_kicked"),
+    (DELTA_UPDATE, "delta_update"),
+    (FULL_UPDATE, "full_update"),
+    (RESET, "reset"),
+];
+
+/// Why a WebSocket handshake was refused.
+#[derive(Clone, Copy)]
+pub enum HandshakeFailure {
+    /// The join request never came or could not be parsed.
+    BadRequest,
+    UnknownGame,
+    RoomNotFound,
+    RoomExists,
+    RoomFull,
+    RoomClosed,
+    Reserved,
+    OutOfIds,
+    RateLimited,
+    InvalidToken,
+    HostAlreadyConnected,
+    HostGone,
+    /// The client or the room runs another protocol or game version.
+    VersionMismatch,
+}
+
+impl HandshakeFailure {
+    const ALL: [HandshakeFailure; 13] = [
+        HandshakeFailure::BadRequest,
+        HandshakeFailure::UnknownGame,
+        HandshakeFailure::RoomNotFound,
+        HandshakeFailure::RoomExists,
+        HandshakeFailure::RoomFull,
+        HandshakeFailure::RoomClosed,
+        HandshakeFailure::Reserved,
+        HandshakeFailure::OutOfIds,
+        HandshakeFailure::RateLimited,
+        HandshakeFailure::InvalidToken,
+        HandshakeFailure::HostAlreadyConnected,
+        HandshakeFailure::HostGone,
+        HandshakeFailure::VersionMismatch,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            HandshakeFailure::BadRequest => "bad_request",
+            HandshakeFailure::UnknownGame => "unknown_game",
+            HandshakeFailure::RoomNotFound => "room_not_found",
+            HandshakeFailure::RoomExists => "room_exists",
+            HandshakeFailure::RoomFull => "room_full",
+            HandshakeFailure::RoomClosed => "room_closed",
+            HandshakeFailure::Reserved => "reserved",
+            HandshakeFailure::OutOfIds => "out_of_ids",
+            HandshakeFailure::RateLimited => "rate_limited",
+            HandshakeFailure::InvalidToken => "invalid_token",
+            HandshakeFailure::HostAlreadyConnected => "host_already_connected",
+            HandshakeFailure::HostGone => "host_gone",
+            HandshakeFailure::VersionMismatch => "version_mismatch",
+        }
+    }
+}
+
+/// Event counters, shared with the relay tasks.
+pub struct Metrics {
+    /// Messages delivered to hosts, indexed by message type.
+    to_host: [AtomicU64; 256],
+    /// Messages received from hosts, indexed by message type.
+    from_host: [AtomicU64; 256],
+    lag_events: AtomicU64,
+    lag_skipped: AtomicU64,
+    handshake_failures: [AtomicU64; HandshakeFailure::ALL.len()],
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self {
+            to_host: std::array::from_fn(|_| AtomicU64::new(0)),
+            from_host: std::array::from_fn(|_| AtomicU64::new(0)),
+            lag_events: AtomicU64::new(0),
+            lag_skipped: AtomicU64::new(0),
+            handshake_failures: Default::default(),
+        }
+    }
+}
+
+impl Metrics {
+    pub fn message_to_host(&self, message_type: u8) {
+        self.to_host[message_type as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn message_from_host(&self, message_type: u8) {
+        self.from_host[message_type as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// A client fell behind the host broadcast and missed `skipped` messages.
+    pub fn broadcast_lagged(&self, skipped: u64) {
+        self.lag_events.fetch_add(1, Ordering::Relaxed);
+        self.lag_skipped.fetch_add(skipped, Ordering::Relaxed);
+    }
+
+    pub fn handshake_failed(&self, reason: HandshakeFailure) {
+        self.handshake_failures[reason as usize].fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+fn counter(array: &[AtomicU64], index: u8) -> u64 {
+    array[index as usize].load(Ordering::Relaxed)
+}
+
+/// Builds a response in the Prometheus text exposition format.
+#[derive(Default)]
+struct Exposition(String);
+
+impl Exposition {
+    fn family(&mut self, name: &str, kind: &str, help: &str) -> &mut Self {
+        let _ = writeln!(self.0, "# HELP {name} {help}");
+        let _ = writeln!(self.0, "# TYPE {name} {kind}");
+        self
+    }
+
+    /// `labels` is either empty or a `{key="value",…}` set.
+    fn sample(&mut self, name: &str, labels: &str, value: impl Display) -> &mut Self {
+        let _ = writeln!(self.0, "{name}{labels} {value}");
+        self
+    }
+}
+
+pub async fn metrics_handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let (rooms, hosts, clients, spectators) = {
+        let rooms = state.rooms.lock().await;
+        let hosts = rooms.values().filter(|r| r.host_connected).count();
+        let clients: usize = rooms.values().map(|r| r.connected_players.len()).sum();
+        let spectators: usize = rooms.values().map(|r| r.spectators.len()).sum();
+        (rooms.len(), hosts, clients, spectators)
+    };
+    let metrics = &state.metrics;
+    let mut out = Exposition::default();
+
+    out.family("relay_rooms", "gauge", "Open rooms, including rooms waiting for their host.")
+        .sample("relay_rooms", "", rooms);
+    out.family("relay_connected_hosts", "gauge", "Hosts connected to their room.")
+        .sample("relay_connected_hosts", "", hosts);
+    out.family("relay_connected_clients", "gauge", "Players connected to a room, hosts excluded.")
+        .sample("relay_connected_clients", "", clients);
+    out.family("relay_connected_spectators", "gauge", "Spectators watching a room.")
+        .sample("relay_connected_spectators", "", spectators);
+
+    let name = "relay_messages_total";
+    out.family(name, "counter", "Messages relayed, by direction and type.");
+    for (message_type, label) in TO_HOST_TYPES {
+        let labels = format!("{{direction=\"to_host\",type=\"{label}\"}}");
+        out.sample(name, &labels, counter(&metrics.to_host, message_type));
+    }
+    for (message_type, label) in FROM_HOST_TYPES {
+        let labels = format!("{{direction=\"from_host\",type=\"{label}\"}}");
+        out.sample(name, &labels, counter(&metrics.from_host, message_type));
+    }
+
+    let name = "relay_broadcast_lag_events_total";
+    out.family(name, "counter", "Clients dropped for falling behind the host broadcast.")
+        .sample(name, "", metrics.lag_events.load(Ordering::Relaxed));
+    let name = "relay_broadcast_lag_skipped_messages_total";
+    out.family(name, "counter", "Broadcast messages missed by lagging clients.")
+        .sample(name, "", metrics.lag_skipped.load(Ordering::Relaxed));
+
+    let name = "relay_handshake_failures_total";
+    out.family(name, "counter", "Refused WebSocket handshakes, by reason.");
+    for reason in HandshakeFailure::ALL {
+        let labels = format!("{{reason=\"{}\"}}", reason.as_str());
+        let count = metrics.handshake_failures[reason as usize].load(Ordering::Relaxed);
+        out.sample(name, &labels, count);
+    }
+
+    let name = "relay_rejected_requests_total";
+    out.family(name, "counter", "Requests refused by a rate limit.");
+    for (limit, count) in state.limits.rejections() {
+        out.sample(name, &format!("{{limit=\"{limit}\"}}"), count);
+    }
+
+    let pool = state.db.status();
+    let name = "relay_db_pool_connections";
+    out.family(name, "gauge", "Database connections, by state.")
+        .sample(name, "{state=\"idle\"}", pool.available)
+        .sample(name, "{state=\"in_use\"}", pool.size - pool.available);
+    out.family("relay_db_pool_max_connections", "gauge", "Size limit of the database pool.")
+        .sample("relay_db_pool_max_connections", "", pool.max_size);
+    out.family("relay_db_pool_waiting", "gauge", "Requests waiting for a database connection.")
+        .sample("relay_db_pool_waiting", "", pool.waiting);
+
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], out.0)
+}
+
+pub async fn healthz_handler(State(state): State<Arc<AppState>>) -> (StatusCode, &'static str) {
+    if state.db.is_closed() {
+        (StatusCode::SERVICE_UNAVAILABLE, "database pool closed")
+    } else {
+        (StatusCode::OK, "ok")
+    }
+}
+
+pub async fn readyz_handler(State(state): State<Arc<AppState>>) -> (StatusCode, &'static str) {
+    let ping = async {
+        let client = state.db.get().await.ok()?;
+        client.query_one("SELECT 1", &[]).await.ok()
+    };
+    if !matches!(tokio::time::timeout(READY_TIMEOUT, ping).await, Ok(Some(_))) {
+        return (StatusCode::SERVICE_UNAVAILABLE, "database unavailable");
+    }
+    if state.configs.read().await.is_empty() {
+        return (StatusCode::SERVICE_UNAVAILABLE, "game config not loaded");
+    }
+    (StatusCode::OK, "ready")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failure_reasons_index_their_counters() {
+        for (index, reason) in HandshakeFailure::ALL.into_iter().enumerate() {
+            assert_eq!(reason as usize, index, "{}", reason.as_str());
+        }
+    }
+
+    #[test]
+    fn exposition_format() {
+        let mut out = Exposition::default();
+        out.family("relay_rooms", "gauge", "Open rooms.")
+            .sample("relay_rooms", "", 2)
+            .sample("relay_rooms", "{game=\"trictrac\"}", 1);
+        assert_eq!(
+            out.0,
+            "# HELP relay_rooms Open rooms.\n\
+             # TYPE relay_rooms gauge\n\
+             relay_rooms 2\n\
+             relay_rooms{game=\"trictrac\"} 1\n"
+        );
+    }
+}
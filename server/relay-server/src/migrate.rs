@@ -0,0 +1,276 @@
+//! Versioned schema migrations.
+//!
+//! Migrations are the SQL files in `migrations/`, embedded in the binary and
+//! applied in order, all pending ones in a single transaction. Applied
+//! versions are recorded in `schema_migrations` with a checksum of their SQL.
+//!
+//! Migrations 001–008 predate that table and are idempotent, so databases
+//! created before it simply replay them once. Later migrations run exactly
+//! once and need not be.
+//!
+//! Usage:
+//!   relay-server migrate            — apply pending migrations
+//!   relay-server migrate status     — list migrations and whether they are applied
+//!   relay-server migrate --dry-run  — apply pending migrations, then roll back
+
+use deadpool_postgres::Pool;
+use ring::digest::{SHA256, digest};
+use std::collections::HashMap;
+use tokio_postgres::GenericClient;
+
+use crate::db::{DbError, now_unix};
+
+/// An embedded up-migration.
+pub struct Migration {
+    pub version: i32,
+    pub name: &'static str,
+    pub sql: &'static str,
+}
+
+macro_rules! migration {
+    ($version:literal, $name:literal) => {
+        Migration {
+            version: $version,
+            name: $name,
+            sql: include_str!(concat!("../migrations/", $name, ".sql")),
+        }
+    };
+}
+
+/// Every migration, in the order they are applied.
+pub const MIGRATIONS: &[Migration] = &[
+    migration!(1, "001_init"),
+    migration!(2, "002_participants_unique"),
+    migration!(3, "003_email_verification"),
+    migration!(4, "004_tournaments"),
+    migration!(5, "005_sessions_and_rooms"),
+    migration!(6, "006_friends_and_challenges"),
+    migration!(7, "007_user_identities"),
+    migration!(8, "008_admin"),
+    migration!(9, "009_game_record_events"),
+    migration!(10, "010_puzzles"),
+];
+
+/// Serialises migrations between relay instances starting together.
+const LOCK_ID: i64 = 0x7472_6963_7472_6163; // "trictrac"
+
+#[derive(Debug, thiserror::Error)]
+pub enum MigrateError {
+    #[error(transparent)]
+    Db(#[from] DbError),
+    #[error(
+        "the database schema is at version {applied}, newer than the {known} migrations this \
+         binary knows: refusing to run an older relay on it"
+    )]
+    SchemaTooNew { applied: i32, known: i32 },
+    #[error("migration {name} failed: {source}")]
+    Failed {
+        name: &'static str,
+        source: tokio_postgres::Error,
+    },
+}
+
+impl From<tokio_postgres::Error> for MigrateError {
+    fn from(e: tokio_postgres::Error) -> Self {
+        MigrateError::Db(e.into())
+    }
+}
+
+impl From<deadpool_postgres::PoolError> for MigrateError {
+    fn from(e: deadpool_postgres::PoolError) -> Self {
+        MigrateError::Db(e.into())
+    }
+}
+
+impl Migration {
+    fn checksum(&self) -> String {
+        digest(&SHA256, self.sql.as_bytes())
+            .as_ref()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+}
+
+fn latest_version() -> i32 {
+    MIGRATIONS.last().map_or(0, |m| m.version)
+}
+
+/// Where the database stands compared to the embedded migrations.
+pub struct Status {
+    /// Checksum of each applied version.
+    applied: HashMap<i32, String>,
+}
+
+impl Status {
+    fn newest_applied(&self) -> i32 {
+        self.applied.keys().copied().max().unwrap_or(0)
+    }
+
+    fn pending(&self) -> impl Iterator<Item = &'static Migration> + '_ {
+        MIGRATIONS.iter().filter(|m| !self.applied.contains_key(&m.version))
+    }
+
+    /// Applied migrations whose SQL changed since they ran.
+    fn modified(&self) -> impl Iterator<Item = &'static Migration> + '_ {
+        MIGRATIONS.iter().filter(|m| {
+            self.applied
+                .get(&m.version)
+                .is_some_and(|checksum| *checksum != m.checksum())
+        })
+    }
+
+    fn check_not_newer(&self) -> Result<(), MigrateError> {
+        let applied = self.newest_applied();
+        if applied > latest_version() {
+            return Err(MigrateError::SchemaTooNew { applied, known: latest_version() });
+        }
+        Ok(())
+    }
+}
+
+async fn read_status(client: &impl GenericClient) -> Result<Status, MigrateError> {
+    client
+        .batch_execute(
+            "CREATE TABLE IF NOT EXISTS schema_migrations (
+                 version    INTEGER PRIMARY KEY,
+                 name       TEXT    NOT NULL,
+                 checksum   TEXT    NOT NULL,
+                 applied_at BIGINT  NOT NULL
+             )",
+        )
+        .await?;
+    let rows = client
+        .query("SELECT version, checksum FROM schema_migrations", &[])
+        .await?;
+    Ok(Status {
+        applied: rows.into_iter().map(|r| (r.get(0), r.get(1))).collect(),
+    })
+}
+
+/// Applies the pending migrations inside `tx`. Returns the names applied.
+async fn apply_pending(
+    tx: &tokio_postgres::Transaction<'_>,
+) -> Result<Vec<&'static str>, MigrateError> {
+    tx.execute("SELECT pg_advisory_xact_lock($1)", &[&LOCK_ID]).await?;
+    // Read under the lock: another instance may have just migrated.
+    let status = read_status(tx).await?;
+    status.check_not_newer()?;
+    let mut applied = Vec::new();
+    for migration in status.pending() {
+        tx.batch_execute(migration.sql)
+            .await
+            .map_err(|source| MigrateError::Failed { name: migration.name, source })?;
+        tx.execute(
+            "INSERT INTO schema_migrations (version, name, checksum, applied_at) \
+             VALUES ($1, $2, $3, $4)",
+            &[&migration.version, &migration.name, &migration.checksum(), &now_unix()],
+        )
+        .await?;
+        applied.push(migration.name);
+    }
+    Ok(applied)
+}
+
+/// Brings the schema up to date at startup. Refuses a schema newer than this binary.
+pub async fn run(pool: &Pool) -> Result<(), MigrateError> {
+    let mut client = pool.get().await?;
+    let tx = client.transaction().await?;
+    let applied = apply_pending(&tx).await?;
+    tx.commit().await?;
+    for name in applied {
+        tracing::info!(migration = name, "Applied migration");
+    }
+
+    let status = read_status(&**client).await?;
+    for migration in status.modified() {
+        tracing::warn!(migration = migration.name, "Migration changed since it was applied");
+    }
+    Ok(())
+}
+
+/// Runs `relay-server migrate [status | --dry-run]`. Returns the exit code.
+pub async fn command(pool: &Pool, args: &[String]) -> i32 {
+    let result = match args.first().map(String::as_str) {
+        None => apply(pool, false).await,
+        Some("--dry-run") => apply(pool, true).await,
+        Some("status") => print_status(pool).await,
+        Some(other) => {
+            eprintln!("unknown migrate option {other:?}\nusage: relay-server migrate [status | --dry-run]");
+            return 2;
+        }
+    };
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("error: {e}");
+            1
+        }
+    }
+}
+
+async fn apply(pool: &Pool, dry_run: bool) -> Result<(), MigrateError> {
+    let mut client = pool.get().await?;
+    let tx = client.transaction().await?;
+    let applied = apply_pending(&tx).await?;
+    if applied.is_empty() {
+        println!("The schema is up to date (version {}).", latest_version());
+        return Ok(());
+    }
+    for name in &applied {
+        println!("{} {name}", if dry_run { "would apply" } else { "applied" });
+    }
+    if dry_run {
+        tx.rollback().await?;
+        println!("Dry run: every pending migration succeeded and was rolled back.");
+    } else {
+        tx.commit().await?;
+    }
+    Ok(())
+}
+
+async fn print_status(pool: &Pool) -> Result<(), MigrateError> {
+    let client = pool.get().await?;
+    let status = read_status(&**client).await?;
+    for migration in MIGRATIONS {
+        let state = match status.applied.get(&migration.version) {
+            None => "pending",
+            Some(checksum) if *checksum != migration.checksum() => "applied, modified since",
+            Some(_) => "applied",
+        };
+        println!("{:>4}  {:<32} {state}", migration.version, migration.name);
+    }
+    let unknown = status.applied.keys().filter(|&&v| v > latest_version()).count();
+    if unknown > 0 {
+        println!("{unknown} applied migration(s) unknown to this binary");
+    }
+    status.check_not_newer()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_are_sequential_and_match_file_names() {
+        for (i, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version, i as i32 + 1);
+            assert!(migration.name.starts_with(&format!("{:03}_", migration.version)));
+        }
+    }
+
+    #[test]
+    fn pending_and_too_new() {
+        let status = Status {
+            applied: [(1, MIGRATIONS[0].checksum()), (2, "edited".to_string())].into(),
+        };
+        assert_eq!(status.pending().next().unwrap().version, 3);
+        assert_eq!(status.modified().map(|m| m.version).collect::<Vec<_>>(), [2]);
+        assert!(status.check_not_newer().is_ok());
+
+        let status = Status {
+            applied: [(latest_version() + 1, String::new())].into(),
+        };
+        assert!(matches!(status.check_not_newer(), Err(MigrateError::SchemaTooNew { .. })));
+    }
+}
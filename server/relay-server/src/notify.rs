@@ -0,0 +1,146 @@
+//! Per-user notification WebSocket.
+//!
+//! A logged-in client keeps one socket open on `GET /notifications` and receives
+//! JSON-encoded [`Notification`]s (friend requests, challenges, …) as text
+//! messages. The socket is one-way: anything the client sends is ignored.
+
+use axum::extract::ws::{Message, WebSocket};
+use axum::extract::{State, WebSocketUpgrade};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum_login::AuthSession;
+use bytes::Bytes;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::sync::{Mutex, mpsc};
+
+use crate::auth::AuthBackend;
+use crate::lobby::AppState;
+
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+
+/// An event pushed to a user.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Notification {
+    FriendRequest { from: String },
+    FriendAccepted { by: String },
+    Challenge {
+        id: i64,
+        from: String,
+        rule_variation: i32,
+        room_code: String,
+    },
+    ChallengeAccepted { id: i64, by: String, room_code: String },
+    ChallengeDeclined { id: i64, by: String },
+    ChallengeCancelled { id: i64, by: String },
+}
+
+/// The open sockets of one user, with the id of each connection.
+type UserConnections = Vec<(u64, mpsc::UnboundedSender<String>)>;
+
+/// Open notification sockets, keyed by user id. A user may be connected from
+/// several tabs or devices at once.
+#[derive(Default)]
+pub struct Notifier {
+    next_connection_id: AtomicU64,
+    connections: Mutex<HashMap<i64, UserConnections>>,
+}
+
+impl Notifier {
+    async fn register(&self, user_id: i64, sender: mpsc::UnboundedSender<String>) -> u64 {
+        let connection_id = self.next_connection_id.fetch_add(1, Ordering::Relaxed);
+        self.connections
+            .lock()
+            .await
+            .entry(user_id)
+            .or_default()
+            .push((connection_id, sender));
+        connection_id
+    }
+
+    async fn unregister(&self, user_id: i64, connection_id: u64) {
+        let mut connections = self.connections.lock().await;
+        if let Some(senders) = connections.get_mut(&user_id) {
+            senders.retain(|(id, _)| *id != connection_id);
+            if senders.is_empty() {
+                connections.remove(&user_id);
+            }
+        }
+    }
+
+    /// Pushes `notification` to every open socket of the user.
+    /// Returns `false` if the user is not connected.
+    pub async fn send(&self, user_id: i64, notification: &Notification) -> bool {
+        let Ok(text) = serde_json::to_string(notification) else {
+            return false;
+        };
+        let connections = self.connections.lock().await;
+        let Some(senders) = connections.get(&user_id) else {
+            return false;
+        };
+        let mut delivered = false;
+        for (_, sender) in senders {
+            delivered |= sender.send(text.clone()).is_ok();
+        }
+        delivered
+    }
+
+    /// Closes every open socket of the user.
+    pub async fn disconnect(&self, user_id: i64) {
+        // Dropping the senders ends the `serve` loops.
+        self.connections.lock().await.remove(&user_id);
+    }
+
+    pub async fn is_online(&self, user_id: i64) -> bool {
+        self.connections.lock().await.contains_key(&user_id)
+    }
+}
+
+/// Upgrades to the notification socket of the logged-in user.
+pub async fn notifications_handler(
+    ws: WebSocketUpgrade,
+    auth_session: AuthSession<AuthBackend>,
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    let Some(user) = auth_session.user else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    ws.on_upgrade(move |socket| serve(socket, state, user.id))
+}
+
+/// Forwards the user's notifications to the socket until either side closes it.
+async fn serve(mut socket: WebSocket, state: Arc<AppState>, user_id: i64) {
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let connection_id = state.notifier.register(user_id, sender).await;
+
+    let mut ping = tokio::time::interval(PING_INTERVAL);
+    ping.tick().await; // Skip first tick.
+    loop {
+        tokio::select! {
+            text = receiver.recv() => match text {
+                Some(text) => {
+                    if socket.send(Message::Text(text.into())).await.is_err() {
+                        break;
+                    }
+                }
+                // Disconnected by the notifier.
+                None => break,
+            },
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+            _ = ping.tick() => {
+                if socket.send(Message::Ping(Bytes::new())).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+
+    state.notifier.unregister(user_id, connection_id).await;
+}
@@ -0,0 +1,529 @@
+//! OpenID Connect login.
+//!
+//! Authorization-code flow with PKCE against any provider publishing a
+//! discovery document. Configured by the `[oidc]` section of the relay
+//! configuration (see `config.rs`); login is disabled without it.
+//!
+//! ID tokens signed with RS256 or ES256 are accepted.
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use jsonwebtoken::jwk::{AlgorithmParameters, Jwk, JwkSet};
+use jsonwebtoken::{Algorithm, DecodingKey, Header, Validation};
+use rand::Rng;
+use rand::distributions::Alphanumeric;
+use reqwest::{StatusCode, Url};
+use ring::digest::{SHA256, digest};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+use crate::config::OidcConfig;
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+/// Tolerated clock skew when checking token expiry, in seconds.
+const EXPIRY_LEEWAY: u64 = 60;
+
+#[derive(Debug, thiserror::Error)]
+pub enum OidcError {
+    #[error("invalid url: {0}")]
+    Url(String),
+    #[error("http error: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("provider answered {0}")]
+    Status(StatusCode),
+    #[error("discovery document names issuer {0}")]
+    WrongIssuer(String),
+    #[error("invalid id token: {0}")]
+    Token(#[from] jsonwebtoken::errors::Error),
+    #[error("invalid id token: {0}")]
+    InvalidToken(&'static str),
+}
+
+// ── Provider documents ────────────────────────────────────────────────────────
+
+/// The parts of the discovery document the flow needs.
+#[derive(Clone, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+/// The claims not checked by [`jsonwebtoken::decode`] itself.
+#[derive(Deserialize)]
+struct Claims {
+    iss: String,
+    sub: String,
+    nonce: Option<String>,
+    email: Option<String>,
+    #[serde(default)]
+    email_verified: bool,
+    preferred_username: Option<String>,
+}
+
+// ── Flow ──────────────────────────────────────────────────────────────────────
+
+/// Secrets of a login in progress, kept in the session between the redirect to
+/// the provider and the callback.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PendingLogin {
+    pub state: String,
+    nonce: String,
+    code_verifier: String,
+}
+
+/// A user as asserted by a verified ID token.
+#[derive(Clone, Debug)]
+pub struct Identity {
+    pub issuer: String,
+    pub subject: String,
+    pub email: Option<String>,
+    pub email_verified: bool,
+    pub preferred_username: Option<String>,
+}
+
+pub struct OidcClient {
+    config: OidcConfig,
+    http: reqwest::Client,
+    /// Fetched from the provider on first use.
+    metadata: RwLock<Option<ProviderMetadata>>,
+    /// Signing keys of the provider, refetched when a token names an unknown key.
+    jwks: RwLock<JwkSet>,
+}
+
+impl OidcClient {
+    pub fn new(config: OidcConfig) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(HTTP_TIMEOUT)
+            .build()
+            .expect("the TLS backend initialises");
+        Self {
+            config,
+            http,
+            metadata: RwLock::new(None),
+            jwks: RwLock::new(JwkSet { keys: Vec::new() }),
+        }
+    }
+
+    pub fn provider_name(&self) -> &str {
+        &self.config.provider_name
+    }
+
+    /// Frontend base URL the callback redirects the browser to.
+    pub fn app_url(&self) -> &str {
+        &self.config.app_url
+    }
+
+    /// Builds the provider URL to send the browser to, and the secrets to keep
+    /// until the callback.
+    pub async fn authorization_url(&self) -> Result<(String, PendingLogin), OidcError> {
+        let metadata = self.metadata().await?;
+        let pending = PendingLogin {
+            state: random_string(32),
+            nonce: random_string(32),
+            code_verifier: random_string(64),
+        };
+        let code_challenge =
+            URL_SAFE_NO_PAD.encode(digest(&SHA256, pending.code_verifier.as_bytes()));
+        let url = Url::parse_with_params(
+            &metadata.authorization_endpoint,
+            [
+                ("response_type", "code"),
+                ("client_id", self.config.client_id.as_str()),
+                ("redirect_uri", self.config.redirect_url.as_str()),
+                ("scope", "openid email profile"),
+                ("state", pending.state.as_str()),
+                ("nonce", pending.nonce.as_str()),
+                ("code_challenge", code_challenge.as_str()),
+                ("code_challenge_method", "S256"),
+            ],
+        )
+        .map_err(|_| OidcError::Url(metadata.authorization_endpoint.clone()))?;
+        Ok((url.into(), pending))
+    }
+
+    /// Redeems the authorization code returned to the callback and verifies the
+    /// ID token it yields.
+    pub async fn exchange_code(
+        &self,
+        code: &str,
+        pending: &PendingLogin,
+    ) -> Result<Identity, OidcError> {
+        let metadata = self.metadata().await?;
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", self.config.redirect_url.as_str()),
+            ("client_id", self.config.client_id.as_str()),
+            ("code_verifier", pending.code_verifier.as_str()),
+        ];
+        if let Some(secret) = &self.config.client_secret {
+            form.push(("client_secret", secret.as_str()));
+        }
+        let body = serde_urlencoded::to_string(form).map_err(|e| OidcError::Url(e.to_string()))?;
+        let request = self
+            .http
+            .post(&metadata.token_endpoint)
+            .header(reqwest::header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(body);
+        let tokens: TokenResponse = fetch_json(request).await?;
+        self.verify_id_token(&tokens.id_token, &metadata, &pending.nonce)
+            .await
+    }
+
+    async fn verify_id_token(
+        &self,
+        token: &str,
+        metadata: &ProviderMetadata,
+        nonce: &str,
+    ) -> Result<Identity, OidcError> {
+        let header = jsonwebtoken::decode_header(token)?;
+        if !matches!(header.alg, Algorithm::RS256 | Algorithm::ES256) {
+            return Err(OidcError::InvalidToken("unsupported algorithm"));
+        }
+        let key = DecodingKey::from_jwk(&self.signing_key(&header).await?)?;
+        let mut validation = Validation::new(header.alg);
+        validation.leeway = EXPIRY_LEEWAY;
+        validation.set_issuer(&[&metadata.issuer]);
+        validation.set_audience(&[&self.config.client_id]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+        let claims = jsonwebtoken::decode::<Claims>(token, &key, &validation)?.claims;
+        if claims.nonce.as_deref() != Some(nonce) {
+            return Err(OidcError::InvalidToken("wrong nonce"));
+        }
+
+        Ok(Identity {
+            issuer: claims.iss,
+            subject: claims.sub,
+            email: claims.email,
+            email_verified: claims.email_verified,
+            preferred_username: claims.preferred_username,
+        })
+    }
+
+    async fn metadata(&self) -> Result<ProviderMetadata, OidcError> {
+        if let Some(metadata) = self.metadata.read().await.as_ref() {
+            return Ok(metadata.clone());
+        }
+        let issuer = self.config.issuer.trim_end_matches('/');
+        let url = format!("{issuer}/.well-known/openid-configuration");
+        let metadata: ProviderMetadata = fetch_json(self.http.get(url)).await?;
+        // Whoever serves the document must not get to vouch for another issuer.
+        if metadata.issuer.trim_end_matches('/') != issuer {
+            return Err(OidcError::WrongIssuer(metadata.issuer));
+        }
+        *self.metadata.write().await = Some(metadata.clone());
+        Ok(metadata)
+    }
+
+    /// Finds the key the token was signed with, refreshing the key set once if
+    /// the provider may have rotated its keys.
+    async fn signing_key(&self, header: &Header) -> Result<Jwk, OidcError> {
+        if let Some(key) = find_key(&*self.jwks.read().await, header) {
+            return Ok(key);
+        }
+        let metadata = self.metadata().await?;
+        let set: JwkSet = fetch_json(self.http.get(&metadata.jwks_uri)).await?;
+        let key = find_key(&set, header);
+        *self.jwks.write().await = set;
+        key.ok_or(OidcError::InvalidToken("unknown signing key"))
+    }
+}
+
+fn find_key(set: &JwkSet, header: &Header) -> Option<Jwk> {
+    if let Some(kid) = &header.kid {
+        return set.find(kid).cloned();
+    }
+    set.keys
+        .iter()
+        .find(|key| {
+            matches!(
+                (&key.algorithm, header.alg),
+                (AlgorithmParameters::RSA(_), Algorithm::RS256)
+                    | (AlgorithmParameters::EllipticCurve(_), Algorithm::ES256)
+            )
+        })
+        .cloned()
+}
+
+/// Sends a request and decodes the body of a successful response.
+async fn fetch_json<T: DeserializeOwned>(request: reqwest::RequestBuilder) -> Result<T, OidcError> {
+    let response = request
+        .header(reqwest::header::ACCEPT, "application/json")
+        .send()
+        .await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(OidcError::Status(status));
+    }
+    Ok(response.json().await?)
+}
+
+fn random_string(len: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(Alphanumeric)
+        .take(len)
+        .map(char::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::State;
+    use axum::routing::{get, post};
+    use axum::{Form, Json, Router};
+    use jsonwebtoken::EncodingKey;
+    use jsonwebtoken::errors::ErrorKind;
+    use ring::rand::SystemRandom;
+    use ring::signature::{ECDSA_P256_SHA256_FIXED_SIGNING, EcdsaKeyPair, KeyPair};
+    use serde_json::{Value, json};
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use crate::db::now_unix;
+
+    /// A provider serving discovery, keys and a token endpoint on a local port.
+    struct MockProvider {
+        issuer: String,
+        key: EncodingKey,
+        /// Issuer named by the discovery document.
+        advertised_issuer: Arc<Mutex<String>>,
+        /// The token endpoint answers with this ID token once PKCE checks out.
+        id_token: Arc<Mutex<String>>,
+        /// Challenge the token endpoint expects the code verifier to match.
+        code_challenge: Arc<Mutex<String>>,
+    }
+
+    #[derive(Clone)]
+    struct MockState {
+        issuer: String,
+        advertised_issuer: Arc<Mutex<String>>,
+        jwk: Value,
+        id_token: Arc<Mutex<String>>,
+        code_challenge: Arc<Mutex<String>>,
+    }
+
+    impl MockProvider {
+        async fn start() -> Self {
+            let rng = SystemRandom::new();
+            let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng).unwrap();
+            let pair =
+                EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, pkcs8.as_ref(), &rng)
+                    .unwrap();
+            let point = pair.public_key().as_ref();
+            let jwk = json!({
+                "kty": "EC",
+                "crv": "P-256",
+                "kid": "test-key",
+                "use": "sig",
+                "x": URL_SAFE_NO_PAD.encode(&point[1..33]),
+                "y": URL_SAFE_NO_PAD.encode(&point[33..]),
+            });
+
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let issuer = format!("http://{}", listener.local_addr().unwrap());
+            let state = MockState {
+                issuer: issuer.clone(),
+                advertised_issuer: Arc::new(Mutex::new(issuer.clone())),
+                jwk,
+                id_token: Arc::default(),
+                code_challenge: Arc::default(),
+            };
+            let app = Router::new()
+                .route("/.well-known/openid-configuration", get(discovery))
+                .route("/jwks", get(jwks))
+                .route("/token", post(token))
+                .with_state(state.clone());
+            tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+            Self {
+                issuer,
+                key: EncodingKey::from_ec_der(pkcs8.as_ref()),
+                advertised_issuer: state.advertised_issuer,
+                id_token: state.id_token,
+                code_challenge: state.code_challenge,
+            }
+        }
+
+        fn client(&self) -> OidcClient {
+            OidcClient::new(OidcConfig {
+                issuer: self.issuer.clone(),
+                client_id: "trictrac".to_string(),
+                client_secret: None,
+                provider_name: "Mock".to_string(),
+                ..OidcConfig::default()
+            })
+        }
+
+        fn claims(&self, nonce: &str) -> Value {
+            json!({
+                "iss": self.issuer,
+                "sub": "user-1",
+                "aud": "trictrac",
+                "exp": now_unix() + 300,
+                "iat": now_unix(),
+                "nonce": nonce,
+                "email": "alice@example.com",
+                "email_verified": true,
+                "preferred_username": "alice",
+            })
+        }
+
+        fn sign(&self, claims: &Value) -> String {
+            let mut header = Header::new(Algorithm::ES256);
+            header.kid = Some("test-key".to_string());
+            jsonwebtoken::encode(&header, claims, &self.key).unwrap()
+        }
+
+        /// Starts a login and has the token endpoint issue `claims` for it.
+        async fn login(&self, client: &OidcClient, claims: impl Fn(&str) -> Value) -> PendingLogin {
+            let (url, pending) = client.authorization_url().await.unwrap();
+            let query: HashMap<String, String> =
+                serde_urlencoded::from_str(url.split_once('?').unwrap().1).unwrap();
+            assert_eq!(query["state"], pending.state);
+            assert_eq!(query["code_challenge_method"], "S256");
+            *self.code_challenge.lock().unwrap() = query["code_challenge"].clone();
+            *self.id_token.lock().unwrap() = self.sign(&claims(&pending.nonce));
+            pending
+        }
+    }
+
+    async fn discovery(State(state): State<MockState>) -> Json<Value> {
+        Json(json!({
+            "issuer": *state.advertised_issuer.lock().unwrap(),
+            "authorization_endpoint": format!("{}/authorize", state.issuer),
+            "token_endpoint": format!("{}/token", state.issuer),
+            "jwks_uri": format!("{}/jwks", state.issuer),
+        }))
+    }
+
+    async fn jwks(State(state): State<MockState>) -> Json<Value> {
+        Json(json!({ "keys": [state.jwk] }))
+    }
+
+    async fn token(
+        State(state): State<MockState>,
+        Form(form): Form<HashMap<String, String>>,
+    ) -> Result<Json<Value>, axum::http::StatusCode> {
+        let verifier = form.get("code_verifier").cloned().unwrap_or_default();
+        let challenge = URL_SAFE_NO_PAD.encode(digest(&SHA256, verifier.as_bytes()));
+        if form.get("grant_type").map(String::as_str) != Some("authorization_code")
+            || challenge != *state.code_challenge.lock().unwrap()
+        {
+            return Err(axum::http::StatusCode::BAD_REQUEST);
+        }
+        let id_token = state.id_token.lock().unwrap().clone();
+        Ok(Json(json!({ "access_token": "at", "token_type": "Bearer", "id_token": id_token })))
+    }
+
+    #[tokio::test]
+    async fn full_flow_yields_identity() {
+        let provider = MockProvider::start().await;
+        let client = provider.client();
+        let pending = provider.login(&client, |nonce| provider.claims(nonce)).await;
+
+        let identity = client.exchange_code("code", &pending).await.unwrap();
+        assert_eq!(identity.issuer, provider.issuer);
+        assert_eq!(identity.subject, "user-1");
+        assert_eq!(identity.email.as_deref(), Some("alice@example.com"));
+        assert!(identity.email_verified);
+        assert_eq!(identity.preferred_username.as_deref(), Some("alice"));
+    }
+
+    #[tokio::test]
+    async fn wrong_code_verifier_is_rejected() {
+        let provider = MockProvider::start().await;
+        let client = provider.client();
+        let mut pending = provider.login(&client, |nonce| provider.claims(nonce)).await;
+        pending.code_verifier = random_string(64);
+
+        let result = client.exchange_code("code", &pending).await;
+        assert!(matches!(result, Err(OidcError::Status(StatusCode::BAD_REQUEST))));
+    }
+
+    #[tokio::test]
+    async fn wrong_nonce_is_rejected() {
+        let provider = MockProvider::start().await;
+        let client = provider.client();
+        let pending = provider.login(&client, |_| provider.claims("replayed")).await;
+
+        let result = client.exchange_code("code", &pending).await;
+        assert!(matches!(result, Err(OidcError::InvalidToken("wrong nonce"))));
+    }
+
+    #[tokio::test]
+    async fn tampered_token_is_rejected() {
+        let provider = MockProvider::start().await;
+        let client = provider.client();
+        let pending = provider.login(&client, |nonce| provider.claims(nonce)).await;
+        let token = provider.id_token.lock().unwrap().clone();
+        let mut parts: Vec<&str> = token.split('.').collect();
+        let mut claims = provider.claims(&pending.nonce);
+        claims["sub"] = json!("someone-else");
+        let forged = URL_SAFE_NO_PAD.encode(claims.to_string());
+        parts[1] = &forged;
+        *provider.id_token.lock().unwrap() = parts.join(".");
+
+        let result = client.exchange_code("code", &pending).await;
+        assert!(
+            matches!(result, Err(OidcError::Token(e)) if *e.kind() == ErrorKind::InvalidSignature)
+        );
+    }
+
+    #[tokio::test]
+    async fn expired_token_is_rejected() {
+        let provider = MockProvider::start().await;
+        let client = provider.client();
+        let pending = provider
+            .login(&client, |nonce| {
+                let mut claims = provider.claims(nonce);
+                claims["exp"] = json!(now_unix() - EXPIRY_LEEWAY as i64 - 1);
+                claims
+            })
+            .await;
+
+        let result = client.exchange_code("code", &pending).await;
+        assert!(
+            matches!(result, Err(OidcError::Token(e)) if *e.kind() == ErrorKind::ExpiredSignature)
+        );
+    }
+
+    #[tokio::test]
+    async fn token_for_other_client_is_rejected() {
+        let provider = MockProvider::start().await;
+        let client = provider.client();
+        let pending = provider
+            .login(&client, |nonce| {
+                let mut claims = provider.claims(nonce);
+                claims["aud"] = json!(["another-app"]);
+                claims
+            })
+            .await;
+
+        let result = client.exchange_code("code", &pending).await;
+        assert!(
+            matches!(result, Err(OidcError::Token(e)) if *e.kind() == ErrorKind::InvalidAudience)
+        );
+    }
+
+    #[tokio::test]
+    async fn discovery_for_another_issuer_is_rejected() {
+        let provider = MockProvider::start().await;
+        *provider.advertised_issuer.lock().unwrap() = "https://evil.example.org".to_string();
+        let client = provider.client();
+
+        let result = client.authorization_url().await;
+        assert!(
+            matches!(result, Err(OidcError::WrongIssuer(issuer)) if issuer == "https://evil.example.org")
+        );
+    }
+}
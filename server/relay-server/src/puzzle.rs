@@ -0,0 +1,135 @@
+//! Puzzles: curated positions for players to solve.
+//!
+//! The relay knows nothing of a puzzle beyond its slug and title: the position
+//! and its solutions are game-specific JSON, served verbatim. The game checks
+//! answers in the browser and reports each attempt, solved or not.
+//!
+//! Puzzles are loaded from files such as `puzzles/trictrac.json`:
+//!
+//! ```json
+//! { "game_id": "trictrac",
+//!   "puzzles": [{ "slug": "…", "title": "…", "puzzle": { … } }] }
+//! ```
+//!
+//! Usage:
+//!   relay-server puzzles import <file>  — add the file's puzzles, updating those
+//!                                          already imported under the same slug
+
+use deadpool_postgres::Pool;
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+
+use crate::db;
+
+const DAY: i64 = 86_400;
+
+#[derive(Deserialize)]
+pub struct PuzzleFile {
+    pub game_id: String,
+    pub puzzles: Vec<PuzzleEntry>,
+}
+
+#[derive(Deserialize)]
+pub struct PuzzleEntry {
+    pub slug: String,
+    pub title: String,
+    /// The game-specific data, stored as JSON text.
+    pub puzzle: JsonValue,
+}
+
+impl PuzzleFile {
+    pub fn parse(json: &str) -> Result<Self, String> {
+        let file: PuzzleFile = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        for (i, p) in file.puzzles.iter().enumerate() {
+            if !valid_slug(&p.slug) {
+                return Err(format!("puzzle {}: invalid slug {:?}", i + 1, p.slug));
+            }
+            if file.puzzles[..i].iter().any(|q| q.slug == p.slug) {
+                return Err(format!("puzzle {}: duplicate slug {:?}", i + 1, p.slug));
+            }
+            if p.title.trim().is_empty() {
+                return Err(format!("puzzle {}: missing title", i + 1));
+            }
+        }
+        Ok(file)
+    }
+}
+
+fn valid_slug(slug: &str) -> bool {
+    !slug.is_empty()
+        && slug.len() <= 64
+        && slug.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+/// Index of the day's puzzle among `count`, at unix time `now`. Every puzzle
+/// comes round in turn, one per UTC day.
+pub fn daily_index(count: usize, now: i64) -> Option<usize> {
+    (count > 0).then(|| (now.div_euclid(DAY) as usize) % count)
+}
+
+/// Runs `relay-server puzzles import <file>`. Returns the exit code.
+pub async fn command(pool: &Pool, args: &[String]) -> i32 {
+    let path = match args {
+        [cmd, path] if cmd == "import" => path,
+        _ => {
+            eprintln!("usage: relay-server puzzles import <file>");
+            return 2;
+        }
+    };
+    let file = match std::fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|json| PuzzleFile::parse(&json))
+    {
+        Ok(file) => file,
+        Err(e) => {
+            eprintln!("error: {path}: {e}");
+            return 1;
+        }
+    };
+    for p in &file.puzzles {
+        let data = p.puzzle.to_string();
+        if let Err(e) = db::upsert_puzzle(pool, &file.game_id, &p.slug, &p.title, &data).await {
+            eprintln!("error: {}: {e}", p.slug);
+            return 1;
+        }
+    }
+    println!("imported {} {} puzzle(s)", file.puzzles.len(), file.game_id);
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shipped_file_parses() {
+        let file = PuzzleFile::parse(include_str!("../puzzles/trictrac.json")).unwrap();
+        assert_eq!(file.game_id, "trictrac");
+        assert!(!file.puzzles.is_empty());
+    }
+
+    #[test]
+    fn bad_slugs_are_refused() {
+        let file = |slugs: &[&str]| {
+            let puzzles: Vec<String> = slugs
+                .iter()
+                .map(|s| format!(r#"{{"slug": "{s}", "title": "T", "puzzle": {{}}}}"#))
+                .collect();
+            PuzzleFile::parse(&format!(r#"{{"game_id": "g", "puzzles": [{}]}}"#, puzzles.join(",")))
+        };
+        assert!(file(&["a-1", "b"]).is_ok());
+        assert!(file(&["a", "a"]).is_err());
+        assert!(file(&["A b"]).is_err());
+        assert!(file(&["../x"]).is_err());
+        assert!(file(&[""]).is_err());
+    }
+
+    #[test]
+    fn each_day_has_the_next_puzzle() {
+        assert_eq!(daily_index(0, 0), None);
+        assert_eq!(daily_index(3, 0), Some(0));
+        assert_eq!(daily_index(3, DAY - 1), Some(0));
+        assert_eq!(daily_index(3, DAY), Some(1));
+        assert_eq!(daily_index(3, 5 * DAY + 10), Some(2));
+    }
+}
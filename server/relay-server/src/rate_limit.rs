@@ -0,0 +1,364 @@
+//! Rate limiting and abuse protection.
+//!
+//! Token buckets throttle auth requests and WebSocket connections per client IP,
+//! room creation per IP and per account, and outgoing emails per address.
+//! Accounts are locked for a while after repeated failed logins, whether they were
+//! tried by username or by email.
+//!
+//! Configured by the `[limits]` section of the relay configuration, see
+//! [`LimitsConfig`]. Behind reverse proxies, limits are keyed on the address
+//! `trusted_proxies` entries from the right of `X-Forwarded-For`, the last one a
+//! trusted proxy appended. The entries left of it are client-supplied and ignored.
+
+use axum::extract::{ConnectInfo, FromRequestParts};
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::config::LimitsConfig;
+use crate::lobby::AppState;
+
+/// Above this many tracked keys, full buckets are dropped on the next check.
+const PRUNE_THRESHOLD: usize = 10_000;
+
+// ── Token bucket ──────────────────────────────────────────────────────────────
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Allows bursts of `capacity` requests per key, refilled evenly over `period`.
+pub struct RateLimiter<K> {
+    capacity: f64,
+    refill_per_sec: f64,
+    buckets: Mutex<HashMap<K, Bucket>>,
+}
+
+impl<K: Hash + Eq + Clone> RateLimiter<K> {
+    pub fn new(capacity: u32, period: Duration) -> Self {
+        Self {
+            capacity: f64::from(capacity),
+            refill_per_sec: f64::from(capacity) / period.as_secs_f64(),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Takes a token for `key`. Returns `false` if its bucket is empty.
+    pub fn try_acquire(&self, key: &K) -> bool {
+        self.try_acquire_at(key, Instant::now())
+    }
+
+    fn try_acquire_at(&self, key: &K, now: Instant) -> bool {
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() > PRUNE_THRESHOLD {
+            buckets.retain(|_, b| self.refilled(b, now) < self.capacity);
+        }
+        let bucket = buckets.entry(key.clone()).or_insert(Bucket {
+            tokens: self.capacity,
+            updated: now,
+        });
+        bucket.tokens = self.refilled(bucket, now);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn refilled(&self, bucket: &Bucket, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        (bucket.tokens + elapsed * self.refill_per_sec).min(self.capacity)
+    }
+}
+
+// ── Login lockout ─────────────────────────────────────────────────────────────
+
+struct Failures {
+    count: u32,
+    locked_until: Option<Instant>,
+}
+
+/// Locks an account after `max_failures` failed attempts in a row.
+pub struct LoginLockout {
+    max_failures: u32,
+    duration: Duration,
+    accounts: Mutex<HashMap<i64, Failures>>,
+}
+
+impl LoginLockout {
+    pub fn new(max_failures: u32, duration: Duration) -> Self {
+        Self {
+            max_failures,
+            duration,
+            accounts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn is_locked_at(&self, user_id: i64, now: Instant) -> bool {
+        let mut accounts = self.accounts.lock().unwrap();
+        match accounts.get(&user_id).and_then(|f| f.locked_until) {
+            Some(until) if until > now => true,
+            Some(_) => {
+                // The lockout is over: start counting afresh.
+                accounts.remove(&user_id);
+                false
+            }
+            None => false,
+        }
+    }
+
+    fn record_failure_at(&self, user_id: i64, now: Instant) {
+        let mut accounts = self.accounts.lock().unwrap();
+        if accounts.len() > PRUNE_THRESHOLD {
+            accounts.retain(|_, f| f.locked_until.is_some_and(|until| until > now));
+        }
+        let failures = accounts
+            .entry(user_id)
+            .or_insert(Failures { count: 0, locked_until: None });
+        failures.count += 1;
+        if failures.count >= self.max_failures {
+            failures.locked_until = Some(now + self.duration);
+        }
+    }
+
+    fn record_success(&self, user_id: i64) {
+        self.accounts.lock().unwrap().remove(&user_id);
+    }
+}
+
+// ── Limits ────────────────────────────────────────────────────────────────────
+
+/// What a refused request was refused by.
+#[derive(Clone, Copy)]
+pub enum Limit {
+    Auth,
+    LoginLockout,
+    WebSocket,
+    Rooms,
+    Emails,
+}
+
+impl Limit {
+    const ALL: [Limit; 5] = [
+        Limit::Auth,
+        Limit::LoginLockout,
+        Limit::WebSocket,
+        Limit::Rooms,
+        Limit::Emails,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Limit::Auth => "auth",
+            Limit::LoginLockout => "login_lockout",
+            Limit::WebSocket => "websocket",
+            Limit::Rooms => "rooms",
+            Limit::Emails => "emails",
+        }
+    }
+}
+
+/// Every limit the relay enforces, with counts of the requests each refused.
+pub struct RateLimits {
+    /// Reverse proxies in front of the relay, each appending to `X-Forwarded-For`.
+    trusted_proxies: usize,
+    auth: RateLimiter<IpAddr>,
+    websocket: RateLimiter<IpAddr>,
+    rooms_by_ip: RateLimiter<IpAddr>,
+    rooms_by_user: RateLimiter<i64>,
+    emails: RateLimiter<String>,
+    lockout: LoginLockout,
+    rejected: [AtomicU64; Limit::ALL.len()],
+}
+
+impl RateLimits {
+    pub fn new(config: &LimitsConfig) -> Self {
+        let minute = Duration::from_secs(60);
+        let hour = Duration::from_secs(3600);
+        Self {
+            trusted_proxies: config.trusted_proxies,
+            auth: RateLimiter::new(config.auth_per_min, minute),
+            websocket: RateLimiter::new(config.ws_per_min, minute),
+            rooms_by_ip: RateLimiter::new(config.rooms_per_hour, hour),
+            rooms_by_user: RateLimiter::new(config.rooms_per_hour, hour),
+            emails: RateLimiter::new(config.emails_per_hour, hour),
+            lockout: LoginLockout::new(
+                config.login_max_failures,
+                Duration::from_secs(config.login_lockout_secs),
+            ),
+            rejected: Default::default(),
+        }
+    }
+
+    fn check(&self, limit: Limit, allowed: bool) -> bool {
+        if !allowed {
+            self.rejected[limit as usize].fetch_add(1, Ordering::Relaxed);
+        }
+        allowed
+    }
+
+    pub fn allow_auth(&self, ip: IpAddr) -> bool {
+        self.check(Limit::Auth, self.auth.try_acquire(&ip))
+    }
+
+    pub fn allow_websocket(&self, ip: IpAddr) -> bool {
+        self.check(Limit::WebSocket, self.websocket.try_acquire(&ip))
+    }
+
+    /// Both the address and, when logged in, the account must have quota left.
+    pub fn allow_room(&self, ip: IpAddr, user_id: Option<i64>) -> bool {
+        let allowed = self.rooms_by_ip.try_acquire(&ip)
+            && user_id.is_none_or(|id| self.rooms_by_user.try_acquire(&id));
+        self.check(Limit::Rooms, allowed)
+    }
+
+    pub fn allow_email(&self, address: &str) -> bool {
+        self.check(Limit::Emails, self.emails.try_acquire(&address.to_lowercase()))
+    }
+
+    pub fn login_locked(&self, user_id: i64) -> bool {
+        let locked = self.lockout.is_locked_at(user_id, Instant::now());
+        self.check(Limit::LoginLockout, !locked);
+        locked
+    }
+
+    pub fn login_failed(&self, user_id: i64) {
+        self.lockout.record_failure_at(user_id, Instant::now());
+    }
+
+    pub fn login_succeeded(&self, user_id: i64) {
+        self.lockout.record_success(user_id);
+    }
+
+    /// Number of refused requests so far, per limit.
+    pub fn rejections(&self) -> impl Iterator<Item = (&'static str, u64)> + '_ {
+        Limit::ALL
+            .iter()
+            .map(|&limit| (limit.as_str(), self.rejected[limit as usize].load(Ordering::Relaxed)))
+    }
+}
+
+// ── Client address ────────────────────────────────────────────────────────────
+
+/// Address of the client a request came from.
+pub struct ClientIp(pub IpAddr);
+
+/// The address `trusted_proxies` entries from the right of an `X-Forwarded-For`
+/// header: the client as seen by the outermost trusted proxy.
+fn forwarded_client(header: &str, trusted_proxies: usize) -> Option<IpAddr> {
+    let entries: Vec<&str> = header.split(',').map(str::trim).collect();
+    let index = entries.len().checked_sub(trusted_proxies)?;
+    entries.get(index)?.parse().ok()
+}
+
+impl FromRequestParts<Arc<AppState>> for ClientIp {
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let trusted_proxies = state.limits.trusted_proxies;
+        if trusted_proxies > 0 {
+            let forwarded = parts
+                .headers
+                .get("x-forwarded-for")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| forwarded_client(v, trusted_proxies));
+            if let Some(ip) = forwarded {
+                return Ok(ClientIp(ip));
+            }
+        }
+        parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| ClientIp(addr.ip()))
+            .ok_or(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_allows_burst_then_refills() {
+        let limiter = RateLimiter::new(3, Duration::from_secs(60));
+        let start = Instant::now();
+        for _ in 0..3 {
+            assert!(limiter.try_acquire_at(&1, start));
+        }
+        assert!(!limiter.try_acquire_at(&1, start));
+        // One token comes back every 20 seconds.
+        assert!(!limiter.try_acquire_at(&1, start + Duration::from_secs(19)));
+        assert!(limiter.try_acquire_at(&1, start + Duration::from_secs(21)));
+        assert!(!limiter.try_acquire_at(&1, start + Duration::from_secs(21)));
+    }
+
+    #[test]
+    fn buckets_are_per_key() {
+        let limiter = RateLimiter::new(1, Duration::from_secs(60));
+        let now = Instant::now();
+        assert!(limiter.try_acquire_at(&"a", now));
+        assert!(!limiter.try_acquire_at(&"a", now));
+        assert!(limiter.try_acquire_at(&"b", now));
+    }
+
+    #[test]
+    fn refill_is_capped_at_capacity() {
+        let limiter = RateLimiter::new(2, Duration::from_secs(60));
+        let start = Instant::now();
+        let later = start + Duration::from_secs(3600);
+        assert!(limiter.try_acquire_at(&1, start));
+        assert!(limiter.try_acquire_at(&1, later));
+        assert!(limiter.try_acquire_at(&1, later));
+        assert!(!limiter.try_acquire_at(&1, later));
+    }
+
+    #[test]
+    fn lockout_after_repeated_failures() {
+        let lockout = LoginLockout::new(3, Duration::from_secs(900));
+        let start = Instant::now();
+        lockout.record_failure_at(1, start);
+        lockout.record_failure_at(1, start);
+        assert!(!lockout.is_locked_at(1, start));
+        lockout.record_failure_at(1, start);
+        assert!(lockout.is_locked_at(1, start));
+        assert!(!lockout.is_locked_at(2, start));
+        // Unlocked once the lockout expires, with a fresh count.
+        let later = start + Duration::from_secs(901);
+        assert!(!lockout.is_locked_at(1, later));
+        lockout.record_failure_at(1, later);
+        assert!(!lockout.is_locked_at(1, later));
+    }
+
+    #[test]
+    fn success_resets_failures() {
+        let lockout = LoginLockout::new(2, Duration::from_secs(900));
+        let now = Instant::now();
+        lockout.record_failure_at(1, now);
+        lockout.record_success(1);
+        lockout.record_failure_at(1, now);
+        assert!(!lockout.is_locked_at(1, now));
+    }
+
+    #[test]
+    fn forwarded_for_is_read_from_the_right() {
+        let header = "6.6.6.6, 203.0.113.7, 10.0.0.2";
+        assert_eq!(forwarded_client(header, 1), Some("10.0.0.2".parse().unwrap()));
+        assert_eq!(forwarded_client(header, 2), Some("203.0.113.7".parse().unwrap()));
+        assert_eq!(forwarded_client("203.0.113.7", 1), Some("203.0.113.7".parse().unwrap()));
+        // Fewer entries than proxies: the header did not come through all of them.
+        assert_eq!(forwarded_client("203.0.113.7", 2), None);
+        assert_eq!(forwarded_client("203.0.113.7, junk", 1), None);
+    }
+}
@@ -0,0 +1,97 @@
+//! PostgreSQL-backed session store for `tower-sessions`.
+//!
+//! Sessions live in the `sessions` table so that logins survive a restart of
+//! the relay server. The session data is stored as JSON, the expiry as a unix
+//! timestamp; expired rows are ignored on load and purged periodically through
+//! [`ExpiredDeletion`].
+
+use async_trait::async_trait;
+use deadpool_postgres::Pool;
+use time::OffsetDateTime;
+use tower_sessions::session::{Id, Record};
+use tower_sessions::session_store::{self, ExpiredDeletion, SessionStore};
+
+use crate::db::{self, DbError};
+
+/// Session key under which the login holds the user id, as `{"user_id": …}`.
+pub const AUTH_DATA_KEY: &str = "axum-login.data";
+
+#[derive(Clone, Debug)]
+pub struct PgSessionStore {
+    pool: Pool,
+}
+
+impl PgSessionStore {
+    pub fn new(pool: Pool) -> Self {
+        Self { pool }
+    }
+}
+
+fn backend(e: DbError) -> session_store::Error {
+    session_store::Error::Backend(e.to_string())
+}
+
+fn encode(record: &Record) -> session_store::Result<String> {
+    serde_json::to_string(&record.data).map_err(|e| session_store::Error::Encode(e.to_string()))
+}
+
+#[async_trait]
+impl SessionStore for PgSessionStore {
+    async fn create(&self, record: &mut Record) -> session_store::Result<()> {
+        let data = encode(record)?;
+        let expiry = record.expiry_date.unix_timestamp();
+        // Draw a new id until the insert does not collide with an existing session.
+        while !db::insert_session(&self.pool, &record.id.to_string(), &data, expiry)
+            .await
+            .map_err(backend)?
+        {
+            record.id = Id::default();
+        }
+        Ok(())
+    }
+
+    async fn save(&self, record: &Record) -> session_store::Result<()> {
+        let data = encode(record)?;
+        db::upsert_session(
+            &self.pool,
+            &record.id.to_string(),
+            &data,
+            record.expiry_date.unix_timestamp(),
+        )
+        .await
+        .map_err(backend)
+    }
+
+    async fn load(&self, session_id: &Id) -> session_store::Result<Option<Record>> {
+        let Some((data, expiry)) = db::get_session(&self.pool, &session_id.to_string())
+            .await
+            .map_err(backend)?
+        else {
+            return Ok(None);
+        };
+        Ok(Some(Record {
+            id: *session_id,
+            data: serde_json::from_str(&data)
+                .map_err(|e| session_store::Error::Decode(e.to_string()))?,
+            expiry_date: OffsetDateTime::from_unix_timestamp(expiry)
+                .map_err(|e| session_store::Error::Decode(e.to_string()))?,
+        }))
+    }
+
+    async fn delete(&self, session_id: &Id) -> session_store::Result<()> {
+        db::delete_session(&self.pool, &session_id.to_string())
+            .await
+            .map_err(backend)
+    }
+}
+
+#[async_trait]
+impl ExpiredDeletion for PgSessionStore {
+    async fn delete_expired(&self) -> session_store::Result<()> {
+        let n = db::delete_expired_sessions(&self.pool).await.map_err(backend)?;
+        if n > 0 {
+            tracing::debug!(n, "Deleted expired sessions");
+        }
+        Ok(())
+    }
+}
@@ -101,6 +101,19 @@ impl Mailer {
         self.send(to_email, "Reset your Trictrac password", body).await;
     }
 
+    pub async fn send_challenge(&self, to_email: &str, from_username: &str) {
+        let link = format!("{}/friends", self.app_url);
+        let body = format!(
+            "{from_username} challenges you to a game of Trictrac!\n\n\
+             Accept or decline the challenge from your friends page:\n\n\
+             {link}\n\n\
+             The challenge expires in 1 hour.\n\
+             You can turn these emails off on the same page.\n"
+        );
+        let subject = format!("{from_username} challenges you to a game");
+        self.send(to_email, &subject, body).await;
+    }
+
     async fn send(&self, to_email: &str, subject: &str, body: String) {
         let to: Mailbox = match to_email.parse() {
             Ok(m) => m,
@@ -0,0 +1,477 @@
+//! Tournament pairing and standings.
+//!
+//! Everything in here is pure: the HTTP handlers load players and games through
+//! [`crate::db`], ask this module for the next round or the standings, and store
+//! the result. Players are identified by user id and given in seed order
+//! (registration order).
+//!
+//! Scores follow the usual conventions: a win or a bye is worth one point, a
+//! draw half a point. Standings are broken by Buchholz (sum of the opponents'
+//! scores), then Sonneborn-Berger (opponents beaten, plus half of those drawn),
+//! then number of wins and finally seed.
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+/// How rounds are paired.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// Players with similar scores meet; nobody is eliminated.
+    Swiss,
+    /// Single elimination: only winners are paired again.
+    Knockout,
+}
+
+impl Format {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Format::Swiss => "swiss",
+            Format::Knockout => "knockout",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "swiss" => Some(Format::Swiss),
+            "knockout" => Some(Format::Knockout),
+            _ => None,
+        }
+    }
+}
+
+/// Lifecycle of a tournament.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    Registration,
+    Running,
+    Finished,
+}
+
+impl Status {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Status::Registration => "registration",
+            Status::Running => "running",
+            Status::Finished => "finished",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "registration" => Some(Status::Registration),
+            "running" => Some(Status::Running),
+            "finished" => Some(Status::Finished),
+            _ => None,
+        }
+    }
+}
+
+/// Result of a tournament game, from the point of view of the room: the host
+/// is player 0, the guest player 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    HostWon,
+    GuestWon,
+    Draw,
+}
+
+impl Outcome {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Outcome::HostWon => "host",
+            Outcome::GuestWon => "guest",
+            Outcome::Draw => "draw",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "host" => Some(Outcome::HostWon),
+            "guest" => Some(Outcome::GuestWon),
+            "draw" => Some(Outcome::Draw),
+            _ => None,
+        }
+    }
+
+    /// Maps the host's outcome as posted to `/games/result` ("win", "loss", "draw").
+    pub fn from_host_outcome(s: &str) -> Option<Self> {
+        match s {
+            "win" => Some(Outcome::HostWon),
+            "loss" => Some(Outcome::GuestWon),
+            "draw" => Some(Outcome::Draw),
+            _ => None,
+        }
+    }
+}
+
+/// A game of a past or current round.
+#[derive(Clone, Debug)]
+pub struct PlayedGame {
+    pub round: i32,
+    pub board: i32,
+    pub host: i64,
+    /// `None` for a bye.
+    pub guest: Option<i64>,
+    /// `None` while the game has not been played.
+    pub outcome: Option<Outcome>,
+}
+
+impl PlayedGame {
+    /// The player going through in a knockout; draws favour the better seed.
+    fn winner(&self, seeds: &HashMap<i64, usize>) -> Option<i64> {
+        let Some(guest) = self.guest else {
+            return Some(self.host);
+        };
+        match self.outcome? {
+            Outcome::HostWon => Some(self.host),
+            Outcome::GuestWon => Some(guest),
+            Outcome::Draw => Some(if seeds.get(&guest) < seeds.get(&self.host) {
+                guest
+            } else {
+                self.host
+            }),
+        }
+    }
+}
+
+/// One game to be created for the next round. `guest == None` is a bye.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pairing {
+    pub host: i64,
+    pub guest: Option<i64>,
+}
+
+/// One line of the standings table.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Standing {
+    pub user_id: i64,
+    pub score: f32,
+    pub buchholz: f32,
+    pub sonneborn_berger: f32,
+    pub wins: u32,
+}
+
+/// Number of rounds a tournament needs. For swiss, `configured` wins when set;
+/// otherwise both formats use enough rounds to separate a single winner.
+pub fn total_rounds(format: Format, configured: i32, player_count: usize) -> i32 {
+    let needed = (player_count.max(2) as u32).next_power_of_two().trailing_zeros() as i32;
+    match format {
+        Format::Swiss if configured > 0 => configured,
+        _ => needed,
+    }
+}
+
+/// Score each player got from one game: (host, guest).
+fn points(game: &PlayedGame) -> Option<(f32, f32)> {
+    if game.guest.is_none() {
+        return Some((1.0, 0.0));
+    }
+    Some(match game.outcome? {
+        Outcome::HostWon => (1.0, 0.0),
+        Outcome::GuestWon => (0.0, 1.0),
+        Outcome::Draw => (0.5, 0.5),
+    })
+}
+
+/// Standings sorted from first to last place.
+pub fn standings(players: &[i64], games: &[PlayedGame]) -> Vec<Standing> {
+    let mut score: HashMap<i64, f32> = players.iter().map(|&p| (p, 0.0)).collect();
+    let mut wins: HashMap<i64, u32> = HashMap::new();
+    for game in games {
+        let Some((host_pts, guest_pts)) = points(game) else {
+            continue;
+        };
+        *score.entry(game.host).or_default() += host_pts;
+        if host_pts == 1.0 {
+            *wins.entry(game.host).or_default() += 1;
+        }
+        if let Some(guest) = game.guest {
+            *score.entry(guest).or_default() += guest_pts;
+            if guest_pts == 1.0 {
+                *wins.entry(guest).or_default() += 1;
+            }
+        }
+    }
+
+    let mut buchholz: HashMap<i64, f32> = HashMap::new();
+    let mut sonneborn_berger: HashMap<i64, f32> = HashMap::new();
+    for game in games {
+        let (Some(guest), Some((host_pts, guest_pts))) = (game.guest, points(game)) else {
+            continue;
+        };
+        let host_score = score.get(&game.host).copied().unwrap_or_default();
+        let guest_score = score.get(&guest).copied().unwrap_or_default();
+        *buchholz.entry(game.host).or_default() += guest_score;
+        *buchholz.entry(guest).or_default() += host_score;
+        *sonneborn_berger.entry(game.host).or_default() += host_pts * guest_score;
+        *sonneborn_berger.entry(guest).or_default() += guest_pts * host_score;
+    }
+
+    let mut table: Vec<Standing> = players
+        .iter()
+        .map(|&user_id| Standing {
+            user_id,
+            score: score[&user_id],
+            buchholz: buchholz.get(&user_id).copied().unwrap_or_default(),
+            sonneborn_berger: sonneborn_berger.get(&user_id).copied().unwrap_or_default(),
+            wins: wins.get(&user_id).copied().unwrap_or_default(),
+        })
+        .collect();
+    // `players` is in seed order and the sort is stable, so seed breaks the last ties.
+    table.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(Ordering::Equal)
+            .then(b.buchholz.partial_cmp(&a.buchholz).unwrap_or(Ordering::Equal))
+            .then(
+                b.sonneborn_berger
+                    .partial_cmp(&a.sonneborn_berger)
+                    .unwrap_or(Ordering::Equal),
+            )
+            .then(b.wins.cmp(&a.wins))
+    });
+    table
+}
+
+/// Pairs the next swiss round.
+///
+/// Players are ranked by the current standings and paired top-down with the
+/// closest player they have not met yet. With an odd number of players the
+/// lowest ranked player without a bye so far sits out. Whoever hosted less
+/// often hosts the game.
+pub fn pair_swiss(players: &[i64], games: &[PlayedGame]) -> Vec<Pairing> {
+    let mut ranked: Vec<i64> = standings(players, games)
+        .into_iter()
+        .map(|s| s.user_id)
+        .collect();
+
+    let mut met: HashSet<(i64, i64)> = HashSet::new();
+    let mut hosted: HashMap<i64, i32> = HashMap::new();
+    let mut had_bye: HashSet<i64> = HashSet::new();
+    for game in games {
+        match game.guest {
+            Some(guest) => {
+                met.insert((game.host, guest));
+                met.insert((guest, game.host));
+                *hosted.entry(game.host).or_default() += 1;
+                *hosted.entry(guest).or_default() -= 1;
+            }
+            None => {
+                had_bye.insert(game.host);
+            }
+        }
+    }
+
+    let mut pairings = Vec::new();
+    if ranked.len() % 2 == 1 {
+        let bye_index = ranked
+            .iter()
+            .rposition(|p| !had_bye.contains(p))
+            .unwrap_or(ranked.len() - 1);
+        let bye = ranked.remove(bye_index);
+        pairings.push(Pairing {
+            host: bye,
+            guest: None,
+        });
+    }
+
+    let pairs = pair_without_rematch(&ranked, &met).unwrap_or_else(|| {
+        // Everybody has met everybody available: allow rematches.
+        ranked.chunks(2).map(|c| (c[0], c[1])).collect()
+    });
+    let mut games_out: Vec<Pairing> = pairs
+        .into_iter()
+        .map(|(a, b)| {
+            let a_hosted = hosted.get(&a).copied().unwrap_or_default();
+            let b_hosted = hosted.get(&b).copied().unwrap_or_default();
+            if b_hosted < a_hosted {
+                Pairing {
+                    host: b,
+                    guest: Some(a),
+                }
+            } else {
+                Pairing {
+                    host: a,
+                    guest: Some(b),
+                }
+            }
+        })
+        .collect();
+    // Top boards first, the bye last.
+    games_out.extend(pairings);
+    games_out
+}
+
+/// Backtracking search pairing `ranked` (even length) in order, avoiding `met`.
+fn pair_without_rematch(ranked: &[i64], met: &HashSet<(i64, i64)>) -> Option<Vec<(i64, i64)>> {
+    let Some((&first, rest)) = ranked.split_first() else {
+        return Some(Vec::new());
+    };
+    for (i, &opponent) in rest.iter().enumerate() {
+        if met.contains(&(first, opponent)) {
+            continue;
+        }
+        let remaining: Vec<i64> = rest
+            .iter()
+            .enumerate()
+            .filter(|&(j, _)| j != i)
+            .map(|(_, &p)| p)
+            .collect();
+        if let Some(mut pairs) = pair_without_rematch(&remaining, met) {
+            pairs.insert(0, (first, opponent));
+            return Some(pairs);
+        }
+    }
+    None
+}
+
+/// Seed positions in a bracket of `size` (a power of two), e.g. 1, 8, 4, 5, 2, 7, 3, 6,
+/// so that the best seeds only meet in the late rounds.
+fn bracket_order(size: usize) -> Vec<usize> {
+    let mut order = vec![1];
+    while order.len() < size {
+        let n = order.len() * 2 + 1;
+        order = order.iter().flat_map(|&s| [s, n - s]).collect();
+    }
+    order
+}
+
+/// Pairs `round` (starting at 1) of a single elimination bracket.
+///
+/// The first round places the seeds in a bracket padded to a power of two; the
+/// best seeds get the byes. Later rounds pair the winners of consecutive boards
+/// of the previous round. Returns an empty list once a champion is known.
+pub fn pair_knockout(players: &[i64], games: &[PlayedGame], round: i32) -> Vec<Pairing> {
+    if round <= 1 {
+        let size = players.len().max(2).next_power_of_two();
+        return bracket_order(size)
+            .chunks(2)
+            .filter_map(|slot| {
+                let host = *players.get(slot[0] - 1)?;
+                Some(Pairing {
+                    host,
+                    guest: players.get(slot[1] - 1).copied(),
+                })
+            })
+            .collect();
+    }
+
+    let seeds: HashMap<i64, usize> = players.iter().enumerate().map(|(i, &p)| (p, i)).collect();
+    let mut previous: Vec<&PlayedGame> = games.iter().filter(|g| g.round == round - 1).collect();
+    previous.sort_by_key(|g| g.board);
+    let winners: Vec<i64> = previous.iter().filter_map(|g| g.winner(&seeds)).collect();
+    if winners.len() < 2 {
+        return Vec::new();
+    }
+    winners
+        .chunks(2)
+        .map(|pair| Pairing {
+            host: pair[0],
+            guest: pair.get(1).copied(),
+        })
+        .collect()
+}
+
+/// Room code of a tournament game. Rooms are opened by the host like any other
+/// room; the code is only reserved for the two paired players. `secret` is a
+/// random part, so that the code cannot be guessed from the pairing.
+pub fn room_code(tournament_id: i64, round: i32, board: i32, secret: &str) -> String {
+    format!("t{tournament_id}-{round}-{board}-{secret}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(round: i32, board: i32, host: i64, guest: Option<i64>, outcome: Outcome) -> PlayedGame {
+        PlayedGame {
+            round,
+            board,
+            host,
+            guest,
+            outcome: Some(outcome),
+        }
+    }
+
+    #[test]
+    fn standings_use_tiebreaks() {
+        let players = [1, 2, 3, 4];
+        let games = [
+            game(1, 1, 1, Some(2), Outcome::HostWon),
+            game(1, 2, 3, Some(4), Outcome::HostWon),
+            game(2, 1, 1, Some(3), Outcome::Draw),
+            game(2, 2, 2, Some(4), Outcome::HostWon),
+        ];
+        let table = standings(&players, &games);
+        let order: Vec<i64> = table.iter().map(|s| s.user_id).collect();
+        // 1 and 3 both have 1.5 points; 1 beat a stronger opponent (2 has 1 point).
+        assert_eq!(order, vec![1, 3, 2, 4]);
+        assert_eq!(table[0].score, 1.5);
+        assert_eq!(table[0].buchholz, 2.5);
+        assert_eq!(table[1].buchholz, 1.5);
+    }
+
+    #[test]
+    fn swiss_avoids_rematches() {
+        let players = [1, 2, 3, 4];
+        let round1 = pair_swiss(&players, &[]);
+        assert_eq!(round1.len(), 2);
+        let games = [
+            game(1, 1, 1, Some(2), Outcome::HostWon),
+            game(1, 2, 3, Some(4), Outcome::HostWon),
+        ];
+        let round2 = pair_swiss(&players, &games);
+        for p in &round2 {
+            let pair = (p.host, p.guest.unwrap());
+            assert!(pair != (1, 2) && pair != (2, 1) && pair != (3, 4) && pair != (4, 3));
+        }
+        // Winners meet on the top board.
+        assert_eq!(
+            (round2[0].host.min(round2[0].guest.unwrap()), round2[0].host.max(round2[0].guest.unwrap())),
+            (1, 3)
+        );
+    }
+
+    #[test]
+    fn swiss_bye_goes_to_lowest_without_bye() {
+        let players = [1, 2, 3];
+        let round1 = pair_swiss(&players, &[]);
+        assert_eq!(round1.last().unwrap(), &Pairing { host: 3, guest: None });
+        let games = [
+            game(1, 1, 1, Some(2), Outcome::GuestWon),
+            game(1, 2, 3, None, Outcome::HostWon),
+        ];
+        let round2 = pair_swiss(&players, &games);
+        // 1 is last and has had no bye yet.
+        assert_eq!(round2.last().unwrap(), &Pairing { host: 1, guest: None });
+    }
+
+    #[test]
+    fn knockout_first_round_gives_byes_to_top_seeds() {
+        let pairings = pair_knockout(&[10, 20, 30, 40, 50, 60], &[], 1);
+        assert_eq!(
+            pairings,
+            vec![
+                Pairing { host: 10, guest: None },
+                Pairing { host: 40, guest: Some(50) },
+                Pairing { host: 20, guest: None },
+                Pairing { host: 30, guest: Some(60) },
+            ]
+        );
+        assert_eq!(total_rounds(Format::Knockout, 0, 6), 3);
+    }
+
+    #[test]
+    fn knockout_pairs_winners() {
+        let players = [1, 2, 3, 4];
+        let games = [
+            game(1, 1, 1, Some(4), Outcome::GuestWon),
+            game(1, 2, 2, Some(3), Outcome::Draw),
+        ];
+        let final_round = pair_knockout(&players, &games, 2);
+        // A draw sends the better seed through.
+        assert_eq!(final_round, vec![Pairing { host: 4, guest: Some(2) }]);
+        let games = [game(2, 1, 4, Some(2), Outcome::HostWon)];
+        assert!(pair_knockout(&players, &games, 3).is_empty());
+    }
+}
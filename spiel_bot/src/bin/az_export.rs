@@ -0,0 +1,111 @@
+//! Export an AlphaZero checkpoint for the web client's bot.
+//!
+//! Reads a `.mpk` checkpoint and writes its weights in the plain format of
+//! [`spiel_bot::network::export`], which the browser reads without Burn.
+//!
+//! # Usage
+//!
+//! ```sh
+//! cargo run -p spiel_bot --bin az_export --release -- \
+//!     --checkpoint model.mpk --arch mlp \
+//!     --out clients/web/assets/bot/trictrac.ttnw
+//!
+//! cargo run -p spiel_bot --bin az_export --release -- \
+//!     --checkpoint model.mpk --arch resnet --hidden 512 \
+//!     --out clients/web/assets/bot/trictrac.ttnw
+//! ```
+//!
+//! # Options
+//!
+//! | Flag | Default | Description |
+//! |------|---------|-------------|
+//! | `--checkpoint <path>` | (required) | `.mpk` file written by `az_train` |
+//! | `--arch mlp\|resnet` | `mlp` | Network architecture |
+//! | `--hidden <N>` | 256 (mlp) / 512 (resnet) | Hidden size |
+//! | `--out <path>` | `trictrac.ttnw` | Exported weights file |
+
+use std::path::PathBuf;
+
+use burn::backend::NdArray;
+
+use spiel_bot::network::{MlpConfig, MlpNet, ResNet, ResNetConfig};
+
+type InferB = NdArray<f32>;
+
+struct Args {
+    checkpoint: Option<PathBuf>,
+    arch: String,
+    hidden: Option<usize>,
+    out: PathBuf,
+}
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("{message}");
+    eprintln!("Usage: az_export --checkpoint <path> [--arch mlp|resnet] [--hidden <N>] [--out <path>]");
+    std::process::exit(2);
+}
+
+fn parse_args() -> Args {
+    let raw: Vec<String> = std::env::args().collect();
+    let mut args = Args {
+        checkpoint: None,
+        arch: "mlp".into(),
+        hidden: None,
+        out: PathBuf::from("trictrac.ttnw"),
+    };
+    // The value following the flag at `i - 1`.
+    let value = |i: usize| {
+        raw.get(i)
+            .cloned()
+            .unwrap_or_else(|| usage_error(&format!("Missing value for {}", raw[i - 1])))
+    };
+    let mut i = 1;
+    while i < raw.len() {
+        match raw[i].as_str() {
+            "--checkpoint" => { i += 1; args.checkpoint = Some(PathBuf::from(value(i))); }
+            "--arch"       => { i += 1; args.arch = value(i); }
+            "--hidden"     => {
+                i += 1;
+                let hidden = value(i).parse().unwrap_or_else(|_| usage_error("--hidden must be an integer"));
+                args.hidden = Some(hidden);
+            }
+            "--out"        => { i += 1; args.out = PathBuf::from(value(i)); }
+            other => usage_error(&format!("Unknown argument: {other}")),
+        }
+        i += 1;
+    }
+    args
+}
+
+fn main() {
+    let args = parse_args();
+    let device: <InferB as burn::tensor::backend::Backend>::Device = Default::default();
+    let Some(checkpoint) = &args.checkpoint else {
+        eprintln!("--checkpoint is required");
+        std::process::exit(1);
+    };
+
+    let writer = match args.arch.as_str() {
+        "resnet" => {
+            let hidden = args.hidden.unwrap_or(512);
+            let cfg = ResNetConfig { obs_size: 217, action_size: 514, hidden_size: hidden };
+            ResNet::<InferB>::load(&cfg, checkpoint, &device)
+                .unwrap_or_else(|e| { eprintln!("Load failed: {e}"); std::process::exit(1); })
+                .export(&cfg)
+        }
+        "mlp" => {
+            let hidden = args.hidden.unwrap_or(256);
+            let cfg = MlpConfig { obs_size: 217, action_size: 514, hidden_size: hidden };
+            MlpNet::<InferB>::load(&cfg, checkpoint, &device)
+                .unwrap_or_else(|e| { eprintln!("Load failed: {e}"); std::process::exit(1); })
+                .export(&cfg)
+        }
+        other => usage_error(&format!("Unknown --arch {other}: expected mlp or resnet")),
+    };
+
+    writer.save(&args.out).unwrap_or_else(|e| {
+        eprintln!("Export failed: {e}");
+        std::process::exit(1);
+    });
+    println!("Wrote {} ({} network)", args.out.display(), args.arch);
+}
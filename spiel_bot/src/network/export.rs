@@ -0,0 +1,70 @@
+//! Plain weights export, for inference without Burn.
+//!
+//! The web client cannot embed Burn, so trained policy-value networks are
+//! shipped as a flat little-endian file that a few lines of Rust can read
+//! back (`game/src/network.rs`).
+//!
+//! ```text
+//! magic        b"TTNW"
+//! version      u32   (1)
+//! arch         u32   (0 = MlpNet, 1 = ResNet)
+//! obs_size     u32
+//! action_size  u32
+//! hidden_size  u32
+//! then every Linear layer, in forward order:
+//!   d_input    u32
+//!   d_output   u32
+//!   weight     f32 × d_input × d_output   (row-major [d_input, d_output], as Burn stores it)
+//!   bias       f32 × d_output
+//! ```
+//!
+//! Layer order: `MlpNet` — fc1, fc2, policy_head, value_head.
+//! `ResNet` — input, block0.fc1, block0.fc2, …, block3.fc2, policy_head, value_head.
+
+use std::path::Path;
+
+use burn::{nn::Linear, tensor::backend::Backend};
+
+pub const MAGIC: &[u8; 4] = b"TTNW";
+pub const VERSION: u32 = 1;
+pub const ARCH_MLP: u32 = 0;
+pub const ARCH_RESNET: u32 = 1;
+
+/// Writer for the weights file; see the module documentation for the layout.
+pub struct WeightsWriter {
+    bytes: Vec<u8>,
+}
+
+impl WeightsWriter {
+    pub fn new(arch: u32, obs_size: usize, action_size: usize, hidden_size: usize) -> Self {
+        let mut w = Self { bytes: MAGIC.to_vec() };
+        for v in [VERSION, arch, obs_size as u32, action_size as u32, hidden_size as u32] {
+            w.bytes.extend_from_slice(&v.to_le_bytes());
+        }
+        w
+    }
+
+    /// Append one layer.  A layer without bias is written with a zero bias.
+    pub fn linear<B: Backend>(&mut self, layer: &Linear<B>) {
+        let [d_input, d_output] = layer.weight.val().dims();
+        self.bytes.extend_from_slice(&(d_input as u32).to_le_bytes());
+        self.bytes.extend_from_slice(&(d_output as u32).to_le_bytes());
+        let weight: Vec<f32> = layer.weight.val().into_data().to_vec().unwrap();
+        let bias: Vec<f32> = match &layer.bias {
+            Some(b) => b.val().into_data().to_vec().unwrap(),
+            None => vec![0.0; d_output],
+        };
+        for v in weight.iter().chain(bias.iter()) {
+            self.bytes.extend_from_slice(&v.to_le_bytes());
+        }
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    pub fn save(self, path: &Path) -> anyhow::Result<()> {
+        std::fs::write(path, self.bytes)
+            .map_err(|e| anyhow::anyhow!("writing {} failed: {e}", path.display()))
+    }
+}
@@ -20,6 +20,7 @@ use burn::{
 };
 use std::path::Path;
 
+use super::export::{WeightsWriter, ARCH_MLP};
 use super::PolicyValueNet;
 
 // ── Config ────────────────────────────────────────────────────────────────────
@@ -87,6 +88,21 @@ impl<B: Backend> MlpNet<B> {
             .map_err(|e| anyhow::anyhow!("MlpNet::load failed: {e:?}"))?;
         Ok(Self::new(config, device).load_record(record))
     }
+
+    /// Write the weights in the plain format of [`super::export`].
+    pub fn export(&self, config: &MlpConfig) -> WeightsWriter {
+        let mut w = WeightsWriter::new(
+            ARCH_MLP,
+            config.obs_size,
+            config.action_size,
+            config.hidden_size,
+        );
+        w.linear(&self.fc1);
+        w.linear(&self.fc2);
+        w.linear(&self.policy_head);
+        w.linear(&self.value_head);
+        w
+    }
 }
 
 impl<B: Backend> PolicyValueNet<B> for MlpNet<B> {
@@ -220,4 +236,16 @@ mod tests {
 
         let _ = std::fs::remove_file(path);
     }
+
+    // ── Export ────────────────────────────────────────────────────────────
+
+    #[test]
+    fn export_size_matches_layout() {
+        let config = MlpConfig { obs_size: 10, action_size: 20, hidden_size: 32 };
+        let net = MlpNet::<B>::new(&config, &device());
+        let bytes = net.export(&config).into_bytes();
+        let floats = (10 * 32 + 32) + (32 * 32 + 32) + (32 * 20 + 20) + (32 + 1);
+        assert_eq!(&bytes[..4], b"TTNW");
+        assert_eq!(bytes.len(), 4 + 5 * 4 + 4 * 2 * 4 + floats * 4);
+    }
 }
@@ -41,7 +41,13 @@
 //!
 //! Callers are responsible for masking illegal actions in `policy_logits`
 //! before passing to softmax.
+//!
+//! # Export
+//!
+//! `MlpNet::export` and `ResNet::export` write the weights in the plain
+//! format of the [`export`] module, read by the web client's bot.
 
+pub mod export;
 pub mod mlp;
 pub mod qnet;
 pub mod resnet;
@@ -26,6 +26,7 @@ use burn::{
 };
 use std::path::Path;
 
+use super::export::{WeightsWriter, ARCH_RESNET};
 use super::PolicyValueNet;
 
 // ── Config ────────────────────────────────────────────────────────────────────
@@ -124,6 +125,24 @@ impl<B: Backend> ResNet<B> {
             .map_err(|e| anyhow::anyhow!("ResNet::load failed: {e:?}"))?;
         Ok(Self::new(config, device).load_record(record))
     }
+
+    /// Write the weights in the plain format of [`super::export`].
+    pub fn export(&self, config: &ResNetConfig) -> WeightsWriter {
+        let mut w = WeightsWriter::new(
+            ARCH_RESNET,
+            config.obs_size,
+            config.action_size,
+            config.hidden_size,
+        );
+        w.linear(&self.input);
+        for block in [&self.block0, &self.block1, &self.block2, &self.block3] {
+            w.linear(&block.fc1);
+            w.linear(&block.fc2);
+        }
+        w.linear(&self.policy_head);
+        w.linear(&self.value_head);
+        w
+    }
 }
 
 impl<B: Backend> PolicyValueNet<B> for ResNet<B> {
@@ -672,7 +672,10 @@ impl GameState {
                 }
             }
             EndGame { reason } => {
-                if let EndGameReason::PlayerWon { winner: _ } = reason {
+                if matches!(
+                    reason,
+                    EndGameReason::PlayerWon { .. } | EndGameReason::Timeout { .. }
+                ) {
                     // Check that the game has started before someone wins it
                     if self.stage != Stage::InGame {
                         return false;
@@ -1114,6 +1117,8 @@ impl GameState {
 pub enum EndGameReason {
     PlayerLeft { player_id: PlayerId },
     PlayerWon { winner: PlayerId },
+    /// The player's clock ran out: they lose the game.
+    Timeout { player_id: PlayerId },
 }
 
 /// An event that progresses the GameState forward
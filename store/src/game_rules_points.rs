@@ -11,7 +11,7 @@ use std::cmp;
 use std::collections::HashMap;
 
 #[allow(clippy::enum_variant_names)]
-#[derive(PartialEq, Eq, Hash, Clone, Debug, Serialize, Deserialize)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Debug, Serialize, Deserialize)]
 pub enum Jan {
     FilledQuarter,
     TrueHitSmallJan,